@@ -8,19 +8,82 @@ use serde_json::value::to_raw_value;
 use serde_json::value::RawValue;
 use serde_json::Value;
 
+use crate::consts;
 use crate::error::Error;
 use crate::method::Method;
 use crate::reqwest_transport::ReqwestHttpTransport;
 use crate::{request, response};
 
+/// How the version of a server relates to the versions supported by this client
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VersionCompatibility {
+    /// The server is in the range of versions supported by this client
+    Compatible,
+
+    /// The server is newer than this client, requests may fail or behave differently
+    Newer,
+}
+
+/// Check the version returned by [`Client::version`] against the versions supported by this
+/// client
+///
+/// Servers older than [`consts::MIN_SERVER_VERSION`] are not supported and return an error.
+/// Versions differing only in the patch (or in the minor before 1.0) are compatible.
+pub fn check_server_version(server_version: &str) -> Result<VersionCompatibility, Error> {
+    let server = parse_version(server_version)?;
+    let min = parse_version(consts::MIN_SERVER_VERSION)?;
+    let client = parse_version(consts::APP_VERSION)?;
+    if server < min {
+        return Err(Error::ServerVersionTooOld {
+            server: server_version.to_string(),
+            min: consts::MIN_SERVER_VERSION.to_string(),
+        });
+    }
+    // Before 1.0 the minor version is the one with breaking changes
+    let breaking = |(major, minor, _): (u64, u64, u64)| (major, if major == 0 { minor } else { 0 });
+    Ok(if breaking(server) > breaking(client) {
+        VersionCompatibility::Newer
+    } else {
+        VersionCompatibility::Compatible
+    })
+}
+
+/// Parse a version as `(major, minor, patch)`, ignoring the pre-release and build metadata
+fn parse_version(version: &str) -> Result<(u64, u64, u64), Error> {
+    let err = || Error::InvalidVersion(version.to_string());
+    let core = version.split(['-', '+']).next().ok_or_else(err)?;
+    let mut parts = core.split('.').map(|p| p.parse::<u64>().map_err(|_| err()));
+    let parsed = (
+        parts.next().ok_or_else(err)??,
+        parts.next().ok_or_else(err)??,
+        parts.next().ok_or_else(err)??,
+    );
+    match parts.next() {
+        Some(_) => Err(err()),
+        None => Ok(parsed),
+    }
+}
+
 pub struct Client {
     client: jsonrpc::Client,
 }
 
 impl Client {
     pub fn new(addr: SocketAddr) -> Result<Self, Error> {
-        let url = format!("http://{addr}");
-        let transport = ReqwestHttpTransport::new(url, TIMEOUT);
+        Self::with_url(&format!("http://{addr}"))
+    }
+
+    /// Connect to the server at the given url, such as `http://example.com:32110`, which can be
+    /// remote or behind a proxy
+    pub fn with_url(url: &str) -> Result<Self, Error> {
+        let parsed = reqwest::Url::parse(url)
+            .map_err(|e| Error::Generic(format!("Invalid RPC url \"{url}\": {e}")))?;
+        if !matches!(parsed.scheme(), "http" | "https") {
+            return Err(Error::Generic(format!(
+                "Invalid RPC url \"{url}\": the scheme must be http or https"
+            )));
+        }
+        let transport = ReqwestHttpTransport::new(url.to_string(), TIMEOUT);
         let client = jsonrpc::Client::with_transport(transport);
         Ok(Self { client })
     }
@@ -53,19 +116,29 @@ impl Client {
         self.make_request(Method::Version, None::<Box<RawValue>>)
     }
 
-    pub fn signer_generate(&self) -> Result<response::SignerGenerate, Error> {
-        self.make_request(Method::SignerGenerate, None::<Box<RawValue>>)
+    pub fn network_details(&self) -> Result<response::NetworkDetails, Error> {
+        self.make_request(Method::NetworkDetails, None::<Box<RawValue>>)
+    }
+
+    pub fn signer_generate(
+        &self,
+        word_count: Option<u8>,
+    ) -> Result<response::SignerGenerate, Error> {
+        let req = request::SignerGenerate { word_count };
+        self.make_request(Method::SignerGenerate, Some(req))
     }
 
     pub fn signer_load_software(
         &self,
         name: String,
         mnemonic: String,
+        passphrase: Option<String>,
         persist: bool,
     ) -> Result<response::Signer, Error> {
         let req = request::SignerLoadSoftware {
             name,
             mnemonic,
+            passphrase,
             persist,
         };
         self.make_request(Method::SignerLoadSoftware, Some(req))
@@ -90,12 +163,36 @@ impl Client {
         self.make_request(Method::SignerLoadExternal, Some(req))
     }
 
+    pub fn signer_load_hwi(
+        &self,
+        name: String,
+        fingerprint: String,
+        device_path: Option<String>,
+    ) -> Result<response::Signer, Error> {
+        let req = request::SignerLoadHwi {
+            name,
+            fingerprint,
+            device_path,
+        };
+        self.make_request(Method::SignerLoadHwi, Some(req))
+    }
+
     pub fn wallet_list(&self) -> Result<response::WalletList, Error> {
         self.make_request(Method::WalletList, None::<Box<RawValue>>)
     }
 
-    pub fn wallet_load(&self, descriptor: String, name: String) -> Result<response::Wallet, Error> {
-        let req = request::WalletLoad { descriptor, name };
+    pub fn wallet_load(
+        &self,
+        descriptor: String,
+        name: String,
+        gap_limit: Option<u32>,
+    ) -> Result<response::Wallet, Error> {
+        let req = request::WalletLoad {
+            descriptor,
+            name,
+            gap_limit,
+            start_height: None,
+        };
         self.make_request(Method::WalletLoad, Some(req))
     }
 
@@ -104,6 +201,15 @@ impl Client {
         self.make_request(Method::WalletUnload, Some(req))
     }
 
+    pub fn wallet_rescan(
+        &self,
+        name: String,
+        start_height: Option<u32>,
+    ) -> Result<response::WalletRescan, Error> {
+        let req = request::WalletRescan { name, start_height };
+        self.make_request(Method::WalletRescan, Some(req))
+    }
+
     pub fn signer_unload(&self, name: String) -> Result<response::SignerUnload, Error> {
         let req = request::SignerUnload { name };
         self.make_request(Method::SignerUnload, Some(req))
@@ -117,8 +223,13 @@ impl Client {
         &self,
         name: String,
         with_tickers: bool,
+        detailed: bool,
     ) -> Result<response::WalletBalance, Error> {
-        let req = request::WalletBalance { name, with_tickers };
+        let req = request::WalletBalance {
+            name,
+            with_tickers,
+            detailed,
+        };
         self.make_request(Method::WalletBalance, Some(req))
     }
 
@@ -129,6 +240,7 @@ impl Client {
         signer: Option<String>,
         with_text_qr: bool,
         with_uri_qr: Option<u8>,
+        confidential: Option<bool>,
     ) -> Result<response::WalletAddress, Error> {
         let req = request::WalletAddress {
             name,
@@ -136,43 +248,113 @@ impl Client {
             signer,
             with_text_qr,
             with_uri_qr,
+            confidential,
         };
         self.make_request(Method::WalletAddress, Some(req))
     }
 
+    pub fn wallet_addresses(
+        &self,
+        name: String,
+        index: Option<u32>,
+        count: u32,
+        with_text_qr: bool,
+        with_uri_qr: Option<u8>,
+        confidential: Option<bool>,
+    ) -> Result<response::WalletAddresses, Error> {
+        let req = request::WalletAddresses {
+            name,
+            index,
+            count,
+            with_text_qr,
+            with_uri_qr,
+            confidential,
+        };
+        self.make_request(Method::WalletAddresses, Some(req))
+    }
+
+    #[allow(clippy::too_many_arguments)]
     pub fn wallet_send_many(
         &self,
         name: String,
         addressees: Vec<UnvalidatedRecipient>,
         fee_rate: Option<f32>,
-    ) -> Result<response::Pset, Error> {
+        fee_rate_unit: Option<request::FeeRateUnit>,
+        utxos: Option<Vec<String>>,
+        enable_rbf: bool,
+        change_address: Option<String>,
+        dry_run: bool,
+        coin_selection: Option<request::CoinSelection>,
+    ) -> Result<response::WalletSend, Error> {
         let req = request::WalletSendMany {
             addressees: addressees.into_iter().map(unvalidate_addressee).collect(),
             fee_rate,
+            fee_rate_unit,
+            utxos,
+            enable_rbf,
+            change_address,
+            dry_run,
+            coin_selection,
             name,
         };
         self.make_request(Method::WalletSendMany, Some(req))
     }
 
+    pub fn wallet_estimate_fee(
+        &self,
+        name: String,
+        addressees: Vec<UnvalidatedRecipient>,
+        fee_rate: Option<f32>,
+        fee_rate_unit: Option<request::FeeRateUnit>,
+        coin_selection: Option<request::CoinSelection>,
+    ) -> Result<response::WalletEstimateFee, Error> {
+        let req = request::WalletEstimateFee {
+            name,
+            addressees: addressees.into_iter().map(unvalidate_addressee).collect(),
+            fee_rate,
+            fee_rate_unit,
+            coin_selection,
+        };
+        self.make_request(Method::WalletEstimateFee, Some(req))
+    }
+
     pub fn wallet_drain(
         &self,
         name: String,
         address: String,
         fee_rate: Option<f32>,
+        fee_rate_unit: Option<request::FeeRateUnit>,
     ) -> Result<response::Pset, Error> {
         let req = request::WalletDrain {
             address,
             fee_rate,
+            fee_rate_unit,
             name,
         };
         self.make_request(Method::WalletDrain, Some(req))
     }
 
+    pub fn wallet_bump_fee(
+        &self,
+        name: String,
+        txid: String,
+        fee_rate: f32,
+        fee_rate_unit: Option<request::FeeRateUnit>,
+    ) -> Result<response::Pset, Error> {
+        let req = request::WalletBumpFee {
+            name,
+            txid,
+            fee_rate,
+            fee_rate_unit,
+        };
+        self.make_request(Method::WalletBumpFee, Some(req))
+    }
+
     pub fn signer_singlesig_descriptor(
         &self,
         name: String,
         descriptor_blinding_key: String,
-        singlesig_kind: String,
+        singlesig_kind: request::SinglesigKind,
     ) -> Result<response::SignerSinglesigDescriptor, Error> {
         let req = request::SignerSinglesigDescriptor {
             name,
@@ -185,15 +367,17 @@ impl Client {
     pub fn wallet_multisig_descriptor(
         &self,
         descriptor_blinding_key: String,
-        multisig_kind: String,
+        multisig_kind: request::MultisigKind,
         threshold: u32,
         keyorigin_xpubs: Vec<String>,
+        sorted: bool,
     ) -> Result<response::WalletMultisigDescriptor, Error> {
         let req = request::WalletMultisigDescriptor {
             descriptor_blinding_key,
             multisig_kind,
             threshold,
             keyorigin_xpubs,
+            sorted,
         };
         self.make_request(Method::WalletMultisigDescriptor, Some(req))
     }
@@ -201,7 +385,7 @@ impl Client {
     pub fn signer_xpub(
         &self,
         name: String,
-        xpub_kind: String,
+        xpub_kind: request::XpubKind,
     ) -> Result<response::SignerXpub, Error> {
         let req = request::SignerXpub { name, xpub_kind };
         self.make_request(Method::SignerXpub, Some(req))
@@ -216,8 +400,47 @@ impl Client {
         self.make_request(Method::SignerRegisterMultisig, Some(req))
     }
 
-    pub fn signer_sign(&self, name: String, pset: String) -> Result<response::Pset, Error> {
-        let req = request::SignerSign { name, pset };
+    pub fn signer_registered_multisigs(
+        &self,
+        name: String,
+    ) -> Result<response::SignerRegisteredMultisigs, Error> {
+        let req = request::SignerRegisteredMultisigs { name };
+        self.make_request(Method::SignerRegisteredMultisigs, Some(req))
+    }
+
+    pub fn signer_confirm_address(
+        &self,
+        name: String,
+        wallet: String,
+        index: u32,
+        change: bool,
+    ) -> Result<response::SignerConfirmAddress, Error> {
+        let req = request::SignerConfirmAddress {
+            name,
+            wallet,
+            index,
+            change,
+        };
+        self.make_request(Method::SignerConfirmAddress, Some(req))
+    }
+
+    pub fn signer_jade_status(&self, name: String) -> Result<response::JadeStatus, Error> {
+        let req = request::SignerJadeStatus { name };
+        self.make_request(Method::SignerJadeStatus, Some(req))
+    }
+
+    pub fn signer_jade_unlock(&self, name: String) -> Result<response::JadeStatus, Error> {
+        let req = request::SignerJadeUnlock { name };
+        self.make_request(Method::SignerJadeUnlock, Some(req))
+    }
+
+    pub fn signer_sign(
+        &self,
+        name: String,
+        pset: String,
+        wallet: Option<String>,
+    ) -> Result<response::Pset, Error> {
+        let req = request::SignerSign { name, pset, wallet };
         self.make_request(Method::SignerSign, Some(req))
     }
 
@@ -240,6 +463,11 @@ impl Client {
         self.make_request(Method::WalletDetails, Some(req))
     }
 
+    pub fn wallet_sync_status(&self, name: String) -> Result<response::WalletSyncStatus, Error> {
+        let req = request::WalletSyncStatus { name };
+        self.make_request(Method::WalletSyncStatus, Some(req))
+    }
+
     pub fn signer_details(&self, name: String) -> Result<response::SignerDetails, Error> {
         let req = request::SignerDetails { name };
         self.make_request(Method::SignerDetails, Some(req))
@@ -268,8 +496,51 @@ impl Client {
         self.make_request(Method::WalletPsetDetails, Some(req))
     }
 
-    pub fn wallet_utxos(&self, name: String) -> Result<response::WalletUtxos, Error> {
-        let req = request::WalletUtxos { name };
+    pub fn wallet_pset_analyze(
+        &self,
+        name: String,
+        pset: String,
+        with_tickers: bool,
+    ) -> Result<response::WalletPsetAnalyze, Error> {
+        let req = request::WalletPsetAnalyze {
+            name,
+            pset,
+            with_tickers,
+        };
+        self.make_request(Method::WalletPsetAnalyze, Some(req))
+    }
+
+    pub fn wallet_proof_of_reserves(
+        &self,
+        name: String,
+        challenge: String,
+    ) -> Result<response::Pset, Error> {
+        let req = request::WalletProofOfReserves { name, challenge };
+        self.make_request(Method::WalletProofOfReserves, Some(req))
+    }
+
+    pub fn verify_proof_of_reserves(
+        &self,
+        proof: String,
+        challenge: String,
+    ) -> Result<response::VerifyProofOfReserves, Error> {
+        let req = request::VerifyProofOfReserves { proof, challenge };
+        self.make_request(Method::VerifyProofOfReserves, Some(req))
+    }
+
+    pub fn wallet_utxos(
+        &self,
+        name: String,
+        with_tickers: bool,
+        asset: Option<String>,
+        min_satoshi: Option<u64>,
+    ) -> Result<response::WalletUtxos, Error> {
+        let req = request::WalletUtxos {
+            name,
+            with_tickers,
+            asset,
+            min_satoshi,
+        };
         self.make_request(Method::WalletUtxos, Some(req))
     }
 
@@ -277,11 +548,27 @@ impl Client {
         &self,
         name: String,
         with_tickers: bool,
+        limit: Option<u32>,
+        offset: Option<u32>,
+        min_height: Option<u32>,
+        max_height: Option<u32>,
     ) -> Result<response::WalletTxs, Error> {
-        let req = request::WalletTxs { name, with_tickers };
+        let req = request::WalletTxs {
+            name,
+            with_tickers,
+            limit,
+            offset,
+            min_height,
+            max_height,
+        };
         self.make_request(Method::WalletTxs, Some(req))
     }
 
+    pub fn wallet_export_csv(&self, name: String) -> Result<response::WalletExportCsv, Error> {
+        let req = request::WalletExportCsv { name };
+        self.make_request(Method::WalletExportCsv, Some(req))
+    }
+
     pub fn wallet_tx(
         &self,
         name: String,
@@ -320,6 +607,81 @@ impl Client {
         self.make_request(Method::WalletSetAddrMemo, Some(req))
     }
 
+    pub fn wallet_set_output_memo(
+        &self,
+        name: String,
+        outpoint: String,
+        memo: String,
+    ) -> Result<response::Empty, Error> {
+        let req = request::WalletSetOutputMemo {
+            name,
+            outpoint,
+            memo,
+        };
+        self.make_request(Method::WalletSetOutputMemo, Some(req))
+    }
+
+    pub fn wallet_labels(&self, name: String) -> Result<response::WalletLabels, Error> {
+        let req = request::WalletLabels { name };
+        self.make_request(Method::WalletLabels, Some(req))
+    }
+
+    pub fn wallet_import_labels(
+        &self,
+        name: String,
+        jsonl: String,
+    ) -> Result<response::WalletImportLabels, Error> {
+        let req = request::WalletImportLabels { name, jsonl };
+        self.make_request(Method::WalletImportLabels, Some(req))
+    }
+
+    pub fn wallet_is_mine(
+        &self,
+        name: String,
+        address_or_script: String,
+    ) -> Result<response::WalletIsMine, Error> {
+        let req = request::WalletIsMine {
+            name,
+            address_or_script,
+        };
+        self.make_request(Method::WalletIsMine, Some(req))
+    }
+
+    pub fn wallet_export_descriptor(
+        &self,
+        name: String,
+        format: request::DescriptorFormat,
+    ) -> Result<response::WalletExportDescriptor, Error> {
+        let req = request::WalletExportDescriptor { name, format };
+        self.make_request(Method::WalletExportDescriptor, Some(req))
+    }
+
+    pub fn validate_address(
+        &self,
+        address: String,
+        name_or_network: String,
+    ) -> Result<response::ValidateAddress, Error> {
+        let req = request::ValidateAddress {
+            address,
+            name_or_network,
+        };
+        self.make_request(Method::ValidateAddress, Some(req))
+    }
+
+    pub fn verify_xpub(
+        &self,
+        fingerprint: String,
+        path: String,
+        xpub: String,
+    ) -> Result<response::VerifyXpub, Error> {
+        let req = request::VerifyXpub {
+            fingerprint,
+            path,
+            xpub,
+        };
+        self.make_request(Method::VerifyXpub, Some(req))
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub fn wallet_issue(
         &self,
@@ -330,7 +692,10 @@ impl Client {
         address_token: Option<String>,
         contract: Option<String>,
         fee_rate: Option<f32>,
-    ) -> Result<response::Pset, Error> {
+        dry_run: bool,
+        change_address: Option<String>,
+        coin_selection: Option<request::CoinSelection>,
+    ) -> Result<response::WalletIssue, Error> {
         let req = request::WalletIssue {
             name,
             satoshi_asset,
@@ -339,10 +704,14 @@ impl Client {
             address_token,
             contract,
             fee_rate,
+            dry_run,
+            change_address,
+            coin_selection,
         };
         self.make_request(Method::WalletIssue, Some(req))
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn wallet_reissue(
         &self,
         name: String,
@@ -350,13 +719,19 @@ impl Client {
         satoshi_asset: u64,
         address_asset: Option<String>,
         fee_rate: Option<f32>,
-    ) -> Result<response::Pset, Error> {
+        dry_run: bool,
+        change_address: Option<String>,
+        coin_selection: Option<request::CoinSelection>,
+    ) -> Result<response::WalletIssue, Error> {
         let req = request::WalletReissue {
             name,
             asset,
             satoshi_asset,
             address_asset,
             fee_rate,
+            dry_run,
+            change_address,
+            coin_selection,
         };
         self.make_request(Method::WalletReissue, Some(req))
     }
@@ -377,6 +752,40 @@ impl Client {
         self.make_request(Method::WalletBurn, Some(req))
     }
 
+    pub fn wallet_consolidate(
+        &self,
+        name: String,
+        asset: Option<String>,
+        max_inputs: u32,
+        fee_rate: Option<f32>,
+    ) -> Result<response::WalletConsolidate, Error> {
+        let req = request::WalletConsolidate {
+            name,
+            asset,
+            max_inputs,
+            fee_rate,
+        };
+        self.make_request(Method::WalletConsolidate, Some(req))
+    }
+
+    pub fn wallet_create_swap(
+        &self,
+        name: String,
+        send_asset: String,
+        send_satoshi: u64,
+        recv_asset: String,
+        recv_satoshi: u64,
+    ) -> Result<response::WalletCreateSwap, Error> {
+        let req = request::WalletCreateSwap {
+            name,
+            send_asset,
+            send_satoshi,
+            recv_asset,
+            recv_satoshi,
+        };
+        self.make_request(Method::WalletCreateSwap, Some(req))
+    }
+
     pub fn asset_contract(
         &self,
         domain: String,
@@ -430,6 +839,15 @@ impl Client {
         self.make_request(Method::AssetFromExplorer, Some(req))
     }
 
+    pub fn asset_registry_lookup(
+        &self,
+        asset_id: String,
+        cache: bool,
+    ) -> Result<response::AssetRegistryLookup, Error> {
+        let req = request::AssetRegistryLookup { asset_id, cache };
+        self.make_request(Method::AssetRegistryLookup, Some(req))
+    }
+
     pub fn asset_publish(&self, asset_id: String) -> Result<response::AssetPublish, Error> {
         let req = request::AssetPublish { asset_id };
         self.make_request(Method::AssetPublish, Some(req))
@@ -443,6 +861,14 @@ impl Client {
         self.make_request(Method::Schema, Some(req))
     }
 
+    pub fn schema_all(&self) -> Result<response::SchemaAll, Error> {
+        self.make_request(Method::SchemaAll, None::<Box<RawValue>>)
+    }
+
+    pub fn openrpc(&self) -> Result<Value, Error> {
+        self.make_request(Method::Openrpc, None::<Box<RawValue>>)
+    }
+
     pub fn signer_jade_id(&self, emulator: Option<SocketAddr>) -> Result<Value, Error> {
         let req = request::SignerJadeId { emulator };
         self.make_request(Method::SignerJadeId, Some(req))
@@ -460,9 +886,66 @@ impl Client {
 }
 
 fn unvalidate_addressee(a: lwk_wollet::UnvalidatedRecipient) -> request::UnvalidatedAddressee {
+    let (locktime_height, csv_blocks) = match a.timelock {
+        None => (None, None),
+        Some(lwk_wollet::Timelock::Height(height)) => (Some(height), None),
+        Some(lwk_wollet::Timelock::Blocks(blocks)) => (None, Some(blocks)),
+    };
     request::UnvalidatedAddressee {
         satoshi: a.satoshi,
         address: a.address,
         asset: a.asset,
+        locktime_height,
+        csv_blocks,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{check_server_version, parse_version, VersionCompatibility};
+    use crate::{consts, Error};
+
+    #[test]
+    fn test_check_server_version() {
+        assert_eq!(parse_version("0.5.1").unwrap(), (0, 5, 1));
+        assert_eq!(parse_version("1.2.3-rc1+build").unwrap(), (1, 2, 3));
+        for invalid in ["", "1", "1.2", "1.2.3.4", "a.b.c", "1.2.x"] {
+            assert!(matches!(
+                parse_version(invalid),
+                Err(Error::InvalidVersion(_))
+            ));
+        }
+
+        assert_eq!(
+            check_server_version(consts::APP_VERSION).unwrap(),
+            VersionCompatibility::Compatible
+        );
+        assert_eq!(
+            check_server_version(consts::MIN_SERVER_VERSION).unwrap(),
+            VersionCompatibility::Compatible
+        );
+        let (major, minor, patch) = parse_version(consts::APP_VERSION).unwrap();
+        let next_patch = format!("{major}.{minor}.{}", patch + 1);
+        assert_eq!(
+            check_server_version(&next_patch).unwrap(),
+            VersionCompatibility::Compatible
+        );
+        let next_minor = format!("{major}.{}.0", minor + 1);
+        assert_eq!(
+            check_server_version(&next_minor).unwrap(),
+            VersionCompatibility::Newer
+        );
+        assert_eq!(
+            check_server_version(&format!("{}.0.0", major + 1)).unwrap(),
+            VersionCompatibility::Newer
+        );
+
+        let err = check_server_version("0.4.9").unwrap_err();
+        assert!(matches!(err, Error::ServerVersionTooOld { .. }));
+        assert!(err.to_string().contains("requires at least 0.5.0"));
+        assert!(matches!(
+            check_server_version("invalid"),
+            Err(Error::InvalidVersion(_))
+        ));
     }
 }
@@ -1,15 +1,107 @@
+use aes_gcm_siv::aead::generic_array::GenericArray;
+use aes_gcm_siv::aead::{AeadInPlace, NewAead};
+use aes_gcm_siv::Aes256GcmSiv;
 use lwk_jade::Network as JadeNetwork;
 use lwk_jade::TIMEOUT;
 use lwk_wollet::elements::AssetId;
-use lwk_wollet::ElementsNetwork;
+use lwk_wollet::hashes::{sha256t_hash_newtype, Hash};
+use lwk_wollet::{
+    BlockFilterClient, BlockchainBackend, ElectrumOptions, ElementsNetwork, EsploraClient,
+    GAP_LIMIT,
+};
+use rand::{thread_rng, Rng};
+use std::fmt;
 use std::fs;
-use std::net::SocketAddr;
+use std::net::{Ipv4Addr, SocketAddr};
 use std::path::PathBuf;
 use std::str::FromStr;
 use std::time::Duration;
+use zeroize::Zeroizing;
 
 use crate::{consts, Error};
 
+sha256t_hash_newtype! {
+    /// The tag of the hash
+    pub struct StateKeyTag = hash_str("LWK-App-State-Encryption-Key/1.0");
+
+    /// A tagged hash to generate the key encrypting the secrets in the server state
+    #[hash_newtype(forward)]
+    pub struct StateKeyHash(_);
+}
+
+/// The key encrypting the secrets persisted in the server state, such as software signers
+/// mnemonics, derived from a password
+#[derive(Clone)]
+pub struct StateKey(Zeroizing<[u8; 32]>);
+
+impl StateKey {
+    pub fn from_password(password: &str) -> Self {
+        Self(Zeroizing::new(
+            StateKeyHash::hash(password.as_bytes()).to_byte_array(),
+        ))
+    }
+
+    fn cipher(&self) -> Aes256GcmSiv {
+        Aes256GcmSiv::new(GenericArray::from_slice(self.0.as_slice()))
+    }
+
+    /// Encrypt the given data, the random nonce is prepended to the ciphertext
+    pub fn encrypt(&self, plaintext: &[u8]) -> Result<Vec<u8>, Error> {
+        let mut nonce_bytes = [0u8; 12];
+        thread_rng().fill(&mut nonce_bytes);
+        let nonce = GenericArray::from_slice(&nonce_bytes);
+
+        let mut ciphertext = plaintext.to_vec();
+        self.cipher()
+            .encrypt_in_place(nonce, b"", &mut ciphertext)
+            .map_err(|_| Error::StateEncryption)?;
+
+        let mut result = Vec::with_capacity(ciphertext.len() + 12);
+        result.extend(nonce.as_slice());
+        result.extend(ciphertext);
+        Ok(result)
+    }
+
+    /// Decrypt data encrypted with [`StateKey::encrypt`]
+    pub fn decrypt(&self, data: &[u8]) -> Result<Zeroizing<Vec<u8>>, Error> {
+        if data.len() < 12 {
+            return Err(Error::StateEncryption);
+        }
+        let nonce = GenericArray::from_slice(&data[..12]);
+        let mut plaintext = Zeroizing::new(data[12..].to_vec());
+        self.cipher()
+            .decrypt_in_place(nonce, b"", &mut *plaintext)
+            .map_err(|_| Error::StateEncryption)?;
+        Ok(plaintext)
+    }
+}
+
+impl fmt::Debug for StateKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("StateKey(..)")
+    }
+}
+
+const MAINNET_ELECTRUM_URL: &str = "blockstream.info:995";
+const MAINNET_ESPLORA_API_URL: &str = "https://blockstream.info/liquid/api/";
+const TESTNET_ELECTRUM_URL: &str = "blockstream.info:465";
+const TESTNET_ESPLORA_API_URL: &str = "https://blockstream.info/liquidtestnet/api/";
+
+/// The source of blockchain data used to scan the wallets and to broadcast transactions
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Backend {
+    /// The electrum server at `electrum_url`
+    #[default]
+    Electrum,
+
+    /// The Esplora HTTP API at `esplora_api_url`
+    Esplora,
+
+    /// The compact block filters served by the node at `node_rpc_url`, more private but using
+    /// more bandwidth, see [`lwk_wollet::BlockFilterClient`]
+    BlockFilter,
+}
+
 #[derive(Clone, Debug)]
 pub struct Config {
     /// The address where the RPC server is listening or the client is connecting to
@@ -20,68 +112,162 @@ pub struct Config {
     pub tls: bool,
     pub validate_domain: bool,
 
+    /// Timeout, retries and proxy of the electrum client
+    pub electrum_options: ElectrumOptions,
+
     pub explorer_url: String,
 
     // Unfortunately we cannot always derive the "api" url from "explorer_url", thus we need two separate values
     pub esplora_api_url: String,
 
+    /// The backend used to scan and broadcast
+    pub backend: Backend,
+
+    /// The node JSON-RPC url used by [`Backend::BlockFilter`], credentials can be in the url
+    pub node_rpc_url: String,
+
+    /// The height of the first block filter downloaded by [`Backend::BlockFilter`]
+    pub block_filter_start_height: u32,
+
     pub registry_url: String,
     pub timeout: Duration,
     pub scanning_interval: Duration,
+
+    /// The maximum number of wallets scanned concurrently, each one with its own connection
+    pub scan_workers: usize,
+
+    /// The gap limit of the wallets loaded without one, see [`lwk_wollet::Wollet::set_gap_limit()`]
+    pub gap_limit: u32,
+
+    /// The key to encrypt the secrets in the server state, if missing they are not persisted
+    pub state_key: Option<StateKey>,
 }
 
 impl Config {
     pub fn default_testnet(datadir: PathBuf) -> Self {
         Self {
-            addr: consts::DEFAULT_ADDR.into(),
+            addr: Self::default_addr(&ElementsNetwork::LiquidTestnet),
             datadir,
-            electrum_url: "blockstream.info:465".into(),
+            electrum_url: TESTNET_ELECTRUM_URL.into(),
             network: ElementsNetwork::LiquidTestnet,
             tls: true,
             validate_domain: true,
+            electrum_options: ElectrumOptions::default(),
             explorer_url: "https://blockstream.info/liquidtestnet/".into(),
-            esplora_api_url: "https://blockstream.info/liquidtestnet/api/".into(),
+            esplora_api_url: TESTNET_ESPLORA_API_URL.into(),
+            backend: Backend::Electrum,
+            node_rpc_url: "".into(),
+            block_filter_start_height: 0,
             registry_url: "https://assets-testnet.blockstream.info/".into(),
             timeout: TIMEOUT,
             scanning_interval: consts::SCANNING_INTERVAL,
+            scan_workers: consts::SCAN_WORKERS,
+            gap_limit: GAP_LIMIT,
+            state_key: None,
         }
     }
 
     pub fn default_mainnet(datadir: PathBuf) -> Self {
         Self {
-            addr: consts::DEFAULT_ADDR.into(),
+            addr: Self::default_addr(&ElementsNetwork::Liquid),
             datadir,
-            electrum_url: "blockstream.info:995".into(),
+            electrum_url: MAINNET_ELECTRUM_URL.into(),
             network: ElementsNetwork::Liquid,
             tls: true,
             validate_domain: true,
+            electrum_options: ElectrumOptions::default(),
             explorer_url: "https://blockstream.info/liquid/".into(),
-            esplora_api_url: "https://blockstream.info/liquid/api/".into(),
+            esplora_api_url: MAINNET_ESPLORA_API_URL.into(),
+            backend: Backend::Electrum,
+            node_rpc_url: "".into(),
+            block_filter_start_height: 0,
             registry_url: "https://assets.blockstream.info/".into(),
             timeout: TIMEOUT,
             scanning_interval: consts::SCANNING_INTERVAL,
+            scan_workers: consts::SCAN_WORKERS,
+            gap_limit: GAP_LIMIT,
+            state_key: None,
         }
     }
 
     /// For regtest there are no reasonable default for `electrum_url`, `explorer_url`, `esplora_api_url` and `registry_url`
     /// It will be caller responsability to mutate them according to regtest env
     pub fn default_regtest(datadir: PathBuf) -> Self {
-        let policy_asset = "5ac9f65c0efcc4775e0baec4ec03abdde22473cd3cf33c0419ca290e0751b225";
-        let policy_asset = AssetId::from_str(policy_asset).expect("static");
+        let network = Self::regtest_network();
         Self {
-            addr: consts::DEFAULT_ADDR.into(),
+            addr: Self::default_addr(&network),
             datadir,
             electrum_url: "".into(),
-            network: ElementsNetwork::ElementsRegtest { policy_asset },
+            network,
             tls: false,
             validate_domain: false,
+            electrum_options: ElectrumOptions::default(),
             explorer_url: "".into(),
             esplora_api_url: "".into(),
+            backend: Backend::Electrum,
+            node_rpc_url: "".into(),
+            block_filter_start_height: 0,
             registry_url: "".into(),
             timeout: TIMEOUT,
             // Scan more frequently while testing
             scanning_interval: Duration::from_secs(1),
+            scan_workers: consts::SCAN_WORKERS,
+            gap_limit: GAP_LIMIT,
+            state_key: None,
+        }
+    }
+
+    /// A builder filling the defaults of the chosen network
+    pub fn builder() -> ConfigBuilder {
+        ConfigBuilder::default()
+    }
+
+    /// The regtest network with the policy asset of a fresh elements regtest node
+    pub fn regtest_network() -> ElementsNetwork {
+        let policy_asset = "5ac9f65c0efcc4775e0baec4ec03abdde22473cd3cf33c0419ca290e0751b225";
+        let policy_asset = AssetId::from_str(policy_asset).expect("static");
+        ElementsNetwork::ElementsRegtest { policy_asset }
+    }
+
+    /// The default RPC address of the given network, on localhost with a port per network so
+    /// that servers of different networks can run together
+    pub fn default_addr(network: &ElementsNetwork) -> SocketAddr {
+        let port = match network {
+            ElementsNetwork::Liquid => consts::DEFAULT_MAINNET_PORT,
+            ElementsNetwork::LiquidTestnet => consts::DEFAULT_TESTNET_PORT,
+            ElementsNetwork::ElementsRegtest { .. } => consts::DEFAULT_REGTEST_PORT,
+        };
+        SocketAddr::new(Ipv4Addr::LOCALHOST.into(), port)
+    }
+
+    /// Check the urls needed by the backend are set and they are not the defaults of another
+    /// network
+    pub fn validate(&self) -> Result<(), Error> {
+        let network = self.network.as_str();
+        let (name, url) = match self.backend {
+            Backend::Electrum => ("electrum url", &self.electrum_url),
+            Backend::Esplora => ("esplora api url", &self.esplora_api_url),
+            Backend::BlockFilter => ("node rpc url", &self.node_rpc_url),
+        };
+        if url.is_empty() {
+            return Err(Error::MissingConfigUrl(name.into(), network.into()));
         }
+        let others: &[&str] = match self.network {
+            ElementsNetwork::Liquid => &[TESTNET_ELECTRUM_URL, TESTNET_ESPLORA_API_URL],
+            ElementsNetwork::LiquidTestnet => &[MAINNET_ELECTRUM_URL, MAINNET_ESPLORA_API_URL],
+            ElementsNetwork::ElementsRegtest { .. } => &[
+                MAINNET_ELECTRUM_URL,
+                MAINNET_ESPLORA_API_URL,
+                TESTNET_ELECTRUM_URL,
+                TESTNET_ESPLORA_API_URL,
+            ],
+        };
+        for url in [&self.electrum_url, &self.esplora_api_url] {
+            if others.contains(&url.as_str()) {
+                return Err(Error::InconsistentConfigUrl(url.clone(), network.into()));
+            }
+        }
+        Ok(())
     }
 
     pub fn jade_network(&self) -> JadeNetwork {
@@ -125,6 +311,196 @@ impl Config {
 
     pub fn electrum_client(&self) -> Result<lwk_wollet::ElectrumClient, Error> {
         // TODO cache it instead of recreating every time
-        Ok(lwk_wollet::ElectrumClient::new(&self.electrum_url())?)
+        Ok(lwk_wollet::ElectrumClient::with_options(
+            &self.electrum_url(),
+            self.electrum_options.clone(),
+        )?)
+    }
+
+    /// The client of the configured [`Backend`]
+    pub fn blockchain_client(&self) -> Result<Box<dyn BlockchainBackend + Send>, Error> {
+        Ok(match self.backend {
+            Backend::Electrum => Box::new(self.electrum_client()?),
+            Backend::Esplora => Box::new(EsploraClient::new(
+                self.esplora_api_url.trim_end_matches('/'),
+            )),
+            Backend::BlockFilter => Box::new(BlockFilterClient::new(
+                &self.node_rpc_url,
+                None,
+                self.block_filter_start_height,
+            )),
+        })
+    }
+}
+
+/// Builder of a [`Config`] starting from the defaults of the network, see [`Config::builder()`]
+#[derive(Clone, Debug, Default)]
+pub struct ConfigBuilder {
+    network: Option<ElementsNetwork>,
+    addr: Option<SocketAddr>,
+    datadir: Option<PathBuf>,
+    backend: Option<Backend>,
+    electrum_url: Option<String>,
+    esplora_api_url: Option<String>,
+    node_rpc_url: Option<String>,
+}
+
+impl ConfigBuilder {
+    /// The network, Liquid testnet if not set
+    pub fn network(mut self, network: ElementsNetwork) -> Self {
+        self.network = Some(network);
+        self
+    }
+
+    /// The RPC address, if not set [`Config::default_addr()`] of the network
+    pub fn rpc_addr(mut self, addr: SocketAddr) -> Self {
+        self.addr = Some(addr);
+        self
+    }
+
+    /// The data directory, if not set [`Config::default_home()`]
+    pub fn datadir(mut self, datadir: PathBuf) -> Self {
+        self.datadir = Some(datadir);
+        self
+    }
+
+    /// The backend, electrum if not set
+    pub fn backend(mut self, backend: Backend) -> Self {
+        self.backend = Some(backend);
+        self
+    }
+
+    /// The electrum url, mandatory on regtest with the electrum backend
+    pub fn electrum_url(mut self, url: impl Into<String>) -> Self {
+        self.electrum_url = Some(url.into());
+        self
+    }
+
+    /// The esplora api url, mandatory on regtest with the esplora backend
+    pub fn esplora_api_url(mut self, url: impl Into<String>) -> Self {
+        self.esplora_api_url = Some(url.into());
+        self
+    }
+
+    /// The node rpc url, mandatory with the block filter backend
+    pub fn node_rpc_url(mut self, url: impl Into<String>) -> Self {
+        self.node_rpc_url = Some(url.into());
+        self
+    }
+
+    /// Build the config, failing if it is inconsistent, see [`Config::validate()`]
+    pub fn build(self) -> Result<Config, Error> {
+        let network = self.network.unwrap_or(ElementsNetwork::LiquidTestnet);
+        let datadir = match self.datadir {
+            Some(datadir) => datadir,
+            None => Config::default_home()?,
+        };
+        let mut config = match network {
+            ElementsNetwork::Liquid => Config::default_mainnet(datadir),
+            ElementsNetwork::LiquidTestnet => Config::default_testnet(datadir),
+            ElementsNetwork::ElementsRegtest { .. } => Config::default_regtest(datadir),
+        };
+        config.network = network;
+        if let Some(addr) = self.addr {
+            config.addr = addr;
+        }
+        if let Some(url) = self.electrum_url {
+            config.electrum_url = url;
+        }
+        if let Some(url) = self.esplora_api_url {
+            config.esplora_api_url = url;
+        }
+        if let Some(url) = self.node_rpc_url {
+            config.node_rpc_url = url;
+        }
+        if let Some(backend) = self.backend {
+            config.backend = backend;
+        }
+        config.validate()?;
+        Ok(config)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use lwk_wollet::ElementsNetwork;
+
+    use super::{Backend, Config};
+    use crate::Error;
+
+    #[test]
+    fn test_config_builder() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let datadir = tempdir.path().to_path_buf();
+        let builder = Config::builder().datadir(datadir.clone());
+
+        let config = builder.clone().build().unwrap();
+        assert_eq!(config.network, ElementsNetwork::LiquidTestnet);
+        assert_eq!(config.addr.port(), 32111);
+        assert_eq!(config.electrum_url, "blockstream.info:465");
+        assert_eq!(config.datadir, datadir);
+
+        let config = builder
+            .clone()
+            .network(ElementsNetwork::Liquid)
+            .build()
+            .unwrap();
+        assert_eq!(config.addr.port(), 32110);
+        assert_eq!(config.electrum_url, "blockstream.info:995");
+
+        let addr = "127.0.0.1:1234".parse().unwrap();
+        let config = builder
+            .clone()
+            .network(Config::regtest_network())
+            .rpc_addr(addr)
+            .electrum_url("127.0.0.1:50001")
+            .build()
+            .unwrap();
+        assert_eq!(config.addr, addr);
+        assert_eq!(config.electrum_url, "127.0.0.1:50001");
+        assert!(!config.tls);
+
+        // Regtest has no default urls
+        let regtest = builder.clone().network(Config::regtest_network());
+        let err = regtest.clone().build().unwrap_err();
+        assert!(matches!(err, Error::MissingConfigUrl(_, _)));
+        let err = regtest
+            .clone()
+            .backend(Backend::Esplora)
+            .electrum_url("127.0.0.1:50001")
+            .build()
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "The esplora api url must be specified on liquid-regtest"
+        );
+        let err = regtest
+            .clone()
+            .electrum_url("blockstream.info:465")
+            .build()
+            .unwrap_err();
+        assert!(matches!(err, Error::InconsistentConfigUrl(_, _)));
+
+        // Urls of another network
+        let err = builder
+            .clone()
+            .network(ElementsNetwork::Liquid)
+            .electrum_url("blockstream.info:465")
+            .build()
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "The url \"blockstream.info:465\" is a default of another network than liquid"
+        );
+        let err = builder
+            .clone()
+            .backend(Backend::Esplora)
+            .esplora_api_url("https://blockstream.info/liquid/api/")
+            .build()
+            .unwrap_err();
+        assert!(matches!(err, Error::InconsistentConfigUrl(_, _)));
+
+        let err = builder.backend(Backend::BlockFilter).build().unwrap_err();
+        assert!(matches!(err, Error::MissingConfigUrl(_, _)));
     }
 }
@@ -3,6 +3,17 @@ use std::time::Duration;
 
 pub const DEFAULT_ADDR: SocketAddrV4 = SocketAddrV4::new(Ipv4Addr::new(127, 0, 0, 1), 32_111);
 
+pub const DEFAULT_MAINNET_PORT: u16 = 32_110;
+
+pub const DEFAULT_TESTNET_PORT: u16 = 32_111;
+
+pub const DEFAULT_REGTEST_PORT: u16 = 32_112;
+
 pub const APP_VERSION: &str = env!("CARGO_PKG_VERSION");
 
+/// The oldest server version this client can talk to, see [`crate::check_server_version`]
+pub const MIN_SERVER_VERSION: &str = "0.5.0";
+
 pub const SCANNING_INTERVAL: Duration = Duration::from_secs(10);
+
+pub const SCAN_WORKERS: usize = 4;
@@ -1,5 +1,6 @@
 use std::sync::{MutexGuard, PoisonError};
 
+use lwk_rpc_model::error::ErrorCode;
 use lwk_tiny_jrpc::error::ImplementationDefinedCode;
 use serde_json::json;
 
@@ -68,6 +69,27 @@ pub enum Error {
     #[error("Wallet '{0}' is already loaded")]
     WalletAlreadyLoaded(String),
 
+    #[error("Wallet '{0}' is already loaded with a different descriptor")]
+    WalletDescriptorMismatch(String),
+
+    #[error("Wallet '{0}' is already loaded with a different gap limit or start height")]
+    WalletOptionsMismatch(String),
+
+    #[error("The {0} must be specified on {1}")]
+    MissingConfigUrl(String, String),
+
+    #[error("The url \"{0}\" is a default of another network than {1}")]
+    InconsistentConfigUrl(String, String),
+
+    #[error("Start height {0} is above the blockchain tip {1}")]
+    StartHeightAboveTip(u32, u32),
+
+    #[error("Nothing to consolidate, only {0} utxos of asset '{1}' would be spent, at least 2 are needed")]
+    NothingToConsolidate(usize, String),
+
+    #[error("The L-BTC balance of {0} satoshi is not enough to pay the fee of the drain")]
+    DrainFeeExceedsBalance(u64),
+
     #[error("Tx '{0}' was not found in wallet '{1}'")]
     WalletTxNotFound(String, String),
 
@@ -77,6 +99,15 @@ pub enum Error {
     #[error("Signer '{0}' is already loaded")]
     SignerAlreadyLoaded(String),
 
+    #[error("Signer '{0}' is not a Jade")]
+    SignerNotJade(String),
+
+    #[error("Mismatching addresses between wallet and jade, wallet: '{wallet}' jade: '{jade}'")]
+    AddressMismatch { wallet: String, jade: String },
+
+    #[error("Invalid address count {0}, it must be between 1 and {max}", max = lwk_rpc_model::request::MAX_ADDRESS_COUNT)]
+    InvalidAddressCount(u32),
+
     #[error("Asset '{0}' does not exist")]
     AssetNotExist(String),
 
@@ -86,6 +117,9 @@ pub enum Error {
     #[error("Given contract does not commit to asset '{0}'")]
     InvalidContractForAsset(String),
 
+    #[error("Timeout while getting data from the asset registry at \"{0}\"")]
+    RegistryTimeout(String),
+
     #[error("Asset '{0}' already inserted")]
     AssetAlreadyInserted(String),
 
@@ -107,42 +141,121 @@ pub enum Error {
     #[error("Received stop command")]
     Stop,
 
+    #[error(
+        "Cannot decrypt the server state, the state password is wrong or the data is corrupted"
+    )]
+    StateEncryption,
+
+    #[error("Wallet store locked, software signers can be persisted only if the server is started with a state password")]
+    StoreLocked,
+
+    #[error("Invalid version \"{0}\", expected MAJOR.MINOR.PATCH")]
+    InvalidVersion(String),
+
+    #[error("Server version {server} is not supported, this client requires at least {min}, upgrade the server")]
+    ServerVersionTooOld { server: String, min: String },
+
     // TODO remove into specific errors
     #[error("Generic error {0}")]
     Generic(String),
 }
 
 impl Error {
-    /// Return error codes, no different variants should return the same value
-    pub fn as_impl_defined_code(&self) -> ImplementationDefinedCode {
+    /// The stable code of the error returned via RPC
+    pub fn error_code(&self) -> ErrorCode {
         match self {
-            Error::Jade(_) => ImplementationDefinedCode::new(-32_013).expect("static"),
-            Error::Wollet(_) => ImplementationDefinedCode::new(-32_005).expect("static"),
-            Error::SignerNew(_) => ImplementationDefinedCode::new(-32_006).expect("static"),
-            Error::Signer(_) => ImplementationDefinedCode::new(-32_007).expect("static"),
-            Error::WalletNotExist(_) => ImplementationDefinedCode::new(-32_008).expect("static"),
-            Error::WalletAlreadyLoaded(_) => {
-                ImplementationDefinedCode::new(-32_009).expect("static")
-            }
-            Error::SignerNotExist(_) => ImplementationDefinedCode::new(-32_010).expect("static"),
-            Error::SignerAlreadyLoaded(_) => {
-                ImplementationDefinedCode::new(-32_011).expect("static")
-            }
-
-            _ => lwk_tiny_jrpc::error::GENERIC,
+            Error::RegistryTimeout(_) => ErrorCode::RegistryTimeout,
+            Error::Jade(_) => ErrorCode::Jade,
+            Error::Wollet(e) => wollet_error_code(e),
+            Error::SignerNew(_) => ErrorCode::InvalidSigner,
+            Error::Signer(_) => ErrorCode::SignerFailed,
+            Error::WalletNotExist(_) => ErrorCode::WalletNotFound,
+            Error::WalletAlreadyLoaded(_) => ErrorCode::WalletAlreadyLoaded,
+            Error::WalletDescriptorMismatch(_) => ErrorCode::WalletAlreadyLoaded,
+            Error::WalletOptionsMismatch(_) => ErrorCode::WalletAlreadyLoaded,
+            Error::SignerNotExist(_) => ErrorCode::SignerNotFound,
+            Error::SignerAlreadyLoaded(_) => ErrorCode::SignerAlreadyLoaded,
+            Error::AssetNotExist(_) => ErrorCode::AssetNotFound,
+            Error::AssetAlreadyInserted(_) => ErrorCode::AssetAlreadyInserted,
+            Error::DrainFeeExceedsBalance(_) => ErrorCode::InsufficientFunds,
+            Error::Address(_) => ErrorCode::InvalidAddress,
+            Error::WalletTxNotFound(_, _) => ErrorCode::TxNotFound,
+            Error::StoreLocked => ErrorCode::StoreLocked,
+            Error::AddressMismatch { .. } => ErrorCode::AddressMismatch,
+            _ => ErrorCode::Generic,
         }
     }
 
+    /// Return the JSON-RPC error code of the [`ErrorCode`] of this error
+    ///
+    /// Many variants map to the same [`ErrorCode`], for instance every error that means the
+    /// funds are not enough is [`ErrorCode::InsufficientFunds`], so variants can share the code.
+    pub fn as_impl_defined_code(&self) -> ImplementationDefinedCode {
+        ImplementationDefinedCode::new(self.error_code().code()).expect("in the allowed range")
+    }
+
     /// Used to create error as structured data, easily parsable by the caller
+    ///
+    /// The data is an object always containing the `error_code`.
     pub fn as_error_value(&self) -> Option<serde_json::Value> {
+        let mut data = match self {
+            Error::WalletNotExist(n) => json!({"name": n.to_string()}),
+            Error::SignerNotExist(n) => json!({"name": n.to_string()}),
+            Error::Wollet(lwk_wollet::Error::InvalidRecipients(invalid)) => {
+                json!({ "invalid_addressees": invalid })
+            }
+            Error::Wollet(lwk_wollet::Error::BroadcastRejected(reason)) => {
+                json!({ "reason": reason })
+            }
+            Error::AddressMismatch { wallet, jade } => {
+                json!({ "wallet_address": wallet, "device_address": jade })
+            }
+            Error::StartHeightAboveTip(start_height, tip_height) => {
+                json!({ "start_height": start_height, "tip_height": tip_height })
+            }
+            _ => json!({}),
+        };
+        data["error_code"] = json!(self.error_code());
+        Some(data)
+    }
+
+    /// The code of an error returned by the server, if it is known
+    pub fn rpc_error_code(&self) -> Option<ErrorCode> {
         match self {
-            Error::WalletNotExist(n) => Some(json!({"name": n.to_string()})),
-            Error::SignerNotExist(n) => Some(json!({"name": n.to_string()})),
+            Error::RpcError(e) => ErrorCode::from_code(e.code.into()),
             _ => None,
         }
     }
 }
 
+fn wollet_error_code(e: &lwk_wollet::Error) -> ErrorCode {
+    use lwk_wollet::Error as E;
+    match e {
+        E::InsufficientFunds => ErrorCode::InsufficientFunds,
+        E::AddressError(_) | E::NotConfidentialAddress | E::AddressNetworkMismatch(_, _) => {
+            ErrorCode::InvalidAddress
+        }
+        E::InvalidRecipients(_)
+        | E::InvalidRecipientFormat(_)
+        | E::InvalidDecimalAmount(_, _)
+        | E::MissingBurnAsset => ErrorCode::InvalidRecipients,
+        E::ElementsMiniscript(_)
+        | E::DescConversion(_)
+        | E::BlindingBareUnsupported
+        | E::BlindingViewWildcardUnsupported
+        | E::BlindingViewMultiUnsupported
+        | E::UnsupportedDescriptorWithoutWildcard
+        | E::UnsupportedMultipathDescriptor
+        | E::UnsupportedDescriptorNonV0 => ErrorCode::InvalidDescriptor,
+        E::BroadcastRejected(_) => ErrorCode::BroadcastRejected,
+        E::TxNotInWallet(_) => ErrorCode::TxNotFound,
+        E::ClientError(_) | E::ElectrumProxy(_, _) | E::Reqwest(_) | E::NodeRpc(_) => {
+            ErrorCode::Backend
+        }
+        _ => ErrorCode::Generic,
+    }
+}
+
 impl From<String> for Error {
     fn from(message: String) -> Self {
         Error::Generic(message)
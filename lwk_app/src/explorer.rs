@@ -19,7 +19,17 @@ pub struct RegistryData {
 pub fn get_registry_data(registry_url: &str, asset: &AssetId) -> Result<RegistryData, Error> {
     let url = format!("{registry_url}{asset}");
     tracing::debug!("getting registry data {url}");
-    let data: RegistryData = reqwest::blocking::get(url)?.json()?;
+    let timeout = |e: reqwest::Error| {
+        if e.is_timeout() {
+            Error::RegistryTimeout(registry_url.to_string())
+        } else {
+            e.into()
+        }
+    };
+    let data: RegistryData = reqwest::blocking::get(url)
+        .map_err(timeout)?
+        .json()
+        .map_err(timeout)?;
     Ok(data)
 }
 
@@ -11,7 +11,7 @@
 //!
 //! All the requests and responses data model are in the [`lwk_rpc_model`] crate.
 
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::net::SocketAddr;
 use std::num::NonZeroU8;
 use std::str::FromStr;
@@ -21,9 +21,9 @@ use std::thread::JoinHandle;
 use std::time::Duration;
 
 use lwk_common::{
-    address_to_text_qr, address_to_uri_qr, keyorigin_xpub_from_str, multisig_desc, singlesig_desc,
-    InvalidBipVariant, InvalidBlindingKeyVariant, InvalidMultisigVariant, InvalidSinglesigVariant,
-    Signer,
+    address_to_text_qr, address_to_uri_qr, keyorigin_xpub_from_str, multisig_desc,
+    signatures_threshold, singlesig_desc, Bip, InvalidBlindingKeyVariant, Multisig, Signer,
+    Singlesig,
 };
 use lwk_jade::derivation_path_to_vec;
 use lwk_jade::get_receive_address::Variant;
@@ -31,28 +31,35 @@ use lwk_jade::register_multisig::{JadeDescriptor, RegisterMultisigParams};
 use lwk_jade::Jade;
 use lwk_signer::{AnySigner, SwSigner};
 use lwk_tiny_jrpc::{tiny_http, JsonRpcServer, Request, Response};
-use lwk_wollet::bitcoin::bip32::Fingerprint;
-use lwk_wollet::bitcoin::XKeyIdentifier;
+use lwk_wollet::bitcoin::bip32::{ChildNumber, DerivationPath, Fingerprint, KeySource, Xpub};
+use lwk_wollet::bitcoin::{PublicKey, XKeyIdentifier};
+use lwk_wollet::elements::address::Payload;
 use lwk_wollet::elements::encode::serialize;
 use lwk_wollet::elements::hex::{FromHex, ToHex};
 use lwk_wollet::elements::pset::PartiallySignedTransaction;
-use lwk_wollet::elements::{Address, AssetId, Txid};
-use lwk_wollet::elements_miniscript::descriptor::{Descriptor, DescriptorType, WshInner};
+use lwk_wollet::elements::{Address, AssetId, OutPoint, Script, Txid};
+use lwk_wollet::elements_miniscript::descriptor::{
+    Descriptor, DescriptorType, SinglePubKey, WshInner,
+};
 use lwk_wollet::elements_miniscript::miniscript::decode::Terminal;
-use lwk_wollet::elements_miniscript::{DescriptorPublicKey, ForEachKey};
-use lwk_wollet::{full_scan_with_electrum_client, Wollet};
-use lwk_wollet::{BlockchainBackend, WolletDescriptor};
+use lwk_wollet::elements_miniscript::{
+    ConfidentialDescriptor, DefiniteDescriptorKey, DescriptorPublicKey, ForEachKey,
+};
+use lwk_wollet::{BlockchainBackend, Update, Wollet};
+use lwk_wollet::{CsvRow, WolletDescriptor};
 use serde_json::Value;
+use zeroize::Zeroizing;
 
-use crate::explorer::{get_registry_data, get_tx};
+use crate::explorer::{get_registry_data, get_tx, RegistryData};
 use crate::method::Method;
-use crate::state::{AppAsset, AppSigner, State};
+use crate::state::{AppAsset, AppSigner, EncryptedRequest, State};
 use lwk_rpc_model::{request, response};
 
-pub use client::Client;
-pub use config::Config;
+pub use client::{check_server_version, Client, VersionCompatibility};
+pub use config::{Backend, Config, ConfigBuilder, StateKey};
 pub use error::Error;
 pub use lwk_tiny_jrpc::RpcError;
+pub use lwk_wollet::ElementsNetwork;
 
 mod client;
 mod config;
@@ -60,6 +67,7 @@ pub mod consts;
 mod error;
 mod explorer;
 pub mod method;
+mod openrpc;
 mod reqwest_transport;
 mod state;
 
@@ -86,31 +94,34 @@ impl App {
         })
     }
 
-    fn apply_request(&self, client: &Client, line: &str) -> Result<(), Error> {
-        let r: Request = serde_json::from_str(line)?;
+    /// Apply a request of the persisted state
+    ///
+    /// If the request is encrypted and the store is locked, it is returned without applying it.
+    fn apply_request(
+        &self,
+        client: &Client,
+        line: &str,
+    ) -> Result<Option<EncryptedRequest>, Error> {
+        let r: Request = match serde_json::from_str::<EncryptedRequest>(line) {
+            Ok(encrypted) => match self.config.state_key.as_ref() {
+                Some(key) => {
+                    let data = Vec::<u8>::from_hex(&encrypted.encrypted)?;
+                    serde_json::from_slice(&key.decrypt(&data)?)?
+                }
+                None => return Ok(Some(encrypted)),
+            },
+            Err(_) => serde_json::from_str(line)?,
+        };
         let method: Method = r.method.parse()?;
         let _value: Value = client.make_request(method, r.params)?;
-        Ok(())
+        Ok(None)
     }
 
     pub fn run(&mut self) -> Result<(), Error> {
         if self.rpc.is_some() {
             return Err(error::Error::AlreadyStarted);
         }
-        let mut state = State {
-            config: self.config.clone(),
-            wollets: Default::default(),
-            signers: Default::default(),
-            assets: Default::default(),
-            tx_memos: Default::default(),
-            addr_memos: Default::default(),
-            do_persist: false,
-            scan_loops_started: 0,
-            scan_loops_completed: 0,
-            interrupt_wait: false,
-        };
-        state.insert_policy_asset();
-        let state = Arc::new(Mutex::new(state));
+        let state = Arc::new(Mutex::new(State::new(self.config.clone())));
         let server = tiny_http::Server::http(self.config.addr)
             .map_err(|_| Error::ServerStart(self.config.addr.to_string()))?;
 
@@ -122,6 +133,7 @@ impl App {
 
         let rpc = lwk_tiny_jrpc::JsonRpcServer::new(server, config, state.clone(), method_handler);
         let path = self.config.state_path()?;
+        let mut locked_requests = vec![];
         match std::fs::read_to_string(&path) {
             Ok(string) => {
                 tracing::info!(
@@ -132,16 +144,31 @@ impl App {
                 let client = self.client()?;
 
                 for (n, line) in string.lines().enumerate() {
-                    self.apply_request(&client, line).map_err(|err| {
+                    let locked = self.apply_request(&client, line).map_err(|err| {
                         Error::StartStateLoad(err.to_string(), n + 1, path.display().to_string())
-                    })?
+                    })?;
+                    locked_requests.extend(locked);
+                }
+                if !locked_requests.is_empty() {
+                    tracing::warn!(
+                        "Wallet store locked, {} encrypted elements not loaded, start the server with the state password to load them",
+                        locked_requests.len()
+                    );
                 }
             }
             Err(_) => {
                 tracing::info!("There is no previous state at {path:?}");
             }
         }
-        state.lock().map_err(|e| e.to_string())?.do_persist = true;
+        {
+            let mut state = state.lock().map_err(|e| e.to_string())?;
+            state.locked_requests = locked_requests;
+            state.do_persist = true;
+            if self.config.state_key.is_some() && path.exists() {
+                // Rewrite the state so that secrets persisted by previous versions are encrypted
+                state.persist_all()?;
+            }
+        }
 
         self.rpc = Some(rpc);
 
@@ -149,38 +176,35 @@ impl App {
         self.is_scanning.store(true, Ordering::Relaxed);
         let is_scanning = self.is_scanning.clone();
         let state_scanning = state.clone();
+        let config = self.config.clone();
         let scanning_interval = self.config.scanning_interval;
         let stop_interval = Duration::from_millis(100);
         let mut interval = Duration::ZERO; // Do not wait in the first scan loop
-        let scanning_handle = std::thread::spawn(move || 'scan: loop {
-            // Sleep for scanning_interval, but check stop signal every stop_interval
-            'stop: loop {
-                if !is_scanning.load(Ordering::Relaxed) {
-                    break 'scan;
-                }
-                if interval == Duration::ZERO
-                    || state_scanning
-                        .lock()
-                        .map(|s| s.interrupt_wait)
-                        .unwrap_or(false)
-                {
-                    interval = scanning_interval; // Reset wait interval
-                    break 'stop;
+        let scanning_handle = std::thread::spawn(move || {
+            let mut scanner = Scanner::default();
+            'scan: loop {
+                // Sleep for scanning_interval, but check stop signal every stop_interval
+                'stop: loop {
+                    if !is_scanning.load(Ordering::Relaxed) {
+                        break 'scan;
+                    }
+                    if interval == Duration::ZERO
+                        || state_scanning
+                            .lock()
+                            .map(|s| s.interrupt_wait)
+                            .unwrap_or(false)
+                    {
+                        interval = scanning_interval; // Reset wait interval
+                        break 'stop;
+                    }
+                    std::thread::sleep(stop_interval);
+                    interval = interval.saturating_sub(stop_interval);
                 }
-                std::thread::sleep(stop_interval);
-                interval = interval.saturating_sub(stop_interval);
-            }
 
-            if let Ok(mut s) = state_scanning.lock() {
-                s.interrupt_wait = false;
-                s.scan_loops_started += 1;
-                if let Ok(mut electrum_client) = s.config.electrum_client() {
-                    for (_name, wollet) in s.wollets.iter_mut() {
-                        // TODO: release lock when doing network calls
-                        let _ = full_scan_with_electrum_client(wollet, &mut electrum_client);
-                    }
+                if let Some((to_scan, unloaded)) = start_scan(&state_scanning) {
+                    scanner.scan(&state_scanning, &config, &to_scan, &unloaded);
+                    finish_scan(&state_scanning);
                 }
-                s.scan_loops_completed += 1;
             }
         });
         self.scanning_handle = Some(scanning_handle);
@@ -226,6 +250,158 @@ impl App {
     }
 }
 
+/// Copies of the loaded wallets, by name, see [`Wollet::snapshot()`]
+type Snapshots = Vec<(String, Wollet)>;
+
+/// Start a scan loop, returning a snapshot of the loaded wallets and the wallets unloaded since
+/// the last loop
+///
+/// The state is locked only to take the snapshots, the wallets are scanned without holding it.
+fn start_scan(state: &Mutex<State>) -> Option<(Snapshots, Vec<Wollet>)> {
+    let mut s = state.lock().ok()?;
+    s.interrupt_wait = false;
+    s.scan_loops_started += 1;
+    let to_scan: Snapshots = s
+        .wollets
+        .iter()
+        .map(|(name, wollet)| (name.to_string(), wollet.snapshot()))
+        .collect();
+    let unloaded = std::mem::take(&mut s.unloaded);
+    s.scanning = to_scan.iter().map(|(name, _)| name.to_string()).collect();
+    Some((to_scan, unloaded))
+}
+
+/// Complete the scan of the wallet called `name`, applying the `update` obtained scanning `snapshot`
+///
+/// The update is discarded if meanwhile the wallet was unloaded or changed, for instance by a
+/// rescan, it will be scanned again in the next loop.
+fn apply_scan(state: &Mutex<State>, name: &str, snapshot: &Wollet, update: Option<Update>) {
+    let status = snapshot.status();
+    if let Ok(mut s) = state.lock() {
+        s.scanning.remove(name);
+        s.scanned.notify_all();
+        // Without an update the snapshot is at the tip
+        s.tip_height = Some(match &update {
+            Some(update) => update.tip.height,
+            None => snapshot.tip().height(),
+        });
+        if let (Ok(wollet), Some(update)) = (s.wollets.get_mut(name), update) {
+            if wollet.status() == status {
+                let _ = wollet.apply_update(update);
+            }
+        }
+    }
+}
+
+fn finish_scan(state: &Mutex<State>) {
+    if let Ok(mut s) = state.lock() {
+        // Wallets not scanned because their client failed
+        s.scanning.clear();
+        s.scanned.notify_all();
+        s.scan_loops_completed += 1;
+    }
+}
+
+/// The clients of the scanning thread and the wallets each one scans
+///
+/// Clients are created if missing and kept across scans, since they cache the already fetched
+/// history and only fetch the new one, thus a wallet is always scanned by the same client.
+/// Clients that failed are dropped, to be created again on the next scan.
+#[derive(Default)]
+struct Scanner {
+    clients: Vec<Option<Box<dyn BlockchainBackend + Send>>>,
+
+    /// The index of the client scanning each wallet, by descriptor
+    assigned: HashMap<String, usize>,
+}
+
+impl Scanner {
+    /// Scan the wallets concurrently with `config.scan_workers` workers, each one with its own
+    /// client, after dropping the cache of the `unloaded` wallets
+    ///
+    /// New wallets are assigned to the client scanning fewer wallets.
+    fn scan(
+        &mut self,
+        state: &Mutex<State>,
+        config: &Config,
+        to_scan: &[(String, Wollet)],
+        unloaded: &[Wollet],
+    ) {
+        let workers = match config.backend {
+            // Used sequentially, to download the filters only once
+            Backend::BlockFilter => 1,
+            _ => config.scan_workers.max(1),
+        };
+        self.clients.resize_with(workers, || None);
+
+        for wollet in unloaded {
+            if let Some(i) = self.assigned.remove(&wollet.descriptor().to_string()) {
+                let failed = self.clients[i]
+                    .as_ref()
+                    .map_or(false, |client| client.forget_wollet(wollet).is_err());
+                if failed {
+                    self.clients[i] = None;
+                }
+            }
+        }
+
+        let mut load = vec![0usize; workers];
+        for i in self.assigned.values() {
+            load[*i] += 1;
+        }
+        let mut batches: Vec<Vec<&(String, Wollet)>> = vec![vec![]; workers];
+        for item in to_scan {
+            let descriptor = item.1.descriptor().to_string();
+            let i = match self.assigned.get(&descriptor) {
+                Some(i) => *i,
+                None => {
+                    let i = (0..workers).min_by_key(|i| load[*i]).unwrap_or(0);
+                    load[i] += 1;
+                    self.assigned.insert(descriptor, i);
+                    i
+                }
+            };
+            batches[i].push(item);
+        }
+
+        for (client, batch) in self.clients.iter_mut().zip(batches.iter()) {
+            if client.is_none() && !batch.is_empty() {
+                *client = config.blockchain_client().ok();
+            }
+        }
+        let failed: Vec<bool> = std::thread::scope(|scope| {
+            let handles: Vec<_> = self
+                .clients
+                .iter_mut()
+                .zip(batches.iter())
+                .map(|(client, batch)| {
+                    scope.spawn(move || {
+                        let Some(client) = client else {
+                            return false;
+                        };
+                        for (name, wollet) in batch {
+                            match client.full_scan(wollet) {
+                                Ok(update) => apply_scan(state, name, wollet, update),
+                                Err(_) => return true,
+                            }
+                        }
+                        false
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .map(|h| h.join().unwrap_or(true))
+                .collect()
+        });
+        for (client, failed) in self.clients.iter_mut().zip(failed) {
+            if failed {
+                *client = None;
+            }
+        }
+    }
+}
+
 fn method_handler(
     request: Request,
     state: Arc<Mutex<State>>,
@@ -255,8 +431,32 @@ fn inner_method_handler(request: Request, state: Arc<Mutex<State>>) -> Result<Re
             let method: Method = r.method.parse()?;
             Response::result(request.id, method.schema(r.direction)?)
         }
+        Method::SchemaAll => {
+            let mut methods = BTreeMap::new();
+            for method in enum_iterator::all::<Method>() {
+                let schema = response::MethodSchema {
+                    request: method.schema(request::Direction::Request)?,
+                    response: method.schema(request::Direction::Response)?,
+                };
+                methods.insert(method.to_string(), schema);
+            }
+            Response::result(
+                request.id,
+                serde_json::to_value(response::SchemaAll { methods })?,
+            )
+        }
+        Method::Openrpc => Response::result(request.id, openrpc::openrpc()?),
         Method::SignerGenerate => {
-            let (_signer, mnemonic) = SwSigner::random(state.lock()?.config.is_mainnet())?;
+            // params can be omitted since all the fields are optional
+            let r: request::SignerGenerate = match params {
+                Value::Null => Default::default(),
+                params => serde_json::from_value(params)?,
+            };
+            let word_count = r.word_count.unwrap_or(request::DEFAULT_WORD_COUNT);
+            let (_signer, mnemonic) = SwSigner::random_with_word_count(
+                word_count as usize,
+                state.lock()?.config.is_mainnet(),
+            )?;
             Response::result(
                 request.id,
                 serde_json::to_value(response::SignerGenerate {
@@ -274,13 +474,43 @@ fn inner_method_handler(request: Request, state: Arc<Mutex<State>>) -> Result<Re
                 })?,
             )
         }
+        Method::NetworkDetails => {
+            let s = state.lock()?;
+            let explorer_url = &s.config.explorer_url;
+            Response::result(
+                request.id,
+                serde_json::to_value(response::NetworkDetails {
+                    network: s.config.network.as_str().to_string(),
+                    policy_asset: s.config.network.policy_asset().to_string(),
+                    explorer_url: (!explorer_url.is_empty()).then(|| explorer_url.clone()),
+                })?,
+            )
+        }
         Method::WalletLoad => {
             let r: request::WalletLoad = serde_json::from_value(params)?;
             let mut s = state.lock()?;
-            // TODO recognize different name same descriptor?
 
             let desc: WolletDescriptor = r.descriptor.parse()?;
-            let wollet = Wollet::with_fs_persist(s.config.network, desc, &s.config.datadir)?;
+            let gap_limit = r.gap_limit.unwrap_or(s.config.gap_limit);
+            if let Ok(existing) = s.wollets.get(&r.name) {
+                // Loading the same wallet again is a no-op
+                if existing.descriptor().to_string() != desc.to_string() {
+                    return Err(Error::WalletDescriptorMismatch(r.name));
+                }
+                if existing.gap_limit() != gap_limit || existing.start_height() != r.start_height {
+                    return Err(Error::WalletOptionsMismatch(r.name));
+                }
+                return Ok(Response::result(
+                    request.id,
+                    serde_json::to_value(response::Wallet {
+                        descriptor: existing.descriptor().to_string(),
+                        name: r.name,
+                    })?,
+                ));
+            }
+            let mut wollet = Wollet::with_fs_persist(s.config.network, desc, &s.config.datadir)?;
+            wollet.set_gap_limit(gap_limit)?;
+            wollet.set_start_height(r.start_height);
             s.wollets.insert(&r.name, wollet)?;
 
             s.persist(&request)?;
@@ -295,43 +525,101 @@ fn inner_method_handler(request: Request, state: Arc<Mutex<State>>) -> Result<Re
         }
         Method::WalletUnload => {
             let r: request::WalletUnload = serde_json::from_value(params)?;
-            let mut s = state.lock()?;
+            // Wait for the wallet scan of the current loop, so that its update is applied and
+            // persisted, and not discarded as the one of a wallet not loaded anymore
+            let s = state.lock()?;
+            let scanned = s.scanned.clone();
+            let mut s = scanned.wait_while(s, |s| s.scanning.contains(&r.name))?;
             let removed = s.wollets.remove(&r.name)?;
             s.tx_memos.remove(&r.name);
             s.addr_memos.remove(&r.name);
+            s.output_memos.remove(&r.name);
             s.persist_all()?;
+            let descriptor = removed.descriptor().to_string();
+            s.unloaded.push(removed);
 
             Response::result(
                 request.id,
                 serde_json::to_value(response::WalletUnload {
                     unloaded: response::Wallet {
                         name: r.name,
-                        descriptor: removed.descriptor().to_string(),
+                        descriptor,
                     },
                 })?,
             )
         }
+        Method::WalletRescan => {
+            let r: request::WalletRescan = serde_json::from_value(params)?;
+            let config = {
+                let s = state.lock()?;
+                s.wollets.get(&r.name)?;
+                s.config.clone()
+            };
+            // Without holding the state lock, not to block the other requests on the backend
+            let tip_height = config.blockchain_client()?.tip()?.height;
+            let mut s = state.lock()?;
+            if let Some(start_height) = r.start_height {
+                if start_height > tip_height {
+                    return Err(Error::StartHeightAboveTip(start_height, tip_height));
+                }
+            }
+            s.wollets.get_mut(&r.name)?.reset_scan(r.start_height)?;
+            s.persist_all()?;
+            s.interrupt_wait = true;
+
+            Response::result(
+                request.id,
+                serde_json::to_value(response::WalletRescan {
+                    start_height: r.start_height,
+                    tip_height,
+                })?,
+            )
+        }
         Method::WalletList => {
             let s = state.lock()?;
-            let wallets = s
+            let mut wallets: Vec<_> = s
                 .wollets
                 .iter()
-                .map(|(name, wollet)| response::Wallet {
-                    descriptor: wollet.descriptor().to_string(),
-                    name: name.clone(),
+                .map(|(name, wollet)| {
+                    let descriptor = wollet.descriptor().to_string();
+                    let checksum = descriptor
+                        .rsplit_once('#')
+                        .map(|(_, c)| c.to_string())
+                        .unwrap_or_default();
+                    response::WalletSummary {
+                        name: name.clone(),
+                        descriptor,
+                        checksum,
+                        network: wollet.network().as_str().to_string(),
+                    }
                 })
                 .collect();
+            wallets.sort_by(|a, b| a.name.cmp(&b.name));
             let r = response::WalletList { wallets };
             Response::result(request.id, serde_json::to_value(r)?)
         }
         Method::SignerLoadSoftware => {
             let r: request::SignerLoadSoftware = serde_json::from_value(params)?;
             let mut s = state.lock()?;
-            let signer = AppSigner::new_sw(&r.mnemonic, s.config.is_mainnet(), r.persist)?;
+            if r.persist && s.config.state_key.is_none() {
+                if s.do_persist {
+                    return Err(Error::StoreLocked);
+                }
+                // Persisted in plaintext by a previous version, kept as it is until the server
+                // is started with a state password
+                tracing::warn!(
+                    "Software signer '{}' persisted in plaintext, start the server with a state password to encrypt it",
+                    r.name
+                );
+            }
+            let mnemonic = Zeroizing::new(r.mnemonic);
+            let passphrase = r.passphrase.map(Zeroizing::new);
+            let signer =
+                AppSigner::new_sw(&mnemonic, passphrase, s.config.is_mainnet(), r.persist)?;
             let resp: response::Signer = signer_response_from(&r.name, &signer)?;
             s.signers.insert(&r.name, signer)?;
             if r.persist {
-                s.persist(&request)?;
+                s.persist_encrypted(&request)?;
             }
             Response::result(request.id, serde_json::to_value(resp)?)
         }
@@ -356,6 +644,17 @@ fn inner_method_handler(request: Request, state: Arc<Mutex<State>>) -> Result<Re
             s.persist(&request)?;
             Response::result(request.id, serde_json::to_value(resp)?)
         }
+        Method::SignerLoadHwi => {
+            let r: request::SignerLoadHwi = serde_json::from_value(params)?;
+            let mut s = state.lock()?;
+            let fingerprint =
+                Fingerprint::from_str(&r.fingerprint).map_err(|e| Error::Generic(e.to_string()))?;
+            let signer =
+                AppSigner::new_hwi(fingerprint, r.device_path.as_deref(), s.config.is_mainnet())?;
+            let resp: response::Signer = signer_response_from(&r.name, &signer)?;
+            s.signers.insert(&r.name, signer)?;
+            Response::result(request.id, serde_json::to_value(resp)?)
+        }
         Method::SignerUnload => {
             let r: request::SignerUnload = serde_json::from_value(params)?;
             let mut s = state.lock()?;
@@ -386,79 +685,67 @@ fn inner_method_handler(request: Request, state: Arc<Mutex<State>>) -> Result<Re
             let r = response::SignerList { signers };
             Response::result(request.id, serde_json::to_value(r)?)
         }
+        Method::WalletAddresses => {
+            let r: request::WalletAddresses = serde_json::from_value(params)?;
+            if r.count == 0 || r.count > request::MAX_ADDRESS_COUNT {
+                return Err(Error::InvalidAddressCount(r.count));
+            }
+            let mut s = state.lock()?;
+
+            let confidential = r.confidential.unwrap_or(true);
+            let form = |addr: lwk_wollet::AddressResult| {
+                if confidential {
+                    addr
+                } else {
+                    addr.to_unconfidential()
+                }
+            };
+
+            let wollet = s.wollets.get_mut(&r.name)?;
+            let start = match r.index {
+                Some(index) => index,
+                None => wollet.address(None)?.index(),
+            };
+            let addrs = (start..start.saturating_add(r.count))
+                .map(|index| wollet.address(Some(index)).map(form))
+                .collect::<Result<Vec<_>, _>>()?;
+            let memos = s.addr_memos.for_wollet(&r.name);
+            let addresses = addrs
+                .iter()
+                .map(|addr| wallet_address_response(addr, &memos, r.with_text_qr, r.with_uri_qr))
+                .collect::<Result<Vec<_>, _>>()?;
+            let r = response::WalletAddresses { addresses };
+            Response::result(request.id, serde_json::to_value(r)?)
+        }
         Method::WalletAddress => {
             let r: request::WalletAddress = serde_json::from_value(params)?;
             let mut s = state.lock()?;
 
+            let confidential = r.confidential.unwrap_or(true);
+            let form = |addr: lwk_wollet::AddressResult| {
+                if confidential {
+                    addr
+                } else {
+                    addr.to_unconfidential()
+                }
+            };
+
             let wollet = s.wollets.get_mut(&r.name)?;
             let addr = wollet.address(r.index)?;
             let definite_desc = wollet
                 .wollet_descriptor()
                 .definite_descriptor(lwk_wollet::Chain::External, addr.index())?;
 
-            let text_qr = r
-                .with_text_qr
-                .then(|| address_to_text_qr(addr.address()))
-                .transpose()?;
-            let uri_qr = r
-                .with_uri_qr
-                .map(|e| {
-                    let pixel_per_module = (e != 0).then_some(e);
-                    address_to_uri_qr(addr.address(), pixel_per_module)
-                })
-                .transpose()?;
-
             if let Some(signer) = r.signer {
                 let signer = s.get_available_signer(&signer)?;
                 if let AnySigner::Jade(jade, _id) = signer {
                     let fingerprint = signer.fingerprint()?;
-
-                    // Get the derivation paths for all signers
-                    let mut paths: Vec<Vec<u32>> = vec![];
-                    // Get the full path for the signer
-                    let mut full_path: Vec<u32> = vec![];
-                    definite_desc.for_each_key(|k| {
-                        if k.master_fingerprint() == fingerprint {
-                            if let Some(path) = k.full_derivation_path() {
-                                full_path = derivation_path_to_vec(&path);
-                            }
-                        }
-                        if let DescriptorPublicKey::XPub(x) = k.as_descriptor_public_key() {
-                            paths.push(derivation_path_to_vec(&x.derivation_path));
-                        }
-                        true
-                    });
-
-                    if full_path.is_empty() {
-                        return Err(Error::Generic("Signer is not in wallet".into()));
-                    }
-                    let jade_addr = match paths.len() {
-                        0 => return Err(Error::Generic("Unsupported signer or descriptor".into())),
-                        1 => {
-                            // Single sig
-                            match definite_desc.desc_type() {
-                                DescriptorType::Wpkh => {
-                                    jade.get_receive_address_single(Variant::Wpkh, full_path)?
-                                }
-                                DescriptorType::ShWpkh => {
-                                    jade.get_receive_address_single(Variant::ShWpkh, full_path)?
-                                }
-                                _ => {
-                                    return Err(Error::Generic(
-                                        "Unsupported signer or descriptor".into(),
-                                    ))
-                                }
-                            }
-                        }
-                        _ => {
-                            // Multi sig
-                            jade.get_receive_address_multi(&r.name, paths)?
-                        }
-                    };
+                    let jade_addr = jade_address(jade, fingerprint, &r.name, &definite_desc)?;
                     if jade_addr != addr.address().to_string() {
-                        return Err(Error::Generic(
-                            "Mismatching addresses between wallet and jade".into(),
-                        ));
+                        return Err(Error::AddressMismatch {
+                            wallet: addr.address().to_string(),
+                            jade: jade_addr,
+                        });
                     }
                 } else {
                     return Err(Error::Generic(
@@ -467,35 +754,54 @@ fn inner_method_handler(request: Request, state: Arc<Mutex<State>>) -> Result<Re
                 }
             };
 
-            let address = addr.address();
+            let addr = form(addr);
             let memos = s.addr_memos.for_wollet(&r.name);
-            let memo = memos.get(address).cloned().unwrap_or_default();
-            Response::result(
-                request.id,
-                serde_json::to_value(response::WalletAddress {
-                    address: address.to_string(),
-                    index: addr.index(),
-                    memo,
-                    text_qr,
-                    uri_qr,
-                })?,
-            )
+            let r = wallet_address_response(&addr, &memos, r.with_text_qr, r.with_uri_qr)?;
+            Response::result(request.id, serde_json::to_value(r)?)
         }
         Method::WalletBalance => {
             let r: request::WalletBalance = serde_json::from_value(params)?;
             let mut s = state.lock()?;
             let wollet = s.wollets.get_mut(&r.name)?;
-            let mut balance = wollet
+            let mut balance: HashMap<String, i64> = wollet
                 .balance()?
                 .into_iter()
                 .map(|(k, v)| (k.to_string(), v as i64))
                 .collect();
+            let mut detailed = if r.detailed {
+                let mut detailed = HashMap::new();
+                detailed.insert(
+                    wollet.policy_asset().to_string(),
+                    response::AssetBalance::default(),
+                );
+                for utxo in wollet.utxos()? {
+                    let entry: &mut response::AssetBalance = detailed
+                        .entry(utxo.unblinded.asset.to_string())
+                        .or_default();
+                    match utxo.height {
+                        Some(_) => entry.confirmed += utxo.unblinded.value,
+                        None => entry.unconfirmed += utxo.unblinded.value,
+                    }
+                    entry.total += utxo.unblinded.value;
+                }
+                Some(detailed)
+            } else {
+                None
+            };
+            let mut balance_formatted = None;
             if r.with_tickers {
+                balance_formatted = Some(s.format_balance(balance.clone()));
                 balance = s.replace_id_with_ticker(balance);
+                detailed =
+                    detailed.map(|d| d.into_iter().map(|(k, v)| (s.ticker_or_id(k), v)).collect());
             }
             Response::result(
                 request.id,
-                serde_json::to_value(response::WalletBalance { balance })?,
+                serde_json::to_value(response::WalletBalance {
+                    balance,
+                    detailed,
+                    balance_formatted,
+                })?,
             )
         }
         Method::WalletSendMany => {
@@ -507,12 +813,76 @@ fn inner_method_handler(request: Request, state: Arc<Mutex<State>>) -> Result<Re
                 .addressees
                 .into_iter()
                 .map(unvalidated_addressee)
-                .collect();
-            let mut tx = wollet
+                .collect::<Result<_, _>>()?;
+            let fee_rate = r.fee_rate.map(|f| fee_rate_kvb(f, r.fee_rate_unit));
+            let mut builder = wollet
                 .tx_builder()
                 .set_unvalidated_recipients(&recipients)?
-                .fee_rate(r.fee_rate)
-                .finish()?;
+                .fee_rate(fee_rate);
+            if r.enable_rbf {
+                builder = builder.enable_rbf();
+            }
+            if let Some(address) = r.change_address {
+                builder = builder.set_change_address(Address::from_str(&address)?)?;
+            }
+            if let Some(utxos) = r.utxos {
+                let utxos = utxos
+                    .iter()
+                    .map(|u| OutPoint::from_str(u))
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|e| e.to_string())?;
+                builder = builder.set_wallet_utxos(utxos);
+            }
+            if let Some(strategy) = r.coin_selection {
+                builder = builder.coin_selection(coin_selection(strategy));
+            }
+            let mut tx = builder.finish()?;
+
+            let recipient_scripts = recipients
+                .iter()
+                .map(|r| Ok(r.validate(wollet.network())?.script_pubkey))
+                .collect::<Result<HashSet<_>, Error>>()?;
+            add_contracts(&mut tx, s.assets.iter());
+            Response::result(
+                request.id,
+                serde_json::to_value(send_response(&tx, &recipient_scripts, r.dry_run))?,
+            )
+        }
+        Method::WalletEstimateFee => {
+            let r: request::WalletEstimateFee = serde_json::from_value(params)?;
+            let s = state.lock()?;
+            let wollet = s.wollets.get(&r.name)?;
+
+            let recipients: Vec<_> = r
+                .addressees
+                .into_iter()
+                .map(unvalidated_addressee)
+                .collect::<Result<_, _>>()?;
+            let fee_rate = r.fee_rate.map(|f| fee_rate_kvb(f, r.fee_rate_unit));
+            let mut builder = wollet
+                .tx_builder()
+                .set_unvalidated_recipients(&recipients)?
+                .fee_rate(fee_rate);
+            if let Some(strategy) = r.coin_selection {
+                builder = builder.coin_selection(coin_selection(strategy));
+            }
+            let estimate = builder.estimate_fee()?;
+            Response::result(
+                request.id,
+                serde_json::to_value(response::WalletEstimateFee {
+                    vsize: estimate.vsize,
+                    fee: estimate.fee,
+                    inputs: estimate.inputs,
+                })?,
+            )
+        }
+        Method::WalletBumpFee => {
+            let r: request::WalletBumpFee = serde_json::from_value(params)?;
+            let mut s = state.lock()?;
+            let wollet: &mut Wollet = s.wollets.get_mut(&r.name)?;
+
+            let txid = Txid::from_str(&r.txid)?;
+            let mut tx = wollet.bump_fee(&txid, fee_rate_kvb(r.fee_rate, r.fee_rate_unit))?;
 
             add_contracts(&mut tx, s.assets.iter());
             Response::result(
@@ -528,12 +898,36 @@ fn inner_method_handler(request: Request, state: Arc<Mutex<State>>) -> Result<Re
             let wollet: &mut Wollet = s.wollets.get_mut(&r.name)?;
 
             let address = Address::from_str(&r.address)?;
-            let mut tx = wollet
+            if !address.is_blinded() {
+                return Err(lwk_wollet::Error::NotConfidentialAddress.into());
+            }
+            let policy_asset = wollet.policy_asset();
+            let balance = wollet.balance()?;
+            let mut builder = wollet
                 .tx_builder()
                 .drain_lbtc_wallet()
                 .drain_lbtc_to(address)
-                .fee_rate(r.fee_rate)
-                .finish()?;
+                .fee_rate(r.fee_rate.map(|f| fee_rate_kvb(f, r.fee_rate_unit)));
+            // Every other asset is sent in full in its own output
+            for asset in balance
+                .iter()
+                .filter(|(asset, satoshi)| **asset != policy_asset && **satoshi > 0)
+                .map(|(asset, _)| asset)
+            {
+                builder = builder.add_unvalidated_recipient(&lwk_wollet::UnvalidatedRecipient {
+                    satoshi: None,
+                    address: r.address.clone(),
+                    asset: asset.to_string(),
+                    timelock: None,
+                })?;
+            }
+            let mut tx = match builder.finish() {
+                Err(lwk_wollet::Error::InsufficientFunds) => {
+                    let lbtc = balance.get(&policy_asset).copied().unwrap_or(0);
+                    return Err(Error::DrainFeeExceedsBalance(lbtc));
+                }
+                r => r?,
+            };
 
             add_contracts(&mut tx, s.assets.iter());
             Response::result(
@@ -550,10 +944,10 @@ fn inner_method_handler(request: Request, state: Arc<Mutex<State>>) -> Result<Re
 
             let signer = s.get_available_signer(&r.name)?;
 
-            let script_variant = r
-                .singlesig_kind
-                .parse()
-                .map_err(|e: InvalidSinglesigVariant| e.to_string())?;
+            let script_variant = match r.singlesig_kind {
+                request::SinglesigKind::Wpkh => Singlesig::Wpkh,
+                request::SinglesigKind::ShWpkh => Singlesig::ShWpkh,
+            };
 
             let blinding_variant = r
                 .descriptor_blinding_key
@@ -561,18 +955,22 @@ fn inner_method_handler(request: Request, state: Arc<Mutex<State>>) -> Result<Re
                 .map_err(|e: InvalidBlindingKeyVariant| e.to_string())?;
 
             let descriptor = singlesig_desc(signer, script_variant, blinding_variant, is_mainnet)?;
+            let details = descriptor_details(&descriptor)?;
             Response::result(
                 request.id,
-                serde_json::to_value(response::SignerSinglesigDescriptor { descriptor })?,
+                serde_json::to_value(response::SignerSinglesigDescriptor {
+                    descriptor,
+                    details,
+                })?,
             )
         }
         Method::WalletMultisigDescriptor => {
             let r: request::WalletMultisigDescriptor = serde_json::from_value(params)?;
 
-            let multisig_variant = r
-                .multisig_kind
-                .parse()
-                .map_err(|e: InvalidMultisigVariant| e.to_string())?;
+            let multisig_variant = match r.multisig_kind {
+                request::MultisigKind::Wsh => Multisig::Wsh,
+                request::MultisigKind::ShWsh => Multisig::ShWsh,
+            };
 
             let blinding_variant = r
                 .descriptor_blinding_key
@@ -592,10 +990,15 @@ fn inner_method_handler(request: Request, state: Arc<Mutex<State>>) -> Result<Re
                 keyorigin_xpubs,
                 multisig_variant,
                 blinding_variant,
+                r.sorted,
             )?;
+            let details = descriptor_details(&descriptor)?;
             Response::result(
                 request.id,
-                serde_json::to_value(response::WalletMultisigDescriptor { descriptor })?,
+                serde_json::to_value(response::WalletMultisigDescriptor {
+                    descriptor,
+                    details,
+                })?,
             )
         }
         Method::SignerRegisterMultisig => {
@@ -616,6 +1019,100 @@ fn inner_method_handler(request: Request, state: Arc<Mutex<State>>) -> Result<Re
             }
             Response::result(request.id, serde_json::to_value(response::Empty {})?)
         }
+        Method::SignerConfirmAddress => {
+            let r: request::SignerConfirmAddress = serde_json::from_value(params)?;
+            let mut s = state.lock()?;
+            let chain = if r.change {
+                lwk_wollet::Chain::Internal
+            } else {
+                lwk_wollet::Chain::External
+            };
+            let wollet = s.wollets.get(&r.wallet)?;
+            let address = match chain {
+                lwk_wollet::Chain::External => wollet.address(Some(r.index))?,
+                lwk_wollet::Chain::Internal => wollet.change(Some(r.index))?,
+            }
+            .address()
+            .to_string();
+            let definite_desc = wollet
+                .wollet_descriptor()
+                .definite_descriptor(chain, r.index)?;
+
+            let signer = s.get_available_signer(&r.name)?;
+            let AnySigner::Jade(jade, _id) = signer else {
+                return Err(Error::SignerNotJade(r.name));
+            };
+            let fingerprint = signer.fingerprint()?;
+            let device_address = jade_address(jade, fingerprint, &r.wallet, &definite_desc)?;
+            if device_address != address {
+                return Err(Error::AddressMismatch {
+                    wallet: address,
+                    jade: device_address,
+                });
+            }
+            Response::result(
+                request.id,
+                serde_json::to_value(response::SignerConfirmAddress {
+                    address,
+                    device_address,
+                    index: r.index,
+                })?,
+            )
+        }
+        Method::SignerJadeStatus => {
+            let r: request::SignerJadeStatus = serde_json::from_value(params)?;
+            let s = state.lock()?;
+            let state = s.signers.get(&r.name)?.jade_status(&r.name)?;
+            Response::result(
+                request.id,
+                serde_json::to_value(response::JadeStatus { state })?,
+            )
+        }
+        Method::SignerJadeUnlock => {
+            let r: request::SignerJadeUnlock = serde_json::from_value(params)?;
+            let mut s = state.lock()?;
+            if s.signers.get(&r.name)?.jade_status(&r.name)? != "ready" {
+                // Connecting a Jade attempts to unlock it, unlock again to surface the errors
+                if let AnySigner::Jade(jade, _id) = s.get_available_signer(&r.name)? {
+                    jade.unlock()?;
+                }
+            }
+            let state = s.signers.get(&r.name)?.jade_status(&r.name)?;
+            Response::result(
+                request.id,
+                serde_json::to_value(response::JadeStatus { state })?,
+            )
+        }
+        Method::SignerRegisteredMultisigs => {
+            let r: request::SignerRegisteredMultisigs = serde_json::from_value(params)?;
+            let mut s = state.lock()?;
+
+            let signer = s.get_available_signer(&r.name)?;
+
+            let registered = match signer {
+                AnySigner::Jade(jade, _id) => jade.registered_multisigs()?,
+                _ => return Err(Error::SignerNotJade(r.name)),
+            };
+            let multisigs = registered
+                .into_iter()
+                .map(|(name, d)| {
+                    let descriptor: Option<ConfidentialDescriptor<DescriptorPublicKey>> =
+                        (&d).try_into().ok();
+                    let multisig = response::RegisteredMultisig {
+                        variant: d.variant.to_string(),
+                        sorted: d.sorted,
+                        threshold: d.threshold,
+                        num_signers: d.signers.len() as u32,
+                        descriptor: descriptor.map(|d| d.to_string()),
+                    };
+                    (name, multisig)
+                })
+                .collect();
+            Response::result(
+                request.id,
+                serde_json::to_value(response::SignerRegisteredMultisigs { multisigs })?,
+            )
+        }
         Method::SignerXpub => {
             let r: request::SignerXpub = serde_json::from_value(params)?;
             let mut s = state.lock()?;
@@ -623,10 +1120,11 @@ fn inner_method_handler(request: Request, state: Arc<Mutex<State>>) -> Result<Re
 
             let signer = s.get_available_signer(&r.name)?;
 
-            let bip = r
-                .xpub_kind
-                .parse()
-                .map_err(|e: InvalidBipVariant| e.to_string())?;
+            let bip = match r.xpub_kind {
+                request::XpubKind::Bip49 => Bip::Bip49,
+                request::XpubKind::Bip84 => Bip::Bip84,
+                request::XpubKind::Bip87 => Bip::Bip87,
+            };
 
             let keyorigin_xpub = signer.keyorigin_xpub(bip, is_mainnet)?;
             Response::result(
@@ -638,12 +1136,44 @@ fn inner_method_handler(request: Request, state: Arc<Mutex<State>>) -> Result<Re
             let r: request::SignerSign = serde_json::from_value(params)?;
             let mut s = state.lock()?;
 
-            let signer = s.get_available_signer(&r.name)?;
-
             let mut pset =
                 PartiallySignedTransaction::from_str(&r.pset).map_err(|e| e.to_string())?;
 
-            signer.sign(&mut pset)?;
+            match r.wallet {
+                None => {
+                    let signer = s.get_available_signer(&r.name)?;
+                    signer.sign(&mut pset)?;
+                }
+                Some(wallet) => {
+                    let wollet = s.wollets.get(&wallet)?;
+                    let is_mine: Vec<bool> = pset
+                        .inputs()
+                        .iter()
+                        .map(|i| {
+                            i.witness_utxo
+                                .as_ref()
+                                .map_or(false, |o| wollet.is_mine(&o.script_pubkey))
+                        })
+                        .collect();
+
+                    // Without derivations the signer can't sign the inputs of other wallets
+                    let mut constrained = pset.clone();
+                    for (input, mine) in constrained.inputs_mut().iter_mut().zip(&is_mine) {
+                        if !mine {
+                            input.bip32_derivation.clear();
+                        }
+                    }
+                    let signer = s.get_available_signer(&r.name)?;
+                    signer.sign(&mut constrained)?;
+
+                    let inputs = pset.inputs_mut().iter_mut().zip(constrained.inputs());
+                    for ((input, signed), mine) in inputs.zip(&is_mine) {
+                        if *mine {
+                            input.partial_sigs = signed.partial_sigs.clone();
+                        }
+                    }
+                }
+            }
 
             // TODO we may want to return other details such as if signatures have been added
 
@@ -662,23 +1192,45 @@ fn inner_method_handler(request: Request, state: Arc<Mutex<State>>) -> Result<Re
             let mut pset =
                 PartiallySignedTransaction::from_str(&r.pset).map_err(|e| e.to_string())?;
             let tx = wollet.finalize(&mut pset)?;
-            let electrum_client = s.config.electrum_client()?;
 
-            if !r.dry_run {
-                electrum_client.broadcast(&tx)?;
+            let mut already_broadcast = false;
+            if r.dry_run {
+                lwk_common::pset_verify_amounts(&pset).map_err(lwk_wollet::Error::from)?;
+            } else {
+                match s.config.blockchain_client()?.broadcast(&tx) {
+                    Ok(_) => (),
+                    Err(e) if e.is_already_broadcast() => already_broadcast = true,
+                    Err(e) => return Err(e.into()),
+                }
             }
 
             Response::result(
                 request.id,
                 serde_json::to_value(response::WalletBroadcast {
                     txid: tx.txid().to_string(),
+                    already_broadcast,
+                })?,
+            )
+        }
+        Method::WalletSyncStatus => {
+            let r: request::WalletSyncStatus = serde_json::from_value(params)?;
+            let s = state.lock()?;
+            let wollet = s.wollets.get(&r.name)?;
+            let last_scanned_height = (!wollet.never_scanned()).then(|| wollet.tip().height());
+            Response::result(
+                request.id,
+                serde_json::to_value(response::WalletSyncStatus {
+                    last_scanned_height,
+                    tip_height: s.tip_height,
+                    scanning: s.scanning.contains(&r.name),
+                    progress: sync_progress(last_scanned_height, s.tip_height),
                 })?,
             )
         }
         Method::WalletDetails => {
             let r: request::WalletDetails = serde_json::from_value(params)?;
-            let mut s = state.lock()?;
-            let wollet = s.wollets.get_mut(&r.name)?;
+            let s = state.lock()?;
+            let wollet = s.wollets.get(&r.name)?;
 
             let descriptor = wollet.descriptor().to_string();
             let type_ = match wollet.descriptor().descriptor.desc_type() {
@@ -692,7 +1244,9 @@ fn inner_method_handler(request: Request, state: Arc<Mutex<State>>) -> Result<Re
                             }
                             _ => response::WalletType::Unknown,
                         },
-                        _ => response::WalletType::Unknown,
+                        WshInner::SortedMulti(smv) => {
+                            response::WalletType::WshMulti(smv.k, smv.pks.len())
+                        }
                     },
                     _ => response::WalletType::Unknown,
                 },
@@ -720,13 +1274,48 @@ fn inner_method_handler(request: Request, state: Arc<Mutex<State>>) -> Result<Re
                 })
                 .collect();
 
+            let mut keys = vec![];
+            wollet.descriptor().descriptor.for_each_key(|k| {
+                let (origin, xpub) = match k {
+                    DescriptorPublicKey::Single(single) => {
+                        let key = match single.key {
+                            SinglePubKey::FullKey(pk) => pk.to_string(),
+                            SinglePubKey::XOnly(pk) => pk.to_string(),
+                        };
+                        (single.origin.as_ref(), key)
+                    }
+                    DescriptorPublicKey::XPub(xkey) => {
+                        (xkey.origin.as_ref(), xkey.xkey.to_string())
+                    }
+                    DescriptorPublicKey::MultiXPub(xkey) => {
+                        (xkey.origin.as_ref(), xkey.xkey.to_string())
+                    }
+                };
+                let fingerprint = k.master_fingerprint();
+                keys.push(response::WalletKey {
+                    fingerprint: fingerprint.to_string(),
+                    origin_path: origin.map(|(_, path)| path.to_string().replace('\'', "h")),
+                    xpub,
+                    watch_only: !s.signers.is_loaded(&fingerprint),
+                });
+                true
+            });
+            let threshold = match type_ {
+                response::WalletType::WshMulti(threshold, _) => Some(threshold as u32),
+                _ => None,
+            };
+
             Response::result(
                 request.id,
                 serde_json::to_value(response::WalletDetails {
                     descriptor,
                     type_: type_.to_string(),
                     signers,
+                    keys,
+                    threshold,
                     warnings: warnings.join(", "),
+                    next_external_index: wollet.next_unused_index(lwk_wollet::Chain::External),
+                    next_internal_index: wollet.next_unused_index(lwk_wollet::Chain::Internal),
                 })?,
             )
         }
@@ -740,10 +1329,16 @@ fn inner_method_handler(request: Request, state: Arc<Mutex<State>>) -> Result<Re
                 psets.push(PartiallySignedTransaction::from_str(&pset).map_err(|e| e.to_string())?);
             }
             let pset = wollet.combine(&psets)?;
+            let tx_hex = wollet
+                .finalize(&mut pset.clone())
+                .ok()
+                .map(|tx| serialize(&tx).to_hex());
             Response::result(
                 request.id,
                 serde_json::to_value(response::WalletCombine {
                     pset: pset.to_string(),
+                    finalizable: tx_hex.is_some(),
+                    tx_hex,
                 })?,
             )
         }
@@ -754,6 +1349,9 @@ fn inner_method_handler(request: Request, state: Arc<Mutex<State>>) -> Result<Re
 
             let pset = PartiallySignedTransaction::from_str(&r.pset).map_err(|e| e.to_string())?;
             let details = wollet.get_details(&pset)?;
+            let vsize = lwk_common::pset_vsize(&pset, wollet.descriptor())
+                .map_err(lwk_wollet::Error::from)?;
+            let threshold = signatures_threshold(&wollet.descriptor().descriptor);
             let mut warnings = vec![];
             let has_signatures_from = details
                 .fingerprints_has()
@@ -771,6 +1369,38 @@ fn inner_method_handler(request: Request, state: Arc<Mutex<State>>) -> Result<Re
                     fingerprint: f.to_string(),
                 })
                 .collect();
+            // Warnings are already collected with the fingerprints above
+            let mut input_warnings = vec![];
+            let mut short_details = |keys: &[(PublicKey, KeySource)]| {
+                keys.iter()
+                    .map(|(_, (f, _))| *f)
+                    .collect::<BTreeSet<_>>()
+                    .iter()
+                    .map(|f| response::SignerShortDetails {
+                        name: s.signers.name_from_fingerprint(f, &mut input_warnings),
+                        fingerprint: f.to_string(),
+                    })
+                    .collect::<Vec<_>>()
+            };
+            let signatures: Vec<_> = details
+                .sig_details
+                .iter()
+                .enumerate()
+                .filter(|(_, d)| !d.has_signature.is_empty() || !d.missing_signature.is_empty())
+                .map(|(vin, d)| {
+                    let has = d.has_signature.len();
+                    let threshold = threshold.unwrap_or(has + d.missing_signature.len());
+                    let signatures_needed = threshold.saturating_sub(has) as u32;
+                    response::InputSigStatus {
+                        vin: vin as u32,
+                        has_signatures_from: short_details(&d.has_signature),
+                        missing_signatures_from: short_details(&d.missing_signature),
+                        signatures_needed,
+                        is_complete: signatures_needed == 0,
+                    }
+                })
+                .collect();
+            let is_complete = signatures.iter().all(|s| s.is_complete);
             let mut balance: HashMap<String, i64> = details
                 .balance
                 .balances
@@ -817,8 +1447,147 @@ fn inner_method_handler(request: Request, state: Arc<Mutex<State>>) -> Result<Re
                     missing_signatures_from,
                     balance,
                     fee: details.balance.fee,
+                    vsize,
+                    fee_rate: details.balance.fee as f64 / vsize as f64,
                     issuances,
                     reissuances,
+                    signatures,
+                    is_complete,
+                    warnings: warnings.join(", "),
+                })?,
+            )
+        }
+        Method::WalletProofOfReserves => {
+            let r: request::WalletProofOfReserves = serde_json::from_value(params)?;
+            let mut s = state.lock()?;
+            let wollet = s.wollets.get_mut(&r.name)?;
+            let pset = wollet.proof_of_reserves(&r.challenge)?;
+            Response::result(
+                request.id,
+                serde_json::to_value(response::Pset {
+                    pset: pset.to_string(),
+                })?,
+            )
+        }
+        Method::VerifyProofOfReserves => {
+            let r: request::VerifyProofOfReserves = serde_json::from_value(params)?;
+            let pset = PartiallySignedTransaction::from_str(&r.proof).map_err(|e| e.to_string())?;
+            let reserves = lwk_wollet::verify_proof_of_reserves(&pset, &r.challenge)?;
+            let outpoints = pset
+                .inputs()
+                .iter()
+                .skip(1)
+                .map(|i| OutPoint::new(i.previous_txid, i.previous_output_index).to_string())
+                .collect();
+            Response::result(
+                request.id,
+                serde_json::to_value(response::VerifyProofOfReserves {
+                    reserves: reserves
+                        .into_iter()
+                        .map(|(asset, satoshi)| (asset.to_string(), satoshi))
+                        .collect(),
+                    outpoints,
+                })?,
+            )
+        }
+        Method::WalletPsetAnalyze => {
+            let r: request::WalletPsetAnalyze = serde_json::from_value(params)?;
+            let mut s = state.lock()?;
+            let wollet = s.wollets.get_mut(&r.name)?;
+
+            let pset = PartiallySignedTransaction::from_str(&r.pset).map_err(|e| e.to_string())?;
+            let mut warnings = vec![];
+            let txos: HashMap<_, _> = wollet
+                .txos_inner(false)?
+                .into_iter()
+                .map(|u| (u.outpoint, u.unblinded))
+                .collect();
+            let prevout = |i: &lwk_wollet::elements::pset::Input| {
+                OutPoint::new(i.previous_txid, i.previous_output_index)
+            };
+
+            // Inputs not owned by the wallet are ignored, unlike with pset details
+            let mut balance: BTreeMap<AssetId, i64> = BTreeMap::new();
+            for input in pset.inputs() {
+                if let Some(secrets) = txos.get(&prevout(input)) {
+                    *balance.entry(secrets.asset).or_default() -= secrets.value as i64;
+                }
+            }
+            let mut fee: Option<u64> = None;
+            for (vout, output) in pset.outputs().iter().enumerate() {
+                if output.script_pubkey.is_empty() {
+                    if let Some(satoshi) = output.amount {
+                        fee = Some(fee.unwrap_or(0) + satoshi);
+                    }
+                } else if wollet.is_mine(&output.script_pubkey) {
+                    match (output.asset, output.amount) {
+                        (Some(asset), Some(satoshi)) => {
+                            *balance.entry(asset).or_default() += satoshi as i64
+                        }
+                        _ => warnings.push(format!("wallet output {vout} has no explicit amount")),
+                    }
+                }
+            }
+            let fee_rate = match (fee, lwk_common::pset_vsize(&pset, wollet.descriptor())) {
+                (Some(fee), Ok(vsize)) => Some(fee as f64 / vsize as f64),
+                _ => None,
+            };
+            let policy_asset = wollet.policy_asset();
+            let receives = balance.values().any(|v| *v > 0);
+            let sends = balance.iter().any(|(asset, v)| {
+                // The policy asset spent only for the fee is not exchanged
+                *v < 0 && (*asset != policy_asset || v.unsigned_abs() > fee.unwrap_or(0))
+            });
+            let is_finalizable = wollet.finalize(&mut pset.clone()).is_ok();
+
+            let mut short_details = |keys: &[(PublicKey, KeySource)]| {
+                keys.iter()
+                    .map(|(_, (f, _))| *f)
+                    .collect::<BTreeSet<_>>()
+                    .iter()
+                    .map(|f| response::SignerShortDetails {
+                        name: s.signers.name_from_fingerprint(f, &mut warnings),
+                        fingerprint: f.to_string(),
+                    })
+                    .collect::<Vec<_>>()
+            };
+            let inputs: Vec<_> = pset
+                .inputs()
+                .iter()
+                .zip(lwk_common::pset_signatures(&pset))
+                .enumerate()
+                .map(|(vin, (input, d))| response::PsetInputAnalysis {
+                    vin: vin as u32,
+                    is_mine: txos.contains_key(&prevout(input)),
+                    is_finalized: input.final_script_witness.is_some()
+                        || input.final_script_sig.is_some(),
+                    sighash: input
+                        .ecdsa_hash_ty()
+                        .map(|t| t.to_string())
+                        .unwrap_or_else(|| "non-standard".to_string()),
+                    has_signatures_from: short_details(&d.has_signature),
+                    missing_signatures_from: short_details(&d.missing_signature),
+                })
+                .collect();
+
+            let mut balance: HashMap<String, i64> = balance
+                .into_iter()
+                .map(|(k, v)| (k.to_string(), v))
+                .collect();
+            if r.with_tickers {
+                balance = s.replace_id_with_ticker(balance);
+            }
+            warnings.sort();
+            warnings.dedup();
+            Response::result(
+                request.id,
+                serde_json::to_value(response::WalletPsetAnalyze {
+                    balance,
+                    fee,
+                    fee_rate,
+                    inputs,
+                    is_finalizable,
+                    is_swap: receives && sends,
                     warnings: warnings.join(", "),
                 })?,
             )
@@ -826,8 +1595,20 @@ fn inner_method_handler(request: Request, state: Arc<Mutex<State>>) -> Result<Re
         Method::WalletUtxos => {
             let r: request::WalletUtxos = serde_json::from_value(params)?;
             let mut s = state.lock()?;
+            let asset = r.asset.map(|a| AssetId::from_str(&a)).transpose()?;
             let wollet = s.wollets.get_mut(&r.name)?;
-            let utxos: Vec<response::Utxo> = wollet.utxos()?.iter().map(convert_utxo).collect();
+            let mut utxos: Vec<response::Utxo> = wollet
+                .utxos()?
+                .iter()
+                .filter(|u| asset.map_or(true, |a| u.unblinded.asset == a))
+                .filter(|u| r.min_satoshi.map_or(true, |m| u.unblinded.value >= m))
+                .map(convert_utxo)
+                .collect();
+            if r.with_tickers {
+                for utxo in &mut utxos {
+                    utxo.asset = s.ticker_or_id(std::mem::take(&mut utxo.asset));
+                }
+            }
             Response::result(
                 request.id,
                 serde_json::to_value(response::WalletUtxos { utxos })?,
@@ -842,11 +1623,26 @@ fn inner_method_handler(request: Request, state: Arc<Mutex<State>>) -> Result<Re
             let mut txs: Vec<response::Tx> = wollet
                 .transactions()?
                 .iter()
+                .filter(|tx| match tx.height {
+                    Some(h) => {
+                        r.min_height.map_or(true, |min| h >= min)
+                            && r.max_height.map_or(true, |max| h <= max)
+                    }
+                    None => r.max_height.is_none(),
+                })
+                .skip(r.offset.unwrap_or(0) as usize)
+                .take(r.limit.map_or(usize::MAX, |l| l as usize))
                 .map(|tx| convert_tx(tx, &explorer_url, &memos))
                 .collect();
             if r.with_tickers {
                 for tx in &mut txs {
+                    tx.balance_formatted = Some(s.format_balance(tx.balance.clone()));
                     tx.balance = s.replace_id_with_ticker(tx.balance.clone());
+                    let burned = std::mem::take(&mut tx.burned);
+                    tx.burned = burned
+                        .into_iter()
+                        .map(|(k, v)| (s.ticker_or_id(k), v))
+                        .collect();
                 }
             }
             Response::result(
@@ -869,6 +1665,68 @@ fn inner_method_handler(request: Request, state: Arc<Mutex<State>>) -> Result<Re
             let tx = serialize(&tx).to_hex();
             Response::result(request.id, serde_json::to_value(response::WalletTx { tx })?)
         }
+        Method::VerifyXpub => {
+            let r: request::VerifyXpub = serde_json::from_value(params)?;
+            let resp = verify_xpub_response(&r.fingerprint, &r.path, &r.xpub)?;
+            Response::result(request.id, serde_json::to_value(resp)?)
+        }
+        Method::ValidateAddress => {
+            let r: request::ValidateAddress = serde_json::from_value(params)?;
+            let s = state.lock()?;
+            let config_network = s.config.network;
+            let network = if s.wollets.get(&r.name_or_network).is_ok() {
+                config_network
+            } else {
+                // The policy asset is irrelevant for addresses
+                r.name_or_network.parse::<ElementsNetwork>().map_err(|_| {
+                    Error::Generic(format!(
+                        "'{}' is neither a loaded wallet nor a network",
+                        r.name_or_network
+                    ))
+                })?
+            };
+            Response::result(
+                request.id,
+                serde_json::to_value(validate_address_response(&r.address, network))?,
+            )
+        }
+        Method::WalletIsMine => {
+            let r: request::WalletIsMine = serde_json::from_value(params)?;
+            let s = state.lock()?;
+            let wollet = s.wollets.get(&r.name)?;
+            Response::result(
+                request.id,
+                serde_json::to_value(is_mine_response(wollet, &r.address_or_script)?)?,
+            )
+        }
+        Method::WalletExportDescriptor => {
+            let r: request::WalletExportDescriptor = serde_json::from_value(params)?;
+            let s = state.lock()?;
+            let wollet = s.wollets.get(&r.name)?;
+            Response::result(
+                request.id,
+                serde_json::to_value(export_descriptor_response(wollet, r.format))?,
+            )
+        }
+        Method::WalletExportCsv => {
+            let r: request::WalletExportCsv = serde_json::from_value(params)?;
+            let mut s = state.lock()?;
+            let wollet = s.wollets.get_mut(&r.name)?;
+            let rows: Vec<_> = wollet
+                .transactions()?
+                .iter()
+                .flat_map(|tx| tx.to_csv_rows())
+                .collect();
+            let mut csv = CsvRow::HEADER.to_string();
+            for row in rows.iter() {
+                csv.push('\n');
+                csv.push_str(&s.csv_row(row));
+            }
+            Response::result(
+                request.id,
+                serde_json::to_value(response::WalletExportCsv { csv })?,
+            )
+        }
         Method::WalletSetTxMemo => {
             let r: request::WalletSetTxMemo = serde_json::from_value(params)?;
             let mut s = state.lock()?;
@@ -891,11 +1749,138 @@ fn inner_method_handler(request: Request, state: Arc<Mutex<State>>) -> Result<Re
             s.persist(&request)?;
             Response::result(request.id, serde_json::to_value(response::Empty {})?)
         }
+        Method::WalletSetOutputMemo => {
+            let r: request::WalletSetOutputMemo = serde_json::from_value(params)?;
+            let mut s = state.lock()?;
+            // Make sure the wallet exists
+            let _wollet = s.wollets.get(&r.name)?;
+            let outpoint =
+                OutPoint::from_str(&r.outpoint).map_err(|e| Error::Generic(e.to_string()))?;
+            s.output_memos.set(&r.name, &outpoint, &r.memo)?;
+            s.persist(&request)?;
+            Response::result(request.id, serde_json::to_value(response::Empty {})?)
+        }
+        Method::WalletLabels => {
+            let r: request::WalletLabels = serde_json::from_value(params)?;
+            let s = state.lock()?;
+            // Make sure the wallet exists
+            let _wollet = s.wollets.get(&r.name)?;
+            let label = |type_: &str, ref_: String, label: String| response::Label {
+                type_: type_.to_string(),
+                ref_,
+                label,
+            };
+            let tx_memos: BTreeMap<_, _> = s.tx_memos.for_wollet(&r.name).into_iter().collect();
+            let mut labels: Vec<_> = tx_memos
+                .into_iter()
+                .map(|(txid, memo)| label("tx", txid.to_string(), memo))
+                .collect();
+            let addr_memos: BTreeMap<_, _> = s
+                .addr_memos
+                .for_wollet(&r.name)
+                .into_iter()
+                .map(|(address, memo)| (address.to_string(), memo))
+                .collect();
+            labels.extend(
+                addr_memos
+                    .into_iter()
+                    .map(|(address, memo)| label("addr", address, memo)),
+            );
+            let output_memos: BTreeMap<_, _> =
+                s.output_memos.for_wollet(&r.name).into_iter().collect();
+            labels.extend(output_memos.into_iter().map(|(outpoint, memo)| {
+                label(
+                    "output",
+                    format!("{}:{}", outpoint.txid, outpoint.vout),
+                    memo,
+                )
+            }));
+            let jsonl = labels
+                .iter()
+                .map(serde_json::to_string)
+                .collect::<Result<Vec<_>, _>>()?
+                .join("\n");
+            Response::result(
+                request.id,
+                serde_json::to_value(response::WalletLabels { labels, jsonl })?,
+            )
+        }
+        Method::WalletImportLabels => {
+            let r: request::WalletImportLabels = serde_json::from_value(params)?;
+            let mut s = state.lock()?;
+            let wollet = s.wollets.get(&r.name)?;
+            let txs: HashSet<_> = wollet.transactions()?.iter().map(|tx| tx.txid).collect();
+            let txos: HashSet<_> = wollet
+                .txos_inner(false)?
+                .iter()
+                .map(|txo| txo.outpoint)
+                .collect();
+            let mut tx_labels = vec![];
+            let mut addr_labels = vec![];
+            let mut output_labels = vec![];
+            let mut skipped = 0;
+            let mut errors = vec![];
+            for (line, record) in parse_bip329(&r.jsonl) {
+                let record = match record {
+                    Ok(record) => record,
+                    Err(error) => {
+                        errors.push(response::InvalidLabel { line, error });
+                        continue;
+                    }
+                };
+                let Some(label) = record.label else {
+                    skipped += 1;
+                    continue;
+                };
+                let invalid = |error: String| response::InvalidLabel { line, error };
+                match record.type_.as_str() {
+                    "tx" => match Txid::from_str(&record.ref_) {
+                        Ok(txid) if txs.contains(&txid) => tx_labels.push((txid, label)),
+                        Ok(_) => skipped += 1,
+                        Err(e) => errors.push(invalid(e.to_string())),
+                    },
+                    "addr" => match Address::from_str(&record.ref_) {
+                        Ok(address) if wollet.is_mine(&address.script_pubkey()) => {
+                            addr_labels.push((address, label))
+                        }
+                        Ok(_) => skipped += 1,
+                        Err(e) => errors.push(invalid(e.to_string())),
+                    },
+                    "output" => match OutPoint::from_str(&record.ref_) {
+                        Ok(outpoint) if txos.contains(&outpoint) => {
+                            output_labels.push((outpoint, label))
+                        }
+                        Ok(_) => skipped += 1,
+                        Err(e) => errors.push(invalid(e.to_string())),
+                    },
+                    _ => skipped += 1,
+                }
+            }
+            let imported = (tx_labels.len() + addr_labels.len() + output_labels.len()) as u32;
+            for (txid, label) in tx_labels {
+                s.tx_memos.set(&r.name, &txid, &label)?;
+            }
+            for (address, label) in addr_labels {
+                s.addr_memos.set(&r.name, &address, &label)?;
+            }
+            for (outpoint, label) in output_labels {
+                s.output_memos.set(&r.name, &outpoint, &label)?;
+            }
+            s.persist_all()?;
+            Response::result(
+                request.id,
+                serde_json::to_value(response::WalletImportLabels {
+                    imported,
+                    skipped,
+                    errors,
+                })?,
+            )
+        }
         Method::WalletIssue => {
             let r: request::WalletIssue = serde_json::from_value(params)?;
             let mut s = state.lock()?;
             let wollet = s.wollets.get_mut(&r.name)?;
-            let tx = wollet
+            let mut builder = wollet
                 .tx_builder()
                 .issue_asset(
                     r.satoshi_asset,
@@ -906,13 +1891,17 @@ fn inner_method_handler(request: Request, state: Arc<Mutex<State>>) -> Result<Re
                         .map(|c| lwk_wollet::Contract::from_str(&c))
                         .transpose()?,
                 )?
-                .fee_rate(r.fee_rate)
-                .finish()?;
+                .fee_rate(r.fee_rate);
+            if let Some(address) = r.change_address {
+                builder = builder.set_change_address(Address::from_str(&address)?)?;
+            }
+            if let Some(strategy) = r.coin_selection {
+                builder = builder.coin_selection(coin_selection(strategy));
+            }
+            let tx = builder.finish()?;
             Response::result(
                 request.id,
-                serde_json::to_value(response::Pset {
-                    pset: tx.to_string(),
-                })?,
+                serde_json::to_value(issue_response(&tx, r.dry_run))?,
             )
         }
         Method::WalletReissue => {
@@ -922,7 +1911,7 @@ fn inner_method_handler(request: Request, state: Arc<Mutex<State>>) -> Result<Re
             let issuance_tx = s.get_issuance_tx(&asset_id);
             let wollet = s.wollets.get_mut(&r.name)?;
 
-            let mut pset = wollet
+            let mut builder = wollet
                 .tx_builder()
                 .reissue_asset(
                     asset_id,
@@ -930,15 +1919,19 @@ fn inner_method_handler(request: Request, state: Arc<Mutex<State>>) -> Result<Re
                     r.address_asset.map(|a| Address::from_str(&a)).transpose()?,
                     issuance_tx,
                 )?
-                .fee_rate(r.fee_rate)
-                .finish()?;
+                .fee_rate(r.fee_rate);
+            if let Some(address) = r.change_address {
+                builder = builder.set_change_address(Address::from_str(&address)?)?;
+            }
+            if let Some(strategy) = r.coin_selection {
+                builder = builder.coin_selection(coin_selection(strategy));
+            }
+            let mut pset = builder.finish()?;
 
             add_contracts(&mut pset, s.assets.iter());
             Response::result(
                 request.id,
-                serde_json::to_value(response::Pset {
-                    pset: pset.to_string(),
-                })?,
+                serde_json::to_value(issue_response(&pset, r.dry_run))?,
             )
         }
         Method::WalletBurn => {
@@ -961,11 +1954,90 @@ fn inner_method_handler(request: Request, state: Arc<Mutex<State>>) -> Result<Re
                 })?,
             )
         }
+        Method::WalletConsolidate => {
+            let r: request::WalletConsolidate = serde_json::from_value(params)?;
+            let mut s = state.lock()?;
+            let wollet = s.wollets.get_mut(&r.name)?;
+            let policy_asset = wollet.policy_asset();
+            let asset = match r.asset {
+                Some(asset) => AssetId::from_str(&asset)?,
+                None => policy_asset,
+            };
+
+            let all_utxos = wollet.utxos()?;
+            let mut utxos: Vec<_> = all_utxos
+                .iter()
+                .filter(|u| u.unblinded.asset == asset)
+                .collect();
+            utxos.sort_by_key(|u| u.unblinded.value);
+            utxos.truncate(r.max_inputs as usize);
+            if utxos.len() < 2 {
+                return Err(Error::NothingToConsolidate(utxos.len(), asset.to_string()));
+            }
+            let satoshi = utxos.iter().map(|u| u.unblinded.value).sum();
+            let inputs = utxos.len() as u32;
+            let mut outpoints: Vec<_> = utxos.iter().map(|u| u.outpoint).collect();
+            if asset != policy_asset {
+                // The largest L-BTC utxo pays the fee
+                let fee_utxo = all_utxos
+                    .iter()
+                    .filter(|u| u.unblinded.asset == policy_asset)
+                    .max_by_key(|u| u.unblinded.value)
+                    .ok_or(lwk_wollet::Error::InsufficientFunds)?;
+                outpoints.push(fee_utxo.outpoint);
+            }
+
+            let mut pset = wollet
+                .tx_builder()
+                .set_wallet_utxos(outpoints)
+                .fee_rate(r.fee_rate)
+                .finish()?;
+
+            add_contracts(&mut pset, s.assets.iter());
+            Response::result(
+                request.id,
+                serde_json::to_value(response::WalletConsolidate {
+                    pset: pset.to_string(),
+                    inputs,
+                    satoshi,
+                })?,
+            )
+        }
+        Method::WalletCreateSwap => {
+            let r: request::WalletCreateSwap = serde_json::from_value(params)?;
+            let s = state.lock()?;
+            let wollet = s.wollets.get(&r.name)?;
+            let send_asset = AssetId::from_str(&r.send_asset)?;
+            let recv_asset = AssetId::from_str(&r.recv_asset)?;
+
+            let (pset, utxo) =
+                wollet.create_swap(send_asset, r.send_satoshi, recv_asset, r.recv_satoshi)?;
+            let secrets = utxo.unblinded;
+            let input_secrets = response::SwapInputSecrets {
+                value: secrets.value,
+                asset: secrets.asset.to_string(),
+                value_blinder: secrets.value_bf.to_string(),
+                asset_blinder: secrets.asset_bf.to_string(),
+            };
+            let summary = format!(
+                "Send {} of {} from {} in exchange for {} of {}",
+                r.send_satoshi, send_asset, utxo.outpoint, r.recv_satoshi, recv_asset
+            );
+            Response::result(
+                request.id,
+                serde_json::to_value(response::WalletCreateSwap {
+                    pset: pset.to_string(),
+                    input_secrets,
+                    summary,
+                })?,
+            )
+        }
         Method::AssetContract => {
             let r: request::AssetContract = serde_json::from_value(params)?;
             let c = lwk_wollet::Contract {
                 entity: lwk_wollet::Entity::Domain(r.domain),
-                issuer_pubkey: Vec::<u8>::from_hex(&r.issuer_pubkey)?,
+                issuer_pubkey: Vec::<u8>::from_hex(&r.issuer_pubkey)
+                    .map_err(|_| lwk_wollet::Error::InvalidIssuerPubkey)?,
                 name: r.name,
                 precision: r.precision,
                 ticker: r.ticker,
@@ -1035,14 +2107,29 @@ fn inner_method_handler(request: Request, state: Arc<Mutex<State>>) -> Result<Re
                 return Err(Error::AssetAlreadyInserted(r.asset_id));
             }
             let registry_data = get_registry_data(&s.config.registry_url, &asset_id)?;
-            let txid = Txid::from_str(&registry_data.issuance_txin.txid)?;
-            let issuance_tx = get_tx(&s.config.esplora_api_url, &txid)?;
-            s.insert_asset(asset_id, issuance_tx, registry_data.contract)?;
-            // convert the request to an AssetInsert to skip network calls
-            let asset_insert_request = s.get_asset(&asset_id)?.request().expect("asset");
-            s.persist(&asset_insert_request)?;
+            insert_registry_asset(&mut s, asset_id, registry_data)?;
             Response::result(request.id, serde_json::to_value(response::Empty {})?)
         }
+        Method::AssetRegistryLookup => {
+            let r: request::AssetRegistryLookup = serde_json::from_value(params)?;
+            let mut s = state.lock()?;
+            let asset_id = AssetId::from_str(&r.asset_id)?;
+            let registry_data = get_registry_data(&s.config.registry_url, &asset_id)?;
+            let contract = registry_data.contract.clone();
+            if r.cache && s.get_asset(&asset_id).is_err() {
+                insert_registry_asset(&mut s, asset_id, registry_data)?;
+            }
+            Response::result(
+                request.id,
+                serde_json::to_value(response::AssetRegistryLookup {
+                    asset_id: asset_id.to_string(),
+                    name: contract.name.clone(),
+                    ticker: contract.ticker.clone(),
+                    precision: contract.precision,
+                    contract: serde_json::from_value(serde_json::to_value(contract)?)?,
+                })?,
+            )
+        }
         Method::SignerJadeId => {
             let r: request::SignerJadeId = serde_json::from_value(params)?;
 
@@ -1072,9 +2159,14 @@ fn inner_method_handler(request: Request, state: Arc<Mutex<State>>) -> Result<Re
             };
             jade.unlock()?;
             let identifier = jade.identifier()?.to_string();
+            let info = jade.version_info()?;
             Response::result(
                 request.id,
-                serde_json::to_value(response::JadeId { identifier })?,
+                serde_json::to_value(response::JadeId {
+                    identifier,
+                    version: info.jade_version,
+                    board_type: info.board_type,
+                })?,
             )
         }
         Method::Scan => {
@@ -1119,6 +2211,21 @@ fn inner_method_handler(request: Request, state: Arc<Mutex<State>>) -> Result<Re
     Ok(response)
 }
 
+/// Insert the asset fetching the issuance transaction from the explorer
+fn insert_registry_asset(
+    s: &mut State,
+    asset_id: AssetId,
+    registry_data: RegistryData,
+) -> Result<(), Error> {
+    let txid = Txid::from_str(&registry_data.issuance_txin.txid)?;
+    let issuance_tx = get_tx(&s.config.esplora_api_url, &txid)?;
+    s.insert_asset(asset_id, issuance_tx, registry_data.contract)?;
+    // convert the request to an AssetInsert to skip network calls
+    let asset_insert_request = s.get_asset(&asset_id)?.request().expect("asset");
+    s.persist(&asset_insert_request)?;
+    Ok(())
+}
+
 fn scan(state: &Arc<Mutex<State>>) -> Result<(), Error> {
     let required_scan_loops = {
         let mut s = state.lock()?;
@@ -1139,18 +2246,413 @@ fn scan(state: &Arc<Mutex<State>>) -> Result<(), Error> {
     Ok(())
 }
 
-fn unvalidated_addressee(a: request::UnvalidatedAddressee) -> lwk_wollet::UnvalidatedRecipient {
-    lwk_wollet::UnvalidatedRecipient {
+/// Percentage of the blockchain scanned, 0 if never scanned, 100 if the tip is not known yet
+fn sync_progress(last_scanned_height: Option<u32>, tip_height: Option<u32>) -> f64 {
+    match (last_scanned_height, tip_height) {
+        (None, _) => 0.0,
+        (Some(_), None | Some(0)) => 100.0,
+        (Some(h), Some(tip)) => (h as f64 * 100.0 / tip as f64).min(100.0),
+    }
+}
+
+/// The (re)issuance response, in dry run it contains the asset and token ids of the issuance input
+fn issue_response(pset: &PartiallySignedTransaction, dry_run: bool) -> response::WalletIssue {
+    let ids = dry_run
+        .then(|| pset.inputs().iter().find(|i| i.has_issuance()))
+        .flatten()
+        .map(|i| i.issuance_ids());
+    response::WalletIssue {
+        pset: pset.to_string(),
+        asset: ids.map(|(asset, _)| asset.to_string()),
+        token: ids.map(|(_, token)| token.to_string()),
+    }
+}
+
+/// Show on the Jade screen the address of `definite_desc` and return it as computed by the device
+///
+/// For multisigs, the wallet must be registered on the Jade as `wallet_name`.
+fn jade_address(
+    jade: &Jade,
+    fingerprint: Fingerprint,
+    wallet_name: &str,
+    definite_desc: &Descriptor<DefiniteDescriptorKey>,
+) -> Result<String, Error> {
+    // Get the derivation paths for all signers
+    let mut paths: Vec<Vec<u32>> = vec![];
+    // Get the full path for the signer
+    let mut full_path: Vec<u32> = vec![];
+    definite_desc.for_each_key(|k| {
+        if k.master_fingerprint() == fingerprint {
+            if let Some(path) = k.full_derivation_path() {
+                full_path = derivation_path_to_vec(&path);
+            }
+        }
+        if let DescriptorPublicKey::XPub(x) = k.as_descriptor_public_key() {
+            paths.push(derivation_path_to_vec(&x.derivation_path));
+        }
+        true
+    });
+
+    if full_path.is_empty() {
+        return Err(Error::Generic("Signer is not in wallet".into()));
+    }
+    Ok(match paths.len() {
+        0 => return Err(Error::Generic("Unsupported signer or descriptor".into())),
+        1 => {
+            // Single sig
+            match definite_desc.desc_type() {
+                DescriptorType::Wpkh => {
+                    jade.get_receive_address_single(Variant::Wpkh, full_path)?
+                }
+                DescriptorType::ShWpkh => {
+                    jade.get_receive_address_single(Variant::ShWpkh, full_path)?
+                }
+                _ => return Err(Error::Generic("Unsupported signer or descriptor".into())),
+            }
+        }
+        _ => {
+            // Multi sig
+            jade.get_receive_address_multi(wallet_name, paths)?
+        }
+    })
+}
+
+/// Whether the wallet owns the address or the script pubkey in hex
+fn is_mine_response(
+    wollet: &Wollet,
+    address_or_script: &str,
+) -> Result<response::WalletIsMine, Error> {
+    let (script_pubkey, blinding_pubkey) = match Address::from_str(address_or_script) {
+        Ok(address) => (address.script_pubkey(), address.blinding_pubkey),
+        Err(_) => {
+            let script = Script::from_hex(address_or_script).map_err(|_| {
+                Error::Generic(format!(
+                    "'{address_or_script}' is neither an address nor a script in hex"
+                ))
+            })?;
+            (script, None)
+        }
+    };
+    let derivation = wollet
+        .script_derivation(&script_pubkey)
+        .map(|(chain, index)| {
+            let address = match chain {
+                lwk_wollet::Chain::External => wollet.address(Some(index))?,
+                lwk_wollet::Chain::Internal => wollet.change(Some(index))?,
+            };
+            Ok::<_, Error>((chain, index, address.address().blinding_pubkey))
+        })
+        .transpose()?
+        .filter(|(_, _, wallet_blinding_pubkey)| {
+            blinding_pubkey.map_or(true, |b| Some(b) == *wallet_blinding_pubkey)
+        });
+    Ok(response::WalletIsMine {
+        is_mine: derivation.is_some(),
+        chain: derivation.map(|(chain, _, _)| {
+            match chain {
+                lwk_wollet::Chain::External => "external",
+                lwk_wollet::Chain::Internal => "internal",
+            }
+            .to_string()
+        }),
+        index: derivation.map(|(_, index, _)| index),
+    })
+}
+
+/// The wallet descriptor in the given format, all including the checksum
+///
+/// The descriptors imported in Elements Core cover the addresses derived by the wallet plus the
+/// gap limit and rescan the whole blockchain.
+fn export_descriptor_response(
+    wollet: &Wollet,
+    format: request::DescriptorFormat,
+) -> response::WalletExportDescriptor {
+    let desc = wollet.wollet_descriptor();
+    match format {
+        request::DescriptorFormat::Confidential => response::WalletExportDescriptor {
+            descriptor: Some(desc.to_string()),
+            import_descriptors: None,
+        },
+        request::DescriptorFormat::Bare => response::WalletExportDescriptor {
+            descriptor: Some(desc.descriptor().to_string()),
+            import_descriptors: None,
+        },
+        request::DescriptorFormat::CoreImport => response::WalletExportDescriptor {
+            descriptor: None,
+            import_descriptors: Some(
+                desc.single_descriptors()
+                    .into_iter()
+                    .map(|(chain, single)| response::ImportDescriptor {
+                        desc: single.to_string(),
+                        timestamp: 0,
+                        active: true,
+                        internal: chain == lwk_wollet::Chain::Internal,
+                        range: [0, wollet.next_unused_index(chain) + wollet.gap_limit()],
+                    })
+                    .collect(),
+            ),
+        },
+    }
+}
+
+/// The fee rate in sat/kvb expected by the transaction builder, a missing `unit` is sat/vb
+fn fee_rate_kvb(fee_rate: f32, unit: Option<request::FeeRateUnit>) -> f32 {
+    match unit {
+        Some(request::FeeRateUnit::SatPerVb) | None => fee_rate * 1000.0,
+        Some(request::FeeRateUnit::SatPerKvb) => fee_rate,
+    }
+}
+
+fn coin_selection(strategy: request::CoinSelection) -> lwk_wollet::CoinSelection {
+    match strategy {
+        request::CoinSelection::LargestFirst => lwk_wollet::CoinSelection::LargestFirst,
+        request::CoinSelection::SmallestFirst => lwk_wollet::CoinSelection::SmallestFirst,
+        request::CoinSelection::BranchAndBound => lwk_wollet::CoinSelection::BranchAndBound,
+    }
+}
+
+/// The response of a send, with the selected inputs, the change and the fee if `dry_run`
+///
+/// The change outputs are the ones not paying a recipient nor the fee.
+fn send_response(
+    pset: &PartiallySignedTransaction,
+    recipient_scripts: &HashSet<Script>,
+    dry_run: bool,
+) -> response::WalletSend {
+    let mut response = response::WalletSend {
+        pset: pset.to_string(),
+        inputs: None,
+        change: None,
+        fee: None,
+    };
+    if !dry_run {
+        return response;
+    }
+    response.inputs = Some(
+        pset.inputs()
+            .iter()
+            .map(|i| format!("{}:{}", i.previous_txid, i.previous_output_index))
+            .collect(),
+    );
+    let (fees, outputs): (Vec<_>, Vec<_>) = pset
+        .outputs()
+        .iter()
+        .enumerate()
+        .partition(|(_, o)| o.script_pubkey.is_empty());
+    response.fee = Some(fees.iter().filter_map(|(_, o)| o.amount).sum());
+    response.change = Some(
+        outputs
+            .into_iter()
+            .filter(|(_, o)| !recipient_scripts.contains(&o.script_pubkey))
+            .map(|(vout, o)| response::ChangeOutput {
+                vout: vout as u32,
+                asset: o.asset.map(|a| a.to_string()).unwrap_or_default(),
+                satoshi: o.amount.unwrap_or(0),
+                script_pubkey: o.script_pubkey.to_hex(),
+            })
+            .collect(),
+    );
+    response
+}
+
+fn wallet_address_response(
+    addr: &lwk_wollet::AddressResult,
+    memos: &HashMap<Address, String>,
+    with_text_qr: bool,
+    with_uri_qr: Option<u8>,
+) -> Result<response::WalletAddress, Error> {
+    let address = addr.address();
+    let text_qr = with_text_qr
+        .then(|| address_to_text_qr(address))
+        .transpose()?;
+    let uri_qr = with_uri_qr
+        .map(|e| {
+            let pixel_per_module = (e != 0).then_some(e);
+            address_to_uri_qr(address, pixel_per_module)
+        })
+        .transpose()?;
+    // Memos are set on confidential addresses, match by script pubkey for the unconfidential ones
+    let memo = memos.get(address).or_else(|| {
+        memos
+            .iter()
+            .find(|(a, _)| a.script_pubkey() == address.script_pubkey())
+            .map(|(_, memo)| memo)
+    });
+    Ok(response::WalletAddress {
+        address: address.to_string(),
+        index: addr.index(),
+        is_confidential: addr.is_confidential(),
+        memo: memo.cloned().unwrap_or_default(),
+        text_qr,
+        uri_qr,
+    })
+}
+
+fn validate_address_response(address: &str, network: ElementsNetwork) -> response::ValidateAddress {
+    let invalid = |reason: String| response::ValidateAddress {
+        valid: false,
+        reason: Some(reason),
+        is_confidential: false,
+        blinding_pubkey: None,
+        network: None,
+        script_type: None,
+    };
+    let decoded = match Address::from_str(address) {
+        Ok(decoded) => decoded,
+        Err(e) => return invalid(e.to_string()),
+    };
+    let address_network = [
+        ElementsNetwork::Liquid,
+        ElementsNetwork::LiquidTestnet,
+        ElementsNetwork::ElementsRegtest {
+            policy_asset: network.policy_asset(),
+        },
+    ]
+    .into_iter()
+    .find(|n| n.address_params() == decoded.params)
+    .map(|n| n.as_str());
+    let script_type = match &decoded.payload {
+        Payload::PubkeyHash(_) => "p2pkh",
+        Payload::ScriptHash(_) => "p2sh",
+        Payload::WitnessProgram { version, program } => match (version.to_u8(), program.len()) {
+            (0, 20) => "p2wpkh",
+            (0, 32) => "p2wsh",
+            (1, 32) => "p2tr",
+            _ => "unknown",
+        },
+    };
+
+    let reason = if address_network != Some(network.as_str()) {
+        Some(format!(
+            "The address is for {}, not for {}",
+            address_network.unwrap_or("an unknown network"),
+            network.as_str()
+        ))
+    } else {
+        lwk_wollet::validate_address(address, network)
+            .err()
+            .map(|e| e.to_string())
+    };
+    response::ValidateAddress {
+        valid: reason.is_none(),
+        reason,
+        is_confidential: decoded.is_blinded(),
+        blinding_pubkey: decoded.blinding_pubkey.map(|pk| pk.to_string()),
+        network: address_network.map(Into::into),
+        script_type: Some(script_type.into()),
+    }
+}
+
+/// A record of a BIP-329 labels export
+#[derive(serde::Deserialize)]
+struct Bip329Record {
+    #[serde(rename = "type")]
+    type_: String,
+
+    #[serde(rename = "ref")]
+    ref_: String,
+
+    label: Option<String>,
+}
+
+/// Parse labels in the BIP-329 format, a JSON object per line
+///
+/// Returns the line number, starting from 1, and the parsed record or why it is invalid.
+/// Empty lines are ignored.
+fn parse_bip329(jsonl: &str) -> impl Iterator<Item = (u32, Result<Bip329Record, String>)> + '_ {
+    jsonl
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            let record = serde_json::from_str(line).map_err(|e| e.to_string());
+            (i as u32 + 1, record)
+        })
+}
+
+fn verify_xpub_response(
+    fingerprint: &str,
+    path: &str,
+    xpub: &str,
+) -> Result<response::VerifyXpub, Error> {
+    let fingerprint =
+        Fingerprint::from_str(fingerprint).map_err(|e| Error::Generic(e.to_string()))?;
+    let path = DerivationPath::from_str(path).map_err(|e| Error::Generic(e.to_string()))?;
+    let xpub = Xpub::from_str(xpub).map_err(|e| Error::Generic(e.to_string()))?;
+
+    let steps: Vec<ChildNumber> = path.into_iter().cloned().collect();
+    let reason = if xpub.depth as usize != steps.len() {
+        Some(format!(
+            "The xpub has depth {}, but the path has {} steps",
+            xpub.depth,
+            steps.len()
+        ))
+    } else {
+        match steps.last() {
+            None if xpub.fingerprint() != fingerprint => Some(format!(
+                "The xpub is a master key with fingerprint {}, not {fingerprint}",
+                xpub.fingerprint()
+            )),
+            Some(child) if xpub.child_number != *child => Some(format!(
+                "The xpub has child number {}, but the path ends with {child}",
+                xpub.child_number
+            )),
+            Some(_) if steps.len() == 1 && xpub.parent_fingerprint != fingerprint => Some(format!(
+                "The xpub has parent fingerprint {}, not {fingerprint}",
+                xpub.parent_fingerprint
+            )),
+            _ => None,
+        }
+    };
+    Ok(response::VerifyXpub {
+        valid: reason.is_none(),
+        reason,
+        fingerprint_checked: steps.len() <= 1,
+        depth: xpub.depth,
+        parent_fingerprint: xpub.parent_fingerprint.to_string(),
+    })
+}
+
+fn descriptor_details(descriptor: &str) -> Result<response::DescriptorDetails, Error> {
+    let checksum = descriptor
+        .rsplit_once('#')
+        .map(|(_, c)| c.to_string())
+        .unwrap_or_default();
+    let desc = WolletDescriptor::from_str(descriptor)?;
+    Ok(response::DescriptorDetails {
+        checksum,
+        normalized: desc.to_string(),
+        is_ranged: desc.descriptor().has_wildcard(),
+        is_multipath: desc.descriptor().is_multipath(),
+    })
+}
+
+fn unvalidated_addressee(
+    a: request::UnvalidatedAddressee,
+) -> Result<lwk_wollet::UnvalidatedRecipient, lwk_wollet::Error> {
+    let timelock = match (a.locktime_height, a.csv_blocks) {
+        (None, None) => None,
+        (Some(height), None) => Some(lwk_wollet::Timelock::Height(height)),
+        (None, Some(blocks)) => Some(lwk_wollet::Timelock::Blocks(blocks)),
+        (Some(_), Some(_)) => {
+            return Err(lwk_wollet::Error::InvalidTimelock(
+                "locktime_height and csv_blocks cannot be both set".to_string(),
+            ))
+        }
+    };
+    Ok(lwk_wollet::UnvalidatedRecipient {
         satoshi: a.satoshi,
         address: a.address,
         asset: a.asset,
-    }
+        timelock,
+    })
 }
 
 fn signer_response_from(name: &str, signer: &AppSigner) -> Result<response::Signer, Error> {
     Ok(response::Signer {
         name: name.to_string(),
         fingerprint: signer.fingerprint()?.to_string(),
+        type_: signer.type_(),
     })
 }
 
@@ -1199,7 +2701,7 @@ fn convert_tx(
     explorer_url: &str,
     memos: &HashMap<Txid, String>,
 ) -> response::Tx {
-    let unblinded_url = tx.unblinded_url(explorer_url);
+    let unblinded_url = tx.unblinded_url(explorer_url, lwk_wollet::ExplorerKind::default());
     let memo = memos.get(&tx.txid).cloned().unwrap_or_default();
     response::Tx {
         txid: tx.txid.to_string(),
@@ -1209,7 +2711,13 @@ fn convert_tx(
             .iter()
             .map(|(k, v)| (k.to_string(), *v))
             .collect(),
+        balance_formatted: None,
         fee: tx.fee,
+        burned: tx
+            .burned()
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v))
+            .collect(),
         timestamp: tx.timestamp,
         type_: tx.type_.clone(),
         unblinded_url,
@@ -1254,4 +2762,984 @@ mod tests {
         app.stop().unwrap();
         app.join_threads().unwrap();
     }
+
+    #[test]
+    fn network_details() {
+        let mut app = app_random_port();
+        let client = app.client().unwrap();
+
+        let r = client.network_details().unwrap();
+        assert_eq!(r.network, "liquid-testnet");
+        assert_eq!(
+            r.policy_asset,
+            "144c654344aa716d6f3abcc1ca90e5641e4e2a7f633bc09fe3baf64585819a49"
+        );
+        assert_eq!(
+            r.explorer_url.as_deref(),
+            Some("https://blockstream.info/liquidtestnet/")
+        );
+
+        app.stop().unwrap();
+        app.join_threads().unwrap();
+    }
+
+    #[test]
+    fn schema_all() {
+        let mut app = app_random_port();
+        let client = app.client().unwrap();
+
+        let r = client.schema_all().unwrap();
+        let names: Vec<_> = r.methods.keys().cloned().collect();
+        let mut expected: Vec<_> = enum_iterator::all::<Method>()
+            .map(|m| m.to_string())
+            .collect();
+        expected.sort();
+        assert_eq!(names, expected);
+        for (name, schema) in r.methods.iter() {
+            let method: Method = name.parse().unwrap();
+            let expected = client.schema(method, request::Direction::Request).unwrap();
+            assert_eq!(schema.request, expected);
+            assert!(schema.response.get("$schema").is_some(), "{name}");
+        }
+
+        app.stop().unwrap();
+        app.join_threads().unwrap();
+    }
+
+    #[test]
+    fn openrpc_document() {
+        let mut app = app_random_port();
+        let client = app.client().unwrap();
+
+        let r = client.openrpc().unwrap();
+        assert_eq!(r, openrpc::openrpc().unwrap());
+        let methods = r["methods"].as_array().unwrap();
+        assert!(methods.iter().any(|m| m["name"] == "openrpc"));
+
+        app.stop().unwrap();
+        app.join_threads().unwrap();
+    }
+
+    #[test]
+    fn signer_generate_word_count() {
+        let mut app = app_random_port();
+        let client = app.client().unwrap();
+
+        let words = |word_count| {
+            let mnemonic = client.signer_generate(word_count).unwrap().mnemonic;
+            mnemonic.split_whitespace().count()
+        };
+        assert_eq!(words(None), request::DEFAULT_WORD_COUNT as usize);
+        for word_count in [12, 15, 18, 21, 24] {
+            assert_eq!(words(Some(word_count)), word_count as usize);
+        }
+        let err = client.signer_generate(Some(13)).unwrap_err();
+        assert!(err.to_string().contains("invalid word count"), "{err}");
+
+        app.stop().unwrap();
+        app.join_threads().unwrap();
+    }
+
+    #[test]
+    fn signer_passphrase() {
+        let mut app = app_random_port();
+        let client = app.client().unwrap();
+
+        let mnemonic = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+        let r = client
+            .signer_load_software("s1".into(), mnemonic.into(), None, false)
+            .unwrap();
+        assert_eq!(r.fingerprint, "73c5da0a");
+        let r = client
+            .signer_load_software("s2".into(), mnemonic.into(), Some("TREZOR".into()), false)
+            .unwrap();
+        assert_eq!(r.fingerprint, "b4e3f5ed");
+
+        app.stop().unwrap();
+        app.join_threads().unwrap();
+    }
+
+    #[test]
+    fn registered_multisigs_not_jade() {
+        let mut app = app_random_port();
+        let client = app.client().unwrap();
+
+        let mnemonic = client.signer_generate(None).unwrap().mnemonic;
+        client
+            .signer_load_software("s".into(), mnemonic, None, false)
+            .unwrap();
+        let err = client.signer_registered_multisigs("s".into()).unwrap_err();
+        assert!(
+            err.to_string().contains("Signer 's' is not a Jade"),
+            "{err}"
+        );
+        let err = client
+            .signer_registered_multisigs("missing".into())
+            .unwrap_err();
+        assert!(err.to_string().contains("does not exist"), "{err}");
+        let err = client.signer_jade_status("s".into()).unwrap_err();
+        assert!(
+            err.to_string().contains("Signer 's' is not a Jade"),
+            "{err}"
+        );
+        let err = client.signer_jade_unlock("s".into()).unwrap_err();
+        assert!(
+            err.to_string().contains("Signer 's' is not a Jade"),
+            "{err}"
+        );
+
+        app.stop().unwrap();
+        app.join_threads().unwrap();
+    }
+
+    #[test]
+    fn descriptor_details() {
+        let mut app = app_random_port();
+        let client = app.client().unwrap();
+
+        let mnemonic = client.signer_generate(None).unwrap().mnemonic;
+        client
+            .signer_load_software("s".into(), mnemonic, None, false)
+            .unwrap();
+        let r = client
+            .signer_singlesig_descriptor("s".into(), "elip151".into(), request::SinglesigKind::Wpkh)
+            .unwrap();
+        assert!(r.descriptor.ends_with(&format!("#{}", r.details.checksum)));
+        assert!(!r.details.normalized.contains("elip151"));
+        assert!(r.details.is_ranged);
+        assert!(r.details.is_multipath);
+        client
+            .wallet_load(r.details.normalized.clone(), "w".into(), None)
+            .unwrap();
+
+        let xpub = client
+            .signer_xpub("s".into(), request::XpubKind::Bip87)
+            .unwrap()
+            .keyorigin_xpub;
+        let r = client
+            .wallet_multisig_descriptor(
+                "slip77-rand".into(),
+                request::MultisigKind::Wsh,
+                1,
+                vec![xpub],
+                true,
+            )
+            .unwrap();
+        assert_eq!(r.details.checksum.len(), 8);
+        // Hardened steps are formatted differently, but they are the same descriptor
+        assert_ne!(r.details.normalized, r.descriptor);
+        let desc: WolletDescriptor = r.descriptor.parse().unwrap();
+        assert_eq!(r.details.normalized, desc.to_string());
+        assert!(r.details.is_ranged);
+        assert!(r.details.is_multipath);
+
+        app.stop().unwrap();
+        app.join_threads().unwrap();
+    }
+
+    #[test]
+    fn persist_software_signer() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let start = |password: Option<&str>| {
+            let addr = TcpListener::bind("127.0.0.1:0")
+                .unwrap()
+                .local_addr()
+                .unwrap();
+            let mut config = Config::default_testnet(tempdir.path().to_path_buf());
+            config.addr = addr;
+            config.state_key = password.map(StateKey::from_password);
+            let mut app = App::new(config).unwrap();
+            app.run().map(|_| app)
+        };
+        let stop = |mut app: App| {
+            app.stop().unwrap();
+            app.join_threads().unwrap();
+        };
+
+        // Without a state password software signers cannot be persisted
+        let app = start(None).unwrap();
+        let client = app.client().unwrap();
+        let mnemonic = client.signer_generate(None).unwrap().mnemonic;
+        let err = client
+            .signer_load_software("s1".into(), mnemonic.clone(), None, true)
+            .unwrap_err();
+        assert!(err.to_string().contains("Wallet store locked"), "{err}");
+        client
+            .signer_load_software("s1".into(), mnemonic.clone(), None, false)
+            .unwrap();
+        stop(app);
+
+        let app = start(Some("password")).unwrap();
+        let client = app.client().unwrap();
+        assert!(client.signer_list().unwrap().signers.is_empty());
+        client
+            .signer_load_software("s1".into(), mnemonic.clone(), Some("pass".into()), true)
+            .unwrap();
+        let other = client.signer_generate(None).unwrap().mnemonic;
+        client
+            .signer_load_software("s2".into(), other, None, false)
+            .unwrap();
+        let desc = client
+            .signer_singlesig_descriptor("s1".into(), "slip77".into(), request::SinglesigKind::Wpkh)
+            .unwrap()
+            .descriptor;
+        client.wallet_load(desc, "w1".into(), None).unwrap();
+        let desc = client.wallet_list().unwrap().wallets[0].descriptor.clone();
+        let expected = client.signer_details("s1".into()).unwrap();
+        stop(app);
+
+        let state_path = Config::default_testnet(tempdir.path().to_path_buf())
+            .state_path()
+            .unwrap();
+        let state = std::fs::read_to_string(&state_path).unwrap();
+        assert!(!state.contains(&mnemonic));
+        assert!(!state.contains("pass\""));
+        assert!(state.contains("encrypted"));
+
+        // Without the state password the store is locked, the encrypted signer is not loaded
+        let app = start(None).unwrap();
+        let client = app.client().unwrap();
+        assert!(client.signer_list().unwrap().signers.is_empty());
+        assert_eq!(client.wallet_list().unwrap().wallets.len(), 1);
+        let err = client
+            .signer_load_software("s3".into(), mnemonic.clone(), None, true)
+            .unwrap_err();
+        assert!(err.to_string().contains("Wallet store locked"), "{err}");
+        // Rewriting the state keeps the encrypted signer
+        let other = client.signer_generate(None).unwrap().mnemonic;
+        client
+            .signer_load_software("s3".into(), other, None, false)
+            .unwrap();
+        let other_desc = client
+            .signer_singlesig_descriptor("s3".into(), "slip77".into(), request::SinglesigKind::Wpkh)
+            .unwrap()
+            .descriptor;
+        client.wallet_load(other_desc, "w3".into(), None).unwrap();
+        client.wallet_unload("w3".into()).unwrap();
+        stop(app);
+
+        let err = start(Some("wrong")).err().unwrap();
+        assert!(err.to_string().contains("Cannot decrypt"), "{err}");
+
+        let app = start(Some("password")).unwrap();
+        let client = app.client().unwrap();
+        let signers = client.signer_list().unwrap().signers;
+        assert_eq!(signers.len(), 1);
+        let details = client.signer_details("s1".into()).unwrap();
+        assert_eq!(details.fingerprint, expected.fingerprint);
+        assert_eq!(details.xpub, expected.xpub);
+        let wallets = client.wallet_list().unwrap().wallets;
+        assert_eq!(wallets.len(), 1);
+        assert_eq!(wallets[0].descriptor, desc);
+        stop(app);
+
+        // The state has been rewritten at start, still encrypted
+        let state = std::fs::read_to_string(&state_path).unwrap();
+        assert!(!state.contains(&mnemonic));
+        assert!(state.contains("encrypted"));
+    }
+
+    #[test]
+    fn legacy_plaintext_software_signer() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let start = |password: Option<&str>| {
+            let addr = TcpListener::bind("127.0.0.1:0")
+                .unwrap()
+                .local_addr()
+                .unwrap();
+            let mut config = Config::default_testnet(tempdir.path().to_path_buf());
+            config.addr = addr;
+            config.state_key = password.map(StateKey::from_password);
+            let mut app = App::new(config).unwrap();
+            app.run().unwrap();
+            app
+        };
+        let stop = |mut app: App| {
+            app.stop().unwrap();
+            app.join_threads().unwrap();
+        };
+
+        // A signer persisted in plaintext by a previous version
+        let state_path = Config::default_testnet(tempdir.path().to_path_buf())
+            .state_path()
+            .unwrap();
+        let mnemonic = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+        let params = request::SignerLoadSoftware {
+            name: "s1".into(),
+            mnemonic: mnemonic.into(),
+            passphrase: None,
+            persist: true,
+        };
+        let line = Request {
+            jsonrpc: "2.0".into(),
+            id: None,
+            method: Method::SignerLoadSoftware.to_string(),
+            params: Some(serde_json::to_value(params).unwrap()),
+        };
+        std::fs::write(&state_path, serde_json::to_string(&line).unwrap() + "\n").unwrap();
+
+        // Without a state password the signer is loaded and kept as it is on rewrites
+        let app = start(None);
+        let client = app.client().unwrap();
+        assert_eq!(client.signer_list().unwrap().signers.len(), 1);
+        let desc = client
+            .signer_singlesig_descriptor("s1".into(), "slip77".into(), request::SinglesigKind::Wpkh)
+            .unwrap()
+            .descriptor;
+        client.wallet_load(desc, "w1".into(), None).unwrap();
+        client.wallet_unload("w1".into()).unwrap();
+        stop(app);
+        let state = std::fs::read_to_string(&state_path).unwrap();
+        assert!(state.contains(mnemonic));
+
+        // With a state password the state is rewritten encrypted
+        let app = start(Some("password"));
+        let client = app.client().unwrap();
+        assert_eq!(client.signer_list().unwrap().signers.len(), 1);
+        stop(app);
+        let state = std::fs::read_to_string(&state_path).unwrap();
+        assert!(!state.contains(mnemonic));
+        assert!(state.contains("encrypted"));
+    }
+
+    /// Send a request to the method handler, without a running server
+    fn handle<Req: serde::Serialize, Res: serde::de::DeserializeOwned>(
+        state: &Arc<Mutex<State>>,
+        method: Method,
+        params: Req,
+    ) -> Res {
+        let request = Request {
+            jsonrpc: "2.0".into(),
+            id: None,
+            method: method.to_string(),
+            params: Some(serde_json::to_value(params).unwrap()),
+        };
+        let response = inner_method_handler(request, state.clone()).unwrap();
+        serde_json::from_value(response.result.unwrap()).unwrap()
+    }
+
+    fn sync_status(state: &Arc<Mutex<State>>, name: &str) -> response::WalletSyncStatus {
+        let params = request::WalletSyncStatus { name: name.into() };
+        handle(state, Method::WalletSyncStatus, params)
+    }
+
+    #[test]
+    fn sync_status_while_scanning() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let config = Config::default_testnet(tempdir.path().to_path_buf());
+        let state = Arc::new(Mutex::new(State::new(config)));
+        let desc: WolletDescriptor = lwk_test_util::wollet_descriptor_string2().parse().unwrap();
+        let bytes = lwk_test_util::update_test_vector_encrypted_bytes2();
+        let update = Update::deserialize_decrypted(&bytes, &desc).unwrap();
+        let tip_height = update.tip.height;
+        let wollet = Wollet::without_persist(ElementsNetwork::LiquidTestnet, desc).unwrap();
+        state.lock().unwrap().wollets.insert("w1", wollet).unwrap();
+
+        let r = sync_status(&state, "w1");
+        assert!(!r.scanning);
+        assert_eq!(r.last_scanned_height, None);
+        assert_eq!(r.tip_height, None);
+        assert_eq!(r.progress, 0.0);
+
+        // The steps of a scan loop, as done by the scanning thread
+        let (to_scan, _) = start_scan(&state).unwrap();
+        assert!(sync_status(&state, "w1").scanning);
+
+        let (name, snapshot) = &to_scan[0];
+        apply_scan(&state, name, snapshot, Some(update));
+        let r = sync_status(&state, "w1");
+        assert!(!r.scanning);
+        assert_eq!(r.last_scanned_height, Some(tip_height));
+        assert_eq!(r.tip_height, Some(tip_height));
+        assert_eq!(r.progress, 100.0);
+        finish_scan(&state);
+    }
+
+    #[test]
+    fn wallet_unload_while_scanning() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let config = Config::default_testnet(tempdir.path().to_path_buf());
+        let state = Arc::new(Mutex::new(State::new(config)));
+        let desc: WolletDescriptor = lwk_test_util::wollet_descriptor_string2().parse().unwrap();
+        let bytes = lwk_test_util::update_test_vector_encrypted_bytes2();
+        let update = Update::deserialize_decrypted(&bytes, &desc).unwrap();
+        let network = ElementsNetwork::LiquidTestnet;
+        let load = || Wollet::with_fs_persist(network, desc.clone(), tempdir.path()).unwrap();
+        state.lock().unwrap().wollets.insert("w1", load()).unwrap();
+
+        // The scan loop is paused after taking the snapshots
+        let (to_scan, _) = start_scan(&state).unwrap();
+        let unload = {
+            let state = state.clone();
+            std::thread::spawn(move || {
+                let params = request::WalletUnload { name: "w1".into() };
+                handle::<_, response::WalletUnload>(&state, Method::WalletUnload, params)
+            })
+        };
+        std::thread::sleep(std::time::Duration::from_millis(200));
+        assert!(!unload.is_finished());
+
+        // Completing the wallet scan resumes the unload
+        let (name, snapshot) = &to_scan[0];
+        apply_scan(&state, name, snapshot, Some(update));
+        assert_eq!(unload.join().unwrap().unloaded.name, "w1");
+        finish_scan(&state);
+        assert!(state.lock().unwrap().wollets.get("w1").is_err());
+
+        // The next loop forgets the wallet
+        let (to_scan, unloaded) = start_scan(&state).unwrap();
+        assert!(to_scan.is_empty());
+        assert_eq!(unloaded.len(), 1);
+        finish_scan(&state);
+
+        // Reloading the wallet restores the scan result from the store
+        let wollet = load();
+        assert!(!wollet.never_scanned());
+        let lbtc = network.policy_asset();
+        assert_eq!(wollet.balance().unwrap()[&lbtc], 100_000);
+    }
+
+    #[test]
+    fn persist_start_height() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let config = Config::default_testnet(tempdir.path().to_path_buf());
+        let state_path = config.state_path().unwrap();
+        let start = || {
+            let mut config = config.clone();
+            config.addr = TcpListener::bind("127.0.0.1:0")
+                .unwrap()
+                .local_addr()
+                .unwrap();
+            let mut app = App::new(config).unwrap();
+            app.run().unwrap();
+            app
+        };
+        let desc = "ct(c25deb86fa11e49d651d7eae27c220ef930fbd86ea023eebfa73e54875647963,elwpkh(tpubD6NzVbkrYhZ4Was8nwnZi7eiWUNJq2LFpPSCMQLioUfUtT1e72GkRbmVeRAZc26j5MRUz2hRLsaVHJfs6L7ppNfLUrm9btQTuaEsLrT7D87/*))#q9cypnmc";
+        // Loading and unloading another wallet rewrites the whole state
+        let rewrite_state = |client: &Client| {
+            let mnemonic = client.signer_generate(None).unwrap().mnemonic;
+            client
+                .signer_load_software("s".into(), mnemonic, None, false)
+                .unwrap();
+            let other = client
+                .signer_singlesig_descriptor(
+                    "s".into(),
+                    "slip77".into(),
+                    request::SinglesigKind::Wpkh,
+                )
+                .unwrap()
+                .descriptor;
+            client.wallet_load(other, "w2".into(), None).unwrap();
+            client.wallet_unload("w2".into()).unwrap();
+            client.signer_unload("s".into()).unwrap();
+        };
+
+        let mut app = start();
+        let client = app.client().unwrap();
+        let req = request::WalletLoad {
+            descriptor: desc.into(),
+            name: "w1".into(),
+            gap_limit: None,
+            start_height: Some(100),
+        };
+        let _: response::Wallet = client.make_request(Method::WalletLoad, Some(req)).unwrap();
+        app.stop().unwrap();
+        app.join_threads().unwrap();
+
+        // After a restart the start height is still there
+        let mut app = start();
+        let client = app.client().unwrap();
+        rewrite_state(&client);
+        let state = std::fs::read_to_string(state_path).unwrap();
+        assert!(state.contains(r#""start_height":100"#), "{state}");
+        app.stop().unwrap();
+        app.join_threads().unwrap();
+    }
+
+    #[test]
+    fn list_wallets_signers() {
+        let mut app = app_random_port();
+        let client = app.client().unwrap();
+
+        for name in ["s2", "s1"] {
+            let mnemonic = client.signer_generate(None).unwrap().mnemonic;
+            client
+                .signer_load_software(name.into(), mnemonic, None, false)
+                .unwrap();
+            let desc = client
+                .signer_singlesig_descriptor(
+                    name.into(),
+                    "slip77".into(),
+                    request::SinglesigKind::Wpkh,
+                )
+                .unwrap()
+                .descriptor;
+            client
+                .wallet_load(desc, name.replace('s', "w"), None)
+                .unwrap();
+        }
+
+        let r = client.signer_list().unwrap();
+        let names: Vec<_> = r.signers.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["s1", "s2"]);
+        assert!(r.signers.iter().all(|s| s.type_ == "software"));
+        assert_eq!(
+            r.signers[0].fingerprint,
+            client.signer_details("s1".into()).unwrap().fingerprint
+        );
+
+        let r = client.wallet_list().unwrap();
+        let names: Vec<_> = r.wallets.iter().map(|w| w.name.as_str()).collect();
+        assert_eq!(names, vec!["w1", "w2"]);
+        for wallet in r.wallets {
+            assert_eq!(wallet.network, "liquid-testnet");
+            assert_eq!(wallet.checksum.len(), 8);
+            assert!(wallet
+                .descriptor
+                .ends_with(&format!("#{}", wallet.checksum)));
+        }
+
+        app.stop().unwrap();
+        app.join_threads().unwrap();
+    }
+
+    #[test]
+    fn wallet_load_idempotent() {
+        use lwk_rpc_model::error::ErrorCode;
+
+        let mut app = app_random_port();
+        let client = app.client().unwrap();
+
+        let desc = "ct(c25deb86fa11e49d651d7eae27c220ef930fbd86ea023eebfa73e54875647963,elwpkh(tpubD6NzVbkrYhZ4Was8nwnZi7eiWUNJq2LFpPSCMQLioUfUtT1e72GkRbmVeRAZc26j5MRUz2hRLsaVHJfs6L7ppNfLUrm9btQTuaEsLrT7D87/*))#q9cypnmc";
+        let r = client.wallet_load(desc.into(), "w1".into(), None).unwrap();
+        assert_eq!(r.descriptor, desc);
+
+        // The descriptors are compared once normalized, with the checksum
+        let no_checksum = desc.split('#').next().unwrap();
+        let r = client
+            .wallet_load(no_checksum.into(), "w1".into(), None)
+            .unwrap();
+        assert_eq!(r.name, "w1");
+        assert_eq!(r.descriptor, desc);
+        assert_eq!(client.wallet_list().unwrap().wallets.len(), 1);
+
+        let mnemonic = client.signer_generate(None).unwrap().mnemonic;
+        client
+            .signer_load_software("s1".into(), mnemonic, None, false)
+            .unwrap();
+        let other = client
+            .signer_singlesig_descriptor("s1".into(), "slip77".into(), request::SinglesigKind::Wpkh)
+            .unwrap()
+            .descriptor;
+        let err = client.wallet_load(other, "w1".into(), None).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("Wallet 'w1' is already loaded with a different descriptor"),
+            "{err}"
+        );
+        assert_eq!(err.rpc_error_code(), Some(ErrorCode::WalletAlreadyLoaded));
+
+        // Same descriptor with a different name is still an error
+        let err = client
+            .wallet_load(desc.into(), "w2".into(), None)
+            .unwrap_err();
+        assert!(err.to_string().contains("Wallet 'w1' is already loaded"));
+        assert_eq!(client.wallet_list().unwrap().wallets.len(), 1);
+
+        // The gap limit and the start height must match too
+        let err = client
+            .wallet_load(desc.into(), "w1".into(), Some(1))
+            .unwrap_err();
+        assert!(
+            err.to_string().contains(
+                "Wallet 'w1' is already loaded with a different gap limit or start height"
+            ),
+            "{err}"
+        );
+        assert_eq!(err.rpc_error_code(), Some(ErrorCode::WalletAlreadyLoaded));
+        let tempdir = tempfile::tempdir().unwrap();
+        let config = Config::default_testnet(tempdir.path().to_path_buf());
+        let state = Arc::new(Mutex::new(State::new(config)));
+        let load = |start_height| Request {
+            jsonrpc: "2.0".into(),
+            id: None,
+            method: Method::WalletLoad.to_string(),
+            params: Some(
+                serde_json::to_value(request::WalletLoad {
+                    descriptor: desc.into(),
+                    name: "w1".into(),
+                    gap_limit: None,
+                    start_height,
+                })
+                .unwrap(),
+            ),
+        };
+        inner_method_handler(load(Some(100)), state.clone()).unwrap();
+        inner_method_handler(load(Some(100)), state.clone()).unwrap();
+        let err = inner_method_handler(load(None), state.clone()).unwrap_err();
+        assert!(matches!(err, Error::WalletOptionsMismatch(name) if name == "w1"));
+
+        app.stop().unwrap();
+        app.join_threads().unwrap();
+    }
+
+    #[test]
+    fn error_codes() {
+        use lwk_rpc_model::error::ErrorCode;
+
+        let mut app = app_random_port();
+        let client = app.client().unwrap();
+
+        let err = client.wallet_details("notexist".into()).unwrap_err();
+        assert_eq!(err.rpc_error_code(), Some(ErrorCode::WalletNotFound));
+        let Error::RpcError(rpc_err) = err else {
+            panic!("unexpected error")
+        };
+        assert_eq!(rpc_err.code, -32_008);
+        assert_eq!(rpc_err.message, "Wallet 'notexist' does not exist");
+        let data: Value = serde_json::from_str(rpc_err.data.unwrap().get()).unwrap();
+        assert_eq!(
+            data,
+            serde_json::json!({"name": "notexist", "error_code": "WALLET_NOT_FOUND"})
+        );
+
+        let err = client.wallet_unload("notexist".into()).unwrap_err();
+        assert_eq!(err.rpc_error_code(), Some(ErrorCode::WalletNotFound));
+
+        let err = client.signer_details("notexist".into()).unwrap_err();
+        assert_eq!(err.rpc_error_code(), Some(ErrorCode::SignerNotFound));
+
+        let err = Error::Wollet(lwk_wollet::Error::InsufficientFunds);
+        assert_eq!(err.error_code(), ErrorCode::InsufficientFunds);
+        let err = Error::Generic("generic".into());
+        assert_eq!(err.error_code(), ErrorCode::Generic);
+        assert_eq!(
+            err.as_error_value(),
+            Some(serde_json::json!({"error_code": "GENERIC"}))
+        );
+        let err = Error::AddressMismatch {
+            wallet: "a".into(),
+            jade: "b".into(),
+        };
+        assert_eq!(
+            err.as_error_value(),
+            Some(serde_json::json!({
+                "wallet_address": "a",
+                "device_address": "b",
+                "error_code": "ADDRESS_MISMATCH"
+            }))
+        );
+
+        app.stop().unwrap();
+        app.join_threads().unwrap();
+    }
+
+    #[test]
+    fn wallet_details_keys() {
+        let mut app = app_random_port();
+        let client = app.client().unwrap();
+
+        let mut xpubs = vec![];
+        for name in ["s1", "s2"] {
+            let mnemonic = client.signer_generate(None).unwrap().mnemonic;
+            client
+                .signer_load_software(name.into(), mnemonic, None, false)
+                .unwrap();
+            let xpub = client
+                .signer_xpub(name.into(), request::XpubKind::Bip87)
+                .unwrap()
+                .keyorigin_xpub;
+            xpubs.push(xpub);
+        }
+        let fingerprint = |name: &str| client.signer_details(name.into()).unwrap().fingerprint;
+        let (fp1, fp2) = (fingerprint("s1"), fingerprint("s2"));
+
+        let desc = client
+            .signer_singlesig_descriptor("s1".into(), "slip77".into(), request::SinglesigKind::Wpkh)
+            .unwrap()
+            .descriptor;
+        client.wallet_load(desc, "ss".into(), None).unwrap();
+        let r = client.wallet_details("ss".into()).unwrap();
+        assert_eq!(r.type_, "wpkh");
+        assert_eq!(r.next_external_index, 0);
+        assert_eq!(r.next_internal_index, 0);
+        assert!(r.threshold.is_none());
+        assert_eq!(r.keys.len(), 1);
+        assert_eq!(r.keys[0].fingerprint, fp1);
+        assert_eq!(r.keys[0].origin_path.as_deref(), Some("m/84h/1h/0h"));
+        assert!(r.keys[0].xpub.starts_with("tpub"));
+        assert!(!r.keys[0].watch_only);
+
+        let desc = client
+            .wallet_multisig_descriptor(
+                "slip77-rand".into(),
+                request::MultisigKind::Wsh,
+                2,
+                xpubs.clone(),
+                true,
+            )
+            .unwrap()
+            .descriptor;
+        client.wallet_load(desc, "ms".into(), None).unwrap();
+        client.signer_unload("s2".into()).unwrap();
+        let r = client.wallet_details("ms".into()).unwrap();
+        assert_eq!(r.type_, "wsh_multi_2of2");
+        assert_eq!(r.threshold, Some(2));
+        let keys: Vec<_> = r
+            .keys
+            .iter()
+            .map(|k| {
+                let path = k.origin_path.as_ref().unwrap().replace("m/", "");
+                (
+                    format!("[{}/{path}]{}", k.fingerprint, k.xpub),
+                    k.watch_only,
+                )
+            })
+            .collect();
+        assert_eq!(
+            keys,
+            vec![(xpubs[0].clone(), false), (xpubs[1].clone(), true)]
+        );
+        assert_eq!(r.keys[1].fingerprint, fp2);
+
+        app.stop().unwrap();
+        app.join_threads().unwrap();
+    }
+
+    #[test]
+    fn wallet_labels() {
+        let mut app = app_random_port();
+        let client = app.client().unwrap();
+
+        let mnemonic = client.signer_generate(None).unwrap().mnemonic;
+        client
+            .signer_load_software("s".into(), mnemonic, None, false)
+            .unwrap();
+        let desc = client
+            .signer_singlesig_descriptor("s".into(), "slip77".into(), request::SinglesigKind::Wpkh)
+            .unwrap()
+            .descriptor;
+        client.wallet_load(desc, "w1".into(), None).unwrap();
+        let mnemonic = client.signer_generate(None).unwrap().mnemonic;
+        client
+            .signer_load_software("s2".into(), mnemonic, None, false)
+            .unwrap();
+        let desc = client
+            .signer_singlesig_descriptor("s2".into(), "slip77".into(), request::SinglesigKind::Wpkh)
+            .unwrap()
+            .descriptor;
+        client.wallet_load(desc, "w2".into(), None).unwrap();
+
+        let address = client
+            .wallet_address("w1".into(), Some(0), None, false, None, None)
+            .unwrap()
+            .address;
+        let txid = "0000000000000000000000000000000000000000000000000000000000000001";
+        client
+            .wallet_set_tx_memo("w1".into(), txid.into(), "tx label".into())
+            .unwrap();
+        client
+            .wallet_set_addr_memo("w1".into(), address.clone(), "addr label".into())
+            .unwrap();
+        let outpoint = format!("{txid}:1");
+        client
+            .wallet_set_output_memo("w1".into(), outpoint.clone(), "output label".into())
+            .unwrap();
+        client
+            .wallet_set_output_memo("w1".into(), "xyz".into(), "a".into())
+            .unwrap_err();
+
+        let r = client.wallet_labels("w1".into()).unwrap();
+        let expected = vec![
+            response::Label {
+                type_: "tx".into(),
+                ref_: txid.into(),
+                label: "tx label".into(),
+            },
+            response::Label {
+                type_: "addr".into(),
+                ref_: address.clone(),
+                label: "addr label".into(),
+            },
+            response::Label {
+                type_: "output".into(),
+                ref_: outpoint,
+                label: "output label".into(),
+            },
+        ];
+        assert_eq!(r.labels, expected);
+        let lines: Vec<response::Label> = r
+            .jsonl
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(lines, expected);
+        assert!(r.jsonl.starts_with(r#"{"type":"tx","ref":"#));
+
+        assert!(client.wallet_labels("w2".into()).unwrap().labels.is_empty());
+
+        // Malformed lines are reported, the other records do not refer to w2 and are skipped,
+        // as are unsupported types and records without label
+        let jsonl = format!(
+            "{}\n\nnot json\n{}\n{}\n{}",
+            r.jsonl,
+            r#"{"type":"tx","ref":"xyz","label":"a"}"#,
+            r#"{"type":"xpub","ref":"tpubD6NzVbkrYhZ4","label":"x"}"#,
+            format_args!(r#"{{"type":"output","ref":"{txid}:0","spendable":false}}"#),
+        );
+        let imported = client.wallet_import_labels("w2".into(), jsonl).unwrap();
+        assert_eq!(imported.imported, 0);
+        assert_eq!(imported.skipped, 5);
+        let lines: Vec<_> = imported.errors.iter().map(|e| e.line).collect();
+        assert_eq!(lines, vec![5, 6]);
+        assert!(client.wallet_labels("w2".into()).unwrap().labels.is_empty());
+
+        client.wallet_labels("w3".into()).unwrap_err();
+
+        app.stop().unwrap();
+        app.join_threads().unwrap();
+    }
+
+    #[test]
+    fn validate_address() {
+        let mut app = app_random_port();
+        let client = app.client().unwrap();
+        let testnet_address = "tlq1qq2xvpcvfup5j8zscjq05u2wxxjcyewk7979f3mmz5l7uw5pqmx6xf5xy50hsn6vhkm5euwt72x878eq6zxx2z58hd7zrsg9qn";
+
+        let r = client
+            .validate_address(testnet_address.into(), "liquid-testnet".into())
+            .unwrap();
+        assert!(r.valid);
+        assert!(r.reason.is_none());
+        assert!(r.is_confidential);
+        assert!(r.blinding_pubkey.is_some());
+        assert_eq!(r.network.as_deref(), Some("liquid-testnet"));
+        assert_eq!(r.script_type.as_deref(), Some("p2wpkh"));
+
+        let r = client
+            .validate_address(testnet_address.into(), "liquid".into())
+            .unwrap();
+        assert!(!r.valid);
+        assert_eq!(
+            r.reason.as_deref(),
+            Some("The address is for liquid-testnet, not for liquid")
+        );
+        assert_eq!(r.network.as_deref(), Some("liquid-testnet"));
+
+        let unconfidential = lwk_wollet::elements::Address::from_str(testnet_address)
+            .unwrap()
+            .to_unconfidential()
+            .to_string();
+        let r = client
+            .validate_address(unconfidential, "liquid-testnet".into())
+            .unwrap();
+        assert!(!r.valid);
+        assert!(!r.is_confidential);
+        assert!(r.reason.is_some());
+
+        let r = client
+            .validate_address("not an address".into(), "liquid-testnet".into())
+            .unwrap();
+        assert!(!r.valid);
+        assert!(r.network.is_none());
+
+        let err = client
+            .validate_address(testnet_address.into(), "not a wallet".into())
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("neither a loaded wallet nor a network"));
+
+        app.stop().unwrap();
+        app.join_threads().unwrap();
+    }
+
+    #[test]
+    fn verify_xpub() {
+        let mut app = app_random_port();
+        let client = app.client().unwrap();
+        let mnemonic = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+        let signer = SwSigner::new(mnemonic, false).unwrap();
+        let fingerprint = signer.fingerprint().to_string();
+        let other = "00000000".to_string();
+        let xpub_at = |path: &str| {
+            let path = DerivationPath::from_str(path).unwrap();
+            signer.derive_xpub(&path).unwrap().to_string()
+        };
+
+        let path = "m/87h/1h/0h";
+        let xpub = xpub_at(path);
+        let r = client
+            .verify_xpub(fingerprint.clone(), path.into(), xpub.clone())
+            .unwrap();
+        assert!(r.valid);
+        assert!(r.reason.is_none());
+        assert!(!r.fingerprint_checked);
+        assert_eq!(r.depth, 3);
+
+        // Only the master fingerprint can't be checked for deep paths
+        let r = client
+            .verify_xpub(other.clone(), path.into(), xpub.clone())
+            .unwrap();
+        assert!(r.valid);
+
+        for wrong_path in ["m/87h/1h", "m/87h/1h/1h", "m/87h/1h/0"] {
+            let r = client
+                .verify_xpub(fingerprint.clone(), wrong_path.into(), xpub.clone())
+                .unwrap();
+            assert!(!r.valid, "{wrong_path}");
+            assert!(r.reason.is_some(), "{wrong_path}");
+        }
+
+        let path = "m/87h";
+        let xpub = xpub_at(path);
+        let r = client
+            .verify_xpub(fingerprint.clone(), path.into(), xpub.clone())
+            .unwrap();
+        assert!(r.valid);
+        assert!(r.fingerprint_checked);
+        assert_eq!(r.parent_fingerprint, fingerprint);
+        let r = client
+            .verify_xpub(other.clone(), path.into(), xpub)
+            .unwrap();
+        assert!(!r.valid);
+        assert!(r.reason.unwrap().contains("parent fingerprint"));
+
+        let master = xpub_at("m");
+        let r = client
+            .verify_xpub(fingerprint.clone(), "m".into(), master.clone())
+            .unwrap();
+        assert!(r.valid);
+        assert!(r.fingerprint_checked);
+        let r = client.verify_xpub(other, "m".into(), master).unwrap();
+        assert!(!r.valid);
+
+        let err = client
+            .verify_xpub(fingerprint, "m".into(), "not an xpub".into())
+            .unwrap_err();
+        assert!(err.to_string().contains("base58"), "{err}");
+
+        app.stop().unwrap();
+        app.join_threads().unwrap();
+    }
+
+    #[test]
+    fn test_fee_rate_kvb() {
+        use request::FeeRateUnit::*;
+        assert_eq!(fee_rate_kvb(0.1, None), 100.0);
+        assert_eq!(fee_rate_kvb(0.1, Some(SatPerVb)), 100.0);
+        assert_eq!(fee_rate_kvb(100.0, Some(SatPerKvb)), 100.0);
+        assert!(fee_rate_kvb(f32::NAN, None).is_nan());
+    }
+
+    #[test]
+    fn test_sync_progress() {
+        assert_eq!(sync_progress(None, Some(100)), 0.0);
+        assert_eq!(sync_progress(None, None), 0.0);
+        assert_eq!(sync_progress(Some(50), Some(100)), 50.0);
+        assert_eq!(sync_progress(Some(100), Some(100)), 100.0);
+        assert_eq!(sync_progress(Some(101), Some(100)), 100.0);
+        assert_eq!(sync_progress(Some(0), Some(0)), 100.0);
+        assert_eq!(sync_progress(Some(50), None), 100.0);
+    }
 }
@@ -13,38 +13,59 @@ pub struct MethodNotExist {
     name: String,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
-#[cfg_attr(test, derive(enum_iterator::Sequence))]
+#[derive(Clone, Debug, PartialEq, Eq, enum_iterator::Sequence)]
 pub enum Method {
     Schema,
+    SchemaAll,
+    Openrpc,
     Version,
+    NetworkDetails,
     Scan,
     Stop,
     WalletLoad,
     WalletUnload,
+    WalletRescan,
     WalletList,
     WalletDetails,
+    WalletSyncStatus,
     WalletAddress,
+    WalletAddresses,
     WalletBalance,
     WalletUtxos,
     WalletTxs,
     WalletTx,
+    WalletExportCsv,
     WalletSendMany,
+    WalletEstimateFee,
     WalletDrain,
+    WalletBumpFee,
     WalletIssue,
     WalletReissue,
     WalletBurn,
+    WalletConsolidate,
+    WalletCreateSwap,
     WalletCombine,
     WalletBroadcast,
     WalletPsetDetails,
+    WalletPsetAnalyze,
+    WalletProofOfReserves,
+    VerifyProofOfReserves,
     WalletMultisigDescriptor,
     WalletSetTxMemo,
     WalletSetAddrMemo,
+    WalletSetOutputMemo,
+    WalletLabels,
+    WalletImportLabels,
+    ValidateAddress,
+    WalletIsMine,
+    WalletExportDescriptor,
+    VerifyXpub,
     SignerGenerate,
     SignerJadeId,
     SignerLoadSoftware,
     SignerLoadJade,
     SignerLoadExternal,
+    SignerLoadHwi,
     SignerUnload,
     SignerList,
     SignerDetails,
@@ -52,12 +73,17 @@ pub enum Method {
     SignerSign,
     SignerSinglesigDescriptor,
     SignerRegisterMultisig,
+    SignerRegisteredMultisigs,
+    SignerConfirmAddress,
+    SignerJadeStatus,
+    SignerJadeUnlock,
     AssetContract,
     AssetInsert,
     AssetRemove,
     AssetList,
     AssetDetails,
     AssetFromExplorer,
+    AssetRegistryLookup,
     AssetPublish,
 }
 impl Method {
@@ -65,34 +91,56 @@ impl Method {
         serde_json::to_value(match direction {
             Direction::Request => match self {
                 Method::Schema => schema_for!(request::Schema),
+                Method::SchemaAll => schema_for!(request::Empty),
+                Method::Openrpc => schema_for!(request::Empty),
                 Method::Version => schema_for!(request::Empty),
+                Method::NetworkDetails => schema_for!(request::Empty),
                 Method::Scan => schema_for!(request::Empty),
                 Method::Stop => schema_for!(request::Empty),
                 Method::WalletLoad => schema_for!(request::WalletLoad),
                 Method::WalletUnload => schema_for!(request::WalletUnload),
+                Method::WalletRescan => schema_for!(request::WalletRescan),
                 Method::WalletList => schema_for!(request::Empty),
                 Method::WalletDetails => schema_for!(request::WalletDetails),
+                Method::WalletSyncStatus => schema_for!(request::WalletSyncStatus),
                 Method::WalletAddress => schema_for!(request::WalletAddress),
+                Method::WalletAddresses => schema_for!(request::WalletAddresses),
                 Method::WalletBalance => schema_for!(request::WalletBalance),
                 Method::WalletUtxos => schema_for!(request::WalletUtxos),
                 Method::WalletTxs => schema_for!(request::WalletTxs),
                 Method::WalletTx => schema_for!(request::WalletTx),
+                Method::WalletExportCsv => schema_for!(request::WalletExportCsv),
                 Method::WalletSendMany => schema_for!(request::WalletSendMany),
+                Method::WalletEstimateFee => schema_for!(request::WalletEstimateFee),
                 Method::WalletDrain => schema_for!(request::WalletDrain),
+                Method::WalletBumpFee => schema_for!(request::WalletBumpFee),
                 Method::WalletIssue => schema_for!(request::WalletIssue),
                 Method::WalletReissue => schema_for!(request::WalletReissue),
                 Method::WalletBurn => schema_for!(request::WalletBurn),
+                Method::WalletConsolidate => schema_for!(request::WalletConsolidate),
+                Method::WalletCreateSwap => schema_for!(request::WalletCreateSwap),
                 Method::WalletCombine => schema_for!(request::WalletCombine),
                 Method::WalletBroadcast => schema_for!(request::WalletBroadcast),
                 Method::WalletPsetDetails => schema_for!(request::WalletPsetDetails),
+                Method::WalletPsetAnalyze => schema_for!(request::WalletPsetAnalyze),
+                Method::WalletProofOfReserves => schema_for!(request::WalletProofOfReserves),
+                Method::VerifyProofOfReserves => schema_for!(request::VerifyProofOfReserves),
                 Method::WalletMultisigDescriptor => schema_for!(request::WalletMultisigDescriptor),
                 Method::WalletSetTxMemo => schema_for!(request::WalletSetTxMemo),
                 Method::WalletSetAddrMemo => schema_for!(request::WalletSetAddrMemo),
-                Method::SignerGenerate => schema_for!(request::Empty),
+                Method::WalletSetOutputMemo => schema_for!(request::WalletSetOutputMemo),
+                Method::WalletLabels => schema_for!(request::WalletLabels),
+                Method::WalletImportLabels => schema_for!(request::WalletImportLabels),
+                Method::ValidateAddress => schema_for!(request::ValidateAddress),
+                Method::WalletIsMine => schema_for!(request::WalletIsMine),
+                Method::WalletExportDescriptor => schema_for!(request::WalletExportDescriptor),
+                Method::VerifyXpub => schema_for!(request::VerifyXpub),
+                Method::SignerGenerate => schema_for!(request::SignerGenerate),
                 Method::SignerJadeId => schema_for!(request::Empty),
                 Method::SignerLoadSoftware => schema_for!(request::SignerLoadSoftware),
                 Method::SignerLoadJade => schema_for!(request::SignerLoadJade),
                 Method::SignerLoadExternal => schema_for!(request::SignerLoadExternal),
+                Method::SignerLoadHwi => schema_for!(request::SignerLoadHwi),
                 Method::SignerUnload => schema_for!(request::SignerUnload),
                 Method::SignerList => schema_for!(request::Empty),
                 Method::SignerDetails => schema_for!(request::SignerDetails),
@@ -102,44 +150,73 @@ impl Method {
                     schema_for!(request::SignerSinglesigDescriptor)
                 }
                 Method::SignerRegisterMultisig => schema_for!(request::SignerRegisterMultisig),
+                Method::SignerRegisteredMultisigs => {
+                    schema_for!(request::SignerRegisteredMultisigs)
+                }
+                Method::SignerConfirmAddress => schema_for!(request::SignerConfirmAddress),
+                Method::SignerJadeStatus => schema_for!(request::SignerJadeStatus),
+                Method::SignerJadeUnlock => schema_for!(request::SignerJadeUnlock),
                 Method::AssetContract => schema_for!(request::AssetContract),
                 Method::AssetInsert => schema_for!(request::AssetInsert),
                 Method::AssetRemove => schema_for!(request::AssetRemove),
                 Method::AssetList => schema_for!(request::Empty),
                 Method::AssetDetails => schema_for!(request::AssetDetails),
                 Method::AssetFromExplorer => schema_for!(request::AssetFromExplorer),
+                Method::AssetRegistryLookup => schema_for!(request::AssetRegistryLookup),
                 Method::AssetPublish => schema_for!(request::AssetPublish),
             },
             Direction::Response => match self {
                 Method::Schema => return serde_json::from_str(include_str!("../schema.json")),
+                Method::SchemaAll => schema_for!(response::SchemaAll),
+                Method::Openrpc => schema_for!(serde_json::Value),
                 Method::Version => schema_for!(response::Version),
+                Method::NetworkDetails => schema_for!(response::NetworkDetails),
                 Method::Scan => schema_for!(response::Empty),
                 Method::Stop => schema_for!(request::Empty),
                 Method::WalletLoad => schema_for!(response::Wallet),
                 Method::WalletUnload => schema_for!(response::WalletUnload),
+                Method::WalletRescan => schema_for!(response::WalletRescan),
                 Method::WalletList => schema_for!(response::WalletList),
                 Method::WalletDetails => schema_for!(response::WalletDetails),
+                Method::WalletSyncStatus => schema_for!(response::WalletSyncStatus),
                 Method::WalletAddress => schema_for!(response::WalletAddress),
+                Method::WalletAddresses => schema_for!(response::WalletAddresses),
                 Method::WalletBalance => schema_for!(response::WalletBalance),
                 Method::WalletUtxos => schema_for!(response::WalletUtxos),
                 Method::WalletTxs => schema_for!(response::WalletTxs),
                 Method::WalletTx => schema_for!(response::WalletTx),
-                Method::WalletSendMany => schema_for!(response::Pset),
+                Method::WalletExportCsv => schema_for!(response::WalletExportCsv),
+                Method::WalletSendMany => schema_for!(response::WalletSend),
+                Method::WalletEstimateFee => schema_for!(response::WalletEstimateFee),
                 Method::WalletDrain => schema_for!(response::Pset),
-                Method::WalletIssue => schema_for!(response::Pset),
-                Method::WalletReissue => schema_for!(response::Pset),
+                Method::WalletBumpFee => schema_for!(response::Pset),
+                Method::WalletIssue => schema_for!(response::WalletIssue),
+                Method::WalletReissue => schema_for!(response::WalletIssue),
                 Method::WalletBurn => schema_for!(response::Pset),
+                Method::WalletConsolidate => schema_for!(response::WalletConsolidate),
+                Method::WalletCreateSwap => schema_for!(response::WalletCreateSwap),
                 Method::WalletCombine => schema_for!(response::WalletCombine),
                 Method::WalletBroadcast => schema_for!(response::WalletBroadcast),
                 Method::WalletPsetDetails => schema_for!(response::WalletPsetDetails),
+                Method::WalletPsetAnalyze => schema_for!(response::WalletPsetAnalyze),
+                Method::WalletProofOfReserves => schema_for!(response::Pset),
+                Method::VerifyProofOfReserves => schema_for!(response::VerifyProofOfReserves),
                 Method::WalletMultisigDescriptor => schema_for!(response::WalletMultisigDescriptor),
                 Method::WalletSetTxMemo => schema_for!(response::Empty),
                 Method::WalletSetAddrMemo => schema_for!(response::Empty),
+                Method::WalletSetOutputMemo => schema_for!(response::Empty),
+                Method::WalletLabels => schema_for!(response::WalletLabels),
+                Method::WalletImportLabels => schema_for!(response::WalletImportLabels),
+                Method::ValidateAddress => schema_for!(response::ValidateAddress),
+                Method::WalletIsMine => schema_for!(response::WalletIsMine),
+                Method::WalletExportDescriptor => schema_for!(response::WalletExportDescriptor),
+                Method::VerifyXpub => schema_for!(response::VerifyXpub),
                 Method::SignerGenerate => schema_for!(response::SignerGenerate),
                 Method::SignerJadeId => schema_for!(response::JadeId),
                 Method::SignerLoadSoftware => schema_for!(response::Signer),
                 Method::SignerLoadJade => schema_for!(response::Signer),
                 Method::SignerLoadExternal => schema_for!(response::Signer),
+                Method::SignerLoadHwi => schema_for!(response::Signer),
                 Method::SignerUnload => schema_for!(response::SignerUnload),
                 Method::SignerList => schema_for!(response::SignerList),
                 Method::SignerDetails => schema_for!(response::SignerDetails),
@@ -149,12 +226,19 @@ impl Method {
                     schema_for!(response::SignerSinglesigDescriptor)
                 }
                 Method::SignerRegisterMultisig => schema_for!(response::Empty),
+                Method::SignerRegisteredMultisigs => {
+                    schema_for!(response::SignerRegisteredMultisigs)
+                }
+                Method::SignerConfirmAddress => schema_for!(response::SignerConfirmAddress),
+                Method::SignerJadeStatus => schema_for!(response::JadeStatus),
+                Method::SignerJadeUnlock => schema_for!(response::JadeStatus),
                 Method::AssetContract => schema_for!(response::AssetContract),
                 Method::AssetInsert => schema_for!(response::Empty),
                 Method::AssetRemove => schema_for!(request::Empty),
                 Method::AssetList => schema_for!(response::AssetList),
                 Method::AssetDetails => schema_for!(response::AssetDetails),
                 Method::AssetFromExplorer => schema_for!(request::Empty),
+                Method::AssetRegistryLookup => schema_for!(response::AssetRegistryLookup),
                 Method::AssetPublish => schema_for!(response::AssetPublish),
             },
         })
@@ -167,34 +251,56 @@ impl FromStr for Method {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         Ok(match s {
             "schema" => Method::Schema,
+            "schema_all" => Method::SchemaAll,
+            "openrpc" => Method::Openrpc,
             "version" => Method::Version,
+            "network_details" => Method::NetworkDetails,
             "scan" => Method::Scan,
             "stop" => Method::Stop,
             "wallet_load" => Method::WalletLoad,
             "wallet_unload" => Method::WalletUnload,
+            "wallet_rescan" => Method::WalletRescan,
             "wallet_list" => Method::WalletList,
             "wallet_details" => Method::WalletDetails,
+            "wallet_sync_status" => Method::WalletSyncStatus,
             "wallet_address" => Method::WalletAddress,
+            "wallet_addresses" => Method::WalletAddresses,
             "wallet_balance" => Method::WalletBalance,
             "wallet_utxos" => Method::WalletUtxos,
             "wallet_txs" => Method::WalletTxs,
             "wallet_tx" => Method::WalletTx,
+            "wallet_export_csv" => Method::WalletExportCsv,
             "wallet_send_many" => Method::WalletSendMany,
+            "wallet_estimate_fee" => Method::WalletEstimateFee,
             "wallet_drain" => Method::WalletDrain,
+            "wallet_bump_fee" => Method::WalletBumpFee,
             "wallet_issue" => Method::WalletIssue,
             "wallet_reissue" => Method::WalletReissue,
             "wallet_burn" => Method::WalletBurn,
+            "wallet_consolidate" => Method::WalletConsolidate,
+            "wallet_create_swap" => Method::WalletCreateSwap,
             "wallet_combine" => Method::WalletCombine,
             "wallet_broadcast" => Method::WalletBroadcast,
             "wallet_pset_details" => Method::WalletPsetDetails,
+            "wallet_pset_analyze" => Method::WalletPsetAnalyze,
+            "wallet_proof_of_reserves" => Method::WalletProofOfReserves,
+            "verify_proof_of_reserves" => Method::VerifyProofOfReserves,
             "wallet_multisig_descriptor" => Method::WalletMultisigDescriptor,
             "wallet_set_tx_memo" => Method::WalletSetTxMemo,
             "wallet_set_addr_memo" => Method::WalletSetAddrMemo,
+            "wallet_set_output_memo" => Method::WalletSetOutputMemo,
+            "wallet_labels" => Method::WalletLabels,
+            "wallet_import_labels" => Method::WalletImportLabels,
+            "validate_address" => Method::ValidateAddress,
+            "wallet_is_mine" => Method::WalletIsMine,
+            "wallet_export_descriptor" => Method::WalletExportDescriptor,
+            "verify_xpub" => Method::VerifyXpub,
             "signer_generate" => Method::SignerGenerate,
             "signer_jade_id" => Method::SignerJadeId,
             "signer_load_software" => Method::SignerLoadSoftware,
             "signer_load_jade" => Method::SignerLoadJade,
             "signer_load_external" => Method::SignerLoadExternal,
+            "signer_load_hwi" => Method::SignerLoadHwi,
             "signer_unload" => Method::SignerUnload,
             "signer_list" => Method::SignerList,
             "signer_details" => Method::SignerDetails,
@@ -202,12 +308,17 @@ impl FromStr for Method {
             "signer_sign" => Method::SignerSign,
             "signer_singlesig_descriptor" => Method::SignerSinglesigDescriptor,
             "signer_register_multisig" => Method::SignerRegisterMultisig,
+            "signer_registered_multisigs" => Method::SignerRegisteredMultisigs,
+            "signer_confirm_address" => Method::SignerConfirmAddress,
+            "signer_jade_status" => Method::SignerJadeStatus,
+            "signer_jade_unlock" => Method::SignerJadeUnlock,
             "asset_contract" => Method::AssetContract,
             "asset_insert" => Method::AssetInsert,
             "asset_remove" => Method::AssetRemove,
             "asset_list" => Method::AssetList,
             "asset_details" => Method::AssetDetails,
             "asset_from_explorer" => Method::AssetFromExplorer,
+            "asset_registry_lookup" => Method::AssetRegistryLookup,
             "asset_publish" => Method::AssetPublish,
             _ => {
                 return Err(MethodNotExist {
@@ -222,34 +333,56 @@ impl std::fmt::Display for Method {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let s = match self {
             Method::Schema => "schema",
+            Method::SchemaAll => "schema_all",
+            Method::Openrpc => "openrpc",
             Method::Version => "version",
+            Method::NetworkDetails => "network_details",
             Method::Scan => "scan",
             Method::Stop => "stop",
             Method::WalletLoad => "wallet_load",
             Method::WalletUnload => "wallet_unload",
+            Method::WalletRescan => "wallet_rescan",
             Method::WalletList => "wallet_list",
             Method::WalletDetails => "wallet_details",
+            Method::WalletSyncStatus => "wallet_sync_status",
             Method::WalletAddress => "wallet_address",
+            Method::WalletAddresses => "wallet_addresses",
             Method::WalletBalance => "wallet_balance",
             Method::WalletUtxos => "wallet_utxos",
             Method::WalletTxs => "wallet_txs",
             Method::WalletTx => "wallet_tx",
+            Method::WalletExportCsv => "wallet_export_csv",
             Method::WalletSendMany => "wallet_send_many",
+            Method::WalletEstimateFee => "wallet_estimate_fee",
             Method::WalletDrain => "wallet_drain",
+            Method::WalletBumpFee => "wallet_bump_fee",
             Method::WalletIssue => "wallet_issue",
             Method::WalletReissue => "wallet_reissue",
             Method::WalletBurn => "wallet_burn",
+            Method::WalletConsolidate => "wallet_consolidate",
+            Method::WalletCreateSwap => "wallet_create_swap",
             Method::WalletCombine => "wallet_combine",
             Method::WalletBroadcast => "wallet_broadcast",
             Method::WalletPsetDetails => "wallet_pset_details",
+            Method::WalletPsetAnalyze => "wallet_pset_analyze",
+            Method::WalletProofOfReserves => "wallet_proof_of_reserves",
+            Method::VerifyProofOfReserves => "verify_proof_of_reserves",
             Method::WalletMultisigDescriptor => "wallet_multisig_descriptor",
             Method::WalletSetTxMemo => "wallet_set_tx_memo",
             Method::WalletSetAddrMemo => "wallet_set_addr_memo",
+            Method::WalletSetOutputMemo => "wallet_set_output_memo",
+            Method::WalletLabels => "wallet_labels",
+            Method::WalletImportLabels => "wallet_import_labels",
+            Method::ValidateAddress => "validate_address",
+            Method::WalletIsMine => "wallet_is_mine",
+            Method::WalletExportDescriptor => "wallet_export_descriptor",
+            Method::VerifyXpub => "verify_xpub",
             Method::SignerGenerate => "signer_generate",
             Method::SignerJadeId => "signer_jade_id",
             Method::SignerLoadSoftware => "signer_load_software",
             Method::SignerLoadJade => "signer_load_jade",
             Method::SignerLoadExternal => "signer_load_external",
+            Method::SignerLoadHwi => "signer_load_hwi",
             Method::SignerUnload => "signer_unload",
             Method::SignerList => "signer_list",
             Method::SignerDetails => "signer_details",
@@ -257,12 +390,17 @@ impl std::fmt::Display for Method {
             Method::SignerSign => "signer_sign",
             Method::SignerSinglesigDescriptor => "signer_singlesig_descriptor",
             Method::SignerRegisterMultisig => "signer_register_multisig",
+            Method::SignerRegisteredMultisigs => "signer_registered_multisigs",
+            Method::SignerConfirmAddress => "signer_confirm_address",
+            Method::SignerJadeStatus => "signer_jade_status",
+            Method::SignerJadeUnlock => "signer_jade_unlock",
             Method::AssetContract => "asset_contract",
             Method::AssetInsert => "asset_insert",
             Method::AssetRemove => "asset_remove",
             Method::AssetList => "asset_list",
             Method::AssetDetails => "asset_details",
             Method::AssetFromExplorer => "asset_from_explorer",
+            Method::AssetRegistryLookup => "asset_registry_lookup",
             Method::AssetPublish => "asset_publish",
         };
         write!(f, "{}", s)
@@ -0,0 +1,192 @@
+//! Build an [OpenRPC](https://spec.open-rpc.org) document describing the whole RPC API
+
+use std::collections::BTreeMap;
+
+use lwk_rpc_model::request::Direction;
+use serde_json::{json, Map, Value};
+
+use crate::consts;
+use crate::method::Method;
+
+/// The version of the OpenRPC specification followed by the document
+pub const OPENRPC_VERSION: &str = "1.2.6";
+
+/// Where the definitions of a JSON schema generated by `schema_for!` are referenced
+const DEFINITIONS_REF: &str = "#/definitions/";
+
+/// Where the definitions are moved in the OpenRPC document
+const COMPONENTS_REF: &str = "#/components/schemas/";
+
+/// Returns the OpenRPC document describing every method of the RPC
+///
+/// The requests params are passed by name, every field of a request is a param. The schema
+/// definitions of all the methods are collected in `components.schemas`.
+pub fn openrpc() -> Result<Value, serde_json::Error> {
+    let mut components = BTreeMap::new();
+    let mut methods = vec![];
+    for method in enum_iterator::all::<Method>() {
+        let request = method.schema(Direction::Request)?;
+        let response = method.schema(Direction::Response)?;
+
+        // Definitions used by the params are at the request root
+        let request = root_schema(request, &mut components);
+
+        let mut params = vec![];
+        let required = request.get("required").and_then(Value::as_array);
+        let is_required = |name: &str| required.map_or(false, |r| r.iter().any(|n| n == name));
+        if let Some(properties) = request.get("properties").and_then(Value::as_object) {
+            for (name, schema) in properties {
+                let mut param = json!({
+                    "name": name,
+                    "required": is_required(name),
+                    "schema": without_description(schema),
+                });
+                if let Some(description) = schema.get("description") {
+                    param["description"] = description.clone();
+                }
+                params.push(param);
+            }
+        }
+
+        let mut result = json!({
+            "name": response.get("title").cloned().unwrap_or_else(|| "result".into()),
+            "schema": root_schema(response.clone(), &mut components),
+        });
+        if let Some(description) = response.get("description") {
+            result["description"] = description.clone();
+        }
+
+        let mut m = json!({
+            "name": method.to_string(),
+            "paramStructure": "by-name",
+            "params": params,
+            "result": result,
+        });
+        if let Some(description) = request.get("description") {
+            m["description"] = description.clone();
+        }
+        methods.push(m);
+    }
+
+    Ok(json!({
+        "openrpc": OPENRPC_VERSION,
+        "info": {
+            "title": "Liquid Wallet Kit",
+            "description": "RPC API of the Liquid Wallet Kit server",
+            "version": consts::APP_VERSION,
+        },
+        "methods": methods,
+        "components": {
+            "schemas": components,
+        },
+    }))
+}
+
+/// Convert a root schema to a schema usable in the OpenRPC document, moving its definitions in
+/// `components`
+fn root_schema(mut schema: Value, components: &mut BTreeMap<String, Value>) -> Value {
+    // A schema with an id, like the JSON meta-schema, is referenced instead of being inlined
+    if let Some(id) = schema.get("$id") {
+        return json!({ "$ref": id });
+    }
+    if let Some(obj) = schema.as_object_mut() {
+        obj.remove("$schema");
+        if let Some(Value::Object(definitions)) = obj.remove("definitions") {
+            for (name, definition) in definitions {
+                components.insert(name, rewrite_refs(definition));
+            }
+        }
+    }
+    rewrite_refs(schema)
+}
+
+/// The description of a param is already in the param itself
+fn without_description(schema: &Value) -> Value {
+    let mut schema = schema.clone();
+    if let Some(obj) = schema.as_object_mut() {
+        obj.remove("description");
+    }
+    schema
+}
+
+/// Make the references to the schema definitions point to the OpenRPC components
+fn rewrite_refs(value: Value) -> Value {
+    match value {
+        Value::Object(obj) => Value::Object(
+            obj.into_iter()
+                .map(|(k, v)| match (k.as_str(), v) {
+                    ("$ref", Value::String(r)) if r.starts_with(DEFINITIONS_REF) => {
+                        let name = &r[DEFINITIONS_REF.len()..];
+                        (k, Value::String(format!("{COMPONENTS_REF}{name}")))
+                    }
+                    (_, v) => (k, rewrite_refs(v)),
+                })
+                .collect::<Map<_, _>>(),
+        ),
+        Value::Array(arr) => Value::Array(arr.into_iter().map(rewrite_refs).collect()),
+        v => v,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Collect all the references in the given value
+    fn refs(value: &Value, acc: &mut Vec<String>) {
+        match value {
+            Value::Object(obj) => {
+                for (k, v) in obj {
+                    match (k.as_str(), v) {
+                        ("$ref", Value::String(r)) => acc.push(r.clone()),
+                        _ => refs(v, acc),
+                    }
+                }
+            }
+            Value::Array(arr) => arr.iter().for_each(|v| refs(v, acc)),
+            _ => {}
+        }
+    }
+
+    #[test]
+    fn test_openrpc() {
+        let doc = openrpc().unwrap();
+        assert_eq!(doc["openrpc"], OPENRPC_VERSION);
+        assert_eq!(doc["info"]["version"], consts::APP_VERSION);
+        assert!(doc["info"]["title"].is_string());
+
+        let methods = doc["methods"].as_array().unwrap();
+        assert_eq!(methods.len(), enum_iterator::all::<Method>().count());
+        for m in methods {
+            let name = m["name"].as_str().unwrap();
+            name.parse::<Method>().unwrap();
+            for param in m["params"].as_array().unwrap() {
+                assert!(param["name"].is_string(), "{name}");
+                assert!(param["required"].is_boolean(), "{name}");
+                assert!(param["schema"].is_object(), "{name}");
+            }
+            assert!(m["result"]["name"].is_string(), "{name}");
+            assert!(m["result"]["schema"].is_object(), "{name}");
+        }
+
+        let wallet_load = methods.iter().find(|m| m["name"] == "wallet_load").unwrap();
+        assert!(wallet_load["description"].is_string());
+        let params = wallet_load["params"].as_array().unwrap();
+        let descriptor = params.iter().find(|p| p["name"] == "descriptor").unwrap();
+        assert_eq!(descriptor["required"], true);
+        assert!(descriptor["description"].is_string());
+
+        // All the references to definitions are resolved in the components
+        let components = doc["components"]["schemas"].as_object().unwrap();
+        let mut all_refs = vec![];
+        refs(&doc, &mut all_refs);
+        assert!(!all_refs.is_empty());
+        for r in all_refs {
+            if let Some(name) = r.strip_prefix(COMPONENTS_REF) {
+                assert!(components.contains_key(name), "{r}");
+            } else {
+                assert!(r.starts_with("http"), "{r}");
+            }
+        }
+    }
+}
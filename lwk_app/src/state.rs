@@ -1,15 +1,19 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs::{File, OpenOptions};
 use std::io::Write;
 use std::net::SocketAddr;
 use std::str::FromStr;
+use std::sync::{Arc, Condvar};
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
+use lwk_common::precision::format_amount;
 use lwk_common::Signer;
+use lwk_jade::protocol::JadeState;
 use lwk_jade::{Jade, Network};
 use lwk_rpc_model::request;
+use lwk_signer::lwk_hwi::Hwi;
 use lwk_signer::AnySigner;
-use lwk_signer::SwSigner;
+use lwk_signer::{SignerError, SwSigner};
 use lwk_tiny_jrpc::Request;
 use lwk_wollet::asset_ids;
 use lwk_wollet::bitcoin::bip32::{Fingerprint, Xpub};
@@ -18,14 +22,22 @@ use lwk_wollet::elements::encode::serialize;
 use lwk_wollet::elements::hex::ToHex;
 use lwk_wollet::elements::pset::elip100::AssetMetadata;
 use lwk_wollet::elements::{Address, AssetId, OutPoint, Transaction, Txid};
-use lwk_wollet::Contract;
 use lwk_wollet::Wollet;
-use serde::Serialize;
+use lwk_wollet::{Contract, CsvRow};
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroizing;
 
 use crate::config::Config;
 use crate::method::Method;
 use crate::Error;
 
+/// A line of the state file containing an encrypted request, used for requests with secrets
+#[derive(Serialize, Deserialize)]
+pub struct EncryptedRequest {
+    /// The request serialized as JSON and encrypted with the state key, in hex
+    pub encrypted: String,
+}
+
 #[derive(Debug)]
 enum AppSignerInner {
     JadeId(XKeyIdentifier, Network),
@@ -33,17 +45,41 @@ enum AppSignerInner {
     ExternalSigner(Fingerprint),
 }
 
-#[derive(Debug)]
 pub struct AppSigner {
     inner: AppSignerInner,
     persist: bool,
+
+    /// The BIP-39 passphrase of a software signer, kept to re-create the load request, wiped on drop
+    passphrase: Option<Zeroizing<String>>,
+}
+
+impl std::fmt::Debug for AppSigner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AppSigner")
+            .field("inner", &self.inner)
+            .field("persist", &self.persist)
+            .finish()
+    }
 }
 
 impl AppSigner {
-    pub fn new_sw(mnemonic: &str, is_mainnet: bool, persist: bool) -> Result<Self, Error> {
-        let sw = SwSigner::new(mnemonic, is_mainnet)?;
+    pub fn new_sw(
+        mnemonic: &str,
+        passphrase: Option<Zeroizing<String>>,
+        is_mainnet: bool,
+        persist: bool,
+    ) -> Result<Self, Error> {
+        let sw = SwSigner::new_with_passphrase(
+            mnemonic,
+            passphrase.as_deref().map_or("", |p| p.as_str()),
+            is_mainnet,
+        )?;
         let inner = AppSignerInner::AvailableSigner(AnySigner::Software(sw));
-        Ok(AppSigner { inner, persist })
+        Ok(AppSigner {
+            inner,
+            persist,
+            passphrase,
+        })
     }
 
     pub fn new_jade(
@@ -61,6 +97,20 @@ impl AppSigner {
         Ok(AppSigner {
             inner,
             persist: true,
+            passphrase: None,
+        })
+    }
+
+    pub fn new_hwi(
+        fingerprint: Fingerprint,
+        device_path: Option<&str>,
+        is_mainnet: bool,
+    ) -> Result<Self, Error> {
+        let hwi = Hwi::connect(fingerprint, device_path, is_mainnet).map_err(SignerError::from)?;
+        Ok(AppSigner {
+            inner: AppSignerInner::AvailableSigner(AnySigner::Hwi(hwi)),
+            persist: false,
+            passphrase: None,
         })
     }
 
@@ -68,6 +118,7 @@ impl AppSigner {
         AppSigner {
             inner: AppSignerInner::ExternalSigner(fingerprint),
             persist: false,
+            passphrase: None,
         }
     }
 
@@ -103,12 +154,39 @@ impl AppSigner {
         }
     }
 
+    /// The status of a Jade signer, without unlocking it
+    ///
+    /// Jades reached via the emulator are always ready, the ones not responding are disconnected.
+    pub fn jade_status(&self, name: &str) -> Result<String, Error> {
+        let state = match &self.inner {
+            AppSignerInner::AvailableSigner(AnySigner::Jade(jade, _)) => {
+                if jade.is_emulator()? {
+                    Some(JadeState::Ready)
+                } else {
+                    jade.state().ok()
+                }
+            }
+            AppSignerInner::JadeId(_, _) => None,
+            _ => return Err(Error::SignerNotJade(name.to_string())),
+        };
+        Ok(match state {
+            Some(JadeState::Uninit) => "uninitialized",
+            Some(JadeState::Unsaved) => "unsaved",
+            Some(JadeState::Locked) => "locked",
+            Some(JadeState::Ready) => "ready",
+            Some(JadeState::Temp) => "temporary",
+            None => "disconnected",
+        }
+        .to_string())
+    }
+
     pub fn type_(&self) -> String {
         match &self.inner {
             AppSignerInner::ExternalSigner(_) => "external".into(),
             AppSignerInner::JadeId(_, _) => "jade-id".into(),
             AppSignerInner::AvailableSigner(AnySigner::Software(_)) => "software".into(),
             AppSignerInner::AvailableSigner(AnySigner::Jade(_, _)) => "jade".into(),
+            AppSignerInner::AvailableSigner(AnySigner::Hwi(_)) => "hwi".into(),
             #[allow(unreachable_patterns)]
             _ => todo!(),
         }
@@ -199,6 +277,15 @@ impl AppAsset {
         }
     }
 
+    /// The number of decimals of the asset, reissuance tokens are not divisible
+    pub fn precision(&self) -> u8 {
+        match self {
+            AppAsset::PolicyAsset(_) => 8,
+            AppAsset::RegistryAsset(d) => d.contract.precision,
+            AppAsset::ReissuanceToken(_) => 0,
+        }
+    }
+
     pub fn asset_metadata(&self) -> Option<AssetMetadata> {
         match self {
             AppAsset::PolicyAsset(_) => None,
@@ -261,6 +348,9 @@ pub struct TxMemos(HashMap<String, HashMap<Txid, String>>);
 #[derive(Default)]
 pub struct AddrMemos(HashMap<String, HashMap<Address, String>>);
 
+#[derive(Default)]
+pub struct OutputMemos(HashMap<String, HashMap<OutPoint, String>>);
+
 pub struct State {
     // TODO: config is read-only, so it's not useful to wrap it in a mutex.
     // Ideally it should be in _another_ struct accessible by method_handler.
@@ -270,6 +360,13 @@ pub struct State {
     pub assets: Assets,
     pub tx_memos: TxMemos,
     pub addr_memos: AddrMemos,
+    pub output_memos: OutputMemos,
+
+    /// Encrypted requests of the state that cannot be applied since the store is locked
+    ///
+    /// They are kept as they are when the state is rewritten.
+    pub locked_requests: Vec<EncryptedRequest>,
+
     pub do_persist: bool,
 
     /// Number of scan loops started
@@ -280,6 +377,18 @@ pub struct State {
 
     /// Signal the scanning thread that we don't want to wait anymore
     pub interrupt_wait: bool,
+
+    /// Wallets unloaded since the last scan loop, forgotten by the client that was scanning them
+    pub unloaded: Vec<Wollet>,
+
+    /// Names of the wallets not yet scanned in the current scan loop
+    pub scanning: HashSet<String>,
+
+    /// Notified when wallets are removed from `scanning`
+    pub scanned: Arc<Condvar>,
+
+    /// Height of the blockchain tip seen by the last wallet scan
+    pub tip_height: Option<u32>,
 }
 
 impl Wollets {
@@ -329,10 +438,6 @@ impl Wollets {
     pub fn iter(&self) -> impl Iterator<Item = (&String, &Wollet)> {
         self.0.iter()
     }
-
-    pub fn iter_mut(&mut self) -> impl Iterator<Item = (&String, &mut Wollet)> {
-        self.0.iter_mut()
-    }
 }
 
 impl Signers {
@@ -389,6 +494,7 @@ impl Signers {
             let signer = AppSigner {
                 inner,
                 persist: true,
+                passphrase: None,
             };
             // replace the existing AppSignerInner::JadeId with AppSignerInner::AvailableSigner
             self.0.insert(name.to_string(), signer);
@@ -447,6 +553,12 @@ impl Signers {
             .collect())
     }
 
+    /// Whether a loaded signer has the given fingerprint
+    pub fn is_loaded(&self, fingerprint: &Fingerprint) -> bool {
+        self.names_matching_fingerprint(fingerprint)
+            .map_or(false, |names| !names.is_empty())
+    }
+
     /// Get a name from the fingerprint
     pub fn name_from_fingerprint(
         &self,
@@ -526,7 +638,52 @@ impl AddrMemos {
     }
 }
 
+impl OutputMemos {
+    pub fn for_wollet(&self, wollet: &str) -> HashMap<OutPoint, String> {
+        self.0.get(wollet).cloned().unwrap_or_default()
+    }
+
+    pub fn set(&mut self, wollet: &str, outpoint: &OutPoint, memo: &str) -> Result<(), Error> {
+        self.0
+            .entry(wollet.to_string())
+            .or_default()
+            .insert(*outpoint, memo.to_string());
+        Ok(())
+    }
+
+    pub fn remove(&mut self, wollet: &str) {
+        self.0.remove(wollet);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &HashMap<OutPoint, String>)> {
+        self.0.iter()
+    }
+}
+
 impl State {
+    pub fn new(config: Config) -> Self {
+        let mut state = State {
+            config,
+            wollets: Default::default(),
+            signers: Default::default(),
+            assets: Default::default(),
+            tx_memos: Default::default(),
+            addr_memos: Default::default(),
+            output_memos: Default::default(),
+            locked_requests: vec![],
+            do_persist: false,
+            scan_loops_started: 0,
+            scan_loops_completed: 0,
+            interrupt_wait: false,
+            unloaded: vec![],
+            scanning: Default::default(),
+            scanned: Default::default(),
+            tip_height: None,
+        };
+        state.insert_policy_asset();
+        state
+    }
+
     pub fn insert_policy_asset(&mut self) {
         let asset_id = self.config.network.policy_asset();
         self.assets
@@ -574,15 +731,53 @@ impl State {
         self.get_asset(asset).ok().and_then(|a| a.issuance_tx())
     }
 
+    /// Returns the ticker of the given asset id if the asset is known, the asset id otherwise
+    pub fn ticker_or_id(&self, asset: String) -> String {
+        self.get_asset_from_str(&asset)
+            .map(|a| a.ticker())
+            .unwrap_or(asset)
+    }
+
+    /// Render the given row with the asset ticker and the amount formatted according to the asset
+    /// precision if the asset is known, the fee is in L-BTC
+    pub fn csv_row(&self, row: &CsvRow) -> String {
+        let (asset, amount) = match self.get_asset(&row.asset) {
+            Ok(a) => (a.ticker(), format_amount(row.amount, a.precision())),
+            Err(_) => (row.asset.to_string(), row.amount.to_string()),
+        };
+        let opt = |v: Option<u32>| v.map(|v| v.to_string()).unwrap_or_default();
+        format!(
+            "{},{},{},{},{},{}",
+            row.txid,
+            opt(row.timestamp),
+            opt(row.height),
+            asset,
+            amount,
+            format_amount(row.fee as i64, 8),
+        )
+    }
+
     pub fn replace_id_with_ticker(
         &self,
         balance: impl IntoIterator<Item = (String, i64)>,
     ) -> HashMap<String, i64> {
         balance
             .into_iter()
-            .map(|(k, v)| {
-                let t = self.get_asset_from_str(&k).map(|a| a.ticker()).unwrap_or(k);
-                (t, v)
+            .map(|(k, v)| (self.ticker_or_id(k), v))
+            .collect()
+    }
+
+    /// Format the amounts according to the precision of the assets, using the tickers as keys
+    /// if the assets are known, the asset ids and the satoshi otherwise
+    pub fn format_balance(
+        &self,
+        balance: impl IntoIterator<Item = (String, i64)>,
+    ) -> HashMap<String, String> {
+        balance
+            .into_iter()
+            .map(|(k, v)| match self.get_asset_from_str(&k) {
+                Ok(a) => (a.ticker(), format_amount(v, a.precision())),
+                Err(_) => (k, v.to_string()),
             })
             .collect()
     }
@@ -601,6 +796,23 @@ impl State {
         Ok(())
     }
 
+    /// Persist a request containing secrets, encrypted with the state key
+    pub fn persist_encrypted(&mut self, request: &Request) -> Result<(), Error> {
+        if !self.do_persist {
+            return Ok(());
+        }
+        let encrypted = self.encrypt_request(request)?;
+        self.persist(encrypted)
+    }
+
+    fn encrypt_request(&self, request: &Request) -> Result<EncryptedRequest, Error> {
+        let key = self.config.state_key.as_ref().ok_or(Error::StoreLocked)?;
+        let data = Zeroizing::new(serde_json::to_vec(request)?);
+        Ok(EncryptedRequest {
+            encrypted: key.encrypt(&data)?.to_hex(),
+        })
+    }
+
     pub fn persist_all(&mut self) -> Result<(), Error> {
         let path = self.config.state_path()?;
         let mut temp = path.clone();
@@ -611,9 +823,20 @@ impl State {
         temp.set_file_name(millis.to_string());
         let mut file = File::create(&temp)?;
         for req in self.as_requests()? {
-            let data = serde_json::to_string(&req)?;
+            // Without a state password, persisted software signers are the ones written in
+            // plaintext by previous versions
+            let data = if req.method == Method::SignerLoadSoftware.to_string()
+                && self.config.state_key.is_some()
+            {
+                serde_json::to_string(&self.encrypt_request(&req)?)?
+            } else {
+                serde_json::to_string(&req)?
+            };
             writeln!(file, "{}", data)?;
         }
+        for req in self.locked_requests.iter() {
+            writeln!(file, "{}", serde_json::to_string(req)?)?;
+        }
         std::fs::rename(temp, path)?;
         file.sync_all()?;
 
@@ -628,6 +851,9 @@ impl State {
             let params = request::WalletLoad {
                 descriptor: w.descriptor().to_string(),
                 name: n.to_string(),
+                // Persisted only if given, so that the wallet follows the server default
+                gap_limit: (w.gap_limit() != self.config.gap_limit).then_some(w.gap_limit()),
+                start_height: w.start_height(),
             };
             let r = Request {
                 jsonrpc: "2.0".into(),
@@ -674,6 +900,24 @@ impl State {
             }
         }
 
+        // Output memos
+        for (name, wollet_memos) in self.output_memos.iter() {
+            for (outpoint, memo) in wollet_memos.iter() {
+                let params = request::WalletSetOutputMemo {
+                    name: name.to_string(),
+                    outpoint: format!("{}:{}", outpoint.txid, outpoint.vout),
+                    memo: memo.to_string(),
+                };
+                let r = Request {
+                    jsonrpc: "2.0".into(),
+                    id: None,
+                    method: Method::WalletSetOutputMemo.to_string(),
+                    params: Some(serde_json::to_value(params)?),
+                };
+                requests.push(r);
+            }
+        }
+
         // Signers
         for (n, s) in self.signers.iter() {
             let (params, method) = match &s.inner {
@@ -686,6 +930,8 @@ impl State {
                     (serde_json::to_value(params)?, Method::SignerLoadJade)
                 }
                 AppSignerInner::AvailableSigner(a) => match a {
+                    // The signer was loaded without asking to persist it
+                    AnySigner::Software(_) if !s.persist => continue,
                     AnySigner::Software(a) => {
                         let params = request::SignerLoadSoftware {
                             name: n.to_string(),
@@ -693,6 +939,7 @@ impl State {
                                 .mnemonic()
                                 .expect("we only create signers from mnemonic")
                                 .to_string(),
+                            passphrase: s.passphrase.as_deref().cloned(),
                             persist: s.persist,
                         };
                         (serde_json::to_value(params)?, Method::SignerLoadSoftware)
@@ -705,6 +952,8 @@ impl State {
                         };
                         (serde_json::to_value(params)?, Method::SignerLoadJade)
                     }
+                    // The device must be connected to load it
+                    AnySigner::Hwi(_) => continue,
                     #[allow(unreachable_patterns)]
                     _ => todo!(),
                 },
@@ -1,4 +1,4 @@
-use crate::Address;
+use crate::{Address, Chain};
 use std::sync::Arc;
 
 #[derive(uniffi::Object)]
@@ -21,6 +21,10 @@ impl AddressResult {
     pub fn index(&self) -> u32 {
         self.inner.index()
     }
+
+    pub fn chain(&self) -> Chain {
+        self.inner.chain().into()
+    }
 }
 
 #[cfg(test)]
@@ -29,6 +33,7 @@ mod tests {
     use std::str::FromStr;
 
     use super::AddressResult;
+    use crate::Chain;
 
     #[test]
     fn address_result() {
@@ -37,6 +42,8 @@ mod tests {
         let wollet_address_result = lwk_wollet::AddressResult::new(
             elements::Address::from_str(address_str).unwrap(),
             index,
+            lwk_wollet::Chain::External,
+            lwk_wollet::bitcoin::bip32::DerivationPath::master(),
         );
 
         let address_result: AddressResult = wollet_address_result.into();
@@ -44,5 +51,7 @@ mod tests {
         assert_eq!(address_result.address().to_string(), address_str);
 
         assert_eq!(address_result.index(), index);
+
+        assert_eq!(address_result.chain(), Chain::External);
     }
 }
@@ -31,6 +31,15 @@ impl WalletTx {
             .collect()
     }
 
+    /// The amounts of each asset burned by the transaction
+    pub fn burned(&self) -> HashMap<AssetId, u64> {
+        self.inner
+            .burned()
+            .into_iter()
+            .map(|(k, v)| (AssetId::from(k), v))
+            .collect()
+    }
+
     pub fn txid(&self) -> Arc<Txid> {
         Arc::new(self.inner.txid.into())
     }
@@ -64,7 +73,8 @@ impl WalletTx {
     }
 
     pub fn unblinded_url(&self, explorer_url: &str) -> String {
-        self.inner.unblinded_url(explorer_url)
+        self.inner
+            .unblinded_url(explorer_url, lwk_wollet::ExplorerKind::default())
     }
 }
 
@@ -91,9 +91,10 @@ impl TxBuilder {
         asset: &AssetId,
     ) -> Result<(), LwkError> {
         let unvalidated_recipient = UnvalidatedRecipient {
-            satoshi,
+            satoshi: Some(satoshi),
             address: address.to_string(),
             asset: asset.to_string(),
+            timelock: None,
         };
         let recipient = unvalidated_recipient.validate(self.network)?;
         self.add_validated_recipient(recipient)
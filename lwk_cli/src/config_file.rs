@@ -0,0 +1,193 @@
+use std::{
+    collections::BTreeMap,
+    net::SocketAddr,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, Context};
+use clap::ValueEnum;
+use serde::Deserialize;
+
+use crate::args::Network;
+
+/// The name of the config file in the default data directory
+pub const CONFIG_FILE_NAME: &str = "config.toml";
+
+/// Settings read from a TOML file, command line flags take precedence over them
+///
+/// ```toml
+/// [network]
+/// name = "liquid"
+///
+/// [rpc]
+/// addr = "127.0.0.1:32110"
+/// url = "http://example.com:32110"
+///
+/// [electrum]
+/// url = "blockstream.info:995"
+/// timeout = 10
+/// retry = 3
+/// proxy = "127.0.0.1:9050"
+/// ```
+#[derive(Debug, Default, Deserialize)]
+pub struct ConfigFile {
+    #[serde(default)]
+    pub network: NetworkSection,
+
+    #[serde(default)]
+    pub rpc: RpcSection,
+
+    #[serde(default)]
+    pub electrum: ElectrumSection,
+
+    #[serde(flatten)]
+    unknown: BTreeMap<String, toml::Value>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct NetworkSection {
+    /// The network, with the same values of `--network`
+    pub name: Option<String>,
+
+    #[serde(flatten)]
+    unknown: BTreeMap<String, toml::Value>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct RpcSection {
+    /// The server socket address
+    pub addr: Option<SocketAddr>,
+
+    /// The url of a running server, used by the client instead of `addr`
+    pub url: Option<String>,
+
+    #[serde(flatten)]
+    unknown: BTreeMap<String, toml::Value>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ElectrumSection {
+    pub url: Option<String>,
+
+    /// Timeout in seconds
+    pub timeout: Option<u8>,
+    pub retry: Option<u8>,
+    pub proxy: Option<SocketAddr>,
+
+    #[serde(flatten)]
+    unknown: BTreeMap<String, toml::Value>,
+}
+
+impl ConfigFile {
+    /// Load the file at `path`, or at the default path if `None`
+    ///
+    /// A missing file is an error only if the path is explicitly given.
+    pub fn load(path: Option<&Path>) -> anyhow::Result<Self> {
+        let (path, explicit) = match path {
+            Some(path) => (path.to_path_buf(), true),
+            None => match default_path() {
+                Some(path) => (path, false),
+                None => return Ok(Self::default()),
+            },
+        };
+        if !explicit && !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(&path)
+            .with_context(|| format!("Cannot read config file {}", path.display()))?;
+        let file = Self::parse(&content)
+            .with_context(|| format!("Invalid config file {}", path.display()))?;
+        for key in file.unknown_keys() {
+            tracing::warn!(
+                "Ignoring unknown key '{key}' in config file {}",
+                path.display()
+            );
+        }
+        Ok(file)
+    }
+
+    fn parse(content: &str) -> anyhow::Result<Self> {
+        Ok(toml::from_str(content)?)
+    }
+
+    /// The network in the file, if any
+    pub fn network(&self) -> anyhow::Result<Option<Network>> {
+        self.network
+            .name
+            .as_deref()
+            .map(|name| Network::from_str(name, true).map_err(|e| anyhow!("Invalid network: {e}")))
+            .transpose()
+    }
+
+    /// The keys not recognized, such as `electrum.typo`
+    fn unknown_keys(&self) -> Vec<String> {
+        let sections = [
+            ("network.", &self.network.unknown),
+            ("rpc.", &self.rpc.unknown),
+            ("electrum.", &self.electrum.unknown),
+            ("", &self.unknown),
+        ];
+        sections
+            .iter()
+            .flat_map(|(prefix, unknown)| unknown.keys().map(move |key| format!("{prefix}{key}")))
+            .collect()
+    }
+}
+
+fn default_path() -> Option<PathBuf> {
+    let mut path = lwk_app::Config::default_home().ok()?;
+    path.push(CONFIG_FILE_NAME);
+    Some(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_file() {
+        let file = ConfigFile::parse(
+            r#"
+            typo = 1
+
+            [network]
+            name = "liquid"
+
+            [rpc]
+            addr = "127.0.0.1:1234"
+            url = "http://example.com:32110"
+
+            [electrum]
+            url = "blockstream.info:995"
+            timeout = 10
+            unknown = "x"
+            "#,
+        )
+        .unwrap();
+        assert!(matches!(file.network().unwrap(), Some(Network::Mainnet)));
+        assert_eq!(file.rpc.addr, Some("127.0.0.1:1234".parse().unwrap()));
+        assert_eq!(file.rpc.url.as_deref(), Some("http://example.com:32110"));
+        assert_eq!(file.electrum.url.as_deref(), Some("blockstream.info:995"));
+        assert_eq!(file.electrum.timeout, Some(10));
+        assert_eq!(file.electrum.retry, None);
+        assert_eq!(file.unknown_keys(), vec!["electrum.unknown", "typo"]);
+
+        let file = ConfigFile::parse("").unwrap();
+        assert!(file.network().unwrap().is_none());
+        assert!(file.unknown_keys().is_empty());
+
+        let file = ConfigFile::parse("[network]\nname = \"liquid-regtest\"").unwrap();
+        assert!(matches!(file.network().unwrap(), Some(Network::Regtest)));
+        let file = ConfigFile::parse("[network]\nname = \"signet\"").unwrap();
+        assert!(file.network().is_err());
+
+        assert!(ConfigFile::parse("[electrum]\ntimeout = 1000").is_err());
+
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join(CONFIG_FILE_NAME);
+        assert!(ConfigFile::load(Some(&path)).is_err());
+        std::fs::write(&path, "[rpc]\naddr = \"127.0.0.1:1234\"").unwrap();
+        let file = ConfigFile::load(Some(&path)).unwrap();
+        assert!(file.rpc.addr.is_some());
+    }
+}
@@ -0,0 +1,137 @@
+use serde_json::Value;
+
+use crate::args::OutputFormat;
+
+/// Render the value returned by [`crate::inner_main`] according to the given format
+pub fn render(value: &Value, format: OutputFormat) -> String {
+    match format {
+        OutputFormat::Json => format!("{}", value),
+        OutputFormat::JsonPretty => format!("{:#}", value),
+        OutputFormat::Table => table(value).unwrap_or_else(|| format!("{:#}", value)),
+    }
+}
+
+/// Returns the table for the values supporting it (balance, utxos and csv), None otherwise
+fn table(value: &Value) -> Option<String> {
+    let obj = value.as_object()?;
+    // The amounts formatted according to the asset precision, if requested
+    let formatted = obj.get("balance_formatted").and_then(Value::as_object);
+    if obj.len() != 1 + formatted.map_or(0, |_| 1) {
+        return None;
+    }
+    if let Some(balance) = obj.get("balance").and_then(Value::as_object) {
+        let rows = balance
+            .iter()
+            .map(|(asset, amount)| {
+                let amount = formatted
+                    .and_then(|f| f.get(asset))
+                    .and_then(Value::as_str)
+                    .map_or_else(|| amount.to_string(), String::from);
+                vec![asset.to_string(), amount]
+            })
+            .collect();
+        Some(columns(&["asset", "amount"], rows))
+    } else if let Some(csv) = obj.get("csv").and_then(Value::as_str) {
+        Some(csv.to_string())
+    } else if let Some(utxos) = obj.get("utxos").and_then(Value::as_array) {
+        let rows = utxos
+            .iter()
+            .map(|u| {
+                let outpoint = format!("{}:{}", str_field(u, "txid"), u.get("vout")?);
+                Some(vec![
+                    outpoint,
+                    str_field(u, "height"),
+                    str_field(u, "asset"),
+                    str_field(u, "value"),
+                ])
+            })
+            .collect::<Option<Vec<_>>>()?;
+        Some(columns(&["outpoint", "height", "asset", "amount"], rows))
+    } else {
+        None
+    }
+}
+
+fn str_field(v: &Value, key: &str) -> String {
+    match v.get(key) {
+        Some(Value::String(s)) => s.clone(),
+        Some(Value::Null) | None => "".to_string(),
+        Some(v) => v.to_string(),
+    }
+}
+
+/// Aligns the given rows in columns, the last column (the amount) is right aligned
+fn columns(headers: &[&str], rows: Vec<Vec<String>>) -> String {
+    let mut widths: Vec<usize> = headers.iter().map(|h| h.len()).collect();
+    for row in rows.iter() {
+        for (w, cell) in widths.iter_mut().zip(row.iter()) {
+            *w = (*w).max(cell.len());
+        }
+    }
+    let headers = headers.iter().map(|h| h.to_string()).collect();
+    let last = widths.len() - 1;
+    std::iter::once(headers)
+        .chain(rows)
+        .map(|row| {
+            row.iter()
+                .enumerate()
+                .map(|(i, cell)| {
+                    if i == last {
+                        format!("{:>width$}", cell, width = widths[i])
+                    } else {
+                        format!("{:<width$}", cell, width = widths[i])
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join("  ")
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let v = json!({"balance": {"L-BTC": 100000, "USDt": 5}});
+        assert_eq!(
+            render(&v, OutputFormat::Json),
+            r#"{"balance":{"L-BTC":100000,"USDt":5}}"#
+        );
+        assert_eq!(
+            render(&v, OutputFormat::Table),
+            "asset  amount\nL-BTC  100000\nUSDt        5"
+        );
+
+        let v = json!({
+            "balance": {"L-BTC": 100000, "USDt": 5},
+            "balance_formatted": {"L-BTC": "0.00100000", "USDt": "0.05"},
+        });
+        assert_eq!(
+            render(&v, OutputFormat::Table),
+            "asset      amount\nL-BTC  0.00100000\nUSDt         0.05"
+        );
+
+        let v = json!({"utxos": [
+            {"txid": "aa", "vout": 1, "height": null, "script_pubkey": "00", "asset": "bb", "value": 10},
+            {"txid": "cc", "vout": 0, "height": 100, "script_pubkey": "00", "asset": "dd", "value": 2000},
+        ]});
+        assert_eq!(
+            render(&v, OutputFormat::Table),
+            "outpoint  height  asset  amount\naa:1              bb         10\ncc:0      100     dd       2000"
+        );
+
+        let v = json!({"csv": "txid,amount\naa,1"});
+        assert_eq!(render(&v, OutputFormat::Table), "txid,amount\naa,1");
+
+        let v = json!({"txid": "aa"});
+        assert_eq!(
+            render(&v, OutputFormat::Table),
+            render(&v, OutputFormat::JsonPretty)
+        );
+    }
+}
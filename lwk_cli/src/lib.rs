@@ -3,68 +3,156 @@
 #![cfg_attr(docsrs, feature(doc_auto_cfg))]
 
 use std::{
-    net::{IpAddr, Ipv4Addr, SocketAddr},
+    path::{Path, PathBuf},
     time::Duration,
 };
 
 use anyhow::{anyhow, Context};
 use clap::CommandFactory;
-use lwk_app::Config;
+use lwk_app::{Config, ElementsNetwork, StateKey, VersionCompatibility};
 use serde_json::Value;
+use tracing_subscriber::fmt::format::{format, Format, Json, Writer};
+use tracing_subscriber::fmt::{FmtContext, FormatEvent, FormatFields};
+use tracing_subscriber::registry::LookupSpan;
 use tracing_subscriber::{filter::LevelFilter, EnvFilter, FmtSubscriber};
 
-use crate::args::{AssetCommand, CliCommand, Network, ServerCommand, SignerCommand, WalletCommand};
-pub use args::Cli;
+use crate::args::{
+    AssetCommand, CliCommand, LogFormat, Network, ServerCommand, SignerCommand, WalletCommand,
+};
+use crate::config_file::ConfigFile;
+pub use args::{Cli, OutputFormat};
+pub use format::render;
 
 pub use args::{
     AssetSubCommandsEnum, ServerSubCommandsEnum, SignerSubCommandsEnum, WalletSubCommandsEnum,
 };
 
 mod args;
+mod config_file;
+mod format;
 mod schema;
 
+/// JSON log lines with the version of the CLI as first field
+struct JsonWithVersion(Format<Json>);
+
+impl<S, N> FormatEvent<S, N> for JsonWithVersion
+where
+    S: tracing::Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(
+        &self,
+        ctx: &FmtContext<'_, S, N>,
+        mut writer: Writer<'_>,
+        event: &tracing::Event<'_>,
+    ) -> std::fmt::Result {
+        let mut line = String::new();
+        self.0.format_event(ctx, Writer::new(&mut line), event)?;
+        match line.strip_prefix('{') {
+            Some(rest) => write!(
+                writer,
+                "{{\"version\":\"{}\",{rest}",
+                env!("CARGO_PKG_VERSION")
+            ),
+            None => writer.write_str(&line),
+        }
+    }
+}
+
+/// Open the log file in append mode, creating it and its directory if missing
+fn open_log_file(path: &Path) -> std::io::Result<std::fs::File> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+}
+
 pub fn inner_main(args: args::Cli) -> anyhow::Result<Value> {
-    let directive = if let CliCommand::Server(args::ServerArgs {
-        command: ServerCommand::Start { .. },
-    }) = args.command
+    let (level, log_file) = if let CliCommand::Server(args::ServerArgs {
+        command: ServerCommand::Start { datadir, .. },
+    }) = &args.command
     {
-        LevelFilter::INFO.into()
+        let datadir = datadir
+            .clone()
+            .unwrap_or_else(|| Config::default_home().unwrap_or(PathBuf::from(".")));
+        (LevelFilter::INFO, Some(datadir.join("debug.log")))
     } else {
-        LevelFilter::WARN.into()
+        (LevelFilter::WARN, None)
     };
+    let level = args.log_level.map_or(level, LevelFilter::from);
+    let log_file = args.log_file.clone().or(log_file);
 
-    let (appender, _guard) = tracing_appender::non_blocking(std::io::stderr());
+    let (file, file_err) = match log_file.as_deref().map(open_log_file) {
+        Some(Ok(file)) => (Some(file), None),
+        Some(Err(e)) => (None, Some(e)),
+        None => (None, None),
+    };
+    let (appender, _guard) = match file {
+        Some(file) => tracing_appender::non_blocking(file),
+        None => tracing_appender::non_blocking(std::io::stderr()),
+    };
     let filter = EnvFilter::builder()
-        .with_default_directive(directive)
+        .with_default_directive(level.into())
         .from_env_lossy();
-    let subscriber = FmtSubscriber::builder()
+    let builder = FmtSubscriber::builder()
         .with_env_filter(filter)
-        .with_writer(appender)
-        .finish();
+        .with_writer(appender);
+    let subscriber: Box<dyn tracing::Subscriber + Send + Sync> = match args.log_format {
+        LogFormat::Text => Box::new(builder.finish()),
+        LogFormat::Json => Box::new(
+            builder
+                .json()
+                .event_format(JsonWithVersion(format().json()))
+                .finish(),
+        ),
+    };
     match tracing::subscriber::set_global_default(subscriber) {
         Ok(_) => tracing::info!("logging initialized"),
         Err(_) => tracing::debug!("logging already initialized"),
     }
+    if let (Some(path), Some(e)) = (&log_file, file_err) {
+        tracing::warn!("cannot open log file {path:?}, logging to stderr: {e}");
+    }
 
     tracing::info!("CLI initialized with args: {:?}", args);
 
-    // TODO: improve network types conversion or comparison
-    let (network, default_port) = match args.network {
-        Network::Mainnet => ("liquid", 32110),
-        Network::Testnet => ("liquid-testnet", 32111),
-        Network::Regtest => ("liquid-regtest", 32112),
+    // Command line flags take precedence over the config file, which takes precedence over defaults
+    let config_file = ConfigFile::load(args.config.as_deref())?;
+    let elements_network = match args.network {
+        Some(network) => ElementsNetwork::from(&network),
+        None => ElementsNetwork::from(&config_file.network()?.unwrap_or(Network::Testnet)),
     };
-
+    let network = elements_network.as_str();
     let addr = args
         .addr
-        .unwrap_or_else(|| SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), default_port));
-    let client = lwk_app::Client::new(addr)?;
+        .or(config_file.rpc.addr)
+        .unwrap_or_else(|| Config::default_addr(&elements_network));
+    // The url is used only to connect to a running server, the flags override the file
+    let rpc_url = match (&args.rpc_url, &args.addr) {
+        (None, None) => config_file.rpc.url.clone(),
+        _ => args.rpc_url.clone(),
+    };
+    let client = match &rpc_url {
+        Some(url) => lwk_app::Client::with_url(url)?,
+        None => lwk_app::Client::new(addr)?,
+    };
+    let target = rpc_url.unwrap_or_else(|| addr.to_string());
 
     // verify the server is up if needed
     if args.command.requires_server_running() {
         let version = client
             .version()
-            .with_context(|| format!("Is the server at {:?} running?", addr))?;
+            .with_context(|| format!("Is the server at {:?} running?", target))?;
+        if lwk_app::check_server_version(&version.version)? == VersionCompatibility::Newer {
+            tracing::warn!(
+                "Server version {} is newer than the CLI version {}, consider upgrading the CLI",
+                version.version,
+                lwk_app::consts::APP_VERSION
+            );
+        }
         let server_network = version.network;
 
         if server_network != network {
@@ -75,110 +163,153 @@ pub fn inner_main(args: args::Cli) -> anyhow::Result<Value> {
     }
 
     Ok(match args.command {
-        CliCommand::Server(a) => {
-            match a.command {
-                ServerCommand::Start {
-                    electrum_url,
-                    #[cfg(feature = "registry")]
-                    registry_url,
-                    esplora_api_url,
-                    datadir,
-                    timeout,
-                    scanning_interval,
-                } => {
-                    let (tx, rx) = std::sync::mpsc::channel();
-                    let _ = ctrlc::try_set_handler(move || {
-                        tx.send(()).expect("Could not send signal on channel.")
-                    });
-
-                    // start the app with default host/port
-                    let datadir = datadir.unwrap_or_else(|| {
-                        Config::default_home().unwrap_or(std::path::PathBuf::from("."))
-                    });
-                    let mut config = match args.network {
-                        Network::Mainnet => Config::default_mainnet(datadir),
-                        Network::Testnet => Config::default_testnet(datadir),
-                        Network::Regtest => Config::default_regtest(datadir),
-                    };
-                    if let Some(timeout) = timeout {
-                        config.timeout = Duration::from_secs(timeout);
-                    };
-                    if let Some(scanning_interval) = scanning_interval {
-                        config.scanning_interval = Duration::from_secs(scanning_interval);
-                    };
-                    if let Some(url) = electrum_url {
-                        config.electrum_url = url;
-                    } else if let Network::Regtest = args.network {
-                        anyhow::bail!("on regtest you have to specify --electrum-url");
-                    };
-                    if let Some(url) = esplora_api_url {
-                        config.esplora_api_url = url;
-                    };
-
-                    #[cfg(feature = "registry")]
-                    if let Some(url) = registry_url {
-                        config.registry_url = url;
-                    };
-
-                    config.addr = addr;
-                    let mut app = lwk_app::App::new(config)?;
-
-                    app.run()?;
-
-                    // get the app version
-                    let version = client.version()?.version;
-                    tracing::info!("App running version {}", version);
-
-                    loop {
-                        match rx.recv_timeout(Duration::from_millis(100)) {
-                            Ok(_) => {
-                                tracing::debug!("Received ctrl-c signal");
+        CliCommand::Server(a) => match a.command {
+            ServerCommand::Start {
+                electrum_url,
+                electrum_timeout,
+                electrum_retry,
+                electrum_proxy,
+                #[cfg(feature = "registry")]
+                registry_url,
+                esplora_api_url,
+                backend,
+                node_rpc_url,
+                block_filter_start_height,
+                datadir,
+                timeout,
+                scanning_interval,
+                scan_workers,
+                gap_limit,
+                state_password,
+                state_password_prompt,
+            } => {
+                if args.rpc_url.is_some() {
+                    anyhow::bail!("--rpc-url is for connecting to a running server, use --addr to choose where the server listens");
+                }
+                let client = lwk_app::Client::new(addr)?;
+                let (tx, rx) = std::sync::mpsc::channel();
+                let _ = ctrlc::try_set_handler(move || {
+                    tx.send(()).expect("Could not send signal on channel.")
+                });
+
+                // start the app with default host/port
+                let datadir = datadir.unwrap_or_else(|| {
+                    Config::default_home().unwrap_or(std::path::PathBuf::from("."))
+                });
+                let mut builder = Config::builder()
+                    .network(elements_network)
+                    .rpc_addr(addr)
+                    .datadir(datadir)
+                    .backend(backend.into());
+                if let Some(url) = electrum_url.or(config_file.electrum.url) {
+                    builder = builder.electrum_url(url);
+                };
+                if let Some(url) = esplora_api_url {
+                    builder = builder.esplora_api_url(url);
+                };
+                if let Some(url) = node_rpc_url {
+                    builder = builder.node_rpc_url(url);
+                };
+                let mut config = builder.build()?;
+                if let Some(timeout) = timeout {
+                    config.timeout = Duration::from_secs(timeout);
+                };
+                if let Some(scanning_interval) = scanning_interval {
+                    config.scanning_interval = Duration::from_secs(scanning_interval);
+                };
+                if let Some(scan_workers) = scan_workers {
+                    config.scan_workers = scan_workers;
+                };
+                if let Some(gap_limit) = gap_limit {
+                    config.gap_limit = gap_limit;
+                };
+                config.electrum_options.timeout = electrum_timeout.or(config_file.electrum.timeout);
+                config.electrum_options.retry = electrum_retry.or(config_file.electrum.retry);
+                config.electrum_options.proxy = electrum_proxy.or(config_file.electrum.proxy);
+                if let Some(height) = block_filter_start_height {
+                    config.block_filter_start_height = height;
+                };
+                let state_password = if state_password_prompt {
+                    eprint!("State password: ");
+                    let mut password = String::new();
+                    std::io::stdin().read_line(&mut password)?;
+                    Some(password.trim_end_matches(['\r', '\n']).to_string())
+                } else {
+                    state_password
+                };
+                config.state_key = state_password.as_deref().map(StateKey::from_password);
+
+                #[cfg(feature = "registry")]
+                if let Some(url) = registry_url {
+                    config.registry_url = url;
+                };
+
+                let mut app = lwk_app::App::new(config)?;
+
+                app.run()?;
+
+                // get the app version
+                let version = client.version()?.version;
+                tracing::info!("App running version {}", version);
+
+                loop {
+                    match rx.recv_timeout(Duration::from_millis(100)) {
+                        Ok(_) => {
+                            tracing::debug!("Received ctrl-c signal");
+                            break;
+                        }
+                        Err(_) => {
+                            if app.is_running().unwrap_or(false) {
+                                continue;
+                            } else {
+                                tracing::debug!("Received stop signal");
                                 break;
                             }
-                            Err(_) => {
-                                if app.is_running().unwrap_or(false) {
-                                    continue;
-                                } else {
-                                    tracing::debug!("Received stop signal");
-                                    break;
-                                }
-                            }
                         }
                     }
-                    app.stop()?;
-                    app.join_threads()?;
-                    tracing::info!("Threads ended");
-                }
-                ServerCommand::Scan => {
-                    client.scan()?;
-                }
-                ServerCommand::Stop => {
-                    client.stop()?;
                 }
+                app.stop()?;
+                app.join_threads()?;
+                tracing::info!("Threads ended");
+                Value::Null
             }
-
-            Value::Null
-        }
+            ServerCommand::Scan => {
+                client.scan()?;
+                Value::Null
+            }
+            ServerCommand::Network => serde_json::to_value(client.network_details()?)?,
+            ServerCommand::Stop => {
+                client.stop()?;
+                Value::Null
+            }
+        },
         CliCommand::Signer(a) => match a.command {
-            SignerCommand::Generate => {
-                let j = client.signer_generate()?;
+            SignerCommand::Generate { word_count } => {
+                let j = client.signer_generate(word_count)?;
                 serde_json::to_value(j)?
             }
             SignerCommand::JadeId { emulator } => {
                 let j = client.signer_jade_id(emulator)?;
                 serde_json::to_value(j)?
             }
-            SignerCommand::Sign { signer, pset } => {
-                let r = client.signer_sign(signer, pset)?;
+            SignerCommand::Sign {
+                signer,
+                pset,
+                pset_file,
+                wallet,
+            } => {
+                let pset = read_pset(pset, pset_file)?;
+                let r = client.signer_sign(signer, pset, wallet)?;
                 serde_json::to_value(r)?
             }
             SignerCommand::LoadSoftware {
                 signer,
                 mnemonic,
+                passphrase,
                 persist,
             } => {
                 let persist = persist.expect("required");
-                let j = client.signer_load_software(signer, mnemonic, persist)?;
+                let j = client.signer_load_software(signer, mnemonic, passphrase, persist)?;
                 serde_json::to_value(j)?
             }
             SignerCommand::LoadJade {
@@ -196,6 +327,14 @@ pub fn inner_main(args: args::Cli) -> anyhow::Result<Value> {
                 let j = client.signer_load_external(signer, fingerprint)?;
                 serde_json::to_value(j)?
             }
+            SignerCommand::LoadHwi {
+                signer,
+                fingerprint,
+                device_path,
+            } => {
+                let j = client.signer_load_hwi(signer, fingerprint, device_path)?;
+                serde_json::to_value(j)?
+            }
             SignerCommand::List => serde_json::to_value(client.signer_list()?)?,
             SignerCommand::Details { signer } => {
                 let r = client.signer_details(signer)?;
@@ -213,39 +352,86 @@ pub fn inner_main(args: args::Cli) -> anyhow::Result<Value> {
                 let r = client.signer_singlesig_descriptor(
                     signer,
                     descriptor_blinding_key.to_string(),
-                    kind.to_string(),
+                    kind.into(),
                 )?;
                 serde_json::to_value(r)?
             }
             SignerCommand::Xpub { signer, kind } => {
-                let r = client.signer_xpub(signer, kind.to_string())?;
+                let r = client.signer_xpub(signer, kind.into())?;
+                serde_json::to_value(r)?
+            }
+            SignerCommand::VerifyXpub {
+                fingerprint,
+                path,
+                xpub,
+            } => {
+                let r = client.verify_xpub(fingerprint, path, xpub)?;
                 serde_json::to_value(r)?
             }
             SignerCommand::RegisterMultisig { signer, wallet } => {
                 let r = client.signer_register_multisig(signer, wallet)?;
                 serde_json::to_value(r)?
             }
+            SignerCommand::RegisteredMultisigs { signer } => {
+                let r = client.signer_registered_multisigs(signer)?;
+                serde_json::to_value(r)?
+            }
+            SignerCommand::ConfirmAddress {
+                signer,
+                wallet,
+                index,
+                change,
+            } => {
+                let r = client.signer_confirm_address(signer, wallet, index, change)?;
+                serde_json::to_value(r)?
+            }
+            SignerCommand::JadeStatus { signer } => {
+                let r = client.signer_jade_status(signer)?;
+                serde_json::to_value(r)?
+            }
+            SignerCommand::JadeUnlock { signer } => {
+                let r = client.signer_jade_unlock(signer)?;
+                serde_json::to_value(r)?
+            }
         },
         CliCommand::Wallet(a) => match a.command {
-            WalletCommand::Load { descriptor, wallet } => {
-                let r = client.wallet_load(descriptor, wallet)?;
+            WalletCommand::Load {
+                descriptor,
+                wallet,
+                gap_limit,
+            } => {
+                let r = client.wallet_load(descriptor, wallet, gap_limit)?;
                 serde_json::to_value(r)?
             }
             WalletCommand::Unload { wallet } => {
                 let r = client.wallet_unload(wallet)?;
                 serde_json::to_value(r)?
             }
+            WalletCommand::Rescan {
+                wallet,
+                start_height,
+            } => {
+                let r = client.wallet_rescan(wallet, start_height)?;
+                serde_json::to_value(r)?
+            }
             WalletCommand::Balance {
                 wallet,
                 with_tickers,
+                detailed,
             } => {
-                let r = client.wallet_balance(wallet, with_tickers)?;
+                let r = client.wallet_balance(wallet, with_tickers, detailed)?;
                 serde_json::to_value(r)?
             }
             WalletCommand::Send {
                 wallet,
                 recipient,
                 fee_rate,
+                fee_rate_unit,
+                utxo,
+                enable_rbf,
+                change_address,
+                dry_run,
+                coin_selection,
             } => {
                 let mut addressees = vec![];
                 for rec in recipient {
@@ -255,15 +441,69 @@ pub fn inner_main(args: args::Cli) -> anyhow::Result<Value> {
                     );
                 }
 
-                let r = client.wallet_send_many(wallet, addressees, fee_rate)?;
+                let r = client.wallet_send_many(
+                    wallet,
+                    addressees,
+                    fee_rate,
+                    fee_rate_unit.map(Into::into),
+                    (!utxo.is_empty()).then_some(utxo),
+                    enable_rbf,
+                    change_address,
+                    dry_run,
+                    coin_selection.map(Into::into),
+                )?;
+                serde_json::to_value(r)?
+            }
+            WalletCommand::EstimateFee {
+                wallet,
+                recipient,
+                fee_rate,
+                fee_rate_unit,
+                coin_selection,
+            } => {
+                let mut addressees = vec![];
+                for rec in recipient {
+                    addressees.push(
+                        rec.try_into()
+                            .with_context(|| "error parsing recipient argument")?,
+                    );
+                }
+
+                let r = client.wallet_estimate_fee(
+                    wallet,
+                    addressees,
+                    fee_rate,
+                    fee_rate_unit.map(Into::into),
+                    coin_selection.map(Into::into),
+                )?;
+                serde_json::to_value(r)?
+            }
+            WalletCommand::BumpFee {
+                wallet,
+                txid,
+                fee_rate,
+                fee_rate_unit,
+            } => {
+                let r = client.wallet_bump_fee(
+                    wallet,
+                    txid,
+                    fee_rate,
+                    fee_rate_unit.map(Into::into),
+                )?;
                 serde_json::to_value(r)?
             }
             WalletCommand::Drain {
                 wallet,
                 address,
                 fee_rate,
+                fee_rate_unit,
             } => {
-                let r = client.wallet_drain(wallet, address, fee_rate)?;
+                let r = client.wallet_drain(
+                    wallet,
+                    address,
+                    fee_rate,
+                    fee_rate_unit.map(Into::into),
+                )?;
                 serde_json::to_value(r)?
             }
             WalletCommand::Address {
@@ -272,9 +512,34 @@ pub fn inner_main(args: args::Cli) -> anyhow::Result<Value> {
                 signer,
                 with_text_qr,
                 with_uri_qr,
+                count,
+                unconfidential,
             } => {
-                let r = client.wallet_address(wallet, index, signer, with_text_qr, with_uri_qr)?;
-                serde_json::to_value(r)?
+                let confidential = unconfidential.then_some(false);
+                match count {
+                    Some(count) if count != 1 => {
+                        let r = client.wallet_addresses(
+                            wallet,
+                            index,
+                            count,
+                            with_text_qr,
+                            with_uri_qr,
+                            confidential,
+                        )?;
+                        serde_json::to_value(r)?
+                    }
+                    _ => {
+                        let r = client.wallet_address(
+                            wallet,
+                            index,
+                            signer,
+                            with_text_qr,
+                            with_uri_qr,
+                            confidential,
+                        )?;
+                        serde_json::to_value(r)?
+                    }
+                }
             }
             WalletCommand::List => serde_json::to_value(client.wallet_list()?)?,
             WalletCommand::Issue {
@@ -285,6 +550,9 @@ pub fn inner_main(args: args::Cli) -> anyhow::Result<Value> {
                 address_token,
                 contract,
                 fee_rate,
+                dry_run,
+                change_address,
+                coin_selection,
             } => {
                 let r = client.wallet_issue(
                     wallet,
@@ -294,6 +562,9 @@ pub fn inner_main(args: args::Cli) -> anyhow::Result<Value> {
                     address_token,
                     contract,
                     fee_rate,
+                    dry_run,
+                    change_address,
+                    coin_selection.map(Into::into),
                 )?;
                 serde_json::to_value(r)?
             }
@@ -303,9 +574,20 @@ pub fn inner_main(args: args::Cli) -> anyhow::Result<Value> {
                 satoshi_asset,
                 address_asset,
                 fee_rate,
+                dry_run,
+                change_address,
+                coin_selection,
             } => {
-                let r =
-                    client.wallet_reissue(wallet, asset, satoshi_asset, address_asset, fee_rate)?;
+                let r = client.wallet_reissue(
+                    wallet,
+                    asset,
+                    satoshi_asset,
+                    address_asset,
+                    fee_rate,
+                    dry_run,
+                    change_address,
+                    coin_selection.map(Into::into),
+                )?;
                 serde_json::to_value(r)?
             }
             WalletCommand::Burn {
@@ -317,25 +599,54 @@ pub fn inner_main(args: args::Cli) -> anyhow::Result<Value> {
                 let r = client.wallet_burn(wallet, asset, satoshi_asset, fee_rate)?;
                 serde_json::to_value(r)?
             }
+            WalletCommand::Consolidate {
+                wallet,
+                asset,
+                max_inputs,
+                fee_rate,
+            } => {
+                let r = client.wallet_consolidate(wallet, asset, max_inputs, fee_rate)?;
+                serde_json::to_value(r)?
+            }
+            WalletCommand::CreateSwap {
+                wallet,
+                send_asset,
+                send_satoshi,
+                recv_asset,
+                recv_satoshi,
+            } => {
+                let r = client.wallet_create_swap(
+                    wallet,
+                    send_asset,
+                    send_satoshi,
+                    recv_asset,
+                    recv_satoshi,
+                )?;
+                serde_json::to_value(r)?
+            }
             WalletCommand::MultisigDesc {
                 descriptor_blinding_key,
                 kind,
                 threshold,
                 keyorigin_xpub,
+                unsorted,
             } => {
                 let r = client.wallet_multisig_descriptor(
                     descriptor_blinding_key.to_string(),
-                    kind.to_string(),
+                    kind.into(),
                     threshold,
                     keyorigin_xpub,
+                    !unsorted,
                 )?;
                 serde_json::to_value(r)?
             }
             WalletCommand::Broadcast {
                 dry_run,
                 pset,
+                pset_file,
                 wallet,
             } => {
+                let pset = read_pset(pset, pset_file)?;
                 let r = client.wallet_broadcast(wallet, dry_run, pset)?;
                 serde_json::to_value(r)?
             }
@@ -343,6 +654,10 @@ pub fn inner_main(args: args::Cli) -> anyhow::Result<Value> {
                 let r = client.wallet_details(wallet)?;
                 serde_json::to_value(r)?
             }
+            WalletCommand::SyncStatus { wallet } => {
+                let r = client.wallet_sync_status(wallet)?;
+                serde_json::to_value(r)?
+            }
             WalletCommand::Combine { wallet, pset } => {
                 let r = client.wallet_combine(wallet, pset)?;
                 serde_json::to_value(r)?
@@ -355,15 +670,51 @@ pub fn inner_main(args: args::Cli) -> anyhow::Result<Value> {
                 let r = client.wallet_pset_details(wallet, pset, with_tickers)?;
                 serde_json::to_value(r)?
             }
-            WalletCommand::Utxos { wallet } => {
-                let r = client.wallet_utxos(wallet)?;
+            WalletCommand::PsetAnalyze {
+                wallet,
+                pset,
+                with_tickers,
+            } => {
+                let r = client.wallet_pset_analyze(wallet, pset, with_tickers)?;
+                serde_json::to_value(r)?
+            }
+            WalletCommand::ProofOfReserves { wallet, challenge } => {
+                let r = client.wallet_proof_of_reserves(wallet, challenge)?;
+                serde_json::to_value(r)?
+            }
+            WalletCommand::VerifyProofOfReserves { proof, challenge } => {
+                let r = client.verify_proof_of_reserves(proof, challenge)?;
+                serde_json::to_value(r)?
+            }
+            WalletCommand::Utxos {
+                wallet,
+                with_tickers,
+                asset,
+                min_satoshi,
+            } => {
+                let r = client.wallet_utxos(wallet, with_tickers, asset, min_satoshi)?;
                 serde_json::to_value(r)?
             }
             WalletCommand::Txs {
                 wallet,
                 with_tickers,
+                limit,
+                offset,
+                min_height,
+                max_height,
             } => {
-                let r = client.wallet_txs(wallet, with_tickers)?;
+                let r = client.wallet_txs(
+                    wallet,
+                    with_tickers,
+                    limit,
+                    offset,
+                    min_height,
+                    max_height,
+                )?;
+                serde_json::to_value(r)?
+            }
+            WalletCommand::ExportCsv { wallet } => {
+                let r = client.wallet_export_csv(wallet)?;
                 serde_json::to_value(r)?
             }
             WalletCommand::Tx {
@@ -386,6 +737,37 @@ pub fn inner_main(args: args::Cli) -> anyhow::Result<Value> {
                 let r = client.wallet_set_addr_memo(wallet, address, memo)?;
                 serde_json::to_value(r)?
             }
+            WalletCommand::SetOutputMemo {
+                wallet,
+                outpoint,
+                memo,
+            } => {
+                let r = client.wallet_set_output_memo(wallet, outpoint, memo)?;
+                serde_json::to_value(r)?
+            }
+            WalletCommand::Labels { wallet } => {
+                let r = client.wallet_labels(wallet)?;
+                serde_json::to_value(r)?
+            }
+            WalletCommand::ImportLabels { wallet, jsonl } => {
+                let r = client.wallet_import_labels(wallet, jsonl)?;
+                serde_json::to_value(r)?
+            }
+            WalletCommand::ValidateAddress { wallet, address } => {
+                let r = client.validate_address(address, wallet)?;
+                serde_json::to_value(r)?
+            }
+            WalletCommand::IsMine {
+                wallet,
+                address_or_script,
+            } => {
+                let r = client.wallet_is_mine(wallet, address_or_script)?;
+                serde_json::to_value(r)?
+            }
+            WalletCommand::ExportDescriptor { wallet, format } => {
+                let r = client.wallet_export_descriptor(wallet, format.into())?;
+                serde_json::to_value(r)?
+            }
         },
         CliCommand::Asset(a) => match a.command {
             AssetCommand::Contract {
@@ -427,6 +809,10 @@ pub fn inner_main(args: args::Cli) -> anyhow::Result<Value> {
                 let r = client.asset_from_explorer(asset)?;
                 serde_json::to_value(r)?
             }
+            AssetCommand::RegistryLookup { asset, cache } => {
+                let r = client.asset_registry_lookup(asset, cache)?;
+                serde_json::to_value(r)?
+            }
             AssetCommand::Publish { asset } => {
                 let r = client.asset_publish(asset)?;
                 serde_json::to_value(r)?
@@ -447,3 +833,91 @@ pub fn inner_main(args: args::Cli) -> anyhow::Result<Value> {
         }
     })
 }
+
+/// Returns the PSET given inline or read from `pset_file` (`-` means stdin), trimming whitespaces
+fn read_pset(pset: Option<String>, pset_file: Option<PathBuf>) -> anyhow::Result<String> {
+    match (pset, pset_file) {
+        (Some(pset), None) => Ok(pset),
+        (None, Some(path)) => {
+            let content = if path == Path::new("-") {
+                std::io::read_to_string(std::io::stdin())?
+            } else {
+                std::fs::read_to_string(&path)
+                    .with_context(|| format!("Cannot read PSET from {}", path.display()))?
+            };
+            Ok(content.trim().to_string())
+        }
+        (Some(_), Some(_)) => Err(anyhow!("Specify either --pset or --pset-file, not both")),
+        (None, None) => Err(anyhow!("Specify one of --pset or --pset-file")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_pset() {
+        let pset = "cHNldP8BAgQCAAAAAQQBAAEFAQABBgEDAfsEAgAAAAA=";
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("pset.txt");
+        std::fs::write(&path, format!("{pset}\n  ")).unwrap();
+
+        assert_eq!(read_pset(Some(pset.to_string()), None).unwrap(), pset);
+        assert_eq!(read_pset(None, Some(path.clone())).unwrap(), pset);
+        let err = read_pset(Some(pset.to_string()), Some(path)).unwrap_err();
+        assert!(err.to_string().contains("not both"));
+        assert!(read_pset(None, None).is_err());
+        assert!(read_pset(None, Some(tmp.path().join("missing"))).is_err());
+    }
+
+    #[test]
+    fn test_open_log_file() {
+        use std::io::Write;
+
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("sub").join("debug.log");
+        open_log_file(&path).unwrap().write_all(b"a").unwrap();
+        open_log_file(&path).unwrap().write_all(b"b").unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "ab");
+
+        // A directory can't be opened as a file
+        assert!(open_log_file(tmp.path()).is_err());
+    }
+
+    #[test]
+    fn test_json_log() {
+        use std::sync::{Arc, Mutex};
+
+        #[derive(Clone, Default)]
+        struct Buf(Arc<Mutex<Vec<u8>>>);
+        impl std::io::Write for Buf {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.0.lock().unwrap().extend_from_slice(buf);
+                Ok(buf.len())
+            }
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let buf = Buf::default();
+        let writer = buf.clone();
+        let subscriber = FmtSubscriber::builder()
+            .with_writer(move || writer.clone())
+            .json()
+            .event_format(JsonWithVersion(format().json()))
+            .finish();
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::warn!(wallet = "w1", "synced");
+        });
+
+        let out = String::from_utf8(buf.0.lock().unwrap().clone()).unwrap();
+        assert!(out.starts_with("{\"version\":"));
+        let line: Value = serde_json::from_str(out.trim_end()).unwrap();
+        assert_eq!(line["version"], env!("CARGO_PKG_VERSION"));
+        assert_eq!(line["level"], "WARN");
+        assert_eq!(line["fields"]["wallet"], "w1");
+        assert_eq!(line["fields"]["message"], "synced");
+    }
+}
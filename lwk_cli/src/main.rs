@@ -1,11 +1,12 @@
 use clap::Parser;
 use lwk_app::Error;
-use lwk_cli::{inner_main, Cli};
+use lwk_cli::{inner_main, render, Cli};
 
 mod args;
 
 fn main() -> anyhow::Result<()> {
     let args = Cli::parse();
+    let format = args.format;
 
     let value = match inner_main(args) {
         Ok(value) => value,
@@ -17,6 +18,6 @@ fn main() -> anyhow::Result<()> {
             }
         }
     };
-    println!("{:#}", value);
+    println!("{}", render(&value, format));
     Ok(())
 }
@@ -23,6 +23,8 @@ pub(crate) fn schema(a: SchemaArgs, client: Client) -> Result<Value, anyhow::Err
             MainCommand::Asset(s) => client.schema(s.command.into(), Direction::Response)?,
             MainCommand::Schema => client.schema(Method::Schema, Direction::Response)?,
         },
+        DirectionCommand::All => serde_json::to_value(client.schema_all()?)?,
+        DirectionCommand::Openrpc => client.openrpc()?,
     })
 }
 
@@ -30,6 +32,7 @@ impl From<ServerSubCommandsEnum> for Method {
     fn from(value: ServerSubCommandsEnum) -> Self {
         match value {
             ServerSubCommandsEnum::Scan => Method::Scan,
+            ServerSubCommandsEnum::Network => Method::NetworkDetails,
             ServerSubCommandsEnum::Stop => Method::Stop,
         }
     }
@@ -40,21 +43,37 @@ impl From<WalletSubCommandsEnum> for Method {
         match value {
             WalletSubCommandsEnum::Load => Method::WalletLoad,
             WalletSubCommandsEnum::Unload => Method::WalletUnload,
+            WalletSubCommandsEnum::Rescan => Method::WalletRescan,
             WalletSubCommandsEnum::List => Method::WalletList,
             WalletSubCommandsEnum::Address => Method::WalletAddress,
             WalletSubCommandsEnum::Balance => Method::WalletBalance,
             WalletSubCommandsEnum::Send => Method::WalletSendMany,
+            WalletSubCommandsEnum::EstimateFee => Method::WalletEstimateFee,
+            WalletSubCommandsEnum::BumpFee => Method::WalletBumpFee,
             WalletSubCommandsEnum::Issue => Method::WalletIssue,
             WalletSubCommandsEnum::Reissue => Method::WalletReissue,
+            WalletSubCommandsEnum::Consolidate => Method::WalletConsolidate,
+            WalletSubCommandsEnum::CreateSwap => Method::WalletCreateSwap,
             WalletSubCommandsEnum::MultisigDesc => Method::WalletMultisigDescriptor,
             WalletSubCommandsEnum::Broadcast => Method::WalletBroadcast,
             WalletSubCommandsEnum::Details => Method::WalletDetails,
+            WalletSubCommandsEnum::SyncStatus => Method::WalletSyncStatus,
             WalletSubCommandsEnum::Combine => Method::WalletCombine,
             WalletSubCommandsEnum::PsetDetails => Method::WalletPsetDetails,
+            WalletSubCommandsEnum::PsetAnalyze => Method::WalletPsetAnalyze,
+            WalletSubCommandsEnum::ProofOfReserves => Method::WalletProofOfReserves,
+            WalletSubCommandsEnum::VerifyProofOfReserves => Method::VerifyProofOfReserves,
             WalletSubCommandsEnum::Utxos => Method::WalletUtxos,
             WalletSubCommandsEnum::Txs => Method::WalletTxs,
+            WalletSubCommandsEnum::ExportCsv => Method::WalletExportCsv,
             WalletSubCommandsEnum::SetTxMemo => Method::WalletSetTxMemo,
             WalletSubCommandsEnum::SetAddrMemo => Method::WalletSetAddrMemo,
+            WalletSubCommandsEnum::SetOutputMemo => Method::WalletSetOutputMemo,
+            WalletSubCommandsEnum::Labels => Method::WalletLabels,
+            WalletSubCommandsEnum::ImportLabels => Method::WalletImportLabels,
+            WalletSubCommandsEnum::ValidateAddress => Method::ValidateAddress,
+            WalletSubCommandsEnum::IsMine => Method::WalletIsMine,
+            WalletSubCommandsEnum::ExportDescriptor => Method::WalletExportDescriptor,
         }
     }
 }
@@ -67,12 +86,19 @@ impl From<SignerSubCommandsEnum> for Method {
             SignerSubCommandsEnum::LoadSoftware => Method::SignerLoadSoftware,
             SignerSubCommandsEnum::LoadJade => Method::SignerLoadJade,
             SignerSubCommandsEnum::LoadExternal => Method::SignerLoadExternal,
+            SignerSubCommandsEnum::LoadHwi => Method::SignerLoadHwi,
             SignerSubCommandsEnum::Unload => Method::SignerUnload,
             SignerSubCommandsEnum::Details => Method::SignerDetails,
             SignerSubCommandsEnum::List => Method::SignerList,
             SignerSubCommandsEnum::Sign => Method::SignerSign,
             SignerSubCommandsEnum::SinglesigDesc => Method::SignerSinglesigDescriptor,
             SignerSubCommandsEnum::Xpub => Method::SignerXpub,
+            SignerSubCommandsEnum::VerifyXpub => Method::VerifyXpub,
+            SignerSubCommandsEnum::RegisterMultisig => Method::SignerRegisterMultisig,
+            SignerSubCommandsEnum::RegisteredMultisigs => Method::SignerRegisteredMultisigs,
+            SignerSubCommandsEnum::ConfirmAddress => Method::SignerConfirmAddress,
+            SignerSubCommandsEnum::JadeStatus => Method::SignerJadeStatus,
+            SignerSubCommandsEnum::JadeUnlock => Method::SignerJadeUnlock,
         }
     }
 }
@@ -85,6 +111,7 @@ impl From<AssetSubCommandsEnum> for Method {
             AssetSubCommandsEnum::List => Method::AssetList,
             AssetSubCommandsEnum::Insert => Method::AssetInsert,
             AssetSubCommandsEnum::Remove => Method::AssetRemove,
+            AssetSubCommandsEnum::RegistryLookup => Method::AssetRegistryLookup,
             AssetSubCommandsEnum::Publish => Method::AssetPublish,
         }
     }
@@ -12,6 +12,7 @@ use elements::encode::serialize;
 use elements::hex::ToHex;
 use elements::{pset::PartiallySignedTransaction, Address};
 use lwk_containers::{testcontainers::clients, JadeEmulator, EMULATOR_PORT};
+use lwk_rpc_model::error::ErrorCode;
 use serde_json::Value;
 
 use lwk_cli::{
@@ -118,8 +119,7 @@ fn setup_cli(
     let addr = get_available_addr().unwrap();
 
     let cli = format!("cli --addr {addr} -n regtest");
-    let params =
-        format!("--datadir {datadir} --electrum-url {electrum_url} {registry_url} {esplora_url}");
+    let params = format!("--datadir {datadir} --electrum-url {electrum_url} {registry_url} {esplora_url} --state-password test");
 
     let t = {
         let cli = cli.clone();
@@ -279,6 +279,13 @@ fn complete(cli: &str, wallet: &str, pset: &str, signers: &[&str]) -> String {
         "{cli} wallet broadcast -w {wallet} --pset {pset_combined}"
     ));
     let txid = get_str(&r, "txid");
+    assert!(!r.get("already_broadcast").unwrap().as_bool().unwrap());
+    // Broadcasting again is not an error
+    let r2 = sh(&format!(
+        "{cli} wallet broadcast -w {wallet} --pset {pset_combined}"
+    ));
+    assert_eq!(get_str(&r2, "txid"), txid);
+    assert!(r2.get("already_broadcast").unwrap().as_bool().unwrap());
     wait_tx(cli, wallet, txid);
     txid.to_string()
 }
@@ -304,7 +311,7 @@ fn test_state_regression() {
     let tmp = tempfile::tempdir().unwrap();
     let datadir = tmp.path().display().to_string();
     let cli = format!("cli --addr {addr} -n regtest");
-    let params = format!("--datadir {datadir} --electrum-url {electrum_url}");
+    let params = format!("--datadir {datadir} --electrum-url {electrum_url} --state-password test");
 
     // copy static state into data dir
     let state = include_str!("./test_data/state.json");
@@ -312,7 +319,7 @@ fn test_state_regression() {
     to.push("liquid-regtest");
     fs::create_dir(&to).unwrap();
     to.push("state.json");
-    fs::write(to, state).unwrap();
+    fs::write(&to, state).unwrap();
 
     let t = {
         let cli = cli.clone();
@@ -332,6 +339,11 @@ fn test_state_regression() {
     let r = sh(&format!("{cli} asset list"));
     assert_eq!(get_len(&r, "assets"), 3);
 
+    // The software signer in the state has been encrypted
+    let state = fs::read_to_string(&to).unwrap();
+    assert!(!state.contains("signer_load_software"));
+    assert!(state.contains("encrypted"));
+
     sh(&format!("{cli} server stop"));
     t.join().unwrap();
 }
@@ -369,6 +381,8 @@ fn test_start_stop_persist() {
     let r = sh(&format!("{cli} signer details -s s3"));
     assert!(r.get("mnemonic").is_none());
     assert_eq!(get_str(&r, "type"), "jade-id");
+    let r = sh(&format!("{cli} signer jade-status -s s3"));
+    assert_eq!(get_str(&r, "state"), "disconnected");
 
     let desc = "ct(c25deb86fa11e49d651d7eae27c220ef930fbd86ea023eebfa73e54875647963,elwpkh(tpubD6NzVbkrYhZ4Was8nwnZi7eiWUNJq2LFpPSCMQLioUfUtT1e72GkRbmVeRAZc26j5MRUz2hRLsaVHJfs6L7ppNfLUrm9btQTuaEsLrT7D87/*))#q9cypnmc";
     sh(&format!("{cli} wallet load --wallet custody -d {desc}"));
@@ -520,6 +534,14 @@ fn test_signer_external() {
     assert_eq!(signers.len(), 1);
     assert_eq!(signers[0].get("name").unwrap().as_str().unwrap(), name);
 
+    // The xpub in the descriptor is a master key, it's not derived at the declared path
+    let r = sh(&format!(
+        "{cli} signer verify-xpub --fingerprint {fingerprint} --path m/0h/0h/0h --xpub {xpub}"
+    ));
+    assert!(!r.get("valid").unwrap().as_bool().unwrap());
+    assert!(get_str(&r, "reason").contains("depth"));
+    assert_eq!(r.get("depth").unwrap().as_u64().unwrap(), 0);
+
     sh(&format!("{cli} server stop"));
     t.join().unwrap();
 }
@@ -532,11 +554,19 @@ fn test_wallet_load_unload_list() {
     assert_eq!(get_len(&r, "wallets"), 0);
 
     let desc = "ct(c25deb86fa11e49d651d7eae27c220ef930fbd86ea023eebfa73e54875647963,elwpkh(tpubD6NzVbkrYhZ4Was8nwnZi7eiWUNJq2LFpPSCMQLioUfUtT1e72GkRbmVeRAZc26j5MRUz2hRLsaVHJfs6L7ppNfLUrm9btQTuaEsLrT7D87/*))#q9cypnmc";
-    let result = sh(&format!("{cli} wallet load --wallet custody -d {desc}"));
+    let err = sh_err(&format!(
+        "{cli} wallet load --wallet custody -d {desc} --gap-limit 0"
+    ));
+    assert!(err.contains("The gap limit must be greater than 0"));
+
+    let result = sh(&format!(
+        "{cli} wallet load --wallet custody -d {desc} --gap-limit 100"
+    ));
     assert_eq!(result.get("descriptor").unwrap().as_str().unwrap(), desc);
 
-    let err = sh_err(&format!("{cli} wallet load --wallet custody -d {desc}"));
-    assert!(err.contains("Wallet 'custody' is already loaded"));
+    // Loading the same wallet again is a no-op
+    let result = sh(&format!("{cli} wallet load --wallet custody -d {desc}"));
+    assert_eq!(result.get("descriptor").unwrap().as_str().unwrap(), desc);
 
     let err = sh_err(&format!(
         "{cli} wallet load --wallet differentname -d {desc}"
@@ -557,6 +587,85 @@ fn test_wallet_load_unload_list() {
     t.join().unwrap();
 }
 
+#[test]
+fn test_wallet_unload() {
+    let (t, _tmp, cli, _params, server, _) = setup_cli(false);
+
+    sw_signer(&cli, "s1");
+    singlesig_wallet(&cli, "w1", "s1", "slip77", "wpkh");
+    fund(&server, &cli, "w1", 1_000_000);
+
+    let err = sh_err(&format!("{cli} wallet unload --wallet notexist"));
+    assert!(err.contains("Wallet 'notexist' does not exist"));
+
+    // Unloading while scanning is covered by the lwk_app tests, driving the scan loop
+    sh(&format!("{cli} wallet unload --wallet w1"));
+    let r = sh(&format!("{cli} wallet list"));
+    assert_eq!(get_len(&r, "wallets"), 0);
+
+    // Reloading the wallet restores it from the store
+    singlesig_wallet(&cli, "w1", "s1", "slip77", "wpkh");
+    let policy_asset = "5ac9f65c0efcc4775e0baec4ec03abdde22473cd3cf33c0419ca290e0751b225";
+    assert_eq!(1_000_000, get_balance(&cli, "w1", policy_asset));
+
+    sh(&format!("{cli} server stop"));
+    t.join().unwrap();
+}
+
+#[test]
+fn test_wallet_load_address() {
+    let (t, _tmp, cli, _params, _server, _) = setup_cli(false);
+
+    let desc = "ct(c25deb86fa11e49d651d7eae27c220ef930fbd86ea023eebfa73e54875647963,elwpkh(tpubD6NzVbkrYhZ4Was8nwnZi7eiWUNJq2LFpPSCMQLioUfUtT1e72GkRbmVeRAZc26j5MRUz2hRLsaVHJfs6L7ppNfLUrm9btQTuaEsLrT7D87/*))#q9cypnmc";
+    sh(&format!("{cli} wallet load --wallet w1 -d {desc}"));
+
+    let r = sh(&format!("{cli} wallet address --wallet w1 --index 0"));
+    assert_eq!(r.get("index").unwrap().as_u64().unwrap(), 0);
+    let addr = Address::from_str(get_str(&r, "address")).unwrap();
+    assert!(addr.is_blinded());
+
+    let r = sh(&format!("{cli} wallet address --wallet w1"));
+    assert!(r.get("address").is_some());
+
+    let err = sh_err(&format!("{cli} wallet address --wallet not-loaded"));
+    assert!(err.contains("Wallet 'not-loaded' does not exist"));
+    let err = sh_result(&format!("{cli} wallet address --wallet not-loaded")).unwrap_err();
+    let err = err.downcast_ref::<lwk_app::Error>().unwrap();
+    assert_eq!(err.rpc_error_code(), Some(ErrorCode::WalletNotFound));
+
+    let r = sh(&format!(
+        "{cli} wallet address --wallet w1 --index 5 --count 3"
+    ));
+    let addresses = r.get("addresses").unwrap().as_array().unwrap();
+    assert_eq!(addresses.len(), 3);
+    for (i, a) in addresses.iter().enumerate() {
+        assert_eq!(a.get("index").unwrap().as_u64().unwrap(), 5 + i as u64);
+    }
+    let r = sh(&format!("{cli} wallet address --wallet w1 --index 6"));
+    assert_eq!(get_str(&r, "address"), get_str(&addresses[1], "address"));
+
+    let r = sh(&format!(
+        "{cli} wallet address --wallet w1 --index 5 --count 1"
+    ));
+    assert_eq!(get_str(&r, "address"), get_str(&addresses[0], "address"));
+
+    let err = sh_err(&format!("{cli} wallet address --wallet w1 --count 1001"));
+    assert!(err.contains("Invalid address count 1001"));
+
+    let r = sh(&format!(
+        "{cli} wallet address --wallet w1 --index 6 --unconfidential"
+    ));
+    assert!(!r.get("is_confidential").unwrap().as_bool().unwrap());
+    assert_eq!(r.get("index").unwrap().as_u64().unwrap(), 6);
+    let confidential = Address::from_str(get_str(&addresses[1], "address")).unwrap();
+    let unconfidential = Address::from_str(get_str(&r, "address")).unwrap();
+    assert!(!unconfidential.is_blinded());
+    assert_eq!(confidential.to_unconfidential(), unconfidential);
+
+    sh(&format!("{cli} server stop"));
+    t.join().unwrap();
+}
+
 #[test]
 fn test_wallet_memos() {
     let (t, _tmp, cli, params, server, _) = setup_cli(false);
@@ -605,6 +714,38 @@ fn test_wallet_memos() {
     assert_eq!(tx_memo(&cli, "w1", &txid), memo1);
     assert_eq!(tx_memo(&cli, "w2", &txid), memo2);
 
+    // Set an output memo for w1
+    let r = sh(&format!("{cli} wallet utxos -w w1"));
+    let utxo = &r.get("utxos").unwrap().as_array().unwrap()[0];
+    let vout = utxo.get("vout").unwrap().as_u64().unwrap();
+    let outpoint = format!("{}:{vout}", get_str(utxo, "txid"));
+    sh(&format!(
+        "{cli} wallet set-output-memo -w w1 --outpoint {outpoint} --memo {memo1}"
+    ));
+
+    // Memos are exported as BIP-329 labels
+    let r = sh(&format!("{cli} wallet labels -w w1"));
+    let labels = r.get("labels").unwrap().as_array().unwrap();
+    assert_eq!(labels.len(), 3);
+    assert_eq!(get_str(&labels[0], "type"), "tx");
+    assert_eq!(get_str(&labels[0], "ref"), txid);
+    assert_eq!(get_str(&labels[1], "type"), "addr");
+    assert_eq!(get_str(&labels[1], "ref"), w1_addr);
+    assert_eq!(get_str(&labels[2], "type"), "output");
+    assert_eq!(get_str(&labels[2], "ref"), outpoint);
+    let jsonl = get_str(&r, "jsonl").to_string();
+
+    // Labels of w1 do not refer to w2, except for the transaction
+    let r = sh(&format!(
+        "{cli} wallet import-labels -w w2 --jsonl '{jsonl}'"
+    ));
+    assert_eq!(r.get("imported").unwrap().as_u64().unwrap(), 1);
+    assert_eq!(r.get("skipped").unwrap().as_u64().unwrap(), 2);
+    assert_eq!(tx_memo(&cli, "w2", &txid), memo1);
+    sh(&format!(
+        "{cli} wallet set-tx-memo -w w2 --txid {txid} --memo {memo2}"
+    ));
+
     // Unload and load wallet, memo is removed
     sh(&format!("{cli} wallet unload --wallet w1"));
     singlesig_wallet(&cli, "w1", "s1", "slip77", "wpkh");
@@ -612,6 +753,20 @@ fn test_wallet_memos() {
     assert_eq!(tx_memo(&cli, "w2", &txid), memo2);
     assert_eq!(addr_memo(&cli, "w1", index), "");
 
+    // Labels can be imported back
+    let r = sh(&format!(
+        "{cli} wallet import-labels -w w1 --jsonl '{jsonl}'"
+    ));
+    assert_eq!(r.get("imported").unwrap().as_u64().unwrap(), 3);
+    assert!(r.get("errors").unwrap().as_array().unwrap().is_empty());
+    let r = sh(&format!("{cli} wallet labels -w w1"));
+    assert_eq!(get_str(&r, "jsonl"), jsonl);
+    assert_eq!(tx_memo(&cli, "w1", &txid), memo1);
+    assert_eq!(addr_memo(&cli, "w1", index), memo1);
+    sh(&format!(
+        "{cli} wallet set-tx-memo -w w1 --txid {txid} --memo ''"
+    ));
+
     // Remove memo
     sh(&format!(
         "{cli} wallet set-tx-memo -w w2 --txid {txid} --memo ''"
@@ -694,8 +849,12 @@ fn test_wallet_details() {
     let xpub2 = get_str(&r, "keyorigin_xpub");
     let r = sh(&format!("{cli} wallet multisig-desc --descriptor-blinding-key slip77-rand --kind wsh --threshold 2 --keyorigin-xpub {xpub1} --keyorigin-xpub {xpub2}"));
     let desc_ms = get_str(&r, "descriptor");
+    assert!(desc_ms.contains("elwsh(sortedmulti(2,"));
     sh(&format!("{cli} wallet load --wallet ms -d {desc_ms}"));
 
+    let r = sh(&format!("{cli} wallet multisig-desc --descriptor-blinding-key slip77-rand --kind wsh --threshold 2 --keyorigin-xpub {xpub1} --keyorigin-xpub {xpub2} --unsorted"));
+    assert!(get_str(&r, "descriptor").contains("elwsh(multi(2,"));
+
     let err = sh_err(&format!("{cli} wallet multisig-desc --descriptor-blinding-key slip77 --kind wsh --threshold 2 --keyorigin-xpub {xpub1} --keyorigin-xpub {xpub2}"));
     let exp_err = "Deterministic slip77 key not supported in multisig descriptor generation";
     assert!(err.contains(exp_err));
@@ -728,10 +887,15 @@ fn test_wallet_details() {
     assert_eq!(get_desc(&r), remove_checksum(desc_ms));
     assert!(get_str(&r, "warnings").is_empty());
     assert_eq!(get_str(&r, "type"), "wsh_multi_2of2");
+    assert_eq!(r.get("threshold").unwrap().as_u64().unwrap(), 2);
     let signers = r.get("signers").unwrap().as_array().unwrap();
     assert_eq!(signers.len(), 2);
     assert_eq!(get_str(&signers[0], "name"), "s1");
     assert_eq!(get_str(&signers[1], "name"), "s2");
+    let keys = r.get("keys").unwrap().as_array().unwrap();
+    assert_eq!(keys.len(), 2);
+    assert_eq!(get_str(&keys[0], "origin_path"), "m/87h/1h/0h");
+    assert!(xpub1.ends_with(get_str(&keys[0], "xpub")));
 
     sh(&format!("{cli} signer unload --signer s2"));
     let r = sh(&format!("{cli} wallet details --wallet ms"));
@@ -739,6 +903,9 @@ fn test_wallet_details() {
     assert_eq!(signers.len(), 2);
     assert_eq!(get_str(&signers[0], "name"), "s1");
     assert!(signers[1].get("name").is_none());
+    let keys = r.get("keys").unwrap().as_array().unwrap();
+    assert!(!keys[0].get("watch_only").unwrap().as_bool().unwrap());
+    assert!(keys[1].get("watch_only").unwrap().as_bool().unwrap());
 
     let r = sh(&format!("{cli} wallet details --wallet ms_same_signers"));
     assert_eq!(
@@ -766,8 +933,465 @@ fn test_broadcast() {
     let policy_asset = "5ac9f65c0efcc4775e0baec4ec03abdde22473cd3cf33c0419ca290e0751b225";
     assert_eq!(1_000_000, get_balance(&cli, "w1", policy_asset));
     let addr = server.node_getnewaddress().to_string();
-    send(&cli, "w1", &addr, policy_asset, 1000, &["s1"]);
+    let txid = send(&cli, "w1", &addr, policy_asset, 1000, &["s1"]);
     assert!(1_000_000 > get_balance(&cli, "w1", policy_asset));
+    let err = sh_err(&format!(
+        "{cli} wallet bump-fee --wallet w1 --txid {txid} --fee-rate 1 --fee-rate-unit sat-per-vb"
+    ));
+    assert!(err.contains("does not signal replaceability"));
+
+    let recipient = format!("--recipient {addr}:1000:{policy_asset}");
+    let r = sh(&format!(
+        "{cli} wallet send --wallet w1 {recipient} --enable-rbf"
+    ));
+    let txid = complete(&cli, "w1", get_str(&r, "pset"), &["s1"]);
+    let balance = get_balance(&cli, "w1", policy_asset);
+    let r = sh(&format!(
+        "{cli} wallet bump-fee --wallet w1 --txid {txid} --fee-rate 1 --fee-rate-unit sat-per-vb"
+    ));
+    let new_txid = complete(&cli, "w1", get_str(&r, "pset"), &["s1"]);
+    assert_ne!(txid, new_txid);
+    assert!(balance > get_balance(&cli, "w1", policy_asset));
+
+    sh(&format!("{cli} server stop"));
+    t.join().unwrap();
+}
+
+#[test]
+fn test_balance_detailed() {
+    let (t, _tmp, cli, _params, server, _) = setup_cli(false);
+
+    sw_signer(&cli, "s1");
+    singlesig_wallet(&cli, "w1", "s1", "slip77", "wpkh");
+    fund(&server, &cli, "w1", 1_000_000);
+
+    let policy_asset = "5ac9f65c0efcc4775e0baec4ec03abdde22473cd3cf33c0419ca290e0751b225";
+    let r = sh(&format!("{cli} wallet balance --wallet w1"));
+    assert!(r.get("detailed").is_none());
+
+    // Receive without confirming
+    let addr = Address::from_str(&address(&cli, "w1")).unwrap();
+    let txid = server.node_sendtoaddress(&addr, 10_000, None).to_string();
+    wait_tx(&cli, "w1", &txid);
+
+    let r = sh(&format!("{cli} wallet balance --wallet w1 --detailed"));
+    let balance = r.get("balance").unwrap().as_object().unwrap();
+    assert_eq!(balance.get(policy_asset).unwrap().as_u64(), Some(1_010_000));
+    let detailed = r.get("detailed").unwrap().get(policy_asset).unwrap();
+    assert_eq!(detailed.get("confirmed").unwrap().as_u64(), Some(1_000_000));
+    assert_eq!(detailed.get("unconfirmed").unwrap().as_u64(), Some(10_000));
+    assert_eq!(detailed.get("total").unwrap().as_u64(), Some(1_010_000));
+
+    let r = sh(&format!(
+        "{cli} wallet balance --wallet w1 --detailed --with-tickers"
+    ));
+    let detailed = r.get("detailed").unwrap().get("L-BTC").unwrap();
+    assert_eq!(detailed.get("unconfirmed").unwrap().as_u64(), Some(10_000));
+
+    sh(&format!("{cli} server stop"));
+    t.join().unwrap();
+}
+
+#[test]
+fn test_send_dry_run() {
+    let (t, _tmp, cli, _params, server, _) = setup_cli(false);
+
+    sw_signer(&cli, "s1");
+    singlesig_wallet(&cli, "w1", "s1", "slip77", "wpkh");
+    fund(&server, &cli, "w1", 1_000_000);
+
+    let policy_asset = "5ac9f65c0efcc4775e0baec4ec03abdde22473cd3cf33c0419ca290e0751b225";
+    let addr = server.node_getnewaddress().to_string();
+    let recipient = format!("--recipient {addr}:1000:{policy_asset}");
+    let r = sh(&format!("{cli} wallet send --wallet w1 {recipient}"));
+    assert!(r.get("inputs").is_none());
+    assert!(r.get("change").is_none());
+    assert!(r.get("fee").is_none());
+
+    let r = sh(&format!(
+        "{cli} wallet send --wallet w1 {recipient} --dry-run"
+    ));
+    let pset: PartiallySignedTransaction = get_str(&r, "pset").parse().unwrap();
+    let inputs = r.get("inputs").unwrap().as_array().unwrap();
+    assert_eq!(inputs.len(), pset.inputs().len());
+    let fee = r.get("fee").unwrap().as_u64().unwrap();
+    assert!(fee > 0);
+    let change = r.get("change").unwrap().as_array().unwrap();
+    assert_eq!(change.len(), 1);
+    assert_eq!(get_str(&change[0], "asset"), policy_asset);
+    assert_eq!(
+        change[0].get("satoshi").unwrap().as_u64(),
+        Some(1_000_000 - 1000 - fee)
+    );
+
+    let r = sh(&format!(
+        "{cli} wallet estimate-fee --wallet w1 {recipient}"
+    ));
+    assert_eq!(r.get("inputs").unwrap().as_u64(), Some(inputs.len() as u64));
+    assert!(r.get("vsize").unwrap().as_u64().unwrap() > 2_000);
+    assert!(r.get("fee").unwrap().as_u64().unwrap().abs_diff(fee) <= 5);
+
+    sh(&format!("{cli} server stop"));
+    t.join().unwrap();
+}
+
+#[test]
+fn test_is_mine() {
+    let (t, _tmp, cli, _params, server, _) = setup_cli(false);
+
+    sw_signer(&cli, "s1");
+    singlesig_wallet(&cli, "w1", "s1", "slip77", "wpkh");
+    singlesig_wallet(&cli, "w2", "s1", "elip151", "wpkh");
+    fund(&server, &cli, "w1", 1_000_000);
+
+    let addr = address(&cli, "w1");
+    let r = sh(&format!(
+        "{cli} wallet is-mine --wallet w1 --address-or-script {addr}"
+    ));
+    assert!(r.get("is_mine").unwrap().as_bool().unwrap());
+    assert_eq!(get_str(&r, "chain"), "external");
+    assert_eq!(r.get("index").unwrap().as_u64(), Some(1));
+
+    // The script pubkey, or the unconfidential address
+    let parsed = Address::from_str(&addr).unwrap();
+    let script = parsed.script_pubkey().to_hex();
+    for address_or_script in [script, parsed.to_unconfidential().to_string()] {
+        let r = sh(&format!(
+            "{cli} wallet is-mine --wallet w1 --address-or-script {address_or_script}"
+        ));
+        assert!(r.get("is_mine").unwrap().as_bool().unwrap());
+        assert_eq!(r.get("index").unwrap().as_u64(), Some(1));
+    }
+
+    // Same script, but a different blinding key
+    let r = sh(&format!("{cli} wallet address --wallet w2 --index 1"));
+    let addr_w2 = get_str(&r, "address");
+    let parsed_w2 = Address::from_str(addr_w2).unwrap();
+    assert_eq!(parsed_w2.script_pubkey(), parsed.script_pubkey());
+    let r = sh(&format!(
+        "{cli} wallet is-mine --wallet w1 --address-or-script {addr_w2}"
+    ));
+    assert!(!r.get("is_mine").unwrap().as_bool().unwrap());
+    assert!(r.get("chain").is_none());
+    assert!(r.get("index").is_none());
+
+    let node_address = server.node_getnewaddress();
+    let r = sh(&format!(
+        "{cli} wallet is-mine --wallet w1 --address-or-script {node_address}"
+    ));
+    assert!(!r.get("is_mine").unwrap().as_bool().unwrap());
+
+    let err = sh_err(&format!(
+        "{cli} wallet is-mine --wallet w1 --address-or-script invalid"
+    ));
+    assert!(err.contains("neither an address nor a script"));
+
+    sh(&format!("{cli} server stop"));
+    t.join().unwrap();
+}
+
+#[test]
+fn test_export_descriptor() {
+    let (t, _tmp, cli, _params, server, _) = setup_cli(false);
+
+    sw_signer(&cli, "s1");
+    let r = sh(&format!(
+        "{cli} signer singlesig-desc -s s1 --descriptor-blinding-key slip77 --kind wpkh"
+    ));
+    let desc = get_str(&r, "descriptor").to_string();
+    sh(&format!("{cli} wallet load -w w1 -d {desc}"));
+    fund(&server, &cli, "w1", 1_000_000);
+
+    let r = sh(&format!("{cli} wallet export-descriptor --wallet w1"));
+    assert_eq!(get_str(&r, "descriptor"), desc);
+    assert!(r.get("import_descriptors").is_none());
+
+    let r = sh(&format!(
+        "{cli} wallet export-descriptor --wallet w1 --format bare"
+    ));
+    let bare = get_str(&r, "descriptor");
+    assert!(bare.starts_with("elwpkh("));
+    assert!(bare.contains('#'));
+    assert!(!bare.contains("slip77"));
+
+    let r = sh(&format!(
+        "{cli} wallet export-descriptor --wallet w1 --format core-import"
+    ));
+    assert!(r.get("descriptor").is_none());
+    let import = r.get("import_descriptors").unwrap().as_array().unwrap();
+    assert_eq!(import.len(), 2);
+    for (i, d) in import.iter().enumerate() {
+        let single = get_str(d, "desc");
+        assert!(single.starts_with("ct(slip77("));
+        assert!(single.contains(&format!("/{i}/*))#")));
+        assert!(d.get("active").unwrap().as_bool().unwrap());
+        assert_eq!(d.get("internal").unwrap().as_bool().unwrap(), i == 1);
+        assert_eq!(d.get("timestamp").unwrap().as_u64(), Some(0));
+    }
+    // The external addresses up to the one funded are covered
+    let range = import[0].get("range").unwrap().as_array().unwrap();
+    assert_eq!(range[0].as_u64(), Some(0));
+    assert_eq!(range[1].as_u64(), Some(21));
+
+    sh(&format!("{cli} server stop"));
+    t.join().unwrap();
+}
+
+#[test]
+fn test_sign_wallet() {
+    let (t, _tmp, cli, _params, server, _) = setup_cli(false);
+
+    sw_signer(&cli, "s1");
+    singlesig_wallet(&cli, "w1", "s1", "slip77", "wpkh");
+    singlesig_wallet(&cli, "w2", "s1", "slip77", "shwpkh");
+    fund(&server, &cli, "w1", 1_000_000);
+
+    let policy_asset = "5ac9f65c0efcc4775e0baec4ec03abdde22473cd3cf33c0419ca290e0751b225";
+    let addr = server.node_getnewaddress().to_string();
+    let recipient = format!("--recipient {addr}:1000:{policy_asset}");
+    let r = sh(&format!("{cli} wallet send --wallet w1 {recipient}"));
+    let pset = get_str(&r, "pset");
+
+    let count_sigs = |pset: &str| -> usize {
+        let pset: PartiallySignedTransaction = pset.parse().unwrap();
+        pset.inputs().iter().map(|i| i.partial_sigs.len()).sum()
+    };
+
+    // The inputs belong to w1, thus the signer doesn't sign when constrained to w2
+    let r = sh(&format!(
+        "{cli} signer sign --signer s1 --pset {pset} --wallet w2"
+    ));
+    assert_eq!(count_sigs(get_str(&r, "pset")), 0);
+
+    let r = sh(&format!(
+        "{cli} signer sign --signer s1 --pset {pset} --wallet w1"
+    ));
+    let pset_w1 = get_str(&r, "pset").to_string();
+    assert!(count_sigs(&pset_w1) > 0);
+
+    let r = sh(&format!("{cli} signer sign --signer s1 --pset {pset}"));
+    assert_eq!(count_sigs(get_str(&r, "pset")), count_sigs(&pset_w1));
+
+    let err = sh_err(&format!(
+        "{cli} signer sign --signer s1 --pset {pset} --wallet missing"
+    ));
+    assert!(err.contains("does not exist"));
+
+    sh(&format!("{cli} server stop"));
+    t.join().unwrap();
+}
+
+#[test]
+fn test_consolidate() {
+    let (t, _tmp, cli, _params, server, _) = setup_cli(false);
+
+    sw_signer(&cli, "s1");
+    singlesig_wallet(&cli, "w1", "s1", "slip77", "wpkh");
+    fund(&server, &cli, "w1", 10_000);
+
+    let err = sh_err(&format!(
+        "{cli} wallet consolidate --wallet w1 --max-inputs 10"
+    ));
+    assert!(err.contains("Nothing to consolidate"));
+
+    fund(&server, &cli, "w1", 20_000);
+    fund(&server, &cli, "w1", 40_000);
+
+    let r = sh(&format!(
+        "{cli} wallet consolidate --wallet w1 --max-inputs 2"
+    ));
+    assert_eq!(r.get("inputs").unwrap().as_u64(), Some(2));
+    assert_eq!(r.get("satoshi").unwrap().as_u64(), Some(30_000));
+    complete(&cli, "w1", get_str(&r, "pset"), &["s1"]);
+
+    let r = sh(&format!("{cli} wallet utxos --wallet w1"));
+    assert_eq!(get_len(&r, "utxos"), 2);
+    let r = sh(&format!(
+        "{cli} wallet utxos --wallet w1 --min-satoshi 40000"
+    ));
+    assert_eq!(get_len(&r, "utxos"), 1);
+
+    sh(&format!("{cli} server stop"));
+    t.join().unwrap();
+}
+
+#[test]
+fn test_create_swap() {
+    let (t, _tmp, cli, _params, server, _) = setup_cli(false);
+
+    sw_signer(&cli, "s1");
+    singlesig_wallet(&cli, "w1", "s1", "slip77", "wpkh");
+    fund(&server, &cli, "w1", 10_000);
+
+    let policy_asset = "5ac9f65c0efcc4775e0baec4ec03abdde22473cd3cf33c0419ca290e0751b225";
+    let asset = "38fca2d939696061a8f76d4e6b5eecd54e3b4221c846f24a6b279e79952850a5";
+    let err = sh_err(&format!("{cli} wallet create-swap --wallet w1 --send-asset {policy_asset} --send-satoshi 5000 --recv-asset {asset} --recv-satoshi 1000"));
+    assert!(err.contains("No utxo of exactly 5000 satoshi"));
+    assert!(err.contains("split a utxo first"));
+
+    let r = sh(&format!("{cli} wallet create-swap --wallet w1 --send-asset {policy_asset} --send-satoshi 10000 --recv-asset {asset} --recv-satoshi 1000"));
+    let secrets = r.get("input_secrets").unwrap();
+    assert_eq!(secrets.get("value").unwrap().as_u64().unwrap(), 10000);
+    assert_eq!(get_str(secrets, "asset"), policy_asset);
+    assert_eq!(get_str(secrets, "value_blinder").len(), 64);
+    assert_eq!(get_str(secrets, "asset_blinder").len(), 64);
+    assert!(get_str(&r, "summary").contains(asset));
+    let pset = get_str(&r, "pset");
+
+    // The PSET has no fee output, it's paid by the counterparty completing it
+    let r = sh(&format!("{cli} signer sign -s s1 --pset {pset}"));
+    assert_ne!(get_str(&r, "pset"), pset);
+
+    sh(&format!("{cli} server stop"));
+    t.join().unwrap();
+}
+
+#[test]
+fn test_proof_of_reserves() {
+    let (t, _tmp, cli, _params, server, _) = setup_cli(false);
+
+    sw_signer(&cli, "s1");
+    singlesig_wallet(&cli, "w1", "s1", "slip77", "wpkh");
+    fund(&server, &cli, "w1", 10_000);
+    fund(&server, &cli, "w1", 20_000);
+
+    let challenge = "audit";
+    let r = sh(&format!(
+        "{cli} wallet proof-of-reserves --wallet w1 --challenge {challenge}"
+    ));
+    let proof = get_str(&r, "pset");
+    let err = sh_err(&format!(
+        "{cli} wallet verify-proof-of-reserves --proof {proof} --challenge {challenge}"
+    ));
+    assert!(err.contains("Invalid proof of reserves"));
+
+    let r = sh(&format!("{cli} signer sign -s s1 --pset {proof}"));
+    let proof = get_str(&r, "pset");
+    let r = sh(&format!(
+        "{cli} wallet verify-proof-of-reserves --proof {proof} --challenge {challenge}"
+    ));
+    let policy_asset = "5ac9f65c0efcc4775e0baec4ec03abdde22473cd3cf33c0419ca290e0751b225";
+    let reserves = r.get("reserves").unwrap();
+    assert_eq!(
+        reserves.get(policy_asset).unwrap().as_u64().unwrap(),
+        30_000
+    );
+    assert_eq!(get_len(&r, "outpoints"), 2);
+
+    let err = sh_err(&format!(
+        "{cli} wallet verify-proof-of-reserves --proof {proof} --challenge other"
+    ));
+    assert!(err.contains("does not commit to the challenge"));
+
+    sh(&format!("{cli} server stop"));
+    t.join().unwrap();
+}
+
+#[test]
+fn test_pset_analyze() {
+    let (t, _tmp, cli, _params, server, _) = setup_cli(false);
+
+    sw_signer(&cli, "s1");
+    singlesig_wallet(&cli, "w1", "s1", "slip77", "wpkh");
+    fund(&server, &cli, "w1", 1_000_000);
+    fund(&server, &cli, "w1", 10_000);
+
+    let policy_asset = "5ac9f65c0efcc4775e0baec4ec03abdde22473cd3cf33c0419ca290e0751b225";
+    let addr = server.node_getnewaddress().to_string();
+    let recipient = format!("--recipient {addr}:1000:{policy_asset}");
+    let r = sh(&format!("{cli} wallet send --wallet w1 {recipient}"));
+    let pset = get_str(&r, "pset");
+
+    let r = sh(&format!(
+        "{cli} wallet pset-analyze --wallet w1 --pset {pset}"
+    ));
+    let fee = r.get("fee").unwrap().as_u64().unwrap();
+    assert!(fee > 0);
+    assert!(r.get("fee_rate").unwrap().as_f64().unwrap() > 0.0);
+    let balance = r.get("balance").unwrap().get(policy_asset).unwrap();
+    assert_eq!(balance.as_i64().unwrap(), -1000 - fee as i64);
+    assert!(!r.get("is_swap").unwrap().as_bool().unwrap());
+    assert!(!r.get("is_finalizable").unwrap().as_bool().unwrap());
+    let inputs = r.get("inputs").unwrap().as_array().unwrap();
+    assert!(inputs.iter().all(|i| i["is_mine"] == true));
+    assert!(inputs.iter().all(|i| i["sighash"] == "SIGHASH_ALL"));
+    assert!(inputs
+        .iter()
+        .all(|i| i["missing_signatures_from"][0]["name"] == "s1"));
+
+    let r = sh(&format!("{cli} signer sign -s s1 --pset {pset}"));
+    let pset = get_str(&r, "pset");
+    let r = sh(&format!(
+        "{cli} wallet pset-analyze --wallet w1 --pset {pset}"
+    ));
+    assert!(r.get("is_finalizable").unwrap().as_bool().unwrap());
+    let inputs = r.get("inputs").unwrap().as_array().unwrap();
+    assert!(inputs
+        .iter()
+        .all(|i| i["has_signatures_from"][0]["name"] == "s1"));
+
+    // A swap offer has no fee and exchanges different assets
+    let asset = "38fca2d939696061a8f76d4e6b5eecd54e3b4221c846f24a6b279e79952850a5";
+    let r = sh(&format!("{cli} wallet create-swap --wallet w1 --send-asset {policy_asset} --send-satoshi 10000 --recv-asset {asset} --recv-satoshi 1000"));
+    let pset = get_str(&r, "pset");
+    let r = sh(&format!(
+        "{cli} wallet pset-analyze --wallet w1 --pset {pset}"
+    ));
+    assert!(r.get("fee").unwrap().is_null());
+    assert!(r.get("fee_rate").unwrap().is_null());
+    assert!(r.get("is_swap").unwrap().as_bool().unwrap());
+    let balance = r.get("balance").unwrap();
+    assert_eq!(balance.get(policy_asset).unwrap().as_i64(), Some(-10_000));
+    assert_eq!(balance.get(asset).unwrap().as_i64(), Some(1000));
+    let inputs = r.get("inputs").unwrap().as_array().unwrap();
+    assert_eq!(inputs[0]["sighash"], "SIGHASH_SINGLE|SIGHASH_ANYONECANPAY");
+
+    sh(&format!("{cli} server stop"));
+    t.join().unwrap();
+}
+
+#[test]
+fn test_rescan() {
+    let (t, _tmp, cli, _params, server, _) = setup_cli(false);
+
+    sw_signer(&cli, "s1");
+    singlesig_wallet(&cli, "w1", "s1", "slip77", "wpkh");
+    fund(&server, &cli, "w1", 1_000_000);
+
+    let policy_asset = "5ac9f65c0efcc4775e0baec4ec03abdde22473cd3cf33c0419ca290e0751b225";
+    assert_eq!(1_000_000, get_balance(&cli, "w1", policy_asset));
+
+    let r = sh(&format!("{cli} wallet rescan --wallet w1"));
+    let tip_height = r.get("tip_height").unwrap().as_u64().unwrap();
+    assert!(r.get("start_height").is_none());
+    sh(&format!("{cli} server scan"));
+    assert_eq!(1_000_000, get_balance(&cli, "w1", policy_asset));
+
+    let r = sh(&format!("{cli} wallet sync-status --wallet w1"));
+    assert_eq!(
+        r.get("last_scanned_height").unwrap().as_u64(),
+        Some(tip_height)
+    );
+    assert_eq!(r.get("tip_height").unwrap().as_u64(), Some(tip_height));
+    assert!(!r.get("scanning").unwrap().as_bool().unwrap());
+    assert_eq!(r.get("progress").unwrap().as_f64(), Some(100.0));
+
+    let r = sh(&format!("{cli} wallet details --wallet w1"));
+    assert_eq!(r.get("next_external_index").unwrap().as_u64(), Some(1));
+    assert_eq!(r.get("next_internal_index").unwrap().as_u64(), Some(0));
+
+    let above = tip_height + 100;
+    let err = sh_err(&format!(
+        "{cli} wallet rescan --wallet w1 --start-height {above}"
+    ));
+    assert!(err.contains("is above the blockchain tip"));
+
+    // The funding tx is confirmed before the tip, thus it's ignored
+    sh(&format!(
+        "{cli} wallet rescan --wallet w1 --start-height {tip_height}"
+    ));
+    sh(&format!("{cli} server scan"));
+    assert_eq!(0, get_balance(&cli, "w1", policy_asset));
 
     sh(&format!("{cli} server stop"));
     t.join().unwrap();
@@ -805,6 +1429,13 @@ fn test_issue() {
     assert_eq!(asset_sats, 1000);
     assert_eq!(token_sats, 1);
 
+    // The wallet has a single utxo, so the dry run spends the same one and computes the same ids
+    let r_dry = sh(&format!(
+        "{cli} wallet issue --wallet w1 --satoshi-asset 1000 --satoshi-token 1 --contract '{contract}' --dry-run"
+    ));
+    assert_eq!(get_str(&r_dry, "asset"), asset);
+    assert_eq!(get_str(&r_dry, "token"), token);
+
     let balance = r.get("balance").unwrap().as_object().unwrap();
     // TODO: util to check balance with less unwrap
     assert_eq!(balance.get(asset).unwrap().as_i64().unwrap(), 1000);
@@ -831,9 +1462,18 @@ fn test_issue() {
     let policy_asset = "5ac9f65c0efcc4775e0baec4ec03abdde22473cd3cf33c0419ca290e0751b225";
     assert_eq!(get_balance(&cli, "w1", asset), 1000);
 
+    let r = sh(&format!("{cli} server network"));
+    assert_eq!(get_str(&r, "network"), "liquid-regtest");
+    assert_eq!(get_str(&r, "policy_asset"), policy_asset);
+    assert!(r.get("explorer_url").is_none());
+
     let r = sh(&format!("{cli} wallet balance --wallet w1 --with-tickers"));
     let balance = r.get("balance").unwrap().as_object().unwrap();
-    assert!(balance.get("L-BTC").unwrap().as_u64().unwrap() > 0);
+    let lbtc = balance.get("L-BTC").unwrap().as_i64().unwrap();
+    assert!(lbtc > 0);
+    let formatted = r.get("balance_formatted").unwrap();
+    let expected = format!("{}.{:08}", lbtc / 100_000_000, lbtc % 100_000_000);
+    assert_eq!(get_str(formatted, "L-BTC"), expected);
 
     let r = sh(&format!("{cli} asset details --asset {policy_asset}"));
     assert_eq!(get_str(&r, "name"), "liquid bitcoin");
@@ -872,9 +1512,16 @@ fn test_issue() {
     let asset_balance_post = get_balance(&cli, "w1", asset);
     assert_eq!(asset_balance_pre, asset_balance_post + 1);
 
+    let r = sh(&format!(
+        "{cli} wallet reissue --wallet w1 --asset {asset} --satoshi-asset 1 --dry-run"
+    ));
+    assert_eq!(get_str(&r, "asset"), asset);
+    assert_eq!(get_str(&r, "token"), token);
+
     let r = sh(&format!(
         "{cli} wallet reissue --wallet w1 --asset {asset} --satoshi-asset 1"
     ));
+    assert!(r.get("asset").is_none());
     complete(&cli, "w1", get_str(&r, "pset"), &["s1"]);
     assert_eq!(asset_balance_post + 1, get_balance(&cli, "w1", asset));
 
@@ -892,10 +1539,52 @@ fn test_issue() {
     let r = sh(&format!("{cli} wallet utxos --wallet w1"));
     assert_eq!(get_len(&r, "utxos"), 4);
 
+    let r = sh(&format!("{cli} wallet utxos --wallet w1 --with-tickers"));
+    let utxos = r.get("utxos").unwrap().as_array().unwrap();
+    let assets: Vec<_> = utxos.iter().map(|u| get_str(u, "asset")).collect();
+    assert!(assets.contains(&"L-BTC"));
+    assert!(assets.contains(&"EXMP"));
+    assert!(!assets.contains(&asset));
+
+    let r = sh(&format!("{cli} wallet utxos --wallet w1 --asset {asset}"));
+    let utxos = r.get("utxos").unwrap().as_array().unwrap();
+    assert!(!utxos.is_empty());
+    assert!(utxos.iter().all(|u| get_str(u, "asset") == asset));
+
+    let r = sh(&format!(
+        "{cli} wallet utxos --wallet w1 --asset {asset} --min-satoshi {asset_balance_post}"
+    ));
+    assert_eq!(get_len(&r, "utxos"), 0);
+
+    let err = sh_err(&format!("{cli} wallet utxos --wallet w1 --asset xyz"));
+    assert!(err.contains("HexArray Error"), "{err}");
+
     let r = sh(&format!("{cli} wallet txs --wallet w1"));
     let txs = r.get("txs").unwrap().as_array().unwrap();
     assert!(!txs.is_empty());
 
+    let r = sh(&format!(
+        "{cli} wallet txs --wallet w1 --offset 1 --limit 2"
+    ));
+    let page = r.get("txs").unwrap().as_array().unwrap();
+    assert_eq!(page, &txs[1..3]);
+
+    // The oldest transaction is the funding one, which is confirmed
+    let height = txs.last().unwrap().get("height").unwrap().as_u64().unwrap();
+    let r = sh(&format!(
+        "{cli} wallet txs --wallet w1 --max-height {height}"
+    ));
+    let filtered = r.get("txs").unwrap().as_array().unwrap();
+    assert!(!filtered.is_empty());
+    for tx in filtered {
+        assert!(tx.get("height").unwrap().as_u64().unwrap() <= height);
+    }
+    // Unconfirmed transactions are included
+    let r = sh(&format!(
+        "{cli} wallet txs --wallet w1 --min-height {height}"
+    ));
+    assert_eq!(r.get("txs").unwrap().as_array().unwrap(), txs);
+
     for tx in txs {
         let balance = tx.get("balance").unwrap().as_object().unwrap();
         assert!(balance.get(policy_asset).is_some());
@@ -926,6 +1615,22 @@ fn test_issue() {
 
     let balance = txs[0].get("balance").unwrap().as_object().unwrap();
     assert!(balance.contains_key("L-BTC"));
+    let formatted = txs[0].get("balance_formatted").unwrap();
+    assert!(get_str(formatted, "L-BTC").contains('.'));
+
+    let r = sh(&format!("{cli} wallet export-csv --wallet w1"));
+    let csv = get_str(&r, "csv");
+    let mut lines = csv.lines();
+    assert_eq!(lines.next(), Some("txid,timestamp,height,asset,amount,fee"));
+    let rows: Vec<Vec<_>> = lines.map(|l| l.split(',').collect()).collect();
+    let n_rows: usize = txs
+        .iter()
+        .map(|tx| tx.get("balance").unwrap().as_object().unwrap().len())
+        .sum();
+    assert_eq!(rows.len(), n_rows);
+    assert!(rows.iter().all(|r| r.len() == 6));
+    assert!(rows.iter().any(|r| r[3] == "L-BTC" && r[4].contains('.')));
+    assert!(rows.iter().any(|r| r[3] == "EXMP"));
 
     // Move the reissuance token to another wallet and perform an "external" reissuance
     sw_signer(&cli, "s2");
@@ -971,6 +1676,8 @@ fn test_jade_emulator() {
     let result = sh(&format!("{cli} signer jade-id --emulator {jade_addr}"));
     let identifier = result.get("identifier").unwrap().as_str().unwrap();
     assert_eq!(identifier, "e3ebcc79ebfedb4f2ae34406827dc1c5cb48e11f");
+    assert_eq!(get_str(&result, "version"), "1");
+    assert_eq!(get_str(&result, "board_type"), "DEV");
 
     sh(&format!(
         "{cli} signer load-jade --signer emul --id {identifier}  --emulator {jade_addr}"
@@ -979,6 +1686,10 @@ fn test_jade_emulator() {
     assert!(r.get("id").is_some());
     assert!(r.get("mnemonic").is_none());
     assert_eq!(get_str(&r, "type"), "jade");
+    let r = sh(&format!("{cli} signer jade-status -s emul"));
+    assert_eq!(get_str(&r, "state"), "ready");
+    let r = sh(&format!("{cli} signer jade-unlock -s emul"));
+    assert_eq!(get_str(&r, "state"), "ready");
     // Load singlesig wallets
     singlesig_wallet(&cli, "ss-wpkh", "emul", "slip77", "wpkh");
     singlesig_wallet(&cli, "ss-shwpkh", "emul", "slip77", "shwpkh");
@@ -996,6 +1707,20 @@ fn test_jade_emulator() {
     sh(&format!("{cli} wallet address -w ss-wpkh -s emul"));
     sh(&format!("{cli} wallet address -w ss-shwpkh -s emul"));
     sh(&format!("{cli} wallet address -w multi -s emul"));
+    for wallet in ["ss-wpkh", "multi"] {
+        for change in ["", "--change"] {
+            let r = sh(&format!(
+                "{cli} signer confirm-address -s emul -w {wallet} --index 3 {change}"
+            ));
+            assert_eq!(get_str(&r, "address"), get_str(&r, "device_address"));
+            assert_eq!(r.get("index").unwrap().as_u64(), Some(3));
+        }
+    }
+    let r = sh(&format!("{cli} wallet address -w ss-wpkh --index 3"));
+    let r2 = sh(&format!(
+        "{cli} signer confirm-address -s emul -w ss-wpkh --index 3"
+    ));
+    assert_eq!(get_str(&r, "address"), get_str(&r2, "device_address"));
 
     singlesig_wallet(&cli, "ss-sw", "sw", "slip77", "wpkh");
     let err = sh_err(&format!("{cli} wallet address -w ss-sw -s emul"));
@@ -1003,6 +1728,10 @@ fn test_jade_emulator() {
 
     let err = sh_err(&format!("{cli} wallet address -w ss-sw -s sw"));
     assert!(err.contains("Cannot display address with software signer"));
+    let err = sh_err(&format!(
+        "{cli} signer confirm-address -s sw -w ss-sw --index 0"
+    ));
+    assert!(err.contains("Signer 'sw' is not a Jade"));
 
     sh(&format!("{cli} server stop"));
     std::thread::sleep(std::time::Duration::from_millis(100));
@@ -1016,6 +1745,10 @@ fn test_commands() {
     let result = sh(&format!("{cli} signer generate"));
     assert!(result.get("mnemonic").is_some());
 
+    let result = sh(&format!("{cli} signer generate --word-count 12"));
+    let mnemonic = get_str(&result, "mnemonic");
+    assert_eq!(mnemonic.split_whitespace().count(), 12);
+
     let desc = "ct(c25deb86fa11e49d651d7eae27c220ef930fbd86ea023eebfa73e54875647963,elwpkh(tpubD6NzVbkrYhZ4Was8nwnZi7eiWUNJq2LFpPSCMQLioUfUtT1e72GkRbmVeRAZc26j5MRUz2hRLsaVHJfs6L7ppNfLUrm9btQTuaEsLrT7D87/*))#q9cypnmc";
     let result = sh(&format!("{cli} wallet load --wallet custody -d {desc}"));
     assert_eq!(result.get("descriptor").unwrap().as_str().unwrap(), desc);
@@ -1147,7 +1880,13 @@ fn test_multisig() {
     ));
     assert!(r.get("warnings").unwrap().as_str().unwrap().is_empty());
     assert!(!r.get("balance").unwrap().as_object().unwrap().is_empty());
-    assert!(r.get("fee").unwrap().as_u64().unwrap() > 0);
+    let fee = r.get("fee").unwrap().as_u64().unwrap();
+    assert!(fee > 0);
+    // The tx is built with the same vsize estimation, at the default fee rate of 0.1 sat/vb,
+    // blinding can change the size by a few bytes
+    let vsize = r.get("vsize").unwrap().as_u64().unwrap();
+    assert!((fee as i64 - (vsize as f64 * 0.1).ceil() as i64).abs() <= 1);
+    assert!(r.get("fee_rate").unwrap().as_f64().unwrap() >= 0.1);
     assert!(r.get("issuances").unwrap().as_array().unwrap().is_empty());
     assert!(r.get("reissuances").unwrap().as_array().unwrap().is_empty());
     let has_sigs = r.get("has_signatures_from").unwrap().as_array().unwrap();
@@ -1181,6 +1920,14 @@ fn test_multisig() {
         .unwrap();
     assert_eq!(missing_sigs.len(), 1);
     assert_eq!(missing_sigs[0].get("name").unwrap().as_str().unwrap(), "s2");
+    assert!(!r.get("is_complete").unwrap().as_bool().unwrap());
+    let signatures = r.get("signatures").unwrap().as_array().unwrap();
+    assert_eq!(signatures.len(), 1);
+    assert_eq!(signatures[0].get("vin").unwrap().as_u64().unwrap(), 0);
+    assert_eq!(get_len(&signatures[0], "has_signatures_from"), 1);
+    assert_eq!(get_len(&signatures[0], "missing_signatures_from"), 1);
+    let needed = signatures[0].get("signatures_needed").unwrap().as_u64();
+    assert_eq!(needed, Some(1));
 
     let r = sh(&format!(
         "{cli} wallet pset-details --wallet multi -p {pset_s2}"
@@ -1201,10 +1948,18 @@ fn test_multisig() {
     assert_eq!(missing_sigs.len(), 1);
     assert_eq!(missing_sigs[0].get("name").unwrap().as_str().unwrap(), "s1");
 
+    let r = sh(&format!(
+        "{cli} wallet combine --wallet multi -p {pset_u} -p {pset_s1}"
+    ));
+    assert!(!r.get("finalizable").unwrap().as_bool().unwrap());
+    assert!(r.get("tx_hex").is_none());
+
     let r = sh(&format!(
         "{cli} wallet combine --wallet multi -p {pset_s1} -p {pset_s2}"
     ));
     let pset_s = r.get("pset").unwrap().as_str().unwrap();
+    assert!(r.get("finalizable").unwrap().as_bool().unwrap());
+    assert!(!get_str(&r, "tx_hex").is_empty());
 
     let r = sh(&format!(
         "{cli} wallet broadcast --wallet multi --pset {pset_s}"
@@ -1223,6 +1978,51 @@ fn test_inconsistent_network() {
     assert!(err.contains("Inconsistent network"));
 }
 
+#[test]
+fn test_rpc_url() {
+    let (t, _tmp, cli, _params, _server, _) = setup_cli(false);
+    let addr = cli.split(' ').nth(2).unwrap();
+
+    let r = sh(&format!(
+        "cli --rpc-url http://{addr}/ -n regtest server network"
+    ));
+    assert_eq!(get_str(&r, "network"), "liquid-regtest");
+
+    let err = sh_err(&format!(
+        "cli --rpc-url ftp://{addr} -n regtest server network"
+    ));
+    assert!(err.contains("the scheme must be http or https"));
+
+    let err = sh_err("cli --rpc-url http://127.0.0.1:1 -n regtest server network");
+    assert!(err.contains("Is the server at \"http://127.0.0.1:1\" running?"));
+
+    sh(&format!("{cli} server stop"));
+    t.join().unwrap();
+}
+
+#[test]
+fn test_config_file() {
+    let (t, tmp, cli, _params, _server, _) = setup_cli(false);
+    let addr = cli.split(' ').nth(2).unwrap();
+    let path = tmp.path().join("config.toml");
+    let content = format!("[network]\nname = \"regtest\"\n\n[rpc]\naddr = \"{addr}\"\n");
+    std::fs::write(&path, content).unwrap();
+    let cli_file = format!("cli --config {}", path.display());
+
+    let r = sh(&format!("{cli_file} server network"));
+    assert_eq!(get_str(&r, "network"), "liquid-regtest");
+
+    // Command line flags take precedence over the file
+    let err = sh_err(&format!("{cli_file} -n testnet server network"));
+    assert!(err.contains("Inconsistent network"));
+
+    let err = sh_err("cli --config /nonexistent/config.toml server network");
+    assert!(err.contains("Cannot read config file"));
+
+    sh(&format!("{cli} server stop"));
+    t.join().unwrap();
+}
+
 #[test]
 fn test_schema() {
     let (t, _tmp, cli, _params, _server, _) = setup_cli(false);
@@ -1267,6 +2067,21 @@ fn test_schema() {
         assert!(result.get("$schema").is_some(), "failed for {}", cmd);
     }
 
+    let result = sh(&format!("{cli} schema all"));
+    let methods = result.get("methods").unwrap().as_object().unwrap();
+    let wallet_load = methods.get("wallet_load").unwrap();
+    assert!(wallet_load.get("request").unwrap().get("$schema").is_some());
+    assert!(wallet_load
+        .get("response")
+        .unwrap()
+        .get("$schema")
+        .is_some());
+
+    let result = sh(&format!("{cli} schema openrpc"));
+    assert!(result.get("openrpc").unwrap().is_string());
+    let methods = result.get("methods").unwrap().as_array().unwrap();
+    assert!(methods.iter().any(|m| m["name"] == "openrpc"));
+
     sh(&format!("{cli} server stop"));
     t.join().unwrap();
 }
@@ -1322,12 +2137,29 @@ fn test_registry_publish() {
 
     sh(&format!("{cli} asset remove --asset {token}"));
 
-    sh(&format!("{cli} asset list"));
+    let r = sh(&format!("{cli} asset list"));
+    assert_eq!(get_len(&r, "assets"), 1);
+
+    let r = sh(&format!("{cli} asset registry-lookup --asset {asset}"));
+    assert_eq!(get_str(&r, "ticker"), "EXMP");
+    assert_eq!(get_str(&r, "name"), "example");
+    let r = sh(&format!("{cli} asset list"));
+    assert_eq!(get_len(&r, "assets"), 1);
 
     sh(&format!("{cli} asset from-explorer --asset {asset}"));
 
-    sh(&format!("{cli} asset list"));
+    let r = sh(&format!("{cli} asset list"));
+    assert_eq!(get_len(&r, "assets"), 3);
+
+    sh(&format!("{cli} asset remove --asset {asset}"));
+    sh(&format!("{cli} asset remove --asset {token}"));
+    sh(&format!(
+        "{cli} asset registry-lookup --asset {asset} --cache"
+    ));
+    let r = sh(&format!("{cli} asset list"));
     assert_eq!(get_len(&r, "assets"), 3);
+    let r = sh(&format!("{cli} asset details --asset {asset}"));
+    assert_eq!(get_str(&r, "ticker"), "EXMP");
 
     sh(&format!("{cli} server stop"));
     t.join().unwrap();
@@ -1388,6 +2220,10 @@ fn test_elip151() {
     sh(&format!("{cli} wallet load -w ssj -d {desc_ssj}"));
     let err = sh_err(&format!("{cli} wallet address -w ssj -s emul"));
     assert!(err.contains("Mismatching addresses between wallet and jade"));
+    let err = sh_err(&format!(
+        "{cli} signer confirm-address -s emul -w ssj --index 0"
+    ));
+    assert!(err.contains("Mismatching addresses between wallet and jade"));
 
     sh(&format!("{cli} server stop"));
     t.join().unwrap();
@@ -1440,6 +2276,145 @@ fn test_start_errors() {
     t.join().unwrap();
 }
 
+#[test]
+fn test_start_version() {
+    // testnet doesn't require an electrum url, no need of a regtest env
+    let tmp = tempfile::tempdir().unwrap();
+    let datadir = tmp.path().display().to_string();
+    let addr = get_available_addr().unwrap();
+    let cli = format!("cli --addr {addr} -n testnet");
+
+    let t = {
+        let cli = cli.clone();
+        std::thread::spawn(move || {
+            sh(&format!("{cli} server start --datadir {datadir}"));
+        })
+    };
+
+    let client = lwk_app::Client::new(addr).unwrap();
+    let mut version = client.version();
+    for _ in 0..50 {
+        if version.is_ok() {
+            break;
+        }
+        wait_ms(100);
+        version = client.version();
+    }
+    let version = version.unwrap();
+    assert_eq!(version.network, "liquid-testnet");
+
+    sh(&format!("{cli} server stop"));
+    t.join().unwrap();
+}
+
+/// Create `n` wallets, each one receiving 10_000 sats in a different transaction
+fn many_funded_wallets(cli: &str, server: &TestElectrumServer, n: usize) -> Vec<String> {
+    let wallets: Vec<_> = (0..n).map(|i| format!("w{i}")).collect();
+    for wallet in wallets.iter() {
+        let signer = format!("s-{wallet}");
+        sw_signer(cli, &signer);
+        singlesig_wallet(cli, wallet, &signer, "slip77", "wpkh");
+        let addr = Address::from_str(&address(cli, wallet)).unwrap();
+        server.node_sendtoaddress(&addr, 10_000, None);
+    }
+    server.generate(2);
+    wallets
+}
+
+#[test]
+fn test_scan_many_wallets() {
+    let (t, _tmp, cli, _params, server, _) = setup_cli(false);
+
+    let wallets = many_funded_wallets(&cli, &server, 10);
+    sh(&format!("{cli} server scan"));
+
+    let policy_asset = "5ac9f65c0efcc4775e0baec4ec03abdde22473cd3cf33c0419ca290e0751b225";
+    for wallet in wallets.iter() {
+        assert_eq!(get_balance(&cli, wallet, policy_asset), 10_000);
+    }
+
+    sh(&format!("{cli} server stop"));
+    t.join().unwrap();
+}
+
+/// Compare the time to restore many wallets scanning them sequentially and concurrently
+///
+/// Run with `cargo test -p lwk_cli --test cli bench_scan_many_wallets -- --ignored --nocapture`
+#[test]
+#[ignore]
+fn bench_scan_many_wallets() {
+    let (t, _tmp, cli, params, server, _) = setup_cli(false);
+    let wallets = many_funded_wallets(&cli, &server, 50);
+    sh(&format!("{cli} server stop"));
+    t.join().unwrap();
+
+    for workers in [1, 4] {
+        let t = {
+            let cli = cli.clone();
+            let params = params.clone();
+            std::thread::spawn(move || {
+                sh(&format!(
+                    "{cli} server start --scanning-interval 1 --scan-workers {workers} {params}"
+                ));
+            })
+        };
+        std::thread::sleep(std::time::Duration::from_millis(1000));
+
+        // New clients, with an empty cache, restore the wallets from scratch
+        for wallet in wallets.iter() {
+            sh(&format!("{cli} wallet rescan --wallet {wallet}"));
+        }
+        let start = std::time::Instant::now();
+        sh(&format!("{cli} server scan"));
+        let elapsed = start.elapsed();
+        println!(
+            "{} wallets, {workers} scan workers: {elapsed:?}",
+            wallets.len()
+        );
+
+        sh(&format!("{cli} server stop"));
+        t.join().unwrap();
+    }
+}
+
+#[test]
+fn test_esplora_backend() {
+    let (t, _tmp, cli, params, server, _) = setup_cli(false);
+    sh(&format!("{cli} server stop"));
+    t.join().unwrap();
+
+    let t = {
+        let cli = cli.clone();
+        let params = params.clone();
+        std::thread::spawn(move || {
+            sh(&format!(
+                "{cli} server start --scanning-interval 1 --backend esplora {params}"
+            ));
+        })
+    };
+    std::thread::sleep(std::time::Duration::from_millis(1000));
+
+    sw_signer(&cli, "sw");
+    singlesig_wallet(&cli, "w1", "sw", "slip77", "wpkh");
+
+    // Scanning goes through esplora
+    fund(&server, &cli, "w1", 1_000_000);
+    let r = sh(&format!("{cli} wallet sync-status --wallet w1"));
+    assert!(r.get("tip_height").unwrap().as_u64().unwrap() > 0);
+
+    // Broadcasting goes through esplora
+    let node_address = server.node_getnewaddress();
+    let r = sh(&format!(
+        "{cli} wallet drain -w w1 --address {node_address} --fee-rate 1 --fee-rate-unit sat-per-vb"
+    ));
+    complete(&cli, "w1", get_str(&r, "pset"), &["sw"]);
+    let policy_asset = "5ac9f65c0efcc4775e0baec4ec03abdde22473cd3cf33c0419ca290e0751b225";
+    assert_eq!(get_balance(&cli, "w1", policy_asset), 0);
+
+    sh(&format!("{cli} server stop"));
+    t.join().unwrap();
+}
+
 #[test]
 fn test_send_all() {
     let (t, _tmp, cli, _params, server, _) = setup_cli(false);
@@ -1449,14 +2424,33 @@ fn test_send_all() {
     let signers = &["sw"];
 
     fund(&server, &cli, "w1", 1_000_000);
+    let r = sh(&format!(
+        "{cli} wallet issue --wallet w1 --satoshi-asset 1000 --satoshi-token 1"
+    ));
+    complete(&cli, "w1", get_str(&r, "pset"), signers);
+    let r = sh(&format!("{cli} wallet balance --wallet w1"));
+    assert_eq!(r.get("balance").unwrap().as_object().unwrap().len(), 3);
 
     let node_address = server.node_getnewaddress();
     let r = sh(&format!(
         "{cli} wallet drain -w w1 --address {node_address}"
     ));
-    complete(&cli, "w1", get_str(&r, "pset"), signers);
+    let pset = get_str(&r, "pset");
+    // Every asset leaves the wallet
+    let r = sh(&format!("{cli} wallet pset-details --wallet w1 -p {pset}"));
+    assert_eq!(r.get("balance").unwrap().as_object().unwrap().len(), 3);
+    complete(&cli, "w1", pset, signers);
     let policy_asset = "5ac9f65c0efcc4775e0baec4ec03abdde22473cd3cf33c0419ca290e0751b225";
     assert_eq!(get_balance(&cli, "w1", policy_asset), 0);
+    let r = sh(&format!("{cli} wallet balance --wallet w1"));
+    let balance = r.get("balance").unwrap().as_object().unwrap();
+    assert!(balance.values().all(|v| v.as_u64() == Some(0)));
+
+    // No L-BTC left to pay the fee
+    let err = sh_err(&format!(
+        "{cli} wallet drain -w w1 --address {node_address}"
+    ));
+    assert!(err.contains("not enough to pay the fee"));
 
     sh(&format!("{cli} server stop"));
     t.join().unwrap();
@@ -3,6 +3,8 @@ use std::str::FromStr;
 use elements::bitcoin::bip32::{DerivationPath, KeySource, Xpub};
 use elements::hex::ToHex;
 use elements_miniscript::descriptor::checksum::desc_checksum;
+use elements_miniscript::descriptor::{ShInner, Wsh, WshInner};
+use elements_miniscript::{Descriptor, DescriptorPublicKey, Miniscript, ScriptContext, Terminal};
 use rand::{thread_rng, Rng};
 use thiserror::Error;
 
@@ -53,11 +55,13 @@ fn fmt_path(path: &DerivationPath) -> String {
 }
 
 // TODO impl error handling
+/// Create a multisig descriptor, with `sortedmulti` if `sorted` is true, `multi` otherwise
 pub fn multisig_desc(
     threshold: u32,
     xpubs: Vec<(Option<KeySource>, Xpub)>,
     script_variant: Multisig,
     blinding_variant: DescriptorBlindingKey,
+    sorted: bool,
 ) -> Result<String, String> {
     if threshold == 0 {
         return Err("Threshold cannot be 0".into());
@@ -65,8 +69,10 @@ pub fn multisig_desc(
         return Err("Threshold cannot be greater than the number of xpubs".into());
     }
 
+    let multi = if sorted { "sortedmulti" } else { "multi" };
     let (prefix, suffix) = match script_variant {
-        Multisig::Wsh => ("elwsh(multi", ")"),
+        Multisig::Wsh => (format!("elwsh({multi}"), ")"),
+        Multisig::ShWsh => (format!("elsh(wsh({multi}"), "))"),
     };
 
     let blinding_key = match blinding_variant {
@@ -100,6 +106,37 @@ pub fn multisig_desc(
     Ok(format!("{desc}#{checksum}"))
 }
 
+/// The number of signatures required to spend from the descriptor
+///
+/// Returns `None` if the descriptor is neither a singlesig nor a `multi`/`sortedmulti` multisig.
+pub fn signatures_threshold(descriptor: &Descriptor<DescriptorPublicKey>) -> Option<usize> {
+    match descriptor {
+        Descriptor::Pkh(_) | Descriptor::Wpkh(_) => Some(1),
+        Descriptor::Wsh(wsh) => wsh_threshold(wsh),
+        Descriptor::Sh(sh) => match sh.as_inner() {
+            ShInner::Wpkh(_) => Some(1),
+            ShInner::Wsh(wsh) => wsh_threshold(wsh),
+            ShInner::SortedMulti(x) => Some(x.k),
+            ShInner::Ms(ms) => ms_threshold(ms),
+        },
+        _ => None,
+    }
+}
+
+fn wsh_threshold(wsh: &Wsh<DescriptorPublicKey>) -> Option<usize> {
+    match wsh.as_inner() {
+        WshInner::SortedMulti(x) => Some(x.k),
+        WshInner::Ms(ms) => ms_threshold(ms),
+    }
+}
+
+fn ms_threshold<Ctx: ScriptContext>(ms: &Miniscript<DescriptorPublicKey, Ctx>) -> Option<usize> {
+    match &ms.node {
+        Terminal::Multi(k, _) => Some(*k),
+        _ => None,
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 pub enum Singlesig {
     /// as defined by bip84
@@ -192,12 +229,17 @@ impl FromStr for Bip {
     }
 }
 
+#[derive(Debug, Clone, Copy)]
 pub enum Multisig {
+    /// Native segwit multisig
     Wsh,
+
+    /// Nested segwit multisig
+    ShWsh,
 }
 
 #[derive(Error, Debug)]
-#[error("Invalid multisig variant '{0}' supported variant are: 'wsh'")]
+#[error("Invalid multisig variant '{0}' supported variant are: 'wsh', 'shwsh'")]
 pub struct InvalidMultisigVariant(String);
 
 impl FromStr for Multisig {
@@ -206,7 +248,68 @@ impl FromStr for Multisig {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         Ok(match s {
             "wsh" => Multisig::Wsh,
+            "shwsh" => Multisig::ShWsh,
             v => return Err(InvalidMultisigVariant(v.to_string())),
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keyorigin_xpub_from_str;
+
+    #[test]
+    fn test_signatures_threshold() {
+        let xpub = "tpubDDCNstnPhbdd4vwbw5UWK3vRQSF1WXQkvBHpNXpKJAkwFYjwu735EH3GVf53qwbWimzewDUv68MUmRDgYtQ1AU8FRCPkazfuaBp7LaEaohG";
+        let xpub2 = "tpubDDExQpZg2tziZ7ACSBCYsY3rYxAZtTRBgWwioRLYqgNBguH6rMHN1D8epTxUQUB5kM5nxkEtr2SNic6PJLPubcGMR6S2fmDZTzL9dHpU7ka";
+        for (desc, expected) in [
+            (format!("elwpkh({xpub}/<0;1>/*)"), Some(1)),
+            (format!("elsh(wpkh({xpub}/<0;1>/*))"), Some(1)),
+            (
+                format!("elwsh(multi(2,{xpub}/<0;1>/*,{xpub2}/<0;1>/*))"),
+                Some(2),
+            ),
+            (
+                format!("elsh(wsh(sortedmulti(1,{xpub}/<0;1>/*,{xpub2}/<0;1>/*)))"),
+                Some(1),
+            ),
+            (
+                format!("elwsh(and_v(v:pk({xpub}/<0;1>/*),pk({xpub2}/<0;1>/*)))"),
+                None,
+            ),
+        ] {
+            let desc: Descriptor<DescriptorPublicKey> = desc.parse().unwrap();
+            assert_eq!(signatures_threshold(&desc), expected, "{desc}");
+        }
+    }
+
+    #[test]
+    fn test_multisig_desc_sorted() {
+        let xpubs = [
+            "[921a39fd/87h/1h/0h]tpubDDCNstnPhbdd4vwbw5UWK3vRQSF1WXQkvBHpNXpKJAkwFYjwu735EH3GVf53qwbWimzewDUv68MUmRDgYtQ1AU8FRCPkazfuaBp7LaEaohG",
+            "[c3ce23b2/87h/1h/0h]tpubDDExQpZg2tziZ7ACSBCYsY3rYxAZtTRBgWwioRLYqgNBguH6rMHN1D8epTxUQUB5kM5nxkEtr2SNic6PJLPubcGMR6S2fmDZTzL9dHpU7ka",
+        ];
+        let xpubs: Vec<_> = xpubs
+            .iter()
+            .map(|s| keyorigin_xpub_from_str(s).unwrap())
+            .collect();
+
+        for (kind, sorted, expected) in [
+            (Multisig::Wsh, true, "ct(elip151,elwsh(sortedmulti(2,"),
+            (Multisig::Wsh, false, "ct(elip151,elwsh(multi(2,"),
+            (Multisig::ShWsh, true, "ct(elip151,elsh(wsh(sortedmulti(2,"),
+            (Multisig::ShWsh, false, "ct(elip151,elsh(wsh(multi(2,"),
+        ] {
+            let desc = multisig_desc(
+                2,
+                xpubs.clone(),
+                kind,
+                DescriptorBlindingKey::Elip151,
+                sorted,
+            )
+            .unwrap();
+            assert!(desc.starts_with(expected), "{desc}");
+        }
+    }
+}
@@ -68,4 +68,10 @@ pub enum Error {
 
     #[error(transparent)]
     Miniscript(#[from] elements_miniscript::Error),
+
+    #[error(transparent)]
+    Pset(#[from] elements_miniscript::elements::pset::Error),
+
+    #[error("Transaction verification failed: {0}")]
+    TxVerification(#[from] elements_miniscript::elements::VerificationError),
 }
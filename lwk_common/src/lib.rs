@@ -3,7 +3,9 @@
 //! A crate containing common code used in multiple other crate in the workspace, such as:
 //!
 //!   * Utils to inspect a PSET: get the net effect of a PSET on a given wallet [`pset_balance()`], or get how many
-//!  signatures are missing , and which signers should provide them [`pset_signatures()`].
+//!  signatures are missing , and which signers should provide them [`pset_signatures()`], or
+//!  estimate the size of the transaction once signed [`pset_vsize()`], or verify its proofs and
+//!  amounts [`pset_verify_amounts()`].
 //!  * [`Signer`] trait: contains the methods to be implemented by a signer such as signing a pset or
 //!  returning an xpub
 //!
@@ -18,9 +20,9 @@ mod qr;
 mod signer;
 
 pub use crate::descriptor::{
-    multisig_desc, singlesig_desc, Bip, DescriptorBlindingKey, InvalidBipVariant,
-    InvalidBlindingKeyVariant, InvalidMultisigVariant, InvalidSinglesigVariant, Multisig,
-    Singlesig,
+    multisig_desc, signatures_threshold, singlesig_desc, Bip, DescriptorBlindingKey,
+    InvalidBipVariant, InvalidBlindingKeyVariant, InvalidMultisigVariant, InvalidSinglesigVariant,
+    Multisig, Singlesig,
 };
 pub use crate::error::Error;
 pub use crate::keyorigin_xpub::{keyorigin_xpub_from_str, InvalidKeyOriginXpub};
@@ -41,7 +43,9 @@ use elements_miniscript::elements::{
     secp256k1_zkp::{All, Generator, PedersenCommitment, Secp256k1},
     AssetId, BlindAssetProofs, BlindValueProofs, OutPoint, Script, TxOutSecrets,
 };
-use elements_miniscript::{ConfidentialDescriptor, DescriptorPublicKey};
+use elements_miniscript::{
+    ConfidentialDescriptor, DefiniteDescriptorKey, Descriptor, DescriptorPublicKey,
+};
 use std::collections::btree_map::BTreeMap;
 
 pub fn derive_script_pubkey(
@@ -276,6 +280,78 @@ pub fn pset_signatures(pset: &PartiallySignedTransaction) -> Vec<PsetSignatures>
         .collect()
 }
 
+/// Estimate the virtual size of the transaction once all the inputs are signed
+///
+/// Finalized inputs are counted with their actual witness, inputs belonging to the descriptor
+/// are counted with the maximum satisfaction weight of the descriptor, so for a multisig the
+/// threshold number of signatures is considered. Other inputs are counted without witness.
+pub fn pset_vsize(
+    pset: &PartiallySignedTransaction,
+    descriptor: &ConfidentialDescriptor<DescriptorPublicKey>,
+) -> Result<usize, Error> {
+    let mut weight = pset.extract_tx()?.weight();
+    for input in pset.inputs() {
+        if input.final_script_witness.is_some() || input.final_script_sig.is_some() {
+            continue;
+        }
+        let script_pubkey = match input.witness_utxo.as_ref() {
+            Some(txout) => &txout.script_pubkey,
+            None => continue,
+        };
+        if let Some(d) = definite_descriptor(script_pubkey, descriptor, &input.bip32_derivation)? {
+            weight += d.max_weight_to_satisfy()?;
+        }
+    }
+    Ok((weight + 4 - 1) / 4)
+}
+
+/// The descriptor derived at the index producing the given script pubkey, if any
+fn definite_descriptor(
+    script_pubkey: &Script,
+    descriptor: &ConfidentialDescriptor<DescriptorPublicKey>,
+    bip32_derivation: &BTreeMap<PublicKey, KeySource>,
+) -> Result<Option<Descriptor<DefiniteDescriptorKey>>, Error> {
+    for (_, path) in bip32_derivation.values() {
+        if path.is_empty() {
+            continue;
+        }
+        let wildcard_index = path[path.len() - 1];
+        for d in descriptor.descriptor.clone().into_single_descriptors()? {
+            let d = d.at_derivation_index(wildcard_index.into())?;
+            if &d.script_pubkey() == script_pubkey {
+                return Ok(Some(d));
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// Verify the range and surjection proofs of the PSET outputs and that the amounts of the outputs
+/// balance the ones of the inputs
+///
+/// All the inputs must have the previous output, the rangeproof is taken from `in_utxo_rangeproof`
+/// if the witness utxo does not have it.
+pub fn pset_verify_amounts(pset: &PartiallySignedTransaction) -> Result<(), Error> {
+    let secp = Secp256k1::new();
+    let mut spent_utxos = vec![];
+    for (idx, input) in pset.inputs().iter().enumerate() {
+        let mut txout = input
+            .witness_utxo
+            .clone()
+            .ok_or_else(|| Error::MissingPreviousOutput {
+                idx,
+                previous_outpoint: OutPoint::new(input.previous_txid, input.previous_output_index),
+            })?;
+        if txout.witness.rangeproof.is_none() {
+            txout.witness.rangeproof = input.in_utxo_rangeproof.clone();
+        }
+        spent_utxos.push(txout);
+    }
+    pset.extract_tx()?
+        .verify_tx_amt_proofs(&secp, &spent_utxos)?;
+    Ok(())
+}
+
 pub fn pset_issuances(pset: &PartiallySignedTransaction) -> Vec<Issuance> {
     pset.inputs().iter().map(Issuance::new).collect()
 }
@@ -290,7 +366,7 @@ mod test {
     use elements::{pset::PartiallySignedTransaction, AssetId};
     use elements_miniscript::{ConfidentialDescriptor, DescriptorPublicKey};
 
-    use crate::pset_balance;
+    use crate::{pset_balance, pset_verify_amounts, pset_vsize, Error};
 
     #[test]
     fn test_pset_details() {
@@ -311,4 +387,63 @@ mod test {
         let v = balance.balances.get(&asset_id).unwrap();
         assert_eq!(*v, -1);
     }
+
+    #[test]
+    fn test_pset_verify_amounts() {
+        for pset_str in [
+            include_str!("../test_data/pset_details/pset.base64"),
+            include_str!("../test_data/pset_details/pset2.base64"),
+        ] {
+            let pset: PartiallySignedTransaction = pset_str.parse().unwrap();
+            pset_verify_amounts(&pset).unwrap();
+
+            // Increasing the fee breaks the balance
+            let mut tampered = pset.clone();
+            let fee_output = tampered
+                .outputs_mut()
+                .iter_mut()
+                .find(|o| o.script_pubkey.is_empty())
+                .unwrap();
+            fee_output.amount = fee_output.amount.map(|a| a + 1);
+            let err = pset_verify_amounts(&tampered).unwrap_err();
+            assert!(err.to_string().contains("balance check failed"), "{err}");
+
+            // Swapping the rangeproofs of two outputs invalidates them
+            let mut tampered = pset.clone();
+            let outputs = tampered.outputs_mut();
+            let proof = outputs[0].value_rangeproof.take();
+            outputs[0].value_rangeproof = outputs[1].value_rangeproof.take();
+            outputs[1].value_rangeproof = proof;
+            let err = pset_verify_amounts(&tampered).unwrap_err();
+            assert!(matches!(
+                err,
+                Error::TxVerification(elements::VerificationError::RangeProofError(0, _))
+            ));
+
+            // Missing previous output
+            let mut tampered = pset.clone();
+            tampered.inputs_mut()[0].witness_utxo = None;
+            let err = pset_verify_amounts(&tampered).unwrap_err();
+            assert!(matches!(err, Error::MissingPreviousOutput { idx: 0, .. }));
+        }
+    }
+
+    #[test]
+    fn test_pset_vsize() {
+        let desc_str = include_str!("../test_data/pset_details/descriptor");
+        let desc: ConfidentialDescriptor<DescriptorPublicKey> = desc_str.parse().unwrap();
+
+        let pset_str = include_str!("../test_data/pset_details/pset.base64");
+        let pset: PartiallySignedTransaction = pset_str.parse().unwrap();
+        let balance = pset_balance(&pset, &desc).unwrap();
+        let vsize = pset_vsize(&pset, &desc).unwrap();
+        // The PSET was created with the default fee rate of 0.1 sat/vb
+        assert_eq!((balance.fee, vsize), (377, 3767));
+
+        let pset_str = include_str!("../test_data/pset_details/pset2.base64");
+        let pset: PartiallySignedTransaction = pset_str.parse().unwrap();
+        let balance = pset_balance(&pset, &desc).unwrap();
+        let vsize = pset_vsize(&pset, &desc).unwrap();
+        assert_eq!((balance.fee, vsize), (377, 3768));
+    }
 }
@@ -16,6 +16,60 @@ pub enum Error {
 
     #[error("Our precision is {our}, given a string with {given}")]
     StringTooPrecise { our: u8, given: u8 },
+
+    #[error("Invalid thousands separators in {0}")]
+    InvalidSeparators(String),
+}
+
+/// Format the `satoshi` of an asset with the given `precision`, e.g. -150 with precision 2 is "-1.50"
+///
+/// If the precision is greater than 8 the satoshi are returned unformatted.
+pub fn format_amount(satoshi: i64, precision: u8) -> String {
+    Precision::new(precision)
+        .map(|p| p.sats_to_string(satoshi))
+        .unwrap_or_else(|_| satoshi.to_string())
+}
+
+/// As [`format_amount()`] with the integer digits grouped by thousands, e.g. "-1,234.50"
+pub fn format_amount_with_separators(satoshi: i64, precision: u8) -> String {
+    let formatted = format_amount(satoshi, precision);
+    let (sign, unsigned) = match formatted.strip_prefix('-') {
+        Some(unsigned) => ("-", unsigned),
+        None => ("", formatted.as_str()),
+    };
+    let (int, frac) = match unsigned.split_once('.') {
+        Some((int, frac)) => (int, format!(".{frac}")),
+        None => (unsigned, String::new()),
+    };
+    let groups: Vec<&str> = int
+        .as_bytes()
+        .rchunks(3)
+        .rev()
+        .map(|c| std::str::from_utf8(c).expect("ascii digits"))
+        .collect();
+    format!("{sign}{}{frac}", groups.join(","))
+}
+
+/// Parse an amount formatted with [`format_amount()`] or [`format_amount_with_separators()`]
+/// to satoshi
+///
+/// ```
+/// # use lwk_common::precision::parse_amount;
+/// assert_eq!(parse_amount("-1,234.5", 2).unwrap(), -123_450);
+/// ```
+pub fn parse_amount(value: &str, precision: u8) -> Result<i64, Error> {
+    let precision = Precision::new(precision)?;
+    if !value.contains(',') {
+        return precision.string_to_sats(value);
+    }
+    let unsigned = value.strip_prefix('-').unwrap_or(value);
+    let int = unsigned.split('.').next().unwrap_or_default();
+    let mut groups = int.split(',');
+    let first_ok = groups.next().map_or(false, |g| (1..=3).contains(&g.len()));
+    if !first_ok || groups.any(|g| g.len() != 3) {
+        return Err(Error::InvalidSeparators(value.to_string()));
+    }
+    precision.string_to_sats(&value.replace(',', ""))
 }
 
 /// Helper to convert satoshi values of an asset to the value with the given precision and viceversa.
@@ -48,7 +102,7 @@ impl Precision {
         }
 
         let negative = if sats < 0 { "-" } else { "" };
-        let sats = sats.abs().to_string();
+        let sats = sats.unsigned_abs().to_string();
         if sats.len() > precision {
             let over = sats.len() - precision;
             format!("{}{}.{}", negative, &sats[..over], &sats[over..])
@@ -160,6 +214,46 @@ mod test {
         assert_eq!(exp, p.string_to_sats("0.01").unwrap_err().to_string());
     }
 
+    #[test]
+    fn test_format_amount() {
+        // (satoshi, precision, formatted, with separators)
+        let cases: [(i64, u8, &str, &str); 12] = [
+            (0, 0, "0", "0"),
+            (0, 8, "0.00000000", "0.00000000"),
+            (1, 8, "0.00000001", "0.00000001"),
+            (100_000_000, 8, "1.00000000", "1.00000000"),
+            (-123_456_789_012, 8, "-1234.56789012", "-1,234.56789012"),
+            (150, 2, "1.50", "1.50"),
+            (-150, 2, "-1.50", "-1.50"),
+            (123_456_789, 2, "1234567.89", "1,234,567.89"),
+            (-999_999, 0, "-999999", "-999,999"),
+            (1_000, 0, "1000", "1,000"),
+            (100, 3, "0.100", "0.100"),
+            (
+                i64::MIN,
+                8,
+                "-92233720368.54775808",
+                "-92,233,720,368.54775808",
+            ),
+        ];
+        for (satoshi, precision, formatted, separated) in cases {
+            assert_eq!(format_amount(satoshi, precision), formatted);
+            assert_eq!(format_amount_with_separators(satoshi, precision), separated);
+            assert_eq!(parse_amount(formatted, precision).unwrap(), satoshi);
+            assert_eq!(parse_amount(separated, precision).unwrap(), satoshi);
+        }
+
+        // Invalid precision
+        assert_eq!(format_amount(100, 9), "100");
+        assert!(parse_amount("100", 9).is_err());
+
+        for invalid in ["1,00", "1234,567", ",123", "1,,234", "-1,2.00"] {
+            let err = parse_amount(invalid, 2).unwrap_err();
+            assert!(matches!(err, Error::InvalidSeparators(_)), "{invalid}");
+        }
+        assert!(parse_amount("1.001", 2).is_err());
+    }
+
     #[test]
     fn test_precision_roundtrips() {
         let mut rng = thread_rng();
@@ -0,0 +1,51 @@
+use elements_miniscript::elements::bitcoin::bip32::Fingerprint;
+
+/// Error codes returned by HWI, from
+/// https://github.com/bitcoin-core/HWI/blob/master/hwilib/errors.py
+const DEVICE_NOT_READY: i64 = -12;
+const DEVICE_NOT_INITIALIZED: i64 = -18;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("HWI is not installed, cannot run '{0}'")]
+    NotInstalled(String),
+
+    #[error("No device with fingerprint {0} is connected")]
+    DeviceNotFound(Fingerprint),
+
+    #[error("The device with fingerprint {0} is locked, unlock it and retry")]
+    DeviceLocked(Fingerprint),
+
+    #[error("The device is not ready, unlock it and retry: {0}")]
+    DeviceNotReady(String),
+
+    #[error("HWI Error {code}: {message}")]
+    Hwi { code: i64, message: String },
+
+    #[error("Unexpected HWI output: {0}")]
+    UnexpectedOutput(String),
+
+    #[error("{0} is not supported by HWI")]
+    NotSupported(&'static str),
+
+    #[error(transparent)]
+    IoError(#[from] std::io::Error),
+
+    #[error(transparent)]
+    SerdeJson(#[from] serde_json::Error),
+
+    #[error(transparent)]
+    Bip32(#[from] elements_miniscript::elements::bitcoin::bip32::Error),
+
+    #[error(transparent)]
+    PsetParse(#[from] elements_miniscript::elements::pset::ParseError),
+}
+
+impl Error {
+    pub(crate) fn from_hwi(code: i64, message: String) -> Self {
+        match code {
+            DEVICE_NOT_READY | DEVICE_NOT_INITIALIZED => Error::DeviceNotReady(message),
+            _ => Error::Hwi { code, message },
+        }
+    }
+}
@@ -2,28 +2,253 @@
 
 // from:
 // https://github.com/bitcoin-core/HWI/blob/70ffb2be827e5b3d304203b4ded1f79c07b04a5f/hwilib/hwwclient.py
-// https://github.com/bitcoin-core/HWI/blob/70ffb2be827e5b3d304203b4ded1f79c07b04a5f/test/test_jade.py
-
-// enumerate
-
-// supported by jade
-
-// get_master_xpub(self, addrtype: AddressType = AddressType.WIT, account: int = 0) -> ExtendedKey:
-// get_master_fingerprint(self) -> bytes
-// get_pubkey_at_path(self, bip32_path: str) -> ExtendedKey
-// sign_tx(self, psbt: PSBT) -> PSBT
-// sign_message(self, message: Union[str, bytes], bip32_path: str) -> str
-// display_singlesig_address(self, bip32_path: str, addr_type: AddressType) -> str
-// display_multisig_address(self, addr_type: AddressType, multisig: MultisigDescriptor,) -> str
-// close(self) -> None
-// can_sign_taproot(self) -> bool (false)
-
-// unsupported by jade
-
-// wipe_device(self) -> bool
-// setup_device(self, label: str = "", passphrase: str = "") -> bool
-// restore_device(self, label: str = "", word_count: int = 24) -> bool
-// backup_device(self, label: str = "", passphrase: str = "") -> bool
-// prompt_pin(self) -> bool
-// send_pin(self, pin: str) -> bool
-// toggle_passphrase(self) -> bool
+// https://github.com/bitcoin-core/HWI/blob/70ffb2be827e5b3d304203b4ded1f79c07b04a5f/hwilib/commands.py
+
+mod error;
+
+pub use error::Error;
+
+use std::process::Command;
+use std::str::FromStr;
+
+use elements_miniscript::confidential::slip77;
+use elements_miniscript::elements::bitcoin::bip32::{DerivationPath, Fingerprint, Xpub};
+use elements_miniscript::elements::pset::PartiallySignedTransaction;
+use lwk_common::Signer;
+use serde::Deserialize;
+use serde_json::Value;
+
+/// The default HWI command, it must be in the `PATH`
+pub const HWI_COMMAND: &str = "hwi";
+
+/// A device as returned by `hwi enumerate`
+#[derive(Debug, Clone, Deserialize)]
+pub struct Device {
+    /// The device type, for example "ledger" or "trezor"
+    #[serde(rename = "type")]
+    pub type_: String,
+
+    /// The device model
+    #[serde(default)]
+    pub model: String,
+
+    /// The path to use to connect to the device
+    pub path: String,
+
+    /// The master fingerprint, missing if the device is locked
+    #[serde(default)]
+    pub fingerprint: Option<String>,
+
+    /// Whether the device needs the PIN to be unlocked
+    #[serde(default)]
+    pub needs_pin_sent: bool,
+
+    /// Whether the device needs the passphrase to be unlocked
+    #[serde(default)]
+    pub needs_passphrase_sent: bool,
+
+    /// The error returned by HWI for this device, if any
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+/// A connected hardware wallet, used through the HWI command line tool
+#[derive(Debug, Clone)]
+pub struct Hwi {
+    command: String,
+    fingerprint: Fingerprint,
+    device: Device,
+    is_mainnet: bool,
+}
+
+impl Hwi {
+    /// Connect to the device with the given fingerprint, if `device_path` is given the device
+    /// at that path must have the given fingerprint.
+    ///
+    /// Errors if HWI is not installed, if the device is not connected or if it's locked.
+    pub fn connect(
+        fingerprint: Fingerprint,
+        device_path: Option<&str>,
+        is_mainnet: bool,
+    ) -> Result<Self, Error> {
+        Self::connect_with_command(HWI_COMMAND, fingerprint, device_path, is_mainnet)
+    }
+
+    /// Like [`Hwi::connect()`] but using the given HWI command
+    pub fn connect_with_command(
+        command: &str,
+        fingerprint: Fingerprint,
+        device_path: Option<&str>,
+        is_mainnet: bool,
+    ) -> Result<Self, Error> {
+        let devices = enumerate_with_command(command, is_mainnet)?;
+        let device = devices
+            .into_iter()
+            .find(|d| match device_path {
+                Some(path) => d.path == path,
+                None => d.fingerprint == Some(fingerprint.to_string()),
+            })
+            .ok_or(Error::DeviceNotFound(fingerprint))?;
+        if device.needs_pin_sent || device.needs_passphrase_sent {
+            return Err(Error::DeviceLocked(fingerprint));
+        }
+        if let Some(error) = device.error.as_ref() {
+            return Err(Error::DeviceNotReady(error.clone()));
+        }
+        if device.fingerprint != Some(fingerprint.to_string()) {
+            return Err(Error::DeviceNotFound(fingerprint));
+        }
+        Ok(Self {
+            command: command.to_string(),
+            fingerprint,
+            device,
+            is_mainnet,
+        })
+    }
+
+    /// The connected device
+    pub fn device(&self) -> &Device {
+        &self.device
+    }
+
+    fn call(&self, args: &[&str]) -> Result<Value, Error> {
+        let mut all_args = vec![
+            "--device-type",
+            &self.device.type_,
+            "--device-path",
+            &self.device.path,
+        ];
+        all_args.extend_from_slice(args);
+        run(&self.command, self.is_mainnet, &all_args)
+    }
+}
+
+/// List the devices connected
+pub fn enumerate(is_mainnet: bool) -> Result<Vec<Device>, Error> {
+    enumerate_with_command(HWI_COMMAND, is_mainnet)
+}
+
+fn enumerate_with_command(command: &str, is_mainnet: bool) -> Result<Vec<Device>, Error> {
+    let value = run(command, is_mainnet, &["enumerate"])?;
+    Ok(serde_json::from_value(value)?)
+}
+
+fn run(command: &str, is_mainnet: bool, args: &[&str]) -> Result<Value, Error> {
+    let chain = if is_mainnet { "main" } else { "test" };
+    let output = Command::new(command)
+        .args(["--chain", chain])
+        .args(args)
+        .output()
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => Error::NotInstalled(command.to_string()),
+            _ => e.into(),
+        })?;
+    parse_output(&output.stdout)
+}
+
+fn parse_output(stdout: &[u8]) -> Result<Value, Error> {
+    let value: Value = serde_json::from_slice(stdout)
+        .map_err(|_| Error::UnexpectedOutput(String::from_utf8_lossy(stdout).to_string()))?;
+    if let Some(message) = value.get("error").and_then(Value::as_str) {
+        let code = value.get("code").and_then(Value::as_i64).unwrap_or(0);
+        return Err(Error::from_hwi(code, message.to_string()));
+    }
+    Ok(value)
+}
+
+fn get_str<'a>(value: &'a Value, key: &str) -> Result<&'a str, Error> {
+    value
+        .get(key)
+        .and_then(Value::as_str)
+        .ok_or_else(|| Error::UnexpectedOutput(value.to_string()))
+}
+
+fn n_sigs(pset: &PartiallySignedTransaction) -> usize {
+    pset.inputs().iter().map(|i| i.partial_sigs.len()).sum()
+}
+
+impl Signer for &Hwi {
+    type Error = crate::Error;
+
+    fn sign(&self, pset: &mut PartiallySignedTransaction) -> Result<u32, Self::Error> {
+        let value = self.call(&["signtx", &pset.to_string()])?;
+        let signed = PartiallySignedTransaction::from_str(get_str(&value, "psbt")?)?;
+        let added = n_sigs(&signed).saturating_sub(n_sigs(pset));
+        *pset = signed;
+        Ok(added as u32)
+    }
+
+    fn derive_xpub(&self, path: &DerivationPath) -> Result<Xpub, Self::Error> {
+        let value = self.call(&["getxpub", &path.to_string()])?;
+        Ok(Xpub::from_str(get_str(&value, "xpub")?)?)
+    }
+
+    fn slip77_master_blinding_key(&self) -> Result<slip77::MasterBlindingKey, Self::Error> {
+        Err(Error::NotSupported(
+            "Exporting the SLIP77 master blinding key",
+        ))
+    }
+
+    fn fingerprint(&self) -> Result<Fingerprint, Self::Error> {
+        Ok(self.fingerprint)
+    }
+}
+
+impl Signer for Hwi {
+    type Error = crate::Error;
+
+    fn sign(&self, pset: &mut PartiallySignedTransaction) -> Result<u32, Self::Error> {
+        Signer::sign(&self, pset)
+    }
+
+    fn derive_xpub(&self, path: &DerivationPath) -> Result<Xpub, Self::Error> {
+        Signer::derive_xpub(&self, path)
+    }
+
+    fn slip77_master_blinding_key(&self) -> Result<slip77::MasterBlindingKey, Self::Error> {
+        Signer::slip77_master_blinding_key(&self)
+    }
+
+    fn fingerprint(&self) -> Result<Fingerprint, Self::Error> {
+        Signer::fingerprint(&self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_output() {
+        let out = br#"[{"type": "trezor", "model": "trezor_t", "path": "webusb:000:1:1", "needs_pin_sent": false, "needs_passphrase_sent": false, "fingerprint": "73c5da0a"}]"#;
+        let devices: Vec<Device> = serde_json::from_value(parse_output(out).unwrap()).unwrap();
+        assert_eq!(devices.len(), 1);
+        assert_eq!(devices[0].type_, "trezor");
+        assert_eq!(devices[0].fingerprint.as_deref(), Some("73c5da0a"));
+
+        let out = br#"{"error": "Trezor is locked. Unlock by using 'promptpin' and then 'sendpin'.", "code": -12}"#;
+        assert!(matches!(parse_output(out), Err(Error::DeviceNotReady(_))));
+
+        let out = br#"{"error": "Unsupported derivation path", "code": -7}"#;
+        assert!(matches!(
+            parse_output(out),
+            Err(Error::Hwi { code: -7, .. })
+        ));
+
+        assert!(matches!(
+            parse_output(b"usage: hwi"),
+            Err(Error::UnexpectedOutput(_))
+        ));
+    }
+
+    #[test]
+    fn test_not_installed() {
+        let fingerprint = Fingerprint::from_str("73c5da0a").unwrap();
+        let err =
+            Hwi::connect_with_command("hwi-not-installed", fingerprint, None, false).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "HWI is not installed, cannot run 'hwi-not-installed'"
+        );
+    }
+}
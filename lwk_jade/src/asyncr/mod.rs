@@ -8,8 +8,8 @@ use crate::protocol::{
     SignMessageParams, UpdatePinserverParams, VersionInfoResult,
 };
 use crate::register_multisig::{
-    GetRegisteredMultisigParams, RegisterMultisigParams, RegisteredMultisig,
-    RegisteredMultisigDetails,
+    validate_multisig_name, GetRegisteredMultisigParams, JadeDescriptor, RegisterMultisigParams,
+    RegisteredMultisig, RegisteredMultisigDetails,
 };
 use crate::sign_liquid_tx::{SignLiquidTxParams, TxInputParams};
 use crate::{json_to_cbor, try_parse_response, vec_to_derivation_path, Error, Network, Result};
@@ -153,6 +153,7 @@ impl<S: Stream> Jade<S> {
     }
 
     pub async fn register_multisig(&self, params: RegisterMultisigParams) -> Result<bool> {
+        validate_multisig_name(&params.multisig_name)?;
         self.invalidate_registered_multisigs().await;
         self.send(Request::RegisterMultisig(params)).await
     }
@@ -180,6 +181,16 @@ impl<S: Stream> Jade<S> {
         }
     }
 
+    /// Returns the multisigs registered on the device by name, it's empty if none is registered
+    pub async fn registered_multisigs(&self) -> Result<BTreeMap<String, JadeDescriptor>> {
+        Ok(self
+            .get_cached_registered_multisigs()
+            .await?
+            .into_iter()
+            .map(|d| (d.multisig_name, d.descriptor))
+            .collect())
+    }
+
     async fn get_cached_registered_multisigs(&self) -> Result<Vec<RegisteredMultisigDetails>> {
         let mut guard = self.multisigs_details.lock().await;
         if let Some(multisigs_details) = guard.as_ref() {
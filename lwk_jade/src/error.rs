@@ -110,6 +110,21 @@ pub enum Error {
     #[error("Unsupported descriptor variant, only multi or sortedmulti are supported")]
     UnsupportedDescriptorVariant,
 
+    #[error("Unknown multisig variant: {0}")]
+    UnknownMultisigVariant(String),
+
+    #[error("Invalid multisig name '{0}', it must be 1 to 16 ASCII letters, digits, '-' or '_'")]
+    InvalidMultisigName(String),
+
+    #[error("Invalid multisig threshold {threshold} for {keys} keys")]
+    InvalidMultisigThreshold { threshold: u32, keys: usize },
+
+    #[error("Xpub {0} is not valid for network {1}")]
+    XpubNetworkMismatch(String, crate::Network),
+
+    #[error(transparent)]
+    Miniscript(#[from] elements_miniscript::Error),
+
     #[error("Slip 77 master blinding keys must be 32 bytes")]
     Slip77MasterBlindingKeyInvalidSize,
 
@@ -219,7 +219,7 @@ pub struct VersionInfoResult {
     pub jade_has_pin: bool,
 }
 
-#[derive(Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[derive(Debug, Deserialize, Serialize, PartialEq, Eq, Clone, Copy)]
 #[serde(rename_all = "UPPERCASE")]
 
 pub enum JadeState {
@@ -26,14 +26,77 @@ pub struct RegisterMultisigParams {
 
     /// Name of the multisig wallet
     ///
-    /// Max 16 chars
+    /// Max 16 ASCII letters, digits, `-` or `_`, see [`validate_multisig_name`]
     pub multisig_name: String,
     pub descriptor: JadeDescriptor,
 }
 
+/// Maximum length of a multisig name registered on Jade
+pub const MAX_MULTISIG_NAME_LEN: usize = 16;
+
+/// Check the multisig name is accepted by Jade before sending a registration request
+///
+/// The name must be non-empty, at most [`MAX_MULTISIG_NAME_LEN`] characters and made only of
+/// ASCII letters, digits, `-` and `_`. Jade uses the name as the key of the record in its storage
+/// and shows it with an ASCII only font, other characters are rejected here instead of failing
+/// on the device.
+pub fn validate_multisig_name(name: &str) -> Result<(), Error> {
+    let valid_char = |c: char| c.is_ascii_alphanumeric() || c == '-' || c == '_';
+    if name.is_empty() || name.len() > MAX_MULTISIG_NAME_LEN || !name.chars().all(valid_char) {
+        return Err(Error::InvalidMultisigName(name.to_string()));
+    }
+    Ok(())
+}
+
+/// Multisig variants for Jade
+#[derive(Debug, Deserialize, Serialize, PartialEq, Eq, Clone, Copy)]
+pub enum MultisigVariant {
+    /// Legacy script hash
+    #[serde(rename = "sh(multi(k))")]
+    ShMulti,
+
+    /// Witness script hash
+    #[serde(rename = "wsh(multi(k))")]
+    WshMulti,
+
+    /// Script hash wrapping a witness script hash AKA nested segwit
+    #[serde(rename = "sh(wsh(multi(k)))")]
+    ShWshMulti,
+}
+
+impl MultisigVariant {
+    fn as_str(&self) -> &'static str {
+        match self {
+            MultisigVariant::ShMulti => "sh(multi(k))",
+            MultisigVariant::WshMulti => "wsh(multi(k))",
+            MultisigVariant::ShWshMulti => "sh(wsh(multi(k)))",
+        }
+    }
+}
+
+impl std::fmt::Display for MultisigVariant {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for MultisigVariant {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "sh(multi(k))" => Ok(MultisigVariant::ShMulti),
+            "wsh(multi(k))" => Ok(MultisigVariant::WshMulti),
+            "sh(wsh(multi(k)))" => Ok(MultisigVariant::ShWshMulti),
+            _ => Err(Error::UnknownMultisigVariant(s.to_string())),
+        }
+    }
+}
+
 #[derive(Deserialize, Serialize, PartialEq, Eq, Clone)]
 pub struct JadeDescriptor {
-    pub variant: String, // only 'wsh(multi(k))' supported for now
+    /// Only [`MultisigVariant::WshMulti`] can be converted from and to a descriptor for now
+    pub variant: MultisigVariant,
     pub sorted: bool,
     pub threshold: u32,
 
@@ -60,7 +123,7 @@ impl TryFrom<&ConfidentialDescriptor<DescriptorPublicKey>> for JadeDescriptor {
     type Error = Error;
 
     fn try_from(desc: &ConfidentialDescriptor<DescriptorPublicKey>) -> Result<Self, Self::Error> {
-        let variant = "wsh(multi(k))".to_string(); // only supported one for now
+        let variant = MultisigVariant::WshMulti; // only supported one for now
         let master_blinding_key = match desc.key {
             Key::Slip77(k) => k.as_bytes().to_vec(),
             _ => return Err(Error::OnlySlip77Supported),
@@ -108,7 +171,7 @@ impl TryFrom<&JadeDescriptor> for ConfidentialDescriptor<DescriptorPublicKey> {
     type Error = Error;
 
     fn try_from(desc: &JadeDescriptor) -> Result<Self, Self::Error> {
-        if &desc.variant != "wsh(multi(k))" {
+        if desc.variant != MultisigVariant::WshMulti {
             return Err(Error::UnsupportedDescriptorType);
         }
         let sorted = if desc.sorted { "sorted" } else { "" };
@@ -126,6 +189,29 @@ impl TryFrom<&JadeDescriptor> for ConfidentialDescriptor<DescriptorPublicKey> {
 }
 
 impl JadeDescriptor {
+    /// Build a `JadeDescriptor` from a `ct(slip77(...),elwsh(multi(...)))` descriptor string
+    ///
+    /// Xpubs must belong to the given `network` and the threshold must not exceed the number of
+    /// keys.
+    pub fn from_descriptor(desc: &str, network: Network) -> Result<Self, Error> {
+        let desc: ConfidentialDescriptor<DescriptorPublicKey> = desc.parse()?;
+        let jade_desc: JadeDescriptor = (&desc).try_into()?;
+        let keys = jade_desc.signers.len();
+        if jade_desc.threshold == 0 || jade_desc.threshold as usize > keys {
+            return Err(Error::InvalidMultisigThreshold {
+                threshold: jade_desc.threshold,
+                keys,
+            });
+        }
+        for signer in jade_desc.signers.iter() {
+            let is_mainnet = signer.xpub.network == elements::bitcoin::Network::Bitcoin;
+            if is_mainnet != network.is_mainnet() {
+                return Err(Error::XpubNetworkMismatch(signer.xpub.to_string(), network));
+            }
+        }
+        Ok(jade_desc)
+    }
+
     /// Derive the witness script
     ///
     /// `JadeDescriptor`s returned from Jade's `get_registered_multisig` signers do not have `path`
@@ -205,7 +291,7 @@ impl MultisigSigner {
 
 #[derive(Deserialize, Serialize)]
 pub struct RegisteredMultisig {
-    variant: String,
+    variant: MultisigVariant,
     sorted: bool,
     threshold: u32,
     pub num_signers: u32,
@@ -239,7 +325,9 @@ mod test {
 
     use crate::register_multisig::MultisigSigner;
 
-    use super::{JadeDescriptor, RegisterMultisigParams};
+    use crate::Network;
+
+    use super::{validate_multisig_name, JadeDescriptor, MultisigVariant, RegisterMultisigParams};
 
     #[test]
     fn parse_register_multisig() {
@@ -248,6 +336,93 @@ mod test {
         let _resp: RegisterMultisigParams = serde_json::from_str(json).unwrap();
     }
 
+    #[test]
+    fn multisig_name() {
+        validate_multisig_name("a").unwrap();
+        validate_multisig_name("finney-satoshi").unwrap();
+        validate_multisig_name("multi_2of3").unwrap();
+        validate_multisig_name(&"a".repeat(16)).unwrap();
+        assert!(validate_multisig_name("").is_err());
+        assert!(validate_multisig_name(&"a".repeat(17)).is_err());
+        assert!(validate_multisig_name("è").is_err());
+        assert!(validate_multisig_name("with space").is_err());
+        assert!(validate_multisig_name("a/b").is_err());
+    }
+
+    #[test]
+    fn jade_desc_from_descriptor() {
+        let a = "[921a39fd/87'/1'/0']tpubDDCNstnPhbdd4vwbw5UWK3vRQSF1WXQkvBHpNXpKJAkwFYjwu735EH3GVf53qwbWimzewDUv68MUmRDgYtQ1AU8FRCPkazfuaBp7LaEaohG";
+        let b = "[c3ce23b2/87'/1'/0']tpubDDExQpZg2tziZ7ACSBCYsY3rYxAZtTRBgWwioRLYqgNBguH6rMHN1D8epTxUQUB5kM5nxkEtr2SNic6PJLPubcGMR6S2fmDZTzL9dHpU7ka";
+        let slip77_key = "9c8e4f05c7711a98c838be228bcb84924d4570ca53f35fa1c793e58841d47023";
+        let desc =
+            format!("ct(slip77({slip77_key}),elwsh(sortedmulti(2,{a}/<0;1>/*,{b}/<0;1>/*)))");
+
+        let jade_desc = JadeDescriptor::from_descriptor(&desc, Network::TestnetLiquid).unwrap();
+        assert_eq!(jade_desc.variant, MultisigVariant::WshMulti);
+        assert!(jade_desc.sorted);
+        assert_eq!(jade_desc.threshold, 2);
+        assert_eq!(
+            jade_desc.master_blinding_key,
+            hex::decode(slip77_key).unwrap()
+        );
+        assert_eq!(jade_desc.signers.len(), 2);
+        assert_eq!(
+            jade_desc.signers[0].fingerprint,
+            Fingerprint::from([146, 26, 57, 253])
+        );
+        assert_eq!(
+            jade_desc.signers[1].derivation,
+            vec![87 + (1 << 31), 1 + (1 << 31), 1 << 31]
+        );
+
+        // Round trip through the device JSON
+        let params = RegisterMultisigParams {
+            network: Network::TestnetLiquid,
+            multisig_name: "multi".to_string(),
+            descriptor: jade_desc.clone(),
+        };
+        let json = serde_json::to_string(&params).unwrap();
+        assert!(json.contains(r#""variant":"wsh(multi(k))""#));
+        let back: RegisterMultisigParams = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.descriptor, jade_desc);
+        let desc2: ConfidentialDescriptor<DescriptorPublicKey> =
+            (&back.descriptor).try_into().unwrap();
+        assert_eq!(
+            desc2.to_string(),
+            desc.parse::<ConfidentialDescriptor<DescriptorPublicKey>>()
+                .unwrap()
+                .to_string()
+        );
+
+        let err = JadeDescriptor::from_descriptor(&desc, Network::Liquid).unwrap_err();
+        assert!(matches!(err, crate::Error::XpubNetworkMismatch(_, _)));
+
+        let desc = format!("ct(slip77({slip77_key}),elwsh(multi(3,{a}/<0;1>/*,{b}/<0;1>/*)))");
+        assert!(JadeDescriptor::from_descriptor(&desc, Network::TestnetLiquid).is_err());
+
+        let desc = format!("ct(slip77({slip77_key}),elwpkh({a}/<0;1>/*))");
+        assert!(JadeDescriptor::from_descriptor(&desc, Network::TestnetLiquid).is_err());
+    }
+
+    #[test]
+    fn multisig_variant_roundtrip() {
+        for (variant, s) in [
+            (MultisigVariant::ShMulti, "sh(multi(k))"),
+            (MultisigVariant::WshMulti, "wsh(multi(k))"),
+            (MultisigVariant::ShWshMulti, "sh(wsh(multi(k)))"),
+        ] {
+            assert_eq!(serde_json::to_string(&variant).unwrap(), format!("\"{s}\""));
+            assert_eq!(
+                serde_json::from_str::<MultisigVariant>(&format!("\"{s}\"")).unwrap(),
+                variant
+            );
+            assert_eq!(variant.to_string(), s);
+            assert_eq!(s.parse::<MultisigVariant>().unwrap(), variant);
+        }
+        assert!("wsh(sortedmulti(k))".parse::<MultisigVariant>().is_err());
+        assert!(serde_json::from_str::<MultisigVariant>("\"multi(k)\"").is_err());
+    }
+
     #[test]
     fn from_desc_to_jade_desc() {
         let a= "tpubDDCNstnPhbdd4vwbw5UWK3vRQSF1WXQkvBHpNXpKJAkwFYjwu735EH3GVf53qwbWimzewDUv68MUmRDgYtQ1AU8FRCPkazfuaBp7LaEaohG";
@@ -266,7 +441,7 @@ mod test {
                 assert_eq!(
                     jade_desc,
                     JadeDescriptor {
-                        variant: "wsh(multi(k))".to_string(),
+                        variant: MultisigVariant::WshMulti,
                         sorted: k == "sortedmulti",
                         threshold: t,
                         master_blinding_key: hex::decode(slip77_key).unwrap(),
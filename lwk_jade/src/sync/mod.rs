@@ -6,12 +6,12 @@ use std::{collections::HashMap, io::ErrorKind};
 use crate::get_receive_address::{GetReceiveAddressParams, SingleOrMulti, Variant};
 use crate::protocol::{
     AuthUserParams, DebugSetMnemonicParams, EntropyParams, EpochParams, GenericMethod,
-    GetMasterBlindingKeyParams, GetSignatureParams, GetXpubParams, IsAuthResult, Request,
-    SignMessageParams, UpdatePinserverParams, VersionInfoResult,
+    GetMasterBlindingKeyParams, GetSignatureParams, GetXpubParams, IsAuthResult, JadeState,
+    Request, SignMessageParams, UpdatePinserverParams, VersionInfoResult,
 };
 use crate::register_multisig::{
-    GetRegisteredMultisigParams, RegisterMultisigParams, RegisteredMultisig,
-    RegisteredMultisigDetails,
+    validate_multisig_name, GetRegisteredMultisigParams, JadeDescriptor, RegisterMultisigParams,
+    RegisteredMultisig, RegisteredMultisigDetails,
 };
 use crate::sign_liquid_tx::{SignLiquidTxParams, TxInputParams};
 use crate::{
@@ -73,6 +73,16 @@ impl Jade {
         self.send(Request::GetVersionInfo)
     }
 
+    /// The state of the device, it doesn't require the Jade to be unlocked
+    pub fn state(&self) -> Result<JadeState> {
+        Ok(self.version_info()?.jade_state)
+    }
+
+    /// Whether the Jade is an emulator, i.e. it's connected via TCP
+    pub fn is_emulator(&self) -> Result<bool> {
+        Ok(matches!(*self.conn.lock()?, Connection::TcpStream(_)))
+    }
+
     pub fn set_epoch(&self, epoch: u64) -> Result<bool> {
         self.send(Request::SetEpoch(EpochParams { epoch }))
     }
@@ -126,6 +136,7 @@ impl Jade {
     }
 
     pub fn register_multisig(&self, params: RegisterMultisigParams) -> Result<bool> {
+        validate_multisig_name(&params.multisig_name)?;
         self.invalidate_registered_multisigs()?;
         self.send(Request::RegisterMultisig(params))
     }
@@ -141,6 +152,15 @@ impl Jade {
         self.send(Request::GetRegisteredMultisig(params))
     }
 
+    /// Returns the multisigs registered on the device by name, it's empty if none is registered
+    pub fn registered_multisigs(&self) -> Result<BTreeMap<String, JadeDescriptor>> {
+        Ok(self
+            .get_cached_registered_multisigs()?
+            .into_iter()
+            .map(|d| (d.multisig_name, d.descriptor))
+            .collect())
+    }
+
     pub fn get_cached_xpub(&self, params: GetXpubParams) -> Result<Xpub> {
         let mut guard = self.cached_xpubs.lock()?;
         let der_path = vec_to_derivation_path(&params.path);
@@ -20,7 +20,8 @@ use lwk_jade::{
         SignMessageParams, UpdatePinserverParams, VersionInfoResult,
     },
     register_multisig::{
-        GetRegisteredMultisigParams, JadeDescriptor, MultisigSigner, RegisterMultisigParams,
+        GetRegisteredMultisigParams, JadeDescriptor, MultisigSigner, MultisigVariant,
+        RegisterMultisigParams,
     },
 };
 use lwk_test_util::{jade::TestJadeEmulator, TEST_MNEMONIC};
@@ -45,6 +46,17 @@ fn debug_set_mnemonic() {
     assert_eq!(result, mock_version_info());
 }
 
+#[test]
+fn state() {
+    let docker = clients::Cli::default();
+    let mut jade = TestJadeEmulator::new(&docker);
+    assert!(jade.jade.is_emulator().unwrap());
+    assert_eq!(jade.jade.state().unwrap(), JadeState::Uninit);
+
+    jade.set_debug_mnemonic(TEST_MNEMONIC);
+    assert_eq!(jade.jade.state().unwrap(), JadeState::Ready);
+}
+
 #[test]
 fn epoch() {
     let docker = clients::Cli::default();
@@ -197,7 +209,7 @@ fn jade_register_multisig() {
         network: lwk_jade::Network::LocaltestLiquid,
         multisig_name: "finney-satoshi".to_string(),
         descriptor: JadeDescriptor {
-            variant: "wsh(multi(k))".to_string(),
+            variant: MultisigVariant::WshMulti,
             sorted: false,
             threshold: 2,
             master_blinding_key: hex::decode(
@@ -238,7 +250,7 @@ fn jade_max_multisigs() {
             network: lwk_jade::Network::LocaltestLiquid,
             multisig_name: index.to_string(),
             descriptor: JadeDescriptor {
-                variant: "wsh(multi(k))".to_string(),
+                variant: MultisigVariant::WshMulti,
                 sorted: false,
                 threshold: 2,
                 master_blinding_key: hex::decode(s).unwrap(),
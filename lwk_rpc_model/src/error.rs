@@ -0,0 +1,153 @@
+//! Stable codes of the errors returned via RPC
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// The kind of an error returned by the server, stable across versions, so that clients can
+/// handle errors without matching the human readable message
+///
+/// Every kind has its own JSON-RPC error `code`, while its name is in the `error_code` field of
+/// the error `data`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorCode {
+    /// An error without a more specific code
+    Generic,
+
+    /// The signer could not be created, for instance for an invalid mnemonic
+    InvalidSigner,
+
+    /// The signer failed, for instance signing a PSET
+    SignerFailed,
+
+    /// No wallet is loaded with the given name
+    WalletNotFound,
+
+    /// A wallet with the given name is already loaded
+    WalletAlreadyLoaded,
+
+    /// No signer is loaded with the given name
+    SignerNotFound,
+
+    /// A signer with the given name is already loaded
+    SignerAlreadyLoaded,
+
+    /// The asset registry did not answer in time
+    RegistryTimeout,
+
+    /// The Jade returned an error or cannot be reached
+    Jade,
+
+    /// The asset is not in the server registry
+    AssetNotFound,
+
+    /// The asset is already in the server registry
+    AssetAlreadyInserted,
+
+    /// The wallet balance is not enough for the requested transaction
+    InsufficientFunds,
+
+    /// The address is invalid or of the wrong kind, for instance not confidential
+    InvalidAddress,
+
+    /// Some recipients are invalid, details in the error data
+    InvalidRecipients,
+
+    /// The descriptor is invalid or not supported
+    InvalidDescriptor,
+
+    /// The blockchain backend rejected the transaction, the reason is in the error data
+    BroadcastRejected,
+
+    /// The transaction is not a transaction of the wallet
+    TxNotFound,
+
+    /// The server state is locked, start the server with a state password
+    StoreLocked,
+
+    /// The blockchain backend cannot be reached or failed
+    Backend,
+
+    /// The address shown by the signer differs from the one derived by the wallet, the address
+    /// must not be used, both addresses are in the error data
+    AddressMismatch,
+}
+
+impl ErrorCode {
+    /// Every error code
+    pub const ALL: [ErrorCode; 20] = [
+        ErrorCode::Generic,
+        ErrorCode::InvalidSigner,
+        ErrorCode::SignerFailed,
+        ErrorCode::WalletNotFound,
+        ErrorCode::WalletAlreadyLoaded,
+        ErrorCode::SignerNotFound,
+        ErrorCode::SignerAlreadyLoaded,
+        ErrorCode::RegistryTimeout,
+        ErrorCode::Jade,
+        ErrorCode::AssetNotFound,
+        ErrorCode::AssetAlreadyInserted,
+        ErrorCode::InsufficientFunds,
+        ErrorCode::InvalidAddress,
+        ErrorCode::InvalidRecipients,
+        ErrorCode::InvalidDescriptor,
+        ErrorCode::BroadcastRejected,
+        ErrorCode::TxNotFound,
+        ErrorCode::StoreLocked,
+        ErrorCode::Backend,
+        ErrorCode::AddressMismatch,
+    ];
+
+    /// The JSON-RPC error code, in the range reserved for implementation defined server errors
+    pub fn code(&self) -> i64 {
+        match self {
+            ErrorCode::Generic => -32_005,
+            ErrorCode::InvalidSigner => -32_006,
+            ErrorCode::SignerFailed => -32_007,
+            ErrorCode::WalletNotFound => -32_008,
+            ErrorCode::WalletAlreadyLoaded => -32_009,
+            ErrorCode::SignerNotFound => -32_010,
+            ErrorCode::SignerAlreadyLoaded => -32_011,
+            ErrorCode::RegistryTimeout => -32_012,
+            ErrorCode::Jade => -32_013,
+            ErrorCode::AssetNotFound => -32_015,
+            ErrorCode::AssetAlreadyInserted => -32_016,
+            ErrorCode::InsufficientFunds => -32_017,
+            ErrorCode::InvalidAddress => -32_018,
+            ErrorCode::InvalidRecipients => -32_019,
+            ErrorCode::InvalidDescriptor => -32_020,
+            ErrorCode::BroadcastRejected => -32_021,
+            ErrorCode::TxNotFound => -32_022,
+            ErrorCode::StoreLocked => -32_023,
+            ErrorCode::Backend => -32_024,
+            ErrorCode::AddressMismatch => -32_025,
+        }
+    }
+
+    /// The error code with the given JSON-RPC error code, if any
+    pub fn from_code(code: i64) -> Option<Self> {
+        Self::ALL.into_iter().find(|c| c.code() == code)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashSet;
+
+    use super::ErrorCode;
+
+    #[test]
+    fn test_error_code() {
+        let codes: HashSet<_> = ErrorCode::ALL.iter().map(|c| c.code()).collect();
+        assert_eq!(codes.len(), ErrorCode::ALL.len());
+        for c in ErrorCode::ALL {
+            assert_eq!(ErrorCode::from_code(c.code()), Some(c));
+            assert!((-32_099..=-32_005).contains(&c.code()));
+        }
+        assert_eq!(ErrorCode::from_code(-32_700), None);
+        assert_eq!(
+            serde_json::to_string(&ErrorCode::WalletNotFound).unwrap(),
+            "\"WALLET_NOT_FOUND\""
+        );
+    }
+}
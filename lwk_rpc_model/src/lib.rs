@@ -5,5 +5,6 @@
 //! in [`request`]s and [`response`]s.
 //!
 
+pub mod error;
 pub mod request;
 pub mod response;
@@ -34,6 +34,9 @@ pub enum Direction {
 }
 
 /// Request to load a wallet in the server, returning [`response::Wallet`]
+///
+/// Loading a wallet with the same name, descriptor, gap limit and start height of an already
+/// loaded one is a no-op.
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct WalletLoad {
     /// The read-only descriptor describing the wallet outputs
@@ -41,15 +44,59 @@ pub struct WalletLoad {
 
     /// The name given to the wallet, will be needed for calls related to the wallet
     pub name: String,
+
+    /// The number of consecutive unused addresses after which the scans stop, if missing the
+    /// server default is used. Raise it to find the funds of wallets with large gaps of unused
+    /// addresses.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gap_limit: Option<u32>,
+
+    /// Ignore the transactions confirmed before this height, as set by [`WalletRescan`], if
+    /// missing the whole chain is scanned
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_height: Option<u32>,
 }
 
 /// Unload the wallet identified by the given name
+///
+/// If the current scan loop has not scanned the wallet yet, the unload waits for its scan to
+/// complete, so that the scan result is persisted.
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct WalletUnload {
     /// The name given to the wallet
     pub name: String,
 }
 
+/// Reset the scan state of a wallet, so that it's restored by the next scans
+///
+/// The start height is kept across restarts of the server.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct WalletRescan {
+    /// The name of the wallet to rescan
+    pub name: String,
+
+    /// Ignore the transactions confirmed before this height, if missing every transaction is
+    /// considered
+    ///
+    /// The history of every wallet script is still fetched, only the older transactions are not
+    /// downloaded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_height: Option<u32>,
+}
+
+/// Request to generate a mnemonic for a software signer
+#[derive(Debug, Default, Serialize, Deserialize, JsonSchema)]
+pub struct SignerGenerate {
+    /// The number of words of the mnemonic: 12, 15, 18, 21 or 24
+    ///
+    /// If missing [`DEFAULT_WORD_COUNT`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub word_count: Option<u8>,
+}
+
+/// The number of words of the mnemonic generated if [`SignerGenerate::word_count`] is missing
+pub const DEFAULT_WORD_COUNT: u8 = 24;
+
 /// Load a signer in the server
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct SignerLoadSoftware {
@@ -59,7 +106,15 @@ pub struct SignerLoadSoftware {
     /// The mnemonic (12 or 24 words)
     pub mnemonic: String,
 
+    /// Optional BIP-39 passphrase, if missing the empty passphrase is used
+    ///
+    /// If the signer is persisted, the passphrase is persisted as well
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub passphrase: Option<String>,
+
     /// Whether to persist the software signer
+    ///
+    /// The mnemonic is persisted encrypted, thus the server must have a state password.
     pub persist: bool,
 }
 
@@ -86,6 +141,24 @@ pub struct SignerLoadExternal {
     pub fingerprint: String,
 }
 
+/// Load a USB hardware wallet (Ledger, Trezor, ...) used through HWI in the server
+///
+/// HWI must be installed and the device connected and unlocked. Since the device must be
+/// connected to load it, this signer is not persisted.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct SignerLoadHwi {
+    /// The name of the signer, will be needed to reference it in other calls
+    pub name: String,
+
+    /// The fingerprint of the device
+    pub fingerprint: String,
+
+    /// The path of the device as returned by `hwi enumerate`, needed only if multiple devices
+    /// with the same fingerprint are connected
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub device_path: Option<String>,
+}
+
 /// Get the signer details
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct SignerDetails {
@@ -117,6 +190,97 @@ pub struct WalletAddress {
 
     /// Return a image QR code encoded as uri with the given pixel per module
     pub with_uri_qr: Option<u8>,
+
+    /// Whether to return the confidential address, if missing true
+    ///
+    /// If false the address is returned without the blinding public key, the script pubkey and
+    /// the derivation index are the same as for the confidential one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confidential: Option<bool>,
+}
+
+/// Request consecutive receiving addresses, returning [`response::WalletAddresses`]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct WalletAddresses {
+    /// The wallet name
+    pub name: String,
+
+    /// The derivation index of the first address, if missing the first unused index is used
+    pub index: Option<u32>,
+
+    /// The number of consecutive addresses to return, at most [`MAX_ADDRESS_COUNT`]
+    pub count: u32,
+
+    /// Whether to return QR codes of the addresses encoded as text
+    pub with_text_qr: bool,
+
+    /// Return image QR codes encoded as uri with the given pixel per module
+    pub with_uri_qr: Option<u8>,
+
+    /// Whether to return the confidential addresses, if missing true
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confidential: Option<bool>,
+}
+
+/// The maximum number of addresses that can be requested with [`WalletAddresses::count`]
+pub const MAX_ADDRESS_COUNT: u32 = 1000;
+
+/// Request to decode and validate an address
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ValidateAddress {
+    /// The address to validate
+    pub address: String,
+
+    /// The name of a loaded wallet or a network ("liquid", "liquid-testnet" or "liquid-regtest")
+    /// the address must be valid for
+    pub name_or_network: String,
+}
+
+/// Request to check whether an address or a script pubkey belongs to a wallet
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct WalletIsMine {
+    /// The wallet name
+    pub name: String,
+
+    /// An address or a script pubkey in hex
+    pub address_or_script: String,
+}
+
+/// Request to export the descriptor of a wallet
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct WalletExportDescriptor {
+    /// The wallet name
+    pub name: String,
+
+    /// The format of the exported descriptor
+    pub format: DescriptorFormat,
+}
+
+/// The format of an exported descriptor
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DescriptorFormat {
+    /// The CT descriptor, including the blinding key
+    Confidential,
+
+    /// The descriptor without the blinding key, it can't unblind the wallet transactions
+    Bare,
+
+    /// The argument of Elements Core `importdescriptors`, one descriptor per chain
+    CoreImport,
+}
+
+/// Request to verify an xpub is derived from a master key at a given path
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct VerifyXpub {
+    /// The fingerprint of the master key, e.g. of an external signer
+    pub fingerprint: String,
+
+    /// The derivation path from the master key, e.g. "m/87h/1h/0h"
+    pub path: String,
+
+    /// The extended public key to verify
+    pub xpub: String,
 }
 
 /// The balance of a wallet
@@ -127,6 +291,10 @@ pub struct WalletBalance {
 
     /// Replace asset ids with tickers when possible
     pub with_tickers: bool,
+
+    /// Also return the balance of every asset split in confirmed and unconfirmed
+    #[serde(default)]
+    pub detailed: bool,
 }
 
 /// Send a transaction from a wallet
@@ -138,38 +306,158 @@ pub struct WalletSendMany {
     /// Recipient addressees
     pub addressees: Vec<UnvalidatedAddressee>,
 
-    /// Optional fee rate in sat/vb
+    /// Optional fee rate, the unit is given by `fee_rate_unit`
     pub fee_rate: Option<f32>,
+
+    /// Optional unit of `fee_rate`, if missing the fee rate is in sat/vb
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fee_rate_unit: Option<FeeRateUnit>,
+
+    /// Optional wallet utxos to spend, in the form "txid:vout", all of them are spent
+    ///
+    /// If missing, the utxos are selected automatically
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub utxos: Option<Vec<String>>,
+
+    /// Signal replaceability, so that the fee can be bumped with `wallet_bump_fee`
+    #[serde(default)]
+    pub enable_rbf: bool,
+
+    /// Optional address receiving the change, if missing an internal address of the wallet is used
+    ///
+    /// If the address is not owned by the wallet, the change leaves the wallet and the wallet
+    /// can't unblind it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub change_address: Option<String>,
+
+    /// Preview the transaction: also return the selected inputs, the change outputs and the fee
+    ///
+    /// The returned PSET is the same that would be returned otherwise, it's not signed nor broadcast
+    #[serde(default)]
+    pub dry_run: bool,
+
+    /// Optional strategy to select the wallet utxos to spend, if missing the default selection is used
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub coin_selection: Option<CoinSelection>,
+}
+
+/// The unit of a fee rate
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FeeRateUnit {
+    /// Satoshi per virtual byte
+    SatPerVb,
+
+    /// Satoshi per 1000 virtual bytes
+    SatPerKvb,
+}
+
+/// The strategy to select the wallet utxos spent by a transaction
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CoinSelection {
+    /// Spend the utxos with the highest value first, minimizing the number of inputs
+    LargestFirst,
+
+    /// Spend the utxos with the lowest value first, consolidating the wallet utxos
+    SmallestFirst,
+
+    /// Spend the utxos whose sum is closest to the amount needed, minimizing the change
+    BranchAndBound,
 }
 
 ///  An addressee which has yet to be validated
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct UnvalidatedAddressee {
     /// The amount to send in satoshi
-    pub satoshi: u64,
+    ///
+    /// If missing, all the remaining balance of the asset is sent (minus fees for the policy
+    /// asset). Only one addressee per asset can omit it.
+    #[serde(default)]
+    pub satoshi: Option<u64>,
 
     /// The address to send to
     ///
-    /// If "burn", the output will be burned
+    /// If "burn", the output will be burned.
+    /// If "op_return:<hex>", the output is an OP_RETURN carrying the given data, at most
+    /// 80 bytes, with zero value (`satoshi` must be 0).
     pub address: String,
 
     /// The asset to send
     ///
     /// If empty, the policy asset
     pub asset: String,
+
+    /// Lock the output until the given block height
+    ///
+    /// The address must be P2WPKH with key `K`, the output is sent to the P2WSH of
+    /// `and_v(v:pkh(K),after(locktime_height))`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub locktime_height: Option<u32>,
+
+    /// Lock the output until it has the given number of confirmations
+    ///
+    /// The address must be P2WPKH with key `K`, the output is sent to the P2WSH of
+    /// `and_v(v:pkh(K),older(csv_blocks))`. Cannot be used with `locktime_height`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub csv_blocks: Option<u16>,
 }
 
-/// Drain a wallet
+/// Drain a wallet, sending all its funds to a single address
+///
+/// Every asset is sent in its own output, the L-BTC output receives the balance minus the fee.
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct WalletDrain {
     /// The wallet name creating the transaction
     pub name: String,
 
-    /// Recipient addresse
+    /// Recipient confidential address
     pub address: String,
 
-    /// Optional fee rate in sat/vb
+    /// Optional fee rate, the unit is given by `fee_rate_unit`
+    pub fee_rate: Option<f32>,
+
+    /// Optional unit of `fee_rate`, if missing the fee rate is in sat/vb
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fee_rate_unit: Option<FeeRateUnit>,
+}
+
+/// Estimate the size and the fee of a transaction sending to the given addressees
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct WalletEstimateFee {
+    /// The wallet name
+    pub name: String,
+
+    /// Recipient addressees
+    pub addressees: Vec<UnvalidatedAddressee>,
+
+    /// Optional fee rate, the unit is given by `fee_rate_unit`
     pub fee_rate: Option<f32>,
+
+    /// Optional unit of `fee_rate`, if missing the fee rate is in sat/vb
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fee_rate_unit: Option<FeeRateUnit>,
+
+    /// Optional strategy to select the wallet utxos to spend, if missing the default selection is used
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub coin_selection: Option<CoinSelection>,
+}
+
+/// Replace an unconfirmed wallet transaction with one paying a higher fee
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct WalletBumpFee {
+    /// The wallet name
+    pub name: String,
+
+    /// The transaction to replace, it must signal replaceability
+    pub txid: String,
+
+    /// The fee rate of the replacement, the unit is given by `fee_rate_unit`
+    pub fee_rate: f32,
+
+    /// Optional unit of `fee_rate`, if missing the fee rate is in sat/vb
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fee_rate_unit: Option<FeeRateUnit>,
 }
 
 /// A request containing information to create a single signature descriptor wallet
@@ -181,8 +469,19 @@ pub struct SignerSinglesigDescriptor {
     /// The descriptor blinding key
     pub descriptor_blinding_key: String,
 
-    /// The singlesig kind // TODO enum
-    pub singlesig_kind: String,
+    /// The singlesig kind
+    pub singlesig_kind: SinglesigKind,
+}
+
+/// The kind of a single signature descriptor
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SinglesigKind {
+    /// Witness pay to public key hash (segwit), as defined by bip84
+    Wpkh,
+
+    /// Script hash witness pay to public key hash (nested segwit), as defined by bip49
+    ShWpkh,
 }
 
 /// A request containing information to create a multi signature descriptor wallet
@@ -191,14 +490,36 @@ pub struct WalletMultisigDescriptor {
     /// The descriptor blinding key
     pub descriptor_blinding_key: String,
 
-    /// The multisig kind // TODO enum
-    pub multisig_kind: String,
+    /// The multisig kind
+    pub multisig_kind: MultisigKind,
 
     /// The number of signatures required to spend
     pub threshold: u32,
 
     /// The partecipants in the multisig wallet xpubs with key origin
     pub keyorigin_xpubs: Vec<String>,
+
+    /// Whether to use `sortedmulti` (the default) or `multi`
+    ///
+    /// Must match how the wallet is registered on signers like Jade, otherwise derived
+    /// addresses differ.
+    #[serde(default = "default_true")]
+    pub sorted: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// The kind of a multi signature descriptor
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum MultisigKind {
+    /// Witness script hash (segwit)
+    Wsh,
+
+    /// Script hash witness script hash (nested segwit)
+    ShWsh,
 }
 
 /// Request to register a multisig wallet on a signer
@@ -211,14 +532,68 @@ pub struct SignerRegisterMultisig {
     pub wallet: String,
 }
 
+/// Request the multisig wallets registered on a signer
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct SignerRegisteredMultisigs {
+    /// The signer name, it must be a Jade
+    pub name: String,
+}
+
+/// Request to show a wallet address on a Jade, so that the user can confirm it matches the one
+/// shown by the host before using it
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct SignerConfirmAddress {
+    /// The signer name, it must be a Jade
+    pub name: String,
+
+    /// The wallet name, multisig wallets must be registered on the Jade with this name
+    pub wallet: String,
+
+    /// The derivation index of the address
+    pub index: u32,
+
+    /// Whether the address is a change address, if missing false
+    #[serde(default)]
+    pub change: bool,
+}
+
+/// Request the status of a Jade, it doesn't unlock the device
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct SignerJadeStatus {
+    /// The signer name, it must be a Jade
+    pub name: String,
+}
+
+/// Request to unlock a Jade, the user enters the PIN on the device while the server performs
+/// the authentication handshake with the PIN server
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct SignerJadeUnlock {
+    /// The signer name, it must be a Jade
+    pub name: String,
+}
+
 /// Request to a signer for a derived xpub
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct SignerXpub {
     /// The signer name
     pub name: String,
 
-    /// The xpub kind // TODO enum
-    pub xpub_kind: String,
+    /// The xpub kind
+    pub xpub_kind: XpubKind,
+}
+
+/// The kind of xpub, determining the derivation path used
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum XpubKind {
+    /// Derivation path `m/49h/<coin_type>h/0h`, for nested segwit singlesig
+    Bip49,
+
+    /// Derivation path `m/84h/<coin_type>h/0h`, for native segwit singlesig
+    Bip84,
+
+    /// Derivation path `m/87h/<coin_type>h/0h`, for multisig
+    Bip87,
 }
 
 /// A request to sign a PSET
@@ -229,6 +604,10 @@ pub struct SignerSign {
 
     /// The PSET in base64
     pub pset: String,
+
+    /// If specified, sign only the inputs belonging to this wallet
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wallet: Option<String>,
 }
 
 /// Request to broadcast a transaction
@@ -237,7 +616,10 @@ pub struct WalletBroadcast {
     /// The wallet name
     pub name: String,
 
-    /// Perform transaction extraction and verification but avoid doing the last broadcast step // TODO verification is not complete at the moment
+    /// Perform transaction extraction and verification but avoid doing the last broadcast step
+    ///
+    /// Verification checks the range and surjection proofs of the outputs and that the amounts
+    /// of the outputs balance the ones of the inputs.
     pub dry_run: bool,
 
     /// The PSET in base64
@@ -251,6 +633,13 @@ pub struct WalletDetails {
     pub name: String,
 }
 
+/// Request the scan progress of a wallet
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct WalletSyncStatus {
+    /// The wallet name
+    pub name: String,
+}
+
 /// Request to do an issuance
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct WalletIssue {
@@ -272,8 +661,25 @@ pub struct WalletIssue {
     /// The contract defininig asset metadata, such as name, ticker and precision. See [`AssetContract`] request to create
     pub contract: Option<String>,
 
-    /// The optional fee rate
+    /// The optional fee rate in sat/kvb
     pub fee_rate: Option<f32>,
+
+    /// Preview the issuance: also return the ids of the asset and of the reissuance token
+    ///
+    /// The returned PSET is the same that would be returned otherwise, it's not signed nor broadcast
+    #[serde(default)]
+    pub dry_run: bool,
+
+    /// Optional address receiving the change, if missing an internal address of the wallet is used
+    ///
+    /// If the address is not owned by the wallet, the change leaves the wallet and the wallet
+    /// can't unblind it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub change_address: Option<String>,
+
+    /// Optional strategy to select the wallet utxos to spend, if missing the default selection is used
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub coin_selection: Option<CoinSelection>,
 }
 
 /// Request to do a reissuance
@@ -291,8 +697,25 @@ pub struct WalletReissue {
     /// The address receiving the asset, if missing a receiving address from the wallet doing the reissuance is used
     pub address_asset: Option<String>,
 
-    /// The optional fee rate
+    /// The optional fee rate in sat/kvb
     pub fee_rate: Option<f32>,
+
+    /// Preview the reissuance: also return the ids of the asset and of the reissuance token
+    ///
+    /// The returned PSET is the same that would be returned otherwise, it's not signed nor broadcast
+    #[serde(default)]
+    pub dry_run: bool,
+
+    /// Optional address receiving the change, if missing an internal address of the wallet is used
+    ///
+    /// If the address is not owned by the wallet, the change leaves the wallet and the wallet
+    /// can't unblind it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub change_address: Option<String>,
+
+    /// Optional strategy to select the wallet utxos to spend, if missing the default selection is used
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub coin_selection: Option<CoinSelection>,
 }
 
 /// Request to do burn an asset
@@ -307,11 +730,53 @@ pub struct WalletBurn {
     /// The number of units of the asset to burn
     pub satoshi_asset: u64,
 
-    /// The optional fee rate
+    /// The optional fee rate in sat/kvb
+    pub fee_rate: Option<f32>,
+}
+
+/// Request to consolidate the smallest utxos of an asset in a single wallet output
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct WalletConsolidate {
+    /// The wallet name
+    pub name: String,
+
+    /// The asset of the utxos to consolidate, if missing the policy asset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub asset: Option<String>,
+
+    /// The maximum number of utxos spent, the smallest ones are chosen
+    pub max_inputs: u32,
+
+    /// The optional fee rate in sat/kvb
     pub fee_rate: Option<f32>,
 }
 
+/// Request to create a PSET offering an asset in exchange for another one
+///
+/// The offered utxo is spent whole, so the wallet must have a utxo of exactly `send_satoshi` of
+/// `send_asset`. If it has not, split one first, for instance sending `send_satoshi` of the asset
+/// to a wallet address.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct WalletCreateSwap {
+    /// The wallet name
+    pub name: String,
+
+    /// The asset offered, the wallet must have a utxo with exactly `send_satoshi` of it
+    pub send_asset: String,
+
+    /// The amount offered in satoshi
+    pub send_satoshi: u64,
+
+    /// The asset requested
+    pub recv_asset: String,
+
+    /// The amount requested in satoshi
+    pub recv_satoshi: u64,
+}
+
 /// A request creating a contract in the JSON format expected by the issue call
+///
+/// The fields are validated, an error reports the first invalid one.
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct AssetContract {
     /// Domain of the issuer
@@ -356,14 +821,64 @@ pub struct WalletPsetDetails {
     pub with_tickers: bool,
 }
 
+/// Request to analyze a PSET, possibly incomplete or with inputs not owned by the wallet
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct WalletPsetAnalyze {
+    /// The wallet name
+    pub name: String,
+
+    /// The PSET in base64 to analyze
+    pub pset: String,
+
+    /// Replace asset ids with tickers when possible
+    pub with_tickers: bool,
+}
+
+/// Request to create a proof of reserves of all the wallet funds
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct WalletProofOfReserves {
+    /// The wallet name
+    pub name: String,
+
+    /// The challenge the proof commits to, e.g. a message from the verifier
+    pub challenge: String,
+}
+
+/// Request to verify a proof of reserves
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct VerifyProofOfReserves {
+    /// The signed proof of reserves, a PSET in base64
+    pub proof: String,
+
+    /// The challenge the proof must commit to
+    pub challenge: String,
+}
+
 /// Request to get the wallet unspet transaction Outputs
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct WalletUtxos {
     /// The wallet name
     pub name: String,
+
+    /// Replace asset ids with tickers when possible
+    #[serde(default)]
+    pub with_tickers: bool,
+
+    /// Return only the utxos of this asset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub asset: Option<String>,
+
+    /// Return only the utxos with at least this value in satoshi
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_satoshi: Option<u64>,
 }
 
 /// Request to get the wallet transactions
+///
+/// Transactions are ordered by height descending, with unconfirmed transactions first and
+/// transactions at the same height ordered by txid descending. The order doesn't depend on the
+/// request so pages obtained with `offset` and `limit` are consistent as long as the wallet
+/// doesn't receive new transactions.
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct WalletTxs {
     /// The wallet name
@@ -371,6 +886,31 @@ pub struct WalletTxs {
 
     /// Replace asset ids with tickers when possible
     pub with_tickers: bool,
+
+    /// Return at most this number of transactions, if missing all of them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u32>,
+
+    /// Skip this number of transactions, applied after the height filters
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub offset: Option<u32>,
+
+    /// Return only transactions with height greater or equal than this one, unconfirmed
+    /// transactions are included
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_height: Option<u32>,
+
+    /// Return only transactions with height lower or equal than this one, unconfirmed
+    /// transactions are excluded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_height: Option<u32>,
+}
+
+/// Request to export the wallet transactions as CSV
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct WalletExportCsv {
+    /// The wallet name
+    pub name: String,
 }
 
 /// Request to get a transaction
@@ -420,6 +960,18 @@ pub struct AssetFromExplorer {
     pub asset_id: String,
 }
 
+/// Request to look up an asset in the asset registry
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct AssetRegistryLookup {
+    /// The asset identifier
+    pub asset_id: String,
+
+    /// Also insert the asset as [`AssetFromExplorer`] does, so that it's known afterwards,
+    /// for example by `with_tickers` requests
+    #[serde(default)]
+    pub cache: bool,
+}
+
 /// Request to publish
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct AssetPublish {
@@ -460,6 +1012,39 @@ pub struct WalletSetAddrMemo {
     pub memo: String,
 }
 
+/// Request to set an output memo
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct WalletSetOutputMemo {
+    /// The wallet name
+    pub name: String,
+
+    /// The output, as "txid:vout"
+    pub outpoint: String,
+
+    /// Output memo
+    pub memo: String,
+}
+
+/// Request to get the transaction, address and output memos of a wallet as labels
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct WalletLabels {
+    /// The wallet name
+    pub name: String,
+}
+
+/// Request to import labels in the BIP-329 format as transaction, address and output memos
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct WalletImportLabels {
+    /// The wallet name
+    pub name: String,
+
+    /// The labels in the BIP-329 format, a JSON object per line
+    ///
+    /// Only "tx", "addr" and "output" labels referring to the wallet are imported, the other
+    /// records are skipped.
+    pub jsonl: String,
+}
+
 #[cfg(test)]
 mod test {
     use schemars::schema_for;
@@ -470,8 +1055,48 @@ mod test {
     fn test_json_schema() {
         let schema = schema_for!(WalletLoad);
         assert_eq!(
-            r#"{"$schema":"http://json-schema.org/draft-07/schema#","title":"WalletLoad","description":"Request to load a wallet in the server, returning [`response::Wallet`]","type":"object","required":["descriptor","name"],"properties":{"descriptor":{"description":"The read-only descriptor describing the wallet outputs","type":"string"},"name":{"description":"The name given to the wallet, will be needed for calls related to the wallet","type":"string"}}}"#,
+            r#"{"$schema":"http://json-schema.org/draft-07/schema#","title":"WalletLoad","description":"Request to load a wallet in the server, returning [`response::Wallet`]\n\nLoading a wallet with the same name, descriptor, gap limit and start height of an already loaded one is a no-op.","type":"object","required":["descriptor","name"],"properties":{"descriptor":{"description":"The read-only descriptor describing the wallet outputs","type":"string"},"gap_limit":{"description":"The number of consecutive unused addresses after which the scans stop, if missing the server default is used. Raise it to find the funds of wallets with large gaps of unused addresses.","type":["integer","null"],"format":"uint32","minimum":0.0},"name":{"description":"The name given to the wallet, will be needed for calls related to the wallet","type":"string"},"start_height":{"description":"Ignore the transactions confirmed before this height, as set by [`WalletRescan`], if missing the whole chain is scanned","type":["integer","null"],"format":"uint32","minimum":0.0}}}"#,
             serde_json::to_string(&schema).unwrap()
         );
     }
+
+    #[test]
+    fn test_json_schema_xpub() {
+        let schema = schema_for!(SignerXpub);
+        assert_eq!(
+            r##"{"$schema":"http://json-schema.org/draft-07/schema#","title":"SignerXpub","description":"Request to a signer for a derived xpub","type":"object","required":["name","xpub_kind"],"properties":{"name":{"description":"The signer name","type":"string"},"xpub_kind":{"description":"The xpub kind","allOf":[{"$ref":"#/definitions/XpubKind"}]}},"definitions":{"XpubKind":{"description":"The kind of xpub, determining the derivation path used","oneOf":[{"description":"Derivation path `m/49h/<coin_type>h/0h`, for nested segwit singlesig","type":"string","enum":["bip49"]},{"description":"Derivation path `m/84h/<coin_type>h/0h`, for native segwit singlesig","type":"string","enum":["bip84"]},{"description":"Derivation path `m/87h/<coin_type>h/0h`, for multisig","type":"string","enum":["bip87"]}]}}}"##,
+            serde_json::to_string(&schema).unwrap()
+        );
+
+        let err =
+            serde_json::from_str::<SignerXpub>(r#"{"name":"s1","xpub_kind":"bip44"}"#).unwrap_err();
+        assert!(err.to_string().contains("unknown variant `bip44`"));
+    }
+
+    #[test]
+    fn test_descriptor_kinds() {
+        let r: SignerSinglesigDescriptor = serde_json::from_str(
+            r#"{"name":"s1","descriptor_blinding_key":"slip77","singlesig_kind":"shwpkh"}"#,
+        )
+        .unwrap();
+        assert_eq!(r.singlesig_kind, SinglesigKind::ShWpkh);
+
+        let err = serde_json::from_str::<SignerSinglesigDescriptor>(
+            r#"{"name":"s1","descriptor_blinding_key":"slip77","singlesig_kind":"wphk"}"#,
+        )
+        .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("unknown variant `wphk`, expected `wpkh` or `shwpkh`"));
+
+        let r: WalletMultisigDescriptor = serde_json::from_str(
+            r#"{"descriptor_blinding_key":"elip151","multisig_kind":"wsh","threshold":1,"keyorigin_xpubs":[]}"#,
+        )
+        .unwrap();
+        assert_eq!(r.multisig_kind, MultisigKind::Wsh);
+        assert_eq!(
+            serde_json::to_string(&MultisigKind::ShWsh).unwrap(),
+            r#""shwsh""#
+        );
+    }
 }
@@ -5,12 +5,29 @@ use crate::request;
 
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 /// An empty response.
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct Empty {}
 
+/// The JSON schemas of every method of the RPC
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct SchemaAll {
+    /// A map from the method name to its schemas, ordered by method name
+    pub methods: BTreeMap<String, MethodSchema>,
+}
+
+/// The JSON schemas of a method of the RPC
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct MethodSchema {
+    /// The JSON schema of the request params
+    pub request: serde_json::Value,
+
+    /// The JSON schema of the response result
+    pub response: serde_json::Value,
+}
+
 /// Server version response
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct Version {
@@ -21,6 +38,20 @@ pub struct Version {
     pub network: String,
 }
 
+/// Network details response
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct NetworkDetails {
+    /// The server network
+    pub network: String,
+
+    /// The policy asset id (L-BTC)
+    pub policy_asset: String,
+
+    /// The base url of the explorer, if known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub explorer_url: Option<String>,
+}
+
 /// Response for generate signer
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct SignerGenerate {
@@ -31,7 +62,7 @@ pub struct SignerGenerate {
 /// Response for list signers call
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct SignerList {
-    /// Returned signers currently loaded in the server
+    /// Returned signers currently loaded in the server, sorted by name
     pub signers: Vec<Signer>,
 }
 
@@ -45,11 +76,27 @@ pub struct Wallet {
     pub name: String,
 }
 
+/// A wallet loaded in the server, returned from [`request::WalletList`]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct WalletSummary {
+    /// The wallet name
+    pub name: String,
+
+    /// Public descriptor definining wallet outputs
+    pub descriptor: String,
+
+    /// The checksum of the descriptor, identifying the wallet
+    pub checksum: String,
+
+    /// The network of the wallet
+    pub network: String,
+}
+
 /// Response for list wallets call
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct WalletList {
-    /// Returned wallets currently loaded in the server
-    pub wallets: Vec<Wallet>,
+    /// Returned wallets currently loaded in the server, sorted by name
+    pub wallets: Vec<WalletSummary>,
 }
 
 /// Response for unload wallet call
@@ -59,6 +106,17 @@ pub struct WalletUnload {
     pub unloaded: Wallet,
 }
 
+/// Response for wallet rescan
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct WalletRescan {
+    /// The height from which the transactions are considered, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_height: Option<u32>,
+
+    /// The height of the blockchain tip when the rescan was requested
+    pub tip_height: u32,
+}
+
 /// Response for unload signer call
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct SignerUnload {
@@ -74,6 +132,10 @@ pub struct Signer {
 
     /// The fingerprint of the signer, 4 bytes returned as 8 hex characters
     pub fingerprint: String,
+
+    /// The type of the signer, for instance "software", "jade" or "external"
+    #[serde(rename = "type")]
+    pub type_: String,
 }
 
 /// Address response
@@ -85,6 +147,9 @@ pub struct WalletAddress {
     /// The index of the derivation of the given address
     pub index: u32,
 
+    /// Whether the address is confidential
+    pub is_confidential: bool,
+
     /// Memo
     pub memo: String,
 
@@ -97,11 +162,143 @@ pub struct WalletAddress {
     pub uri_qr: Option<String>,
 }
 
+/// Multiple addresses response
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct WalletAddresses {
+    /// The receiving addresses, with consecutive indexes
+    pub addresses: Vec<WalletAddress>,
+}
+
+/// Decoded address and whether it can be used to receive funds on the requested network
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ValidateAddress {
+    /// Whether the address can be used as recipient on the requested network
+    pub valid: bool,
+
+    /// Why the address is not valid, missing if valid
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+
+    /// Whether the address has a blinding public key
+    pub is_confidential: bool,
+
+    /// The blinding public key of the address, if confidential
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blinding_pubkey: Option<String>,
+
+    /// The network of the address ("liquid", "liquid-testnet" or "liquid-regtest"), missing if
+    /// the address can't be decoded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub network: Option<String>,
+
+    /// The script type of the address ("p2pkh", "p2sh", "p2wpkh", "p2wsh", "p2tr" or
+    /// "unknown"), missing if the address can't be decoded
+    ///
+    /// Note that "p2sh" includes wrapped segwit addresses, which can't be distinguished.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub script_type: Option<String>,
+}
+
+/// Whether an address or a script pubkey belongs to a wallet
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct WalletIsMine {
+    /// Whether the wallet derives the script pubkey, for confidential addresses the blinding
+    /// public key must be the one of the wallet too
+    pub is_mine: bool,
+
+    /// The chain of the address, "external" or "internal" (change), missing if not mine
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chain: Option<String>,
+
+    /// The derivation index of the address, missing if not mine
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub index: Option<u32>,
+}
+
+/// The descriptor of a wallet in the requested format
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct WalletExportDescriptor {
+    /// The descriptor including the checksum, missing for the "core_import" format
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub descriptor: Option<String>,
+
+    /// The descriptors to pass to Elements Core `importdescriptors`, missing unless the format
+    /// is "core_import"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub import_descriptors: Option<Vec<ImportDescriptor>>,
+}
+
+/// An element of the Elements Core `importdescriptors` argument
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ImportDescriptor {
+    /// The single chain CT descriptor including the checksum
+    pub desc: String,
+
+    /// The unix time from which the blockchain is rescanned, 0 rescans it entirely
+    pub timestamp: u64,
+
+    /// Whether the descriptor is used to derive new addresses
+    pub active: bool,
+
+    /// Whether the descriptor derives change addresses
+    pub internal: bool,
+
+    /// The range of derivation indexes imported, covering the addresses used by the wallet
+    pub range: [u32; 2],
+}
+
+/// Whether an xpub is consistent with a master fingerprint and a derivation path
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct VerifyXpub {
+    /// Whether the xpub is consistent with the fingerprint and the path
+    pub valid: bool,
+
+    /// Why the xpub is not consistent, missing if valid
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+
+    /// Whether the master fingerprint has been checked
+    ///
+    /// An xpub only contains the fingerprint of its parent, so the master fingerprint can be
+    /// checked only for paths with at most one step. For longer paths only the depth and the last
+    /// child number are checked.
+    pub fingerprint_checked: bool,
+
+    /// The depth of the xpub
+    pub depth: u8,
+
+    /// The fingerprint of the parent key of the xpub
+    pub parent_fingerprint: String,
+}
+
 /// Balance respone
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct WalletBalance {
     /// A map of the balance of every asset in the wallet
     pub balance: HashMap<String, i64>,
+
+    /// A map of the balance of every asset in the wallet split in confirmed and unconfirmed,
+    /// present only if requested
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detailed: Option<HashMap<String, AssetBalance>>,
+
+    /// The `balance` with the amounts formatted according to the asset precision, in satoshi for
+    /// unknown assets, present only if requested `with_tickers`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub balance_formatted: Option<HashMap<String, String>>,
+}
+
+/// The balance of an asset split in confirmed and unconfirmed
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct AssetBalance {
+    /// The sum of the unspent outputs confirmed in a block
+    pub confirmed: u64,
+
+    /// The sum of the unspent outputs not yet confirmed
+    pub unconfirmed: u64,
+
+    /// The sum of all the unspent outputs, `confirmed + unconfirmed`
+    pub total: u64,
 }
 
 /// PSET response
@@ -111,11 +308,204 @@ pub struct Pset {
     pub pset: String,
 }
 
+/// The projected size and fee of a transaction
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct WalletEstimateFee {
+    /// The virtual size of the transaction once signed
+    pub vsize: usize,
+
+    /// The fee in satoshi
+    pub fee: u64,
+
+    /// The number of wallet utxos spent
+    pub inputs: usize,
+}
+
+/// A PSET sending to some recipients
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct WalletSend {
+    /// The PSET in base64 format
+    pub pset: String,
+
+    /// The wallet outputs spent, in the format "txid:vout", returned only in dry run
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub inputs: Option<Vec<String>>,
+
+    /// The outputs receiving the change, returned only in dry run
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub change: Option<Vec<ChangeOutput>>,
+
+    /// The fee in satoshi, returned only in dry run
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fee: Option<u64>,
+}
+
+/// An output of a PSET receiving the change
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ChangeOutput {
+    /// The index of the output in the transaction
+    pub vout: u32,
+
+    /// The asset of the change
+    pub asset: String,
+
+    /// The amount of the change in satoshi
+    pub satoshi: u64,
+
+    /// The script pubkey receiving the change, in hex
+    pub script_pubkey: String,
+}
+
+/// A valid proof of reserves
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct VerifyProofOfReserves {
+    /// The amount proven for every asset, in satoshi
+    pub reserves: HashMap<String, u64>,
+
+    /// The outputs spent by the proof, in the format "txid:vout"
+    ///
+    /// The proof does not show they are unspent, it must be checked separately.
+    pub outpoints: Vec<String>,
+}
+
+/// A label of a transaction, an address or an output, a record in the BIP-329 format
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct Label {
+    /// The type of the labelled item, "tx", "addr" or "output"
+    #[serde(rename = "type")]
+    pub type_: String,
+
+    /// The txid, the address or the outpoint ("txid:vout")
+    #[serde(rename = "ref")]
+    pub ref_: String,
+
+    /// The label, i.e. the memo of the transaction, of the address or of the output
+    pub label: String,
+}
+
+/// The labels of a wallet
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct WalletLabels {
+    /// The transaction labels followed by the address labels and the output labels
+    pub labels: Vec<Label>,
+
+    /// The labels in the BIP-329 format, a JSON object per line
+    pub jsonl: String,
+}
+
+/// A line of a labels import that could not be parsed
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct InvalidLabel {
+    /// The line number, starting from 1
+    pub line: u32,
+
+    /// Why the line is invalid
+    pub error: String,
+}
+
+/// Result of a labels import
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct WalletImportLabels {
+    /// The number of labels imported
+    pub imported: u32,
+
+    /// The number of records skipped since their type is not supported, they have no label or
+    /// they do not refer to the wallet
+    pub skipped: u32,
+
+    /// The lines that could not be parsed, they are not imported
+    pub errors: Vec<InvalidLabel>,
+}
+
+/// Consolidation response
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct WalletConsolidate {
+    /// The PSET in base64 format
+    pub pset: String,
+
+    /// The number of utxos consolidated
+    pub inputs: u32,
+
+    /// The sum of the values of the utxos consolidated, in satoshi
+    ///
+    /// For the policy asset the output receives this amount minus the fee
+    pub satoshi: u64,
+}
+
+/// Swap creation response
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct WalletCreateSwap {
+    /// The PSET in base64 format
+    ///
+    /// Its input must be signed by the wallet signers, which use
+    /// `SIGHASH_SINGLE | SIGHASH_ANYONECANPAY`, then the counterparty completes it.
+    pub pset: String,
+
+    /// The secrets of the offered utxo, needed by the counterparty to blind the transaction
+    pub input_secrets: SwapInputSecrets,
+
+    /// A human readable description of the swap terms
+    pub summary: String,
+}
+
+/// The secrets of the utxo offered in a swap
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct SwapInputSecrets {
+    /// The value in satoshi
+    pub value: u64,
+
+    /// The asset id
+    pub asset: String,
+
+    /// The value blinding factor, in hex
+    pub value_blinder: String,
+
+    /// The asset blinding factor, in hex
+    pub asset_blinder: String,
+}
+
+/// Issuance or reissuance response
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct WalletIssue {
+    /// The PSET in base64 format
+    pub pset: String,
+
+    /// The asset (re)issued, returned only in dry run
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub asset: Option<String>,
+
+    /// The reissuance token, returned only in dry run
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+}
+
+/// Details of a generated descriptor
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct DescriptorDetails {
+    /// The descriptor checksum, without the leading `#`
+    pub checksum: String,
+
+    /// The descriptor as it is parsed when loading a wallet, including the checksum
+    ///
+    /// For instance elip151 descriptor blinding keys are replaced with the derived key.
+    pub normalized: String,
+
+    /// Whether the descriptor has wildcards
+    pub is_ranged: bool,
+
+    /// Whether the descriptor has multipath keys, for instance `<0;1>`
+    pub is_multipath: bool,
+}
+
 /// Response containing a single signature descriptor
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct SignerSinglesigDescriptor {
     /// The singlesig descriptor
     pub descriptor: String,
+
+    /// Details of the descriptor
+    #[serde(flatten)]
+    pub details: DescriptorDetails,
 }
 
 /// Response containing a multi signature descriptor
@@ -123,6 +513,63 @@ pub struct SignerSinglesigDescriptor {
 pub struct WalletMultisigDescriptor {
     /// The multisig descriptor
     pub descriptor: String,
+
+    /// Details of the descriptor
+    #[serde(flatten)]
+    pub details: DescriptorDetails,
+}
+
+/// A multisig wallet registered on a signer
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct RegisteredMultisig {
+    /// The multisig variant, for example "wsh(multi(k))"
+    pub variant: String,
+
+    /// Whether the keys are sorted
+    pub sorted: bool,
+
+    /// The number of signatures required to spend
+    pub threshold: u32,
+
+    /// The number of signers
+    pub num_signers: u32,
+
+    /// The confidential descriptor, missing if the variant is not supported
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub descriptor: Option<String>,
+}
+
+/// The multisig wallets registered on a signer
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct SignerRegisteredMultisigs {
+    /// The registered multisigs by name
+    pub multisigs: BTreeMap<String, RegisteredMultisig>,
+}
+
+/// An address shown on a Jade
+///
+/// If the Jade computes a different address the request fails with the error code
+/// `ADDRESS_MISMATCH`.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct SignerConfirmAddress {
+    /// The address derived by the wallet
+    pub address: String,
+
+    /// The address computed and shown by the Jade
+    pub device_address: String,
+
+    /// The derivation index of the address
+    pub index: u32,
+}
+
+/// The status of a Jade
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct JadeStatus {
+    /// One of "uninitialized", "unsaved" (mnemonic not yet persisted with the PIN server),
+    /// "locked", "ready", "temporary" (wallet not persisted) or "disconnected"
+    ///
+    /// A Jade reached via the emulator is always "ready".
+    pub state: String,
 }
 
 /// A response containing an xpub with keyorigin
@@ -137,6 +584,12 @@ pub struct SignerXpub {
 pub struct WalletBroadcast {
     /// The txid of the transaction just broadacasted
     pub txid: String,
+
+    /// True if the backend already knew the transaction, in the mempool or in a block
+    ///
+    /// Broadcasting again the same transaction is not an error, so retries are safe.
+    #[serde(default)]
+    pub already_broadcast: bool,
 }
 
 /// A response of a JSON contract containing asset metadata and validated according to the contract rules
@@ -221,8 +674,57 @@ pub struct WalletDetails {
     /// Signers of this wallet
     pub signers: Vec<SignerShortDetails>,
 
+    /// Keys of the wallet descriptor, with their key origin
+    pub keys: Vec<WalletKey>,
+
+    /// Number of signatures required to spend, for multisig wallets
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub threshold: Option<u32>,
+
     /// Warnings on this wallet
     pub warnings: String,
+
+    /// The index of the first external address not used by any wallet transaction
+    pub next_external_index: u32,
+
+    /// The index of the first internal (change) address not used by any wallet transaction
+    pub next_internal_index: u32,
+}
+
+/// A key of a wallet descriptor
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct WalletKey {
+    /// The fingerprint of the master key of the signer
+    pub fingerprint: String,
+
+    /// The derivation path from the master key to the xpub, missing if the key has no key origin
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub origin_path: Option<String>,
+
+    /// The xpub, or the public key for single keys
+    pub xpub: String,
+
+    /// Whether no loaded signer has the fingerprint of the key
+    pub watch_only: bool,
+}
+
+/// Scan progress of a wallet
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct WalletSyncStatus {
+    /// The blockchain height of the last completed scan, missing if the wallet was never scanned
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_scanned_height: Option<u32>,
+
+    /// The height of the blockchain tip seen by the last scan of a wallet, missing if no wallet
+    /// has been scanned since the server started
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tip_height: Option<u32>,
+
+    /// Whether the current scan loop has not scanned the wallet yet
+    pub scanning: bool,
+
+    /// Rough percentage of the scan progress, from 0 to 100
+    pub progress: f64,
 }
 
 /// Response to wallet combine
@@ -230,6 +732,13 @@ pub struct WalletDetails {
 pub struct WalletCombine {
     /// PSET in base64 format
     pub pset: String,
+
+    /// Whether the combined PSET has enough signatures to be finalized
+    pub finalizable: bool,
+
+    /// The finalized transaction in hex, present if `finalizable`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tx_hex: Option<String>,
 }
 
 /// Response containing detail of a PSET
@@ -294,16 +803,97 @@ pub struct WalletPsetDetails {
     /// Fee of the transaction
     pub fee: u64,
 
+    /// Estimated virtual size of the transaction once all the wallet inputs are signed
+    pub vsize: usize,
+
+    /// Fee rate in sat/vb computed on the estimated virtual size
+    pub fee_rate: f64,
+
     /// Issuances contained in the PSET
     pub issuances: Vec<Issuance>,
 
     /// Reissuance contained in the PSET
     pub reissuances: Vec<Reissuance>,
 
+    /// Signing progress of the inputs having keys of the wallet
+    pub signatures: Vec<InputSigStatus>,
+
+    /// Whether all the inputs in `signatures` have enough signatures
+    pub is_complete: bool,
+
+    /// Warnings
+    pub warnings: String,
+}
+
+/// The signatures of a PSET input
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct InputSigStatus {
+    /// The input index
+    pub vin: u32,
+
+    /// Signatures contained in the input
+    pub has_signatures_from: Vec<SignerShortDetails>,
+
+    /// Signers that could sign the input but have not signed yet
+    pub missing_signatures_from: Vec<SignerShortDetails>,
+
+    /// How many signatures are still needed to reach the descriptor threshold
+    pub signatures_needed: u32,
+
+    /// Whether the input has enough signatures
+    pub is_complete: bool,
+}
+
+/// Analysis of a PSET from the point of view of a wallet
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct WalletPsetAnalyze {
+    /// Net balance of the assets for the point of view of the given wallet
+    ///
+    /// The amounts of the outputs are the ones declared in the PSET.
+    pub balance: HashMap<String, i64>,
+
+    /// Fee of the transaction, missing if the PSET has no fee output
+    pub fee: Option<u64>,
+
+    /// Fee rate in sat/vb computed on the estimated virtual size, missing if the fee or the
+    /// size cannot be computed
+    pub fee_rate: Option<f64>,
+
+    /// Signing status of every input
+    pub inputs: Vec<PsetInputAnalysis>,
+
+    /// Whether all the inputs have the signatures to be finalized
+    pub is_finalizable: bool,
+
+    /// Whether the wallet exchanges an asset for a different one
+    pub is_swap: bool,
+
     /// Warnings
     pub warnings: String,
 }
 
+/// Analysis of a PSET input
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct PsetInputAnalysis {
+    /// The input index
+    pub vin: u32,
+
+    /// Whether the input spends an output of the wallet
+    pub is_mine: bool,
+
+    /// Whether the input is already finalized
+    pub is_finalized: bool,
+
+    /// The sighash type the input must be signed with
+    pub sighash: String,
+
+    /// Signatures contained in the input
+    pub has_signatures_from: Vec<SignerShortDetails>,
+
+    /// Signers with keys in the input that have not signed yet
+    pub missing_signatures_from: Vec<SignerShortDetails>,
+}
+
 /// Unspent Transaction Output
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct Utxo {
@@ -348,9 +938,19 @@ pub struct Tx {
     /// Net balance for the transaction
     pub balance: HashMap<String, i64>,
 
+    /// The `balance` with the amounts formatted according to the asset precision, in satoshi for
+    /// unknown assets, present only if requested `with_tickers`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub balance_formatted: Option<HashMap<String, String>>,
+
     /// Fee
     pub fee: u64,
 
+    /// The amounts of each asset burned by the transaction in explicit outputs, present only if
+    /// the transaction burns some asset
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub burned: HashMap<String, u64>,
+
     /// Type
     #[serde(rename = "type")]
     pub type_: String,
@@ -369,6 +969,18 @@ pub struct WalletTxs {
     pub txs: Vec<Tx>,
 }
 
+/// Wallet transactions exported as CSV
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct WalletExportCsv {
+    /// CSV with header `txid,timestamp,height,asset,amount,fee` and a row for each asset of each
+    /// transaction
+    ///
+    /// The asset is the ticker if known, the asset id otherwise. Amounts are formatted according
+    /// to the asset precision if known, in satoshi otherwise. The fee is in L-BTC and it is set
+    /// only in the first row of each transaction.
+    pub csv: String,
+}
+
 /// Transaction
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct WalletTx {
@@ -386,6 +998,25 @@ pub struct AssetDetails {
     pub ticker: String,
 }
 
+/// Asset data from the asset registry
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct AssetRegistryLookup {
+    /// The asset identifier
+    pub asset_id: String,
+
+    /// Name of the asset
+    pub name: String,
+
+    /// Ticker of the asset
+    pub ticker: String,
+
+    /// Precision of the asset
+    pub precision: u8,
+
+    /// The contract committed to the asset
+    pub contract: AssetContract,
+}
+
 /// Asset details
 #[derive(Debug, Serialize, Deserialize, JsonSchema, PartialOrd, Ord, PartialEq, Eq)]
 pub struct Asset {
@@ -418,6 +1049,12 @@ pub struct AssetList {
 pub struct JadeId {
     /// The jade full identifier (20 bytes as 40 hex chars), the first 4 bytes are the fingerprint
     pub identifier: String,
+
+    /// The firmware version, e.g. "1.0.27", the emulator reports the version it emulates
+    pub version: String,
+
+    /// The board type, e.g. "JADE_V1.1" or "DEV" for the emulator
+    pub board_type: String,
 }
 
 /// The wallet type // TODO move to response
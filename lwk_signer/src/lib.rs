@@ -1,7 +1,8 @@
 #![cfg_attr(not(test), deny(clippy::unwrap_used))]
 #![cfg_attr(docsrs, feature(doc_auto_cfg))]
 
-//! Contains a software signer [`SwSigner`] and an [`AnySigner`] that can be a Jade or a Software signer.
+//! Contains a software signer [`SwSigner`] and an [`AnySigner`] that can be a Jade, a Software
+//! signer or, with the `hwi` feature, a hardware wallet used through HWI.
 //!
 //! Signers should implement [`lwk_common::Signer`]
 
@@ -10,6 +11,9 @@ mod software;
 pub use crate::software::{NewError, SignError, SwSigner};
 pub use bip39;
 
+#[cfg(feature = "hwi")]
+pub use lwk_hwi;
+
 use elements_miniscript::bitcoin::bip32::{self, DerivationPath, Fingerprint};
 use elements_miniscript::elements::bitcoin::bip32::Xpub;
 use elements_miniscript::elements::pset::PartiallySignedTransaction;
@@ -29,6 +33,10 @@ pub enum SignerError {
     #[error(transparent)]
     LedgerError(#[from] lwk_ledger::Error),
 
+    #[cfg(feature = "hwi")]
+    #[error(transparent)]
+    HwiError(#[from] lwk_hwi::Error),
+
     #[error(transparent)]
     Bip32Error(#[from] bip32::Error),
 }
@@ -46,6 +54,9 @@ pub enum AnySigner {
         lwk_ledger::Ledger,
         elements_miniscript::bitcoin::XKeyIdentifier,
     ),
+
+    #[cfg(feature = "hwi")]
+    Hwi(lwk_hwi::Hwi),
 }
 
 impl Signer for AnySigner {
@@ -82,6 +93,9 @@ impl Signer for &AnySigner {
 
             #[cfg(feature = "ledger")]
             AnySigner::Ledger(signer, _) => signer.sign(pset)?,
+
+            #[cfg(feature = "hwi")]
+            AnySigner::Hwi(signer) => signer.sign(pset)?,
         })
     }
 
@@ -94,6 +108,9 @@ impl Signer for &AnySigner {
 
             #[cfg(feature = "ledger")]
             AnySigner::Ledger(s, _) => s.derive_xpub(path)?,
+
+            #[cfg(feature = "hwi")]
+            AnySigner::Hwi(s) => s.derive_xpub(path)?,
         })
     }
 
@@ -108,6 +125,9 @@ impl Signer for &AnySigner {
 
             #[cfg(feature = "ledger")]
             AnySigner::Ledger(s, _) => s.slip77_master_blinding_key()?,
+
+            #[cfg(feature = "hwi")]
+            AnySigner::Hwi(s) => s.slip77_master_blinding_key()?,
         })
     }
 
@@ -120,6 +140,9 @@ impl Signer for &AnySigner {
 
             #[cfg(feature = "ledger")]
             AnySigner::Ledger(s, _) => s.fingerprint()?,
+
+            #[cfg(feature = "hwi")]
+            AnySigner::Hwi(s) => s.fingerprint()?,
         })
     }
 }
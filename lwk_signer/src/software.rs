@@ -55,6 +55,7 @@ pub struct SwSigner {
     pub(crate) xprv: Xpriv,
     pub(crate) secp: Secp256k1<All>, // could be sign only, but it is likely the caller already has the All context.
     pub(crate) mnemonic: Option<Mnemonic>,
+    pub(crate) seed: Option<[u8; 64]>,
 }
 
 impl core::fmt::Debug for SwSigner {
@@ -69,9 +70,20 @@ impl SwSigner {
     /// Takes also a flag if the network is mainnet so that generated extended keys are in the
     /// correct form xpub/tpub (there is no need to discriminate between regtest and testnet)
     pub fn new(mnemonic: &str, is_mainnet: bool) -> Result<Self, NewError> {
+        Self::new_with_passphrase(mnemonic, "", is_mainnet)
+    }
+
+    /// Creates a new software signer from the given mnemonic and BIP-39 passphrase.
+    ///
+    /// The passphrase is only used to derive the seed and it's not kept by the signer.
+    pub fn new_with_passphrase(
+        mnemonic: &str,
+        passphrase: &str,
+        is_mainnet: bool,
+    ) -> Result<Self, NewError> {
         let secp = Secp256k1::new();
         let mnemonic: Mnemonic = mnemonic.parse()?;
-        let seed = mnemonic.to_seed("");
+        let seed = mnemonic.to_seed(passphrase);
 
         let network = if is_mainnet {
             bitcoin::Network::Bitcoin
@@ -85,11 +97,20 @@ impl SwSigner {
             xprv,
             secp,
             mnemonic: Some(mnemonic),
+            seed: Some(seed),
         })
     }
 
     pub fn random(is_mainnet: bool) -> Result<(Self, Mnemonic), NewError> {
-        let mnemonic = Mnemonic::generate(12)?;
+        Self::random_with_word_count(12, is_mainnet)
+    }
+
+    /// Creates a signer from a random mnemonic with the given number of words (12, 15, 18, 21 or 24)
+    pub fn random_with_word_count(
+        word_count: usize,
+        is_mainnet: bool,
+    ) -> Result<(Self, Mnemonic), NewError> {
+        let mnemonic = Mnemonic::generate(word_count)?;
         Ok((SwSigner::new(&mnemonic.to_string(), is_mainnet)?, mnemonic))
     }
 
@@ -98,6 +119,7 @@ impl SwSigner {
             xprv,
             secp: Secp256k1::new(),
             mnemonic: None,
+            seed: None,
         }
     }
 
@@ -106,7 +128,7 @@ impl SwSigner {
     }
 
     pub fn seed(&self) -> Option<[u8; 64]> {
-        self.mnemonic.as_ref().map(|m| m.to_seed(""))
+        self.seed
     }
 
     pub fn mnemonic(&self) -> Option<Mnemonic> {
@@ -138,11 +160,13 @@ impl Signer for SwSigner {
             messages.push(msg);
         }
 
-        // Fixme: Take a parameter
-        let hash_ty = elements_miniscript::elements::EcdsaSighashType::All;
-
         let signer_fingerprint = self.fingerprint();
         for (input, msg) in pset.inputs_mut().iter_mut().zip(messages) {
+            // The message has been computed with the input sighash type, SIGHASH_ALL if not set
+            let hash_ty = input
+                .sighash_type
+                .and_then(|t| t.ecdsa_hash_ty())
+                .unwrap_or(elements_miniscript::elements::EcdsaSighashType::All);
             for (want_public_key, (fingerprint, derivation_path)) in input.bip32_derivation.iter() {
                 if &signer_fingerprint == fingerprint {
                     let ext_derived = self.xprv.derive_priv(&self.secp, derivation_path)?;
@@ -205,6 +229,25 @@ mod tests {
         );
     }
 
+    #[test]
+    fn new_signer_with_passphrase() {
+        // BIP-39 test vector
+        let signer =
+            SwSigner::new_with_passphrase(lwk_test_util::TEST_MNEMONIC, "TREZOR", false).unwrap();
+        assert_eq!(signer.seed().unwrap().to_hex(), "c55257c360c07c72029aebc1b53c05ed0362ada38ead3e3e9efa3708e53495531f09a6987599d18264c1e1c92f2cf141630c7a3c4ab7c81b2f001698e7463b04");
+        assert_eq!(signer.fingerprint().to_string(), "b4e3f5ed");
+        assert_eq!(
+            signer.mnemonic().unwrap().to_string(),
+            lwk_test_util::TEST_MNEMONIC
+        );
+
+        let no_passphrase = SwSigner::new(lwk_test_util::TEST_MNEMONIC, false).unwrap();
+        let empty_passphrase =
+            SwSigner::new_with_passphrase(lwk_test_util::TEST_MNEMONIC, "", false).unwrap();
+        assert_eq!(no_passphrase.fingerprint(), empty_passphrase.fingerprint());
+        assert_ne!(no_passphrase.fingerprint(), signer.fingerprint());
+    }
+
     #[test]
     fn from_xprv() {
         use std::str::FromStr;
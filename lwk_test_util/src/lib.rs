@@ -154,6 +154,7 @@ impl TestElectrumServer {
             "-chain=liquidregtest",
             "-initialfreecoins=2100000000",
             "-validatepegin=0",
+            "-blockfilterindex=1",
         ];
         let network = "liquidregtest";
 
@@ -211,6 +212,12 @@ impl TestElectrumServer {
         node_generate(&self.node.client, blocks);
     }
 
+    /// The node JSON-RPC url and its user and password
+    pub fn node_rpc(&self) -> (String, (String, String)) {
+        let cookie = self.node.params.get_cookie_values().unwrap().unwrap();
+        (self.node.rpc_url(), (cookie.user, cookie.password))
+    }
+
     pub fn node_sendtoaddress(
         &self,
         address: &Address,
@@ -591,14 +598,16 @@ impl TestWollet {
         let ass2 = asset2.to_string();
         let addressees: Vec<UnvalidatedRecipient> = vec![
             UnvalidatedRecipient {
-                satoshi: 1_000,
+                satoshi: Some(1_000),
                 address: addr1,
                 asset: ass1,
+                timelock: None,
             },
             UnvalidatedRecipient {
-                satoshi: 2_000,
+                satoshi: Some(2_000),
                 address: addr2,
                 asset: ass2,
+                timelock: None,
             },
         ];
 
@@ -699,6 +708,9 @@ impl TestWollet {
         assert!(!issuance.is_reissuance);
         assert_eq!(issuance.asset_amount, Some(satoshi_asset));
         assert_eq!(issuance.token_amount, Some(satoshi_token));
+        // Issuances created by the wallet have explicit amounts
+        assert_eq!(issuance.asset_blinder, Some(ValueBlindingFactor::zero()));
+        assert_eq!(issuance.token_blinder, Some(ValueBlindingFactor::zero()));
 
         let prevout = OutPoint::new(
             issuance_input.previous_txid,
@@ -774,6 +786,7 @@ impl TestWollet {
         assert!(reissuance.is_reissuance);
         assert_eq!(reissuance.asset_amount, Some(satoshi_asset));
         assert!(reissuance.token_amount.is_none());
+        assert_eq!(reissuance.asset_blinder, Some(ValueBlindingFactor::zero()));
     }
 
     pub fn burnasset(
@@ -818,6 +831,7 @@ impl TestWollet {
         let txid = self.send(&mut pset);
         let tx = self.get_tx(&txid);
         assert_eq!(&tx.type_, "burn");
+        assert_eq!(tx.burned(), [(*asset, satoshi_asset)].into());
 
         assert_eq!(self.balance(asset), balance_asset_before - satoshi_asset);
         assert!(self.balance_btc() < balance_btc_before);
@@ -31,6 +31,12 @@ impl WalletTx {
         Ok(self.inner.balance.serialize(&serializer)?)
     }
 
+    /// The amounts of each asset burned by the transaction
+    pub fn burned(&self) -> Result<JsValue, Error> {
+        let serializer = Serializer::new().serialize_large_number_types_as_bigints(true);
+        Ok(self.inner.burned().serialize(&serializer)?)
+    }
+
     pub fn txid(&self) -> Txid {
         self.inner.txid.into()
     }
@@ -68,7 +74,8 @@ impl WalletTx {
 
     #[wasm_bindgen(js_name = unblindedUrl)]
     pub fn unblinded_url(&self, explorer_url: &str) -> String {
-        self.inner.unblinded_url(explorer_url)
+        self.inner
+            .unblinded_url(explorer_url, lwk_wollet::ExplorerKind::default())
     }
 }
 
@@ -62,6 +62,7 @@ impl WolletDescriptor {
             xpubs,
             Multisig::Wsh,
             DescriptorBlindingKey::Slip77Rand,
+            false,
         )
         .map_err(Error::Generic)?;
         let desc = lwk_wollet::WolletDescriptor::from_str(&desc)?;
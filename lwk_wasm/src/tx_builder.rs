@@ -81,9 +81,10 @@ impl TxBuilder {
         asset: &AssetId,
     ) -> Result<TxBuilder, Error> {
         let unvalidated_recipient = UnvalidatedRecipient {
-            satoshi,
+            satoshi: Some(satoshi),
             address: address.to_string(),
             asset: asset.to_string(),
+            timelock: None,
         };
         Ok(self
             .inner
@@ -1,7 +1,6 @@
 use crate::{AddressResult, Error, Network, Pset, PsetDetails, Update, WalletTx, WolletDescriptor};
 use lwk_jade::derivation_path_to_vec;
 use lwk_wollet::elements::pset::PartiallySignedTransaction;
-use lwk_wollet::elements_miniscript::ForEachKey;
 use serde::Serialize;
 use serde_wasm_bindgen::Serializer;
 use wasm_bindgen::prelude::*;
@@ -44,22 +43,8 @@ impl Wollet {
 
     #[wasm_bindgen(js_name = addressFullPath)]
     pub fn address_full_path(&self, index: u32) -> Result<Vec<u32>, Error> {
-        // TODO we should add the full path to lwk_wollet::AddressResult
-
-        let definite_desc = self
-            .inner
-            .wollet_descriptor()
-            .definite_descriptor(lwk_wollet::Chain::External, index)?;
-        let mut full_path: Vec<u32> = vec![];
-        definite_desc.for_each_key(|k| {
-            if let Some(path) = k.full_derivation_path() {
-                full_path = derivation_path_to_vec(&path);
-            }
-
-            true
-        });
-
-        Ok(full_path)
+        let address_result = self.inner.address(Some(index))?;
+        Ok(derivation_path_to_vec(&address_result.full_path()))
     }
 
     #[wasm_bindgen(js_name = applyUpdate)]
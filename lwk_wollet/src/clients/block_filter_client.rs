@@ -0,0 +1,254 @@
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Mutex, MutexGuard};
+
+use elements::bitcoin::bip158::BlockFilter;
+use elements::encode::{deserialize, serialize, Decodable};
+use elements::hashes::hex::FromHex;
+use elements::hex::ToHex;
+use elements::{bitcoin, Block, BlockHash, BlockHeader, OutPoint, Script, Transaction, Txid};
+use serde_json::{json, Value};
+
+use crate::{store::Height, BlockchainBackend, Error, MerkleProof};
+
+use super::History;
+
+/// A blockchain backend based on compact block filters ([BIP-158](https://github.com/bitcoin/bips/blob/master/bip-0158.mediawiki))
+/// served by a node through its JSON-RPC interface
+///
+/// The filter of every block since `start_height` is downloaded and matched locally against the
+/// wallet scripts, then only the matching blocks are downloaded. The node doesn't learn which
+/// scripts belong to the wallet, at the cost of more bandwidth than electrum or esplora: a
+/// filter is roughly 1/50 of the block size, moreover every matching block is downloaded in full.
+///
+/// The node must run with `-blockfilterindex=1`.
+///
+/// Transactions are seen only once confirmed, and wallet outputs received before
+/// `start_height` are not tracked, thus their spending transactions are not found.
+#[derive(Debug)]
+pub struct BlockFilterClient {
+    url: String,
+    auth: Option<(String, String)>,
+    start_height: Height,
+    cache: Mutex<Cache>,
+}
+
+#[derive(Debug, Default)]
+struct Cache {
+    /// Hash and filter of the blocks since `start_height`, in height order
+    filters: Vec<(BlockHash, BlockFilter)>,
+
+    /// The downloaded blocks, the ones matching the filters
+    blocks: HashMap<BlockHash, Block>,
+}
+
+impl BlockFilterClient {
+    /// Create a client connecting to the node JSON-RPC at `url`, with optional user and password
+    pub fn new(url: &str, auth: Option<(String, String)>, start_height: Height) -> Self {
+        Self {
+            url: url.to_string(),
+            auth,
+            start_height,
+            cache: Mutex::new(Cache::default()),
+        }
+    }
+
+    fn call(&self, method: &str, params: Value) -> Result<Value, Error> {
+        let body = json!({"jsonrpc": "1.0", "id": "lwk", "method": method, "params": params});
+        let mut request = reqwest::blocking::Client::new().post(&self.url).json(&body);
+        if let Some((user, password)) = &self.auth {
+            request = request.basic_auth(user, Some(password));
+        }
+        let mut response: Value = request.send()?.json()?;
+        match response.get("error") {
+            Some(error) if !error.is_null() => Err(Error::NodeRpc(
+                error
+                    .get("message")
+                    .and_then(|m| m.as_str())
+                    .map(|m| m.to_string())
+                    .unwrap_or_else(|| error.to_string()),
+            )),
+            _ => Ok(response["result"].take()),
+        }
+    }
+
+    fn call_str(&self, method: &str, params: Value) -> Result<String, Error> {
+        self.call(method, params)?
+            .as_str()
+            .map(|s| s.to_string())
+            .ok_or_else(|| Error::NodeRpc(format!("Unexpected result of {method}")))
+    }
+
+    fn call_hex<T: Decodable>(&self, method: &str, params: Value) -> Result<T, Error> {
+        let hex = self.call_str(method, params)?;
+        Ok(deserialize(&Vec::<u8>::from_hex(&hex)?)?)
+    }
+
+    fn block_hash(&self, height: Height) -> Result<BlockHash, Error> {
+        Ok(self.call_str("getblockhash", json!([height]))?.parse()?)
+    }
+
+    fn header(&self, block_hash: &BlockHash) -> Result<BlockHeader, Error> {
+        self.call_hex("getblockheader", json!([block_hash.to_string(), false]))
+    }
+
+    fn lock_cache(&self) -> Result<MutexGuard<Cache>, Error> {
+        self.cache.lock().map_err(|e| Error::Generic(e.to_string()))
+    }
+
+    /// Download the filters of the new blocks, after dropping the ones no longer in the best chain
+    fn sync_filters(&self, cache: &mut Cache) -> Result<(), Error> {
+        let tip_height = self
+            .call("getblockcount", json!([]))?
+            .as_u64()
+            .ok_or_else(|| Error::NodeRpc("Unexpected result of getblockcount".into()))?
+            as Height;
+
+        while let Some((block_hash, _)) = cache.filters.last() {
+            let height = self.start_height + cache.filters.len() as Height - 1;
+            if height <= tip_height && self.block_hash(height)? == *block_hash {
+                break;
+            }
+            let block_hash = *block_hash;
+            cache.filters.pop();
+            cache.blocks.remove(&block_hash);
+        }
+
+        for height in (self.start_height + cache.filters.len() as Height)..=tip_height {
+            let block_hash = self.block_hash(height)?;
+            let result = self.call("getblockfilter", json!([block_hash.to_string(), "basic"]))?;
+            let filter = result["filter"]
+                .as_str()
+                .ok_or_else(|| Error::NodeRpc("Unexpected result of getblockfilter".into()))?;
+            let filter = BlockFilter::new(&Vec::<u8>::from_hex(filter)?);
+            cache.filters.push((block_hash, filter));
+        }
+        Ok(())
+    }
+}
+
+impl BlockchainBackend for BlockFilterClient {
+    fn tip(&mut self) -> Result<BlockHeader, Error> {
+        let block_hash: BlockHash = self.call_str("getbestblockhash", json!([]))?.parse()?;
+        self.header(&block_hash)
+    }
+
+    fn broadcast(&self, tx: &Transaction) -> Result<Txid, Error> {
+        let txid = self
+            .call_str("sendrawtransaction", json!([serialize(tx).to_hex()]))
+            .map_err(|e| match e {
+                Error::NodeRpc(message) => Error::BroadcastRejected(message),
+                e => e,
+            })?;
+        Ok(txid.parse()?)
+    }
+
+    fn get_transactions(&self, txids: &[Txid]) -> Result<Vec<Transaction>, Error> {
+        let cache = self.lock_cache()?;
+        let mut cached: HashMap<Txid, &Transaction> = HashMap::new();
+        for tx in cache.blocks.values().flat_map(|b| b.txdata.iter()) {
+            cached.insert(tx.txid(), tx);
+        }
+        txids
+            .iter()
+            .map(|txid| match cached.get(txid) {
+                Some(tx) => Ok((*tx).clone()),
+                None => self.call_hex("getrawtransaction", json!([txid.to_string()])),
+            })
+            .collect()
+    }
+
+    fn get_headers(
+        &self,
+        heights: &[Height],
+        height_blockhash: &HashMap<Height, BlockHash>,
+    ) -> Result<Vec<BlockHeader>, Error> {
+        heights
+            .iter()
+            .map(|height| {
+                let block_hash = match height_blockhash.get(height) {
+                    Some(block_hash) => *block_hash,
+                    None => self.block_hash(*height)?,
+                };
+                self.header(&block_hash)
+            })
+            .collect()
+    }
+
+    fn get_merkle_proofs(&self, txs: &[(Txid, Height)]) -> Result<Vec<MerkleProof>, Error> {
+        let mut cache = self.lock_cache()?;
+        let mut result = vec![];
+        for (txid, height) in txs.iter() {
+            let block_hash = self.block_hash(*height)?;
+            let block = match cache.blocks.entry(block_hash) {
+                Entry::Occupied(e) => e.into_mut(),
+                Entry::Vacant(e) => {
+                    e.insert(self.call_hex("getblock", json!([block_hash.to_string(), 0]))?)
+                }
+            };
+            let txids: Vec<Txid> = block.txdata.iter().map(|tx| tx.txid()).collect();
+            let proof = txids
+                .iter()
+                .position(|t| t == txid)
+                .and_then(|pos| MerkleProof::from_txids(block.header.clone(), &txids, pos as u32))
+                .ok_or_else(|| Error::NodeRpc(format!("{txid} is not in block {block_hash}")))?;
+            result.push(proof);
+        }
+        Ok(result)
+    }
+
+    fn get_scripts_history(&self, scripts: &[&Script]) -> Result<Vec<Vec<History>>, Error> {
+        let mut cache = self.lock_cache()?;
+        self.sync_filters(&mut cache)?;
+        let Cache { filters, blocks } = &mut *cache;
+
+        let script_index: HashMap<&[u8], usize> = scripts
+            .iter()
+            .enumerate()
+            .map(|(i, s)| (s.as_bytes(), i))
+            .collect();
+        // The outputs received by the scripts, to find the transactions spending them
+        let mut outpoint_index: HashMap<OutPoint, usize> = HashMap::new();
+        let mut result: Vec<Vec<History>> = scripts.iter().map(|_| vec![]).collect();
+
+        for (i, (block_hash, filter)) in filters.iter().enumerate() {
+            let filter_hash = bitcoin::BlockHash::from_raw_hash(block_hash.to_raw_hash());
+            let query = scripts.iter().map(|s| s.as_bytes());
+            if !filter.match_any(&filter_hash, query)? {
+                continue;
+            }
+            let block = match blocks.entry(*block_hash) {
+                Entry::Occupied(e) => e.into_mut(),
+                Entry::Vacant(e) => {
+                    e.insert(self.call_hex("getblock", json!([block_hash.to_string(), 0]))?)
+                }
+            };
+            let height = (self.start_height + i as Height) as i32;
+
+            for tx in block.txdata.iter() {
+                let txid = tx.txid();
+                let mut involved = HashSet::new();
+                for input in tx.input.iter() {
+                    if let Some(i) = outpoint_index.get(&input.previous_output) {
+                        involved.insert(*i);
+                    }
+                }
+                for (vout, output) in tx.output.iter().enumerate() {
+                    if let Some(i) = script_index.get(output.script_pubkey.as_bytes()) {
+                        outpoint_index.insert(OutPoint::new(txid, vout as u32), *i);
+                        involved.insert(*i);
+                    }
+                }
+                for i in involved {
+                    result[i].push(History {
+                        txid,
+                        height,
+                        block_hash: Some(*block_hash),
+                        block_timestamp: None,
+                    });
+                }
+            }
+        }
+        Ok(result)
+    }
+}
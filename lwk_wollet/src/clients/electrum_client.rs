@@ -1,13 +1,17 @@
 use crate::store::Height;
-use crate::Error;
+use crate::{Error, MerkleProof, Wollet};
 use electrum_client::ScriptStatus;
-use electrum_client::{Client, ConfigBuilder, ElectrumApi, GetHistoryRes};
+use electrum_client::{Client, ConfigBuilder, ElectrumApi, GetHistoryRes, Socks5Config};
 use elements::encode::deserialize as elements_deserialize;
 use elements::encode::serialize as elements_serialize;
+use elements::hashes::{sha256d, Hash};
 use elements::Address;
 use elements::{bitcoin, BlockHash, BlockHeader, Script, Transaction, Txid};
 use std::collections::HashMap;
 use std::fmt::Debug;
+use std::net::SocketAddr;
+use std::sync::Mutex;
+use std::time::Duration;
 
 use super::History;
 
@@ -18,8 +22,13 @@ pub struct ElectrumClient {
     tip: BlockHeader,
 
     script_status: HashMap<Script, ScriptStatus>,
+
+    /// Status and history of the scripts queried, only the changed ones are queried again
+    scripts_history: Mutex<ScriptsHistory>,
 }
 
+type ScriptsHistory = HashMap<Script, (Option<ScriptStatus>, Vec<History>)>;
+
 #[derive(Debug, Clone)]
 pub enum ElectrumUrl {
     Tls(String, bool), // the bool value indicates if the domain name should be validated
@@ -50,9 +59,51 @@ impl ElectrumUrl {
             }
             ElectrumUrl::Plaintext(url) => (format!("tcp://{}", url), builder),
         };
-        let builder = builder.timeout(options.timeout);
+        let builder = builder
+            .timeout(options.timeout)
+            .retry(options.retry.unwrap_or(DEFAULT_RETRY))
+            .socks5(options.proxy.map(Socks5Config::new));
         Ok(Client::from_config(&url, builder.build())?)
     }
+
+    /// Build the client, retrying with exponential backoff if the connection fails
+    fn connect(&self, options: &ElectrumOptions) -> Result<Client, Error> {
+        let retry = options.retry.unwrap_or(0);
+        let mut attempt = 0;
+        loop {
+            match self.build_client(options) {
+                Err(Error::ClientError(e)) if connection_failed(&e) && attempt < retry => {
+                    attempt += 1;
+                    std::thread::sleep(backoff(attempt));
+                }
+                Err(Error::ClientError(e)) if connection_failed(&e) => {
+                    return Err(match options.proxy {
+                        Some(proxy) => Error::ElectrumProxy(proxy, e.to_string()),
+                        None => e.into(),
+                    })
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+/// Whether the connection failed, a refused connection is reported as failed attempts to each
+/// of the resolved addresses
+fn connection_failed(error: &electrum_client::Error) -> bool {
+    match error {
+        electrum_client::Error::IOError(_) => true,
+        electrum_client::Error::AllAttemptsErrored(errors) => errors.iter().all(connection_failed),
+        _ => false,
+    }
+}
+
+/// Retries of the underlying client if not specified
+const DEFAULT_RETRY: u8 = 1;
+
+/// Wait time before the given retry, the same used by the underlying client
+fn backoff(attempt: u8) -> Duration {
+    Duration::from_secs((1u64 << attempt).min(30))
 }
 
 impl Debug for ElectrumClient {
@@ -63,9 +114,22 @@ impl Debug for ElectrumClient {
     }
 }
 
-#[derive(Default)]
+/// Options of the [`ElectrumClient`]
+#[derive(Debug, Clone, Default)]
 pub struct ElectrumOptions {
-    timeout: Option<u8>,
+    /// Timeout in seconds for connecting to and reading from the server
+    pub timeout: Option<u8>,
+
+    /// Number of retries of requests failing for network errors, with exponential backoff
+    ///
+    /// Errors returned by the server, such as a rejected broadcast, are not retried.
+    /// If `None` requests are retried once, while a failed connection is not retried.
+    pub retry: Option<u8>,
+
+    /// SOCKS5 proxy the connections go through, such as a Tor daemon
+    ///
+    /// The server domain is resolved by the proxy, thus onion addresses can be used.
+    pub proxy: Option<SocketAddr>,
 }
 
 impl ElectrumClient {
@@ -76,7 +140,7 @@ impl ElectrumClient {
 
     /// Creates an Electrum client specifying non default options like timeout
     pub fn with_options(url: &ElectrumUrl, options: ElectrumOptions) -> Result<Self, Error> {
-        let client = url.build_client(&options)?;
+        let client = url.connect(&options)?;
         let header = client.block_headers_subscribe_raw()?;
         let tip: BlockHeader = elements_deserialize(&header.header)?;
 
@@ -84,6 +148,7 @@ impl ElectrumClient {
             client,
             tip,
             script_status: HashMap::new(),
+            scripts_history: Mutex::new(HashMap::new()),
         })
     }
 
@@ -129,7 +194,17 @@ impl super::BlockchainBackend for ElectrumClient {
     fn broadcast(&self, tx: &Transaction) -> Result<Txid, Error> {
         let txid = self
             .client
-            .transaction_broadcast_raw(&elements_serialize(tx))?;
+            .transaction_broadcast_raw(&elements_serialize(tx))
+            .map_err(|e| match e {
+                electrum_client::Error::Protocol(value) => Error::BroadcastRejected(
+                    value
+                        .get("message")
+                        .and_then(|m| m.as_str())
+                        .map(|m| m.to_string())
+                        .unwrap_or_else(|| value.to_string()),
+                ),
+                e => e.into(),
+            })?;
         Ok(Txid::from_raw_hash(txid.to_raw_hash()))
     }
 
@@ -160,19 +235,145 @@ impl super::BlockchainBackend for ElectrumClient {
         Ok(result)
     }
 
+    fn get_merkle_proofs(&self, txs: &[(Txid, Height)]) -> Result<Vec<MerkleProof>, Error> {
+        let heights: Vec<Height> = txs.iter().map(|(_, height)| *height).collect();
+        let headers = self.get_headers(&heights, &HashMap::new())?;
+        txs.iter()
+            .zip(headers)
+            .map(|((txid, height), header)| {
+                let bitcoin_txid = bitcoin::Txid::from_raw_hash(txid.to_raw_hash());
+                let res = self
+                    .client
+                    .transaction_get_merkle(&bitcoin_txid, *height as usize)?;
+                // The hashes are returned in display order
+                let merkle = res
+                    .merkle
+                    .into_iter()
+                    .map(|mut bytes| {
+                        bytes.reverse();
+                        sha256d::Hash::from_byte_array(bytes)
+                    })
+                    .collect();
+                Ok(MerkleProof {
+                    txid: *txid,
+                    header,
+                    pos: res.pos as u32,
+                    merkle,
+                })
+            })
+            .collect()
+    }
+
+    /// Scripts are subscribed, so that on the next calls their history is queried only if their
+    /// status changed, making scans of already synced wallets much cheaper
     fn get_scripts_history(&self, scripts: &[&Script]) -> Result<Vec<Vec<History>>, Error> {
-        let scripts: Vec<&bitcoin::Script> = scripts
-            .iter()
-            .map(|t| bitcoin::Script::from_bytes(t.as_bytes()))
-            .collect();
+        let mut cache = self
+            .scripts_history
+            .lock()
+            .map_err(|e| Error::Generic(e.to_string()))?;
+        // Receive the pending status notifications
+        self.client.ping()?;
 
-        Ok(self
-            .client
-            .batch_script_get_history(&scripts)?
-            .into_iter()
-            .map(|e| e.into_iter().map(Into::into).collect())
+        let (known, new): (Vec<&Script>, Vec<&Script>) =
+            scripts.iter().partition(|s| cache.contains_key(**s));
+        // The scripts to query with their status, if `None` the history is not cached
+        let mut changed: Vec<(&Script, Option<Option<ScriptStatus>>)> = vec![];
+        for script in known {
+            let bitcoin_script = bitcoin::Script::from_bytes(script.as_bytes());
+            let status = match self.client.script_subscribe(bitcoin_script) {
+                // Not subscribed with the current connection, e.g. after a reconnection
+                Ok(status) => status,
+                // The status changed only if there is a notification
+                Err(electrum_client::Error::AlreadySubscribed(_)) => {
+                    let mut last = None;
+                    while let Some(status) = self.client.script_pop(bitcoin_script)? {
+                        last = Some(status);
+                    }
+                    match last {
+                        Some(status) => Some(status),
+                        None => continue,
+                    }
+                }
+                Err(e) => return Err(e.into()),
+            };
+            if cache
+                .get(script)
+                .map_or(true, |(cached, _)| *cached != status)
+            {
+                changed.push((script, Some(status)));
+            }
+        }
+        if !new.is_empty() {
+            let bitcoin_scripts: Vec<&bitcoin::Script> = new
+                .iter()
+                .map(|s| bitcoin::Script::from_bytes(s.as_bytes()))
+                .collect();
+            match self.client.batch_script_subscribe(&bitcoin_scripts) {
+                Ok(statuses) => changed.extend(new.into_iter().zip(statuses.into_iter().map(Some))),
+                // Some already subscribed, e.g. by `address_status`, the others may be marked as
+                // subscribed without being so, thus their notifications can't be trusted
+                Err(electrum_client::Error::AlreadySubscribed(_)) => {
+                    changed.extend(new.into_iter().map(|s| (s, None)))
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+
+        let mut uncached = HashMap::new();
+        if !changed.is_empty() {
+            let bitcoin_scripts: Vec<&bitcoin::Script> = changed
+                .iter()
+                .map(|(s, _)| bitcoin::Script::from_bytes(s.as_bytes()))
+                .collect();
+            let histories = match self.client.batch_script_get_history(&bitcoin_scripts) {
+                Ok(histories) => histories,
+                Err(e) => {
+                    // The notifications are consumed, don't rely on the cached status anymore
+                    for (script, _) in changed {
+                        cache.remove(script);
+                    }
+                    return Err(e.into());
+                }
+            };
+            for ((script, status), history) in changed.into_iter().zip(histories) {
+                let history: Vec<History> = history.into_iter().map(Into::into).collect();
+                match status {
+                    Some(status) => {
+                        cache.insert(script.clone(), (status, history));
+                    }
+                    None => {
+                        uncached.insert(script, history);
+                    }
+                }
+            }
+        }
+
+        Ok(scripts
+            .iter()
+            .map(|s| match uncached.remove(*s) {
+                Some(history) => history,
+                None => cache.get(*s).map(|(_, h)| h.clone()).unwrap_or_default(),
+            })
             .collect())
     }
+
+    /// Scripts are also unsubscribed, so that the server stops sending their notifications
+    fn forget_wollet(&self, wollet: &Wollet) -> Result<(), Error> {
+        let mut cache = self
+            .scripts_history
+            .lock()
+            .map_err(|e| Error::Generic(e.to_string()))?;
+        for script in wollet.store.cache.paths.keys() {
+            if cache.remove(script).is_some() {
+                let bitcoin_script = bitcoin::Script::from_bytes(script.as_bytes());
+                match self.client.script_unsubscribe(bitcoin_script) {
+                    Ok(_) | Err(electrum_client::Error::NotSubscribed(_)) => (),
+                    Err(e) => return Err(e.into()),
+                }
+            }
+        }
+        Ok(())
+    }
 }
 
 impl From<GetHistoryRes> for History {
@@ -185,3 +386,115 @@ impl From<GetHistoryRes> for History {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BlockchainBackend;
+    use elements::hex::ToHex;
+    use elements::BlockExtData;
+    use elements::{hashes::Hash, TxMerkleNode};
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::{TcpListener, TcpStream};
+
+    /// Answer `blockchain.headers.subscribe` requests with a dummy header
+    fn serve_headers(stream: TcpStream) {
+        let header = BlockHeader {
+            version: 0,
+            prev_blockhash: BlockHash::all_zeros(),
+            merkle_root: TxMerkleNode::all_zeros(),
+            time: 0,
+            height: 1,
+            ext: BlockExtData::Proof {
+                challenge: Script::new(),
+                solution: Script::new(),
+            },
+        };
+        let header_hex = elements_serialize(&header).to_hex();
+        let mut writer = stream.try_clone().unwrap();
+        for line in BufReader::new(stream).lines() {
+            let request: serde_json::Value = serde_json::from_str(&line.unwrap()).unwrap();
+            let response = serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": request["id"],
+                "result": {"hex": header_hex, "height": 1},
+            });
+            writeln!(writer, "{}", response).unwrap();
+        }
+    }
+
+    /// A server dropping the first `failures` connections, then answering the requests
+    fn flaky_server(failures: usize) -> ElectrumUrl {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = ElectrumUrl::new(&listener.local_addr().unwrap().to_string(), false, false);
+        std::thread::spawn(move || {
+            for (i, stream) in listener.incoming().enumerate() {
+                let stream = stream.unwrap();
+                if i >= failures {
+                    std::thread::spawn(move || serve_headers(stream));
+                }
+            }
+        });
+        url
+    }
+
+    /// A server refusing the connections until it starts listening after `delay`
+    fn late_server(delay: Duration) -> ElectrumUrl {
+        // A free port where nothing is listening yet
+        let addr = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        std::thread::spawn(move || {
+            std::thread::sleep(delay);
+            let listener = TcpListener::bind(addr).unwrap();
+            for stream in listener.incoming() {
+                let stream = stream.unwrap();
+                std::thread::spawn(move || serve_headers(stream));
+            }
+        });
+        ElectrumUrl::new(&addr.to_string(), false, false)
+    }
+
+    #[test]
+    fn test_electrum_retry() {
+        let options = |retry| ElectrumOptions {
+            timeout: Some(5),
+            retry: Some(retry),
+            proxy: None,
+        };
+
+        let url = flaky_server(1);
+        let err = ElectrumClient::with_options(&url, options(0)).unwrap_err();
+        assert!(matches!(err, Error::ClientError(_)));
+
+        let url = flaky_server(1);
+        let mut client = ElectrumClient::with_options(&url, options(1)).unwrap();
+        assert_eq!(client.tip().unwrap().height, 1);
+
+        // The first connection is refused, the server is up before the retry
+        let url = late_server(backoff(1) / 2);
+        let err = ElectrumClient::with_options(&url, options(0)).unwrap_err();
+        assert!(matches!(err, Error::ClientError(_)));
+
+        let url = late_server(backoff(1) / 2);
+        let mut client = ElectrumClient::with_options(&url, options(1)).unwrap();
+        assert_eq!(client.tip().unwrap().height, 1);
+    }
+
+    #[test]
+    fn test_electrum_proxy_unreachable() {
+        // A free port where nothing is listening
+        let proxy = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let url = ElectrumUrl::new("example.onion:50001", false, false);
+        let options = ElectrumOptions {
+            proxy: Some(proxy),
+            ..Default::default()
+        };
+        let err = ElectrumClient::with_options(&url, options).unwrap_err();
+        assert!(matches!(err, Error::ElectrumProxy(p, _) if p == proxy));
+    }
+}
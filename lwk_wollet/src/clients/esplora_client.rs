@@ -2,7 +2,7 @@ use std::{collections::HashMap, str::FromStr};
 
 use elements::{
     encode::Decodable,
-    hashes::{hex::FromHex, sha256, Hash},
+    hashes::{hex::FromHex, sha256, sha256d, Hash},
     hex::ToHex,
     pset::serialize::Serialize,
     BlockHash, Script, Txid,
@@ -10,7 +10,7 @@ use elements::{
 use reqwest::blocking::Response;
 use serde::Deserialize;
 
-use crate::{store::Height, BlockchainBackend, Error};
+use crate::{store::Height, BlockchainBackend, Error, MerkleProof};
 
 use super::History;
 
@@ -54,6 +54,9 @@ impl BlockchainBackend for EsploraClient {
         let tx_bytes = tx.serialize();
         let client = reqwest::blocking::Client::new();
         let response = client.post(&self.broadcast_url).body(tx_bytes).send()?;
+        if !response.status().is_success() {
+            return Err(Error::BroadcastRejected(response.text()?));
+        }
         let txid = elements::Txid::from_str(&response.text()?)?;
         Ok(txid)
     }
@@ -114,6 +117,30 @@ impl BlockchainBackend for EsploraClient {
         }
         Ok(result)
     }
+
+    fn get_merkle_proofs(&self, txs: &[(Txid, Height)]) -> Result<Vec<MerkleProof>, Error> {
+        let heights: Vec<Height> = txs.iter().map(|(_, height)| *height).collect();
+        let headers = self.get_headers(&heights, &HashMap::new())?;
+        let mut result = vec![];
+        for ((txid, _), header) in txs.iter().zip(headers) {
+            let url = format!("{}/tx/{}/merkle-proof", self.base_url, txid);
+            let response = get_with_retry(&url, 0)?;
+            let proof: EsploraMerkleProof = response.json()?;
+            // The hashes are in display order, as parsed by `FromStr`
+            let merkle = proof
+                .merkle
+                .iter()
+                .map(|hash| sha256d::Hash::from_str(hash))
+                .collect::<Result<_, _>>()?;
+            result.push(MerkleProof {
+                txid: *txid,
+                header,
+                pos: proof.pos,
+                merkle,
+            });
+        }
+        Ok(result)
+    }
 }
 
 fn get_with_retry(url: &str, attempt: usize) -> Result<Response, Error> {
@@ -152,6 +179,12 @@ impl From<EsploraTx> for History {
     }
 }
 
+#[derive(Deserialize)]
+struct EsploraMerkleProof {
+    merkle: Vec<String>,
+    pos: u32,
+}
+
 #[derive(Deserialize)]
 struct EsploraTx {
     txid: elements::Txid,
@@ -3,7 +3,7 @@
 
 use super::{try_unblind, History};
 use crate::{
-    store::{Height, Store, Timestamp, BATCH_SIZE},
+    store::{Height, Store, Timestamp},
     update::DownloadTxResult,
     Chain, Error, Update, Wollet, WolletDescriptor,
 };
@@ -188,7 +188,8 @@ impl EsploraWasmClient {
         } = if self.waterfall {
             self.get_history_waterfall(&descriptor, store).await?
         } else {
-            self.get_history(&descriptor, store).await?
+            self.get_history(&descriptor, store, wollet.gap_limit())
+                .await?
         };
 
         let tip = self.tip().await?;
@@ -266,13 +267,14 @@ impl EsploraWasmClient {
         &mut self,
         descriptor: &WolletDescriptor,
         store: &Store,
+        gap_limit: u32,
     ) -> Result<Data, Error> {
         let mut data = Data::default();
         for descriptor in descriptor.descriptor().clone().into_single_descriptors()? {
             let mut batch_count = 0;
             let chain: Chain = (&descriptor).try_into().unwrap_or(Chain::External);
             loop {
-                let batch = store.get_script_batch(batch_count, &descriptor)?;
+                let batch = store.get_script_batch(batch_count, &descriptor, gap_limit)?;
 
                 let s: Vec<_> = batch.value.iter().map(|e| &e.0).collect();
                 let result: Vec<Vec<History>> = self.get_scripts_history(&s).await?;
@@ -288,10 +290,10 @@ impl EsploraWasmClient {
                 if let Some(max) = max {
                     match chain {
                         Chain::External => {
-                            data.last_unused.external = 1 + max + batch_count * BATCH_SIZE
+                            data.last_unused.external = 1 + max + batch_count * gap_limit
                         }
                         Chain::Internal => {
-                            data.last_unused.internal = 1 + max + batch_count * BATCH_SIZE
+                            data.last_unused.internal = 1 + max + batch_count * gap_limit
                         }
                     }
                 };
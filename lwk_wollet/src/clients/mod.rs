@@ -1,11 +1,11 @@
 use crate::{
-    store::{Height, Store, Timestamp, BATCH_SIZE},
-    update::{DownloadTxResult, Update},
-    Chain, Error, Wollet, WolletDescriptor, EC,
+    store::{Height, Store, Timestamp},
+    update::{DownloadTxResult, Update, VerifiableUpdate},
+    Chain, Error, HeaderChain, MerkleProof, Wollet, WolletDescriptor, EC,
 };
 use elements::{
     bitcoin::bip32::ChildNumber,
-    confidential::{Asset, Nonce, Value},
+    confidential::{Asset, AssetBlindingFactor, Nonce, Value, ValueBlindingFactor},
     OutPoint, Script, TxOut, TxOutSecrets,
 };
 use elements::{BlockHash, BlockHeader, Transaction, Txid};
@@ -23,6 +23,12 @@ pub(crate) mod electrum_client;
 #[cfg(feature = "esplora_wasm")]
 pub(crate) mod esplora_wasm_client;
 
+#[cfg(feature = "bip158")]
+pub(crate) mod block_filter_client;
+
+/// Number of headers requested at once when validating a [`HeaderChain`]
+const HEADERS_BATCH_SIZE: usize = 1000;
+
 /// Trait implemented by types that can fetch data from a blockchain data source.
 pub trait BlockchainBackend {
     /// Get the blockchain latest block
@@ -46,10 +52,78 @@ pub trait BlockchainBackend {
     /// Get the transactions involved in a list of scripts
     fn get_scripts_history(&self, scripts: &[&Script]) -> Result<Vec<Vec<History>>, Error>;
 
+    /// Get the proofs that the transactions are included in the blocks at the given heights
+    ///
+    /// Backends that cannot provide proofs return [`Error::MerkleProofsUnsupported`].
+    fn get_merkle_proofs(&self, _txs: &[(Txid, Height)]) -> Result<Vec<MerkleProof>, Error> {
+        Err(Error::MerkleProofsUnsupported)
+    }
+
+    /// Drop what is cached about the scripts of a wallet that will not be scanned anymore
+    ///
+    /// Backends without a cache have nothing to drop.
+    fn forget_wollet(&self, _wollet: &Wollet) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Like [`BlockchainBackend::full_scan()`], also fetching the proofs of the confirmed
+    /// transactions, so that the update can be verified by a wallet not trusting this backend
+    fn full_scan_verifiable(&mut self, wollet: &Wollet) -> Result<Option<VerifiableUpdate>, Error> {
+        let update = match self.full_scan(wollet)? {
+            Some(update) => update,
+            None => return Ok(None),
+        };
+        let confirmed: Vec<(Txid, Height)> = update
+            .txid_height_new
+            .iter()
+            .filter_map(|(txid, height)| height.map(|h| (*txid, h)))
+            .collect();
+        let proofs = self.get_merkle_proofs(&confirmed)?;
+        Ok(Some(VerifiableUpdate { update, proofs }))
+    }
+
+    /// Like [`BlockchainBackend::full_scan_verifiable()`], also validating the headers up to the
+    /// tip in `headers`, which is kept across scans, and verifying the update against them
+    ///
+    /// The confirmation heights and timestamps are thus proven by the block signatures, if any
+    /// proof fails the update is rejected.
+    fn full_scan_spv(
+        &mut self,
+        wollet: &Wollet,
+        headers: &mut HeaderChain,
+    ) -> Result<Option<VerifiableUpdate>, Error> {
+        let update = match self.full_scan_verifiable(wollet)? {
+            Some(update) => update,
+            None => return Ok(None),
+        };
+        self.sync_headers(headers, update.update.tip.height)?;
+        update.verify_with_headers(headers)?;
+        Ok(Some(update))
+    }
+
+    /// Validate the headers up to `height` in `headers`, following reorgs
+    fn sync_headers(&self, headers: &mut HeaderChain, height: Height) -> Result<(), Error> {
+        // The last height shared with the server chain
+        let mut start = headers.tip_height().min(height);
+        while start > headers.start_height() {
+            let header = self.get_headers(&[start], &HashMap::new())?;
+            if header.first().map_or(false, |h| headers.contains(h)) {
+                break;
+            }
+            start -= 1;
+        }
+        let heights: Vec<Height> = ((start + 1)..=height).collect();
+        for heights in heights.chunks(HEADERS_BATCH_SIZE) {
+            headers.extend(&self.get_headers(heights, &HashMap::new())?)?;
+        }
+        Ok(())
+    }
+
     /// Scan the blockchain for the scripts generated by a watch-only wallet
     fn full_scan(&mut self, wollet: &Wollet) -> Result<Option<Update>, Error> {
         let descriptor = wollet.wollet_descriptor();
         let store = &wollet.store;
+        let gap_limit = wollet.gap_limit();
         let mut txid_height = HashMap::new();
         let mut scripts = HashMap::new();
 
@@ -61,7 +135,7 @@ pub trait BlockchainBackend {
             let mut batch_count = 0;
             let chain: Chain = (&descriptor).try_into().unwrap_or(Chain::External);
             loop {
-                let batch = store.get_script_batch(batch_count, &descriptor)?;
+                let batch = store.get_script_batch(batch_count, &descriptor, gap_limit)?;
 
                 let s: Vec<_> = batch.value.iter().map(|e| &e.0).collect();
                 let result: Vec<Vec<History>> = self.get_scripts_history(&s)?;
@@ -76,12 +150,8 @@ pub trait BlockchainBackend {
                     .max();
                 if let Some(max) = max {
                     match chain {
-                        Chain::External => {
-                            last_unused_external = 1 + max + batch_count * BATCH_SIZE
-                        }
-                        Chain::Internal => {
-                            last_unused_internal = 1 + max + batch_count * BATCH_SIZE
-                        }
+                        Chain::External => last_unused_external = 1 + max + batch_count * gap_limit,
+                        Chain::Internal => last_unused_internal = 1 + max + batch_count * gap_limit,
                     }
                 };
 
@@ -97,6 +167,9 @@ pub trait BlockchainBackend {
                     // but we threat those tx the same
                     let height = el.height.max(0);
                     let txid = el.txid;
+                    if height > 0 && wollet.start_height().unwrap_or(0) > height as u32 {
+                        continue;
+                    }
                     if height == 0 {
                         txid_height.insert(txid, None);
                     } else {
@@ -244,7 +317,7 @@ pub trait BlockchainBackend {
     }
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Clone)]
 /// Position of a transaction involving a certain script
 pub struct History {
     /// Transaction ID
@@ -272,6 +345,13 @@ pub fn try_unblind(output: TxOut, descriptor: &WolletDescriptor) -> Result<TxOut
 
             Ok(txout_secrets)
         }
+        // Explicit outputs, for instance the ones of swaps, do not need unblinding
+        (Asset::Explicit(asset), Value::Explicit(value), _) => Ok(TxOutSecrets::new(
+            asset,
+            AssetBlindingFactor::zero(),
+            value,
+            ValueBlindingFactor::zero(),
+        )),
         _ => Err(Error::Generic(
             "received unconfidential or null asset/value/nonce".into(),
         )),
@@ -282,11 +362,37 @@ pub fn try_unblind(output: TxOut, descriptor: &WolletDescriptor) -> Result<TxOut
 mod tests {
     use std::time::Instant;
 
+    use elements::{confidential::Asset, confidential::Value, AssetId, Script, TxOut};
+
     use crate::{
         clients::esplora_client::EsploraClient, BlockchainBackend, ElectrumClient, ElectrumUrl,
-        ElementsNetwork,
+        ElementsNetwork, WolletDescriptor,
     };
 
+    use super::{try_unblind, History};
+    use crate::{store::Height, Error};
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_try_unblind_explicit() {
+        let desc: WolletDescriptor = lwk_test_util::TEST_DESCRIPTOR.parse().unwrap();
+        let asset = AssetId::default();
+        let mut output = TxOut {
+            asset: Asset::Explicit(asset),
+            value: Value::Explicit(1000),
+            script_pubkey: Script::new(),
+            ..Default::default()
+        };
+        let secrets = try_unblind(output.clone(), &desc).unwrap();
+        assert_eq!(secrets.asset, asset);
+        assert_eq!(secrets.value, 1000);
+        assert_eq!(secrets.asset_bf.to_string(), "0".repeat(64));
+        assert_eq!(secrets.value_bf.to_string(), "0".repeat(64));
+
+        output.value = Value::Null;
+        assert!(try_unblind(output, &desc).is_err());
+    }
+
     #[test]
     #[ignore = "test with prod servers"]
     fn esplora_electrum_compare() {
@@ -350,4 +456,37 @@ mod tests {
             );
         }
     }
+
+    struct NoProofs;
+
+    impl BlockchainBackend for NoProofs {
+        fn tip(&mut self) -> Result<elements::BlockHeader, Error> {
+            unimplemented!()
+        }
+        fn broadcast(&self, _tx: &elements::Transaction) -> Result<elements::Txid, Error> {
+            unimplemented!()
+        }
+        fn get_transactions(
+            &self,
+            _txids: &[elements::Txid],
+        ) -> Result<Vec<elements::Transaction>, Error> {
+            unimplemented!()
+        }
+        fn get_headers(
+            &self,
+            _heights: &[Height],
+            _height_blockhash: &HashMap<Height, elements::BlockHash>,
+        ) -> Result<Vec<elements::BlockHeader>, Error> {
+            unimplemented!()
+        }
+        fn get_scripts_history(&self, _scripts: &[&Script]) -> Result<Vec<Vec<History>>, Error> {
+            unimplemented!()
+        }
+    }
+
+    #[test]
+    fn test_merkle_proofs_unsupported() {
+        let err = NoProofs.get_merkle_proofs(&[]).unwrap_err();
+        assert!(matches!(err, Error::MerkleProofsUnsupported));
+    }
 }
@@ -0,0 +1,184 @@
+use crate::model::WalletTxOut;
+
+/// Maximum number of branches explored by [`CoinSelection::BranchAndBound`]
+const BNB_MAX_TRIES: usize = 100_000;
+
+/// Strategy to select the wallet utxos spent by a transaction
+///
+/// If not set, the utxos of the assets are spent in the order they are returned by the wallet
+/// until the amount is covered, while all the L-BTC utxos are spent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CoinSelection {
+    /// Spend the utxos with the highest value first, minimizing the number of inputs
+    LargestFirst,
+
+    /// Spend the utxos with the lowest value first, consolidating the wallet utxos
+    SmallestFirst,
+
+    /// Search the utxos whose sum is closest to the amount needed, minimizing the change
+    ///
+    /// For assets other than L-BTC, if the utxos match the amount exactly, the change output is
+    /// omitted. If the search doesn't find a solution, it falls back to
+    /// [`CoinSelection::LargestFirst`].
+    BranchAndBound,
+}
+
+/// Select the utxos covering `target`, the value of each utxo is reduced by `input_cost`, the
+/// fee needed to spend it
+///
+/// If the utxos are not enough, they're all returned.
+pub(crate) fn select_utxos(
+    strategy: CoinSelection,
+    utxos: Vec<WalletTxOut>,
+    target: u64,
+    input_cost: u64,
+) -> Vec<WalletTxOut> {
+    let values: Vec<u64> = utxos
+        .iter()
+        .map(|u| u.unblinded.value.saturating_sub(input_cost))
+        .collect();
+    match select(strategy, &values, target) {
+        Some(indexes) => indexes.into_iter().map(|i| utxos[i].clone()).collect(),
+        None => utxos,
+    }
+}
+
+/// The indexes of the `values` selected to cover `target`, `None` if they are not enough
+fn select(strategy: CoinSelection, values: &[u64], target: u64) -> Option<Vec<usize>> {
+    let mut sorted: Vec<usize> = (0..values.len()).filter(|i| values[*i] > 0).collect();
+    match strategy {
+        CoinSelection::LargestFirst => {
+            sorted.sort_by_key(|i| std::cmp::Reverse(values[*i]));
+            accumulate(values, &sorted, target)
+        }
+        CoinSelection::SmallestFirst => {
+            sorted.sort_by_key(|i| values[*i]);
+            accumulate(values, &sorted, target)
+        }
+        CoinSelection::BranchAndBound => {
+            sorted.sort_by_key(|i| std::cmp::Reverse(values[*i]));
+            branch_and_bound(values, &sorted, target)
+                .or_else(|| accumulate(values, &sorted, target))
+        }
+    }
+}
+
+/// Take the `values` in the given order until `target` is covered
+fn accumulate(values: &[u64], order: &[usize], target: u64) -> Option<Vec<usize>> {
+    let mut sum = 0;
+    let mut selected = vec![];
+    for i in order {
+        if sum >= target && !selected.is_empty() {
+            break;
+        }
+        sum += values[*i];
+        selected.push(*i);
+    }
+    (sum >= target && !selected.is_empty()).then_some(selected)
+}
+
+/// Depth first search of the subset of `values` covering `target` with the lowest excess
+///
+/// `order` must be sorted by decreasing value.
+fn branch_and_bound(values: &[u64], order: &[usize], target: u64) -> Option<Vec<usize>> {
+    // remaining[d] is the sum of the values from depth d on
+    let mut remaining = vec![0u64; order.len() + 1];
+    for d in (0..order.len()).rev() {
+        remaining[d] = remaining[d + 1] + values[order[d]];
+    }
+    if remaining[0] < target {
+        return None;
+    }
+
+    let mut best: Option<(u64, Vec<usize>)> = None;
+    let mut included = vec![];
+    let mut sum = 0;
+    let mut depth = 0;
+    let mut tries = 0;
+    loop {
+        tries += 1;
+        let covered = sum >= target && !included.is_empty();
+        let hopeless = sum + remaining[depth] < target;
+        let worse = best
+            .as_ref()
+            .map_or(false, |(excess, _)| sum >= target + excess);
+        if covered
+            && best
+                .as_ref()
+                .map_or(true, |(excess, _)| sum - target < *excess)
+        {
+            best = Some((sum - target, included.clone()));
+            if sum == target {
+                break;
+            }
+        }
+        if covered || hopeless || worse || depth == order.len() {
+            // Backtrack to the last included value and try excluding it
+            match included.pop() {
+                Some(last) => {
+                    sum -= values[order[last]];
+                    depth = last + 1;
+                }
+                None => break,
+            }
+        } else {
+            // Include the value at the current depth
+            sum += values[order[depth]];
+            included.push(depth);
+            depth += 1;
+        }
+        if tries >= BNB_MAX_TRIES {
+            break;
+        }
+    }
+    best.map(|(_, included)| included.into_iter().map(|d| order[d]).collect())
+}
+
+#[cfg(test)]
+mod test {
+    use super::{select, CoinSelection};
+
+    fn selected(strategy: CoinSelection, values: &[u64], target: u64) -> Option<Vec<u64>> {
+        select(strategy, values, target).map(|indexes| {
+            let mut v: Vec<u64> = indexes.into_iter().map(|i| values[i]).collect();
+            v.sort();
+            v
+        })
+    }
+
+    #[test]
+    fn test_coin_selection() {
+        let values = [5, 1, 10, 3, 7];
+        let largest = selected(CoinSelection::LargestFirst, &values, 12).unwrap();
+        assert_eq!(largest, vec![7, 10]);
+        let smallest = selected(CoinSelection::SmallestFirst, &values, 12).unwrap();
+        assert_eq!(smallest, vec![1, 3, 5, 7]);
+        let bnb = selected(CoinSelection::BranchAndBound, &values, 12).unwrap();
+        assert_eq!(bnb.iter().sum::<u64>(), 12);
+
+        // The change of each strategy
+        let change = |v: Vec<u64>| v.iter().sum::<u64>() - 12;
+        assert_eq!(change(largest), 5);
+        assert_eq!(change(smallest), 4);
+        assert_eq!(change(bnb), 0);
+
+        // No exact match, the lowest excess
+        let bnb = selected(CoinSelection::BranchAndBound, &[10, 6, 4], 11).unwrap();
+        assert_eq!(bnb, vec![4, 10]);
+
+        for strategy in [
+            CoinSelection::LargestFirst,
+            CoinSelection::SmallestFirst,
+            CoinSelection::BranchAndBound,
+        ] {
+            assert_eq!(selected(strategy, &values, 27), None);
+            assert_eq!(
+                selected(strategy, &values, 26).unwrap(),
+                vec![1, 3, 5, 7, 10]
+            );
+            assert_eq!(selected(strategy, &[], 1), None);
+            // Values not worth spending are skipped
+            assert_eq!(selected(strategy, &[0, 2], 1).unwrap(), vec![2]);
+        }
+    }
+}
@@ -6,6 +6,11 @@ const LIQUID_POLICY_ASSET_STR: &str =
     "6f0279e9ed041c3d710a9f57d0c02928416460c4b722ae3457a11eec381c526d";
 const LIQUID_TESTNET_POLICY_ASSET_STR: &str =
     "144c654344aa716d6f3abcc1ca90e5641e4e2a7f633bc09fe3baf64585819a49";
+const ELEMENTS_REGTEST_POLICY_ASSET_STR: &str =
+    "5ac9f65c0efcc4775e0baec4ec03abdde22473cd3cf33c0419ca290e0751b225";
+
+/// The strings accepted by [`ElementsNetwork::from_str`]
+pub(crate) const NETWORK_NAMES: &str = "liquid, mainnet, liquidtestnet, liquid-testnet, testnet, elementsregtest, liquid-regtest, regtest";
 
 #[derive(Debug, Clone, PartialEq, Eq, Copy, Hash)]
 pub enum ElementsNetwork {
@@ -49,6 +54,25 @@ impl ElementsNetwork {
     }
 }
 
+/// Parse a network name, case insensitive
+///
+/// Regtest uses the policy asset of a default elements regtest chain.
+impl FromStr for ElementsNetwork {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.to_lowercase().as_str() {
+            "liquid" | "mainnet" => ElementsNetwork::Liquid,
+            "liquidtestnet" | "liquid-testnet" | "testnet" => ElementsNetwork::LiquidTestnet,
+            "elementsregtest" | "liquid-regtest" | "regtest" => ElementsNetwork::ElementsRegtest {
+                policy_asset: AssetId::from_str(ELEMENTS_REGTEST_POLICY_ASSET_STR)
+                    .expect("can't fail on const"),
+            },
+            _ => return Err(Error::InvalidNetwork(s.to_string())),
+        })
+    }
+}
+
 #[derive(Debug, Clone, Hash)]
 pub struct Config {
     network: ElementsNetwork,
@@ -79,7 +103,10 @@ mod test {
         hash::{Hash, Hasher},
     };
 
-    use super::Config;
+    use std::str::FromStr;
+
+    use super::{Config, ElementsNetwork};
+    use crate::Error;
 
     #[test]
     fn test_config_hash() {
@@ -88,4 +115,30 @@ mod test {
         config.hash(&mut hasher);
         assert_eq!(13646096770106105413, hasher.finish());
     }
+
+    #[test]
+    fn test_network_from_str() {
+        let liquid = "6f0279e9ed041c3d710a9f57d0c02928416460c4b722ae3457a11eec381c526d";
+        let testnet = "144c654344aa716d6f3abcc1ca90e5641e4e2a7f633bc09fe3baf64585819a49";
+        let regtest = "5ac9f65c0efcc4775e0baec4ec03abdde22473cd3cf33c0419ca290e0751b225";
+        for (s, name, policy_asset) in [
+            ("liquid", "liquid", liquid),
+            ("mainnet", "liquid", liquid),
+            ("Liquid", "liquid", liquid),
+            ("liquidtestnet", "liquid-testnet", testnet),
+            ("liquid-testnet", "liquid-testnet", testnet),
+            ("testnet", "liquid-testnet", testnet),
+            ("elementsregtest", "liquid-regtest", regtest),
+            ("liquid-regtest", "liquid-regtest", regtest),
+            ("REGTEST", "liquid-regtest", regtest),
+        ] {
+            let network = ElementsNetwork::from_str(s).unwrap();
+            assert_eq!(network.as_str(), name, "{s}");
+            assert_eq!(network.policy_asset().to_string(), policy_asset, "{s}");
+        }
+
+        let err = ElementsNetwork::from_str("signet").unwrap_err();
+        assert!(matches!(err, Error::InvalidNetwork(_)));
+        assert!(err.to_string().contains("liquid, mainnet"));
+    }
 }
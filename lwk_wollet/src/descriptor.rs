@@ -3,7 +3,8 @@ use std::{fmt::Display, str::FromStr};
 use aes_gcm_siv::aead::generic_array::GenericArray;
 use aes_gcm_siv::aead::NewAead;
 use aes_gcm_siv::Aes256GcmSiv;
-use elements::bitcoin::{bip32::ChildNumber, WitnessVersion};
+use elements::bitcoin::bip32::{ChildNumber, DerivationPath};
+use elements::bitcoin::WitnessVersion;
 use elements::hashes::{sha256t_hash_newtype, Hash};
 use elements::{Address, AddressParams};
 use elements_miniscript::{
@@ -93,10 +94,13 @@ impl FromStr for WolletDescriptor {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone, Copy, PartialOrd, Ord)]
+#[derive(
+    Debug, Default, Serialize, Deserialize, PartialEq, Eq, Hash, Clone, Copy, PartialOrd, Ord,
+)]
 pub enum Chain {
     /// External address, shown when asked for a payment.
     /// Wallet having a single descriptor are considered External
+    #[default]
     External,
 
     /// Internal address, used for the change
@@ -162,6 +166,44 @@ impl WolletDescriptor {
         })
     }
 
+    /// The single chain descriptors and the chain of the addresses they derive
+    ///
+    /// Multipath descriptors are split in the external and the internal descriptor, the others
+    /// are returned as the external one.
+    pub fn single_descriptors(&self) -> Vec<(Chain, WolletDescriptor)> {
+        if self.descriptor().is_multipath() {
+            [Chain::External, Chain::Internal]
+                .into_iter()
+                .map(|c| (c, self.inner_descriptor_if_available(c)))
+                .collect()
+        } else {
+            vec![(Chain::External, self.clone())]
+        }
+    }
+
+    /// The chain of the addresses derived for `ext_int`, descriptors that are not multipath
+    /// derive all the addresses from the external chain
+    pub fn address_chain(&self, ext_int: Chain) -> Chain {
+        if self.descriptor().is_multipath() {
+            ext_int
+        } else {
+            Chain::External
+        }
+    }
+
+    /// Get the full derivation path of the address at the given index of the given chain
+    ///
+    /// For descriptors with multiple keys, the path of the first key is returned.
+    pub fn full_path(&self, ext_int: Chain, index: u32) -> Result<DerivationPath, crate::Error> {
+        let definite_desc = self.definite_descriptor(ext_int, index)?;
+        let mut full_path = None;
+        definite_desc.for_each_key(|k| {
+            full_path = k.full_derivation_path();
+            false
+        });
+        Ok(full_path.unwrap_or_else(DerivationPath::master))
+    }
+
     pub fn change(
         &self,
         index: u32,
@@ -221,7 +263,9 @@ mod test {
         hash::{Hash, Hasher},
     };
 
-    use crate::WolletDescriptor;
+    use elements::AddressParams;
+
+    use crate::{Chain, WolletDescriptor};
 
     #[test]
     fn test_wollet_hash() {
@@ -232,4 +276,31 @@ mod test {
         desc.hash(&mut hasher);
         assert_eq!(12055616352728229988, hasher.finish());
     }
+
+    #[test]
+    fn test_single_descriptors() {
+        let desc_str = "ct(slip77(ab5824f4477b4ebb00a132adfd8eb0b7935cf24f6ac151add5d1913db374ce92),elwpkh([759db348/84'/1'/0']tpubDCRMaF33e44pcJj534LXVhFbHibPbJ5vuLhSSPFAw57kYURv4tzXFL6LSnd78bkjqdmE3USedkbpXJUPA1tdzKfuYSL7PianceqAhwL2UkA/<0;1>/*))#cch6wrnp";
+        let desc: WolletDescriptor = desc_str.parse().unwrap();
+        let single = desc.single_descriptors();
+        assert_eq!(single.len(), 2);
+        assert_eq!(single[0].0, Chain::External);
+        assert_eq!(single[1].0, Chain::Internal);
+        assert!(single[0].1.to_string().contains("/0/*))#"));
+        assert!(single[1].1.to_string().contains("/1/*))#"));
+        for (chain, single) in single {
+            let parsed: WolletDescriptor = single.to_string().parse().unwrap();
+            assert_eq!(
+                parsed.address(0, &AddressParams::ELEMENTS).unwrap(),
+                desc.inner_address(0, &AddressParams::ELEMENTS, chain)
+                    .unwrap()
+            );
+        }
+
+        let desc_str = desc_str.replace("<0;1>", "0").replace("#cch6wrnp", "");
+        let desc: WolletDescriptor = desc_str.parse().unwrap();
+        let single = desc.single_descriptors();
+        assert_eq!(single.len(), 1);
+        assert_eq!(single[0].0, Chain::External);
+        assert_eq!(single[0].1.to_string(), desc.to_string());
+    }
 }
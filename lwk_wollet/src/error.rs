@@ -28,6 +28,10 @@ pub enum Error {
     #[error(transparent)]
     ClientError(#[from] electrum_client::Error),
 
+    #[cfg(feature = "electrum")]
+    #[error("Cannot connect to the electrum server through the proxy {0}: {1}")]
+    ElectrumProxy(std::net::SocketAddr, String),
+
     #[error(transparent)]
     ElementsEncode(#[from] crate::elements::encode::Error),
 
@@ -43,6 +47,9 @@ pub enum Error {
     #[error(transparent)]
     Secp256k1(#[from] crate::secp256k1::Error),
 
+    #[error(transparent)]
+    Secp256k1Zkp(#[from] crate::elements::secp256k1_zkp::Error),
+
     #[error(transparent)]
     HexToBytesError(#[from] crate::hashes::hex::HexToBytesError),
 
@@ -76,10 +83,18 @@ pub enum Error {
     #[error(transparent)]
     ParseInt(#[from] std::num::ParseIntError),
 
-    #[cfg(any(feature = "esplora_wasm", feature = "esplora"))]
+    #[cfg(any(feature = "esplora_wasm", feature = "esplora", feature = "bip158"))]
     #[error(transparent)]
     Reqwest(#[from] reqwest::Error),
 
+    #[cfg(feature = "bip158")]
+    #[error("Node RPC error: {0}")]
+    NodeRpc(String),
+
+    #[cfg(feature = "bip158")]
+    #[error(transparent)]
+    BlockFilter(#[from] crate::bitcoin::bip158::Error),
+
     #[error(transparent)]
     PersistError(#[from] crate::persister::PersistError),
 
@@ -104,25 +119,61 @@ pub enum Error {
     #[error("Invalid amount")]
     InvalidAmount,
 
+    #[error(r#"Invalid recipient "{0}", it should be "address:satoshi:assetid""#)]
+    InvalidRecipientFormat(String),
+
+    #[error("Unknown asset '{0}', it must be an asset id")]
+    UnknownAsset(String),
+
+    #[error("Invalid amount '{0}': {1}")]
+    InvalidDecimalAmount(String, String),
+
+    #[error("Burn recipients must specify the asset, even if it is the policy asset")]
+    MissingBurnAsset,
+
+    #[error("Address {0} is not for network {1}")]
+    AddressNetworkMismatch(String, String),
+
+    #[error("Invalid recipients: {}", .0.iter().map(|r| r.to_string()).collect::<Vec<_>>().join(", "))]
+    InvalidRecipients(Vec<crate::model::InvalidRecipient>),
+
+    #[error("Invalid URI: {0}")]
+    InvalidUri(String),
+
+    #[error("Invalid OP_RETURN data: {0}")]
+    InvalidOpReturn(String),
+
+    #[error("Invalid timelock: {0}")]
+    InvalidTimelock(String),
+
+    #[error("Only one recipient can receive all the remaining balance of asset {0}")]
+    MultipleMaxRecipients(crate::elements::AssetId),
+
+    #[error("Utxo {0} is not an unspent output of the wallet")]
+    MissingWalletUtxo(crate::elements::OutPoint),
+
+    #[error("Invalid fee rate {0}, it must be positive and finite")]
+    InvalidFeeRate(f32),
+
     #[error("The script is not owned by this wallet")]
     ScriptNotMine,
 
-    #[error("Invalid domain")]
+    #[error("Invalid domain, it must be a lowercase hostname like \"example.com\"")]
     InvalidDomain,
 
-    #[error("Invalid version")]
+    #[error("Invalid version, it must be 0")]
     InvalidVersion,
 
-    #[error("Invalid precision")]
+    #[error("Invalid precision, it must be at most 8")]
     InvalidPrecision,
 
-    #[error("Invalid name")]
+    #[error("Invalid name, it must be 1 to 255 ASCII characters")]
     InvalidName,
 
-    #[error("Invalid ticker")]
+    #[error("Invalid ticker, it must be 3 to 24 letters, digits, '.' or '-'")]
     InvalidTicker,
 
-    #[error("Invalid issuer pubkey")]
+    #[error("Invalid issuer pubkey, it must be a compressed secp256k1 public key (33 bytes)")]
     InvalidIssuerPubkey,
 
     #[error("Descriptor without wildcard not supported")]
@@ -139,6 +190,36 @@ pub enum Error {
     #[error("Missing PSET")]
     MissingPset,
 
+    #[error("Invalid network '{0}', accepted values are: {names}", names = crate::config::NETWORK_NAMES)]
+    InvalidNetwork(String),
+
+    #[error("Transaction rejected by the backend: {0}")]
+    BroadcastRejected(String),
+
+    #[error("Transaction {0} is not a wallet transaction")]
+    TxNotInWallet(crate::elements::Txid),
+
+    #[error("Transaction {0} is already confirmed")]
+    TxAlreadyConfirmed(crate::elements::Txid),
+
+    #[error("Transaction {0} does not signal replaceability")]
+    TxNotReplaceable(crate::elements::Txid),
+
+    #[error("Cannot bump the fee of the transaction: {0}")]
+    BumpFeeUnsupported(String),
+
+    #[error("Fee estimation of (re)issuances is not supported")]
+    FeeEstimationIssuanceUnsupported,
+
+    #[error("The blockchain backend cannot provide merkle proofs")]
+    MerkleProofsUnsupported,
+
+    #[error("The new fee {fee} must be higher than the original fee {original}")]
+    FeeNotIncreased { fee: u64, original: u64 },
+
+    #[error("PSET #{0} has a different unsigned transaction and cannot be combined")]
+    IncompatiblePset(usize),
+
     #[error("Send many cannot be called with an empty addressee list")]
     SendManyEmptyAddressee,
 
@@ -156,6 +237,49 @@ pub enum Error {
 
     #[error("An issuance has already being set on this tx builder")]
     IssuanceAlreadySet,
+
+    #[error("A swap must exchange different assets")]
+    SwapSameAsset,
+
+    #[error("No utxo of exactly {satoshi} satoshi of asset {asset}, split a utxo first sending {satoshi} satoshi of the asset to a wallet address")]
+    SwapUtxoNotFound {
+        satoshi: u64,
+        asset: crate::elements::AssetId,
+    },
+
+    #[error("Invalid proof of reserves: {0}")]
+    InvalidProofOfReserves(String),
+
+    #[error("The gap limit must be greater than 0")]
+    ZeroGapLimit,
+
+    #[error("Invalid verifiable update: {0}")]
+    InvalidVerifiableUpdate(String),
+
+    #[error("SPV verification failed: {0}")]
+    SpvVerification(String),
+}
+
+impl Error {
+    /// Whether the error is a broadcast rejection because the transaction is already known by
+    /// the backend, either in the mempool or in a block
+    pub fn is_already_broadcast(&self) -> bool {
+        match self {
+            Error::BroadcastRejected(reason) => {
+                let reason = reason.to_lowercase();
+                [
+                    "txn-already-in-mempool",
+                    "txn-already-known",
+                    "txn-same-nonwitness-data-in-mempool",
+                    "transaction already in block chain",
+                    "transaction outputs already in utxo set",
+                ]
+                .iter()
+                .any(|known| reason.contains(known))
+            }
+            _ => false,
+        }
+    }
 }
 
 // cannot derive automatically with this error because of trait bound
@@ -164,3 +288,21 @@ impl From<aes_gcm_siv::aead::Error> for Error {
         Self::Aes(err.to_string())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::Error;
+
+    #[test]
+    fn test_is_already_broadcast() {
+        let rejected = |r: &str| Error::BroadcastRejected(r.to_string());
+        assert!(rejected("txn-already-in-mempool").is_already_broadcast());
+        assert!(rejected(
+            r#"sendrawtransaction RPC error: {"code":-27,"message":"Transaction already in block chain"}"#
+        )
+        .is_already_broadcast());
+        assert!(!rejected("min relay fee not met").is_already_broadcast());
+        assert!(!rejected("bad-txns-inputs-missingorspent").is_already_broadcast());
+        assert!(!Error::MissingPset.is_already_broadcast());
+    }
+}
@@ -0,0 +1,296 @@
+use elements::hashes::{sha256, Hash};
+use elements::opcodes::all;
+use elements::script::Instruction;
+use elements::BlockExtData;
+use elements::{BlockHash, BlockHeader, Script};
+
+use crate::secp256k1::{ecdsa::Signature, Message, PublicKey};
+use crate::store::Height;
+use crate::{Error, EC};
+
+/// A chain of block headers validated from a trusted checkpoint
+///
+/// Every header must link to the previous one and must be signed by the block signers, thus a
+/// server cannot make up confirmations without the signatures of the federation.
+///
+/// The signatures must satisfy the block signing script of the checkpoint, the supported ones
+/// are `OP_TRUE` (regtest) and `m <pubkeys> n OP_CHECKMULTISIG` (Liquid), also wrapped in P2WSH
+/// as it happens at the activation of dynamic federations. Other changes of the block signing
+/// script are not supported.
+#[derive(Clone, Debug)]
+pub struct HeaderChain {
+    /// Height of the checkpoint
+    start_height: Height,
+
+    /// Hashes of the validated blocks, starting from the checkpoint
+    hashes: Vec<BlockHash>,
+
+    /// The script the block signatures must satisfy
+    signblockscript: Script,
+}
+
+impl HeaderChain {
+    /// Create a chain from a header that must be trusted, for instance because its hash has been
+    /// checked with a trusted source
+    pub fn new(checkpoint: &BlockHeader) -> Result<Self, Error> {
+        let (signblockscript, _) = signblockscript_and_witness(checkpoint)?;
+        Ok(Self {
+            start_height: checkpoint.height,
+            hashes: vec![checkpoint.block_hash()],
+            signblockscript,
+        })
+    }
+
+    /// The height of the checkpoint
+    pub fn start_height(&self) -> Height {
+        self.start_height
+    }
+
+    /// The height of the last validated header
+    pub fn tip_height(&self) -> Height {
+        self.start_height + self.hashes.len() as Height - 1
+    }
+
+    /// The hash of the validated block at the given height
+    pub fn block_hash(&self, height: Height) -> Option<BlockHash> {
+        let index = height.checked_sub(self.start_height)?;
+        self.hashes.get(index as usize).copied()
+    }
+
+    /// Whether the header is part of this chain
+    pub fn contains(&self, header: &BlockHeader) -> bool {
+        self.block_hash(header.height) == Some(header.block_hash())
+    }
+
+    /// Validate and append headers with consecutive heights
+    ///
+    /// If the first header doesn't follow the tip, the blocks from its height on are replaced,
+    /// following a reorg. If any header is invalid the chain is not changed.
+    pub fn extend(&mut self, headers: &[BlockHeader]) -> Result<(), Error> {
+        let first = match headers.first() {
+            Some(first) => first,
+            None => return Ok(()),
+        };
+        if first.height <= self.start_height || first.height > self.tip_height() + 1 {
+            return Err(Error::SpvVerification(format!(
+                "block {} doesn't follow the chain",
+                first.height
+            )));
+        }
+        let mut hashes = self.hashes[..(first.height - self.start_height) as usize].to_vec();
+        let mut signblockscript = self.signblockscript.clone();
+        for header in headers {
+            let prev = hashes.last().expect("contains the checkpoint");
+            if header.prev_blockhash != *prev
+                || header.height != self.start_height + hashes.len() as Height
+            {
+                return Err(Error::SpvVerification(format!(
+                    "block {} doesn't link to the previous one",
+                    header.height
+                )));
+            }
+            let (script, witness) = signblockscript_and_witness(header)?;
+            if script != signblockscript {
+                if script != Script::new_v0_wsh(&signblockscript.wscript_hash()) {
+                    return Err(Error::SpvVerification(format!(
+                        "block signing script changed at block {}",
+                        header.height
+                    )));
+                }
+                signblockscript = script.clone();
+            }
+            if !satisfies(&script, &witness, &header.block_hash()) {
+                return Err(Error::SpvVerification(format!(
+                    "invalid signature of block {}",
+                    header.height
+                )));
+            }
+            hashes.push(header.block_hash());
+        }
+        self.hashes = hashes;
+        self.signblockscript = signblockscript;
+        Ok(())
+    }
+}
+
+/// The block signing script and the stack satisfying it
+fn signblockscript_and_witness(header: &BlockHeader) -> Result<(Script, Vec<Vec<u8>>), Error> {
+    match &header.ext {
+        BlockExtData::Proof {
+            challenge,
+            solution,
+        } => {
+            let mut stack = vec![];
+            for instruction in solution.instructions() {
+                match instruction {
+                    Ok(Instruction::PushBytes(bytes)) => stack.push(bytes.to_vec()),
+                    _ => return Err(Error::SpvVerification("invalid block solution".into())),
+                }
+            }
+            Ok((challenge.clone(), stack))
+        }
+        BlockExtData::Dynafed {
+            current,
+            signblock_witness,
+            ..
+        } => {
+            let script = current
+                .signblockscript()
+                .ok_or_else(|| Error::SpvVerification("missing block signing script".into()))?;
+            Ok((script.clone(), signblock_witness.clone()))
+        }
+    }
+}
+
+/// Whether the stack satisfies the block signing script, with signatures of the block hash
+fn satisfies(script: &Script, stack: &[Vec<u8>], block_hash: &BlockHash) -> bool {
+    if script.is_v0_p2wsh() {
+        return match stack.split_last() {
+            Some((witness_script, stack)) => {
+                sha256::Hash::hash(witness_script).as_byte_array()[..] == script.as_bytes()[2..]
+                    && satisfies(&Script::from(witness_script.clone()), stack, block_hash)
+            }
+            None => false,
+        };
+    }
+    if script.as_bytes() == [all::OP_PUSHNUM_1.into_u8()] {
+        return true;
+    }
+    let (threshold, pubkeys) = match parse_multisig(script) {
+        Some(multisig) => multisig,
+        None => return false,
+    };
+    // The first element is the dummy consumed by OP_CHECKMULTISIG
+    let signatures = match stack.split_first() {
+        Some((dummy, signatures)) if dummy.is_empty() && signatures.len() == threshold => {
+            signatures
+        }
+        _ => return false,
+    };
+    let message = Message::from_digest(block_hash.to_byte_array());
+    // As OP_CHECKMULTISIG, the signatures must be in the same order of the public keys
+    let mut pubkeys = pubkeys.iter();
+    signatures.iter().all(|signature| {
+        let signature = match Signature::from_der(signature) {
+            Ok(signature) => signature,
+            Err(_) => return false,
+        };
+        pubkeys.any(|pubkey| EC.verify_ecdsa(&message, &signature, pubkey).is_ok())
+    })
+}
+
+/// Parse a `m <pubkeys> n OP_CHECKMULTISIG` script
+fn parse_multisig(script: &Script) -> Option<(usize, Vec<PublicKey>)> {
+    let instructions: Vec<Instruction> = script.instructions().collect::<Result<_, _>>().ok()?;
+    let (first, rest) = instructions.split_first()?;
+    let (last, rest) = rest.split_last()?;
+    let (n, keys) = rest.split_last()?;
+    if *last != Instruction::Op(all::OP_CHECKMULTISIG) {
+        return None;
+    }
+    let pubkeys = keys
+        .iter()
+        .map(|key| match key {
+            Instruction::PushBytes(bytes) => PublicKey::from_slice(bytes).ok(),
+            _ => None,
+        })
+        .collect::<Option<Vec<_>>>()?;
+    let (m, n) = (small_int(first)?, small_int(n)?);
+    (m >= 1 && m <= n && n == pubkeys.len()).then_some((m, pubkeys))
+}
+
+/// The value of `OP_PUSHNUM_1` to `OP_PUSHNUM_16`
+fn small_int(instruction: &Instruction) -> Option<usize> {
+    match instruction {
+        Instruction::Op(op) => {
+            let n = op.into_u8().checked_sub(all::OP_PUSHNUM_1.into_u8())? as usize + 1;
+            (n <= 16).then_some(n)
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use elements::hashes::Hash;
+    use elements::BlockExtData;
+    use elements::{BlockHash, BlockHeader, Script};
+
+    use super::{satisfies, signblockscript_and_witness, HeaderChain};
+
+    /// A header following `prev`, signed with `OP_TRUE`
+    fn next(prev: &BlockHeader) -> BlockHeader {
+        let mut header = prev.clone();
+        header.prev_blockhash = prev.block_hash();
+        header.height = prev.height + 1;
+        header.ext = BlockExtData::Proof {
+            challenge: Script::from(vec![0x51]),
+            solution: Script::new(),
+        };
+        header
+    }
+
+    #[test]
+    fn test_header_chain_signatures() {
+        // Liquid block 1, signed by 11 of the 15 block signers
+        let block_1 = lwk_test_util::liquid_block_1().header;
+        let (script, witness) = signblockscript_and_witness(&block_1).unwrap();
+        assert!(satisfies(&script, &witness, &block_1.block_hash()));
+        assert!(!satisfies(&script, &witness, &BlockHash::all_zeros()));
+        assert!(!satisfies(&script, &witness[..11], &block_1.block_hash()));
+        let mut swapped = witness.clone();
+        swapped.swap(1, 2);
+        assert!(!satisfies(&script, &swapped, &block_1.block_hash()));
+
+        // Wrapped in P2WSH
+        let wsh = Script::new_v0_wsh(&script.wscript_hash());
+        let mut wsh_witness = witness.clone();
+        wsh_witness.push(script.to_bytes());
+        assert!(satisfies(&wsh, &wsh_witness, &block_1.block_hash()));
+        wsh_witness.last_mut().unwrap().push(0);
+        assert!(!satisfies(&wsh, &wsh_witness, &block_1.block_hash()));
+
+        let mut chain = HeaderChain::new(&block_1).unwrap();
+        assert!(chain.contains(&block_1));
+        assert_eq!(chain.tip_height(), 1);
+
+        // A different signing script is rejected
+        let err = chain.extend(&[next(&block_1)]).unwrap_err();
+        assert!(err.to_string().contains("block signing script changed"));
+        assert_eq!(chain.tip_height(), 1);
+
+        // A block not signed is rejected
+        let mut unsigned = next(&block_1);
+        unsigned.ext = block_1.ext.clone();
+        let err = chain.extend(&[unsigned]).unwrap_err();
+        assert!(err.to_string().contains("invalid signature"));
+    }
+
+    #[test]
+    fn test_header_chain_reorg() {
+        let checkpoint = next(&lwk_test_util::liquid_block_1().header);
+        let mut chain = HeaderChain::new(&checkpoint).unwrap();
+        let b1 = next(&checkpoint);
+        let b2 = next(&b1);
+        chain.extend(&[b1.clone(), b2.clone()]).unwrap();
+        assert_eq!(chain.tip_height(), checkpoint.height + 2);
+
+        // Not linked
+        let mut unlinked = next(&b2);
+        unlinked.prev_blockhash = b1.block_hash();
+        assert!(chain.extend(&[unlinked]).is_err());
+        assert!(chain.contains(&b2));
+
+        // Reorg of the last block
+        let mut b2_reorg = next(&b1);
+        b2_reorg.time += 1;
+        let b3 = next(&b2_reorg);
+        chain.extend(&[b2_reorg.clone(), b3.clone()]).unwrap();
+        assert!(!chain.contains(&b2));
+        assert!(chain.contains(&b2_reorg));
+        assert!(chain.contains(&b3));
+
+        // The checkpoint cannot be replaced
+        assert!(chain.extend(&[checkpoint.clone()]).is_err());
+    }
+}
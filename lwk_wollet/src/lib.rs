@@ -68,9 +68,10 @@
 //! # )?;
 //! // Create a transaction
 //! let recipient = UnvalidatedRecipient {
-//!     satoshi: 1000,
+//!     satoshi: Some(1000),
 //!     address: "tlq1qqgpjea0jcel4tqeln5kyxlrgqx2eh4vw67ecswm54476mddy3n0klrlmty5gn0wsdw4045rtl2y2wdtr4rdu6v93zds6zn8xd".to_string(),
 //!     asset: "144c654344aa716d6f3abcc1ca90e5641e4e2a7f633bc09fe3baf64585819a49".to_string(),
+//!     timelock: None,
 //! };
 //! let pset = wollet
 //!     .tx_builder()
@@ -83,14 +84,18 @@
 //! ```
 
 mod clients;
+mod coin_selection;
 mod config;
 mod descriptor;
 mod domain;
 mod error;
+mod headers;
+mod merkle;
 mod model;
 mod persister;
 mod pset_create;
 mod registry;
+mod reserves;
 mod store;
 mod tx_builder;
 mod update;
@@ -98,18 +103,25 @@ mod util;
 mod wollet;
 
 pub use crate::clients::{BlockchainBackend, History};
+pub use crate::coin_selection::CoinSelection;
 pub use crate::config::ElementsNetwork;
 pub use crate::descriptor::{Chain, WolletDescriptor};
 pub use crate::error::Error;
+pub use crate::headers::HeaderChain;
+pub use crate::merkle::MerkleProof;
 pub use crate::model::{
-    AddressResult, IssuanceDetails, Recipient, UnvalidatedRecipient, WalletTx, WalletTxOut,
+    AddressResult, CsvRow, ExplorerKind, InvalidRecipient, IssuanceDetails, Recipient, Timelock,
+    TxType, UnvalidatedRecipient, WalletTx, WalletTxOut,
 };
-pub use crate::persister::{FsPersister, NoPersist, PersistError, Persister};
+pub use crate::persister::{FsPersister, MemoryPersister, NoPersist, PersistError, Persister};
+pub use crate::pset_create::validate_address;
 pub use crate::registry::{asset_ids, issuance_ids, Contract, Entity};
-pub use crate::tx_builder::{TxBuilder, WolletTxBuilder};
-pub use crate::update::{DownloadTxResult, Update};
+pub use crate::reserves::{challenge_outpoint, verify_proof_of_reserves};
+pub use crate::store::GAP_LIMIT;
+pub use crate::tx_builder::{FeeEstimate, TxBuilder, WolletTxBuilder};
+pub use crate::update::{DownloadTxResult, Update, VerifiableUpdate};
 pub use crate::util::EC;
-pub use crate::wollet::{Tip, Wollet};
+pub use crate::wollet::{full_scan_with_backend, Tip, Wollet};
 
 #[cfg(feature = "electrum")]
 pub use crate::wollet::full_scan_with_electrum_client;
@@ -122,6 +134,9 @@ pub use clients::esplora_client::EsploraClient;
 #[cfg(feature = "esplora_wasm")]
 pub use clients::esplora_wasm_client::EsploraWasmClient;
 
+#[cfg(feature = "bip158")]
+pub use clients::block_filter_client::BlockFilterClient;
+
 #[cfg(feature = "esplora_wasm")]
 pub use clients::esplora_wasm_client::async_sleep;
 
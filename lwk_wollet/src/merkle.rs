@@ -0,0 +1,148 @@
+use elements::encode::{Decodable, Encodable};
+use elements::hashes::{sha256d, Hash, HashEngine};
+use elements::{BlockHeader, Txid};
+
+/// Proof that a transaction is included in a block
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct MerkleProof {
+    /// The proven transaction
+    pub txid: Txid,
+
+    /// The header of the block including the transaction
+    pub header: BlockHeader,
+
+    /// The position of the transaction in the block
+    pub pos: u32,
+
+    /// The hashes of the merkle tree branch, from the sibling of the transaction up to the root
+    pub merkle: Vec<sha256d::Hash>,
+}
+
+impl MerkleProof {
+    /// Create the proof of the transaction at `pos` of the block with the given header and `txids`
+    pub fn from_txids(header: BlockHeader, txids: &[Txid], pos: u32) -> Option<Self> {
+        let txid = *txids.get(pos as usize)?;
+        let mut level: Vec<sha256d::Hash> = txids.iter().map(|t| t.to_raw_hash()).collect();
+        let mut index = pos as usize;
+        let mut merkle = vec![];
+        while level.len() > 1 {
+            // The last hash of a level with odd length is paired with itself
+            merkle.push(*level.get(index ^ 1).unwrap_or(&level[index]));
+            level = level
+                .chunks(2)
+                .map(|pair| hash_pair(&pair[0], pair.get(1).unwrap_or(&pair[0])))
+                .collect();
+            index /= 2;
+        }
+        Some(Self {
+            txid,
+            header,
+            pos,
+            merkle,
+        })
+    }
+
+    /// Whether the transaction is included in the block of the header
+    ///
+    /// The header itself is not validated.
+    pub fn verify(&self) -> bool {
+        let mut index = self.pos;
+        let mut cur = self.txid.to_raw_hash();
+        for hash in self.merkle.iter() {
+            cur = if index % 2 == 0 {
+                hash_pair(&cur, hash)
+            } else {
+                hash_pair(hash, &cur)
+            };
+            index /= 2;
+        }
+        index == 0 && cur == self.header.merkle_root.to_raw_hash()
+    }
+}
+
+fn hash_pair(left: &sha256d::Hash, right: &sha256d::Hash) -> sha256d::Hash {
+    let mut engine = sha256d::Hash::engine();
+    engine.input(left.as_byte_array());
+    engine.input(right.as_byte_array());
+    sha256d::Hash::from_engine(engine)
+}
+
+impl Encodable for MerkleProof {
+    fn consensus_encode<W: std::io::Write>(
+        &self,
+        mut w: W,
+    ) -> Result<usize, elements::encode::Error> {
+        let mut bytes_written = 0;
+        bytes_written += self.txid.consensus_encode(&mut w)?;
+        bytes_written += self.header.consensus_encode(&mut w)?;
+        bytes_written += self.pos.consensus_encode(&mut w)?;
+        bytes_written += elements::VarInt(self.merkle.len() as u64).consensus_encode(&mut w)?;
+        for hash in self.merkle.iter() {
+            bytes_written += hash.consensus_encode(&mut w)?;
+        }
+        Ok(bytes_written)
+    }
+}
+
+impl Decodable for MerkleProof {
+    fn consensus_decode<D: std::io::Read>(mut d: D) -> Result<Self, elements::encode::Error> {
+        let txid = Txid::consensus_decode(&mut d)?;
+        let header = BlockHeader::consensus_decode(&mut d)?;
+        let pos = u32::consensus_decode(&mut d)?;
+        let len = elements::VarInt::consensus_decode(&mut d)?.0;
+        let mut merkle = vec![];
+        for _ in 0..len {
+            merkle.push(sha256d::Hash::consensus_decode(&mut d)?);
+        }
+        Ok(Self {
+            txid,
+            header,
+            pos,
+            merkle,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use elements::encode::{deserialize, serialize};
+    use elements::hashes::Hash;
+    use elements::{bitcoin, TxMerkleNode, Txid};
+
+    use super::MerkleProof;
+
+    #[test]
+    fn test_merkle_proof() {
+        let block = lwk_test_util::liquid_block_1();
+        let header = block.header.clone();
+        let txid = block.txdata[0].txid();
+        let proof = MerkleProof::from_txids(header.clone(), &[txid], 0).unwrap();
+        assert!(proof.merkle.is_empty());
+        assert!(proof.verify());
+        assert_eq!(proof, deserialize(&serialize(&proof)).unwrap());
+        assert!(MerkleProof::from_txids(header.clone(), &[txid], 1).is_none());
+
+        let txids: Vec<Txid> = (0..5u8).map(|i| Txid::hash(&[i])).collect();
+        let root = bitcoin::merkle_tree::calculate_root(
+            txids
+                .iter()
+                .map(|t| bitcoin::TxMerkleNode::from_raw_hash(t.to_raw_hash())),
+        )
+        .unwrap();
+        let mut header = header;
+        header.merkle_root = TxMerkleNode::from_raw_hash(root.to_raw_hash());
+        for pos in 0..txids.len() as u32 {
+            let proof = MerkleProof::from_txids(header.clone(), &txids, pos).unwrap();
+            assert_eq!(proof.merkle.len(), 3);
+            assert!(proof.verify());
+            assert_eq!(proof, deserialize(&serialize(&proof)).unwrap());
+
+            let mut wrong = proof.clone();
+            wrong.pos = (pos + 1) % txids.len() as u32;
+            assert!(!wrong.verify());
+            let mut wrong = proof.clone();
+            wrong.txid = Txid::all_zeros();
+            assert!(!wrong.verify());
+        }
+    }
+}
@@ -1,10 +1,19 @@
+use crate::bitcoin::bip32::DerivationPath;
 use crate::descriptor::Chain;
+use crate::elements::confidential::ValueBlindingFactor;
+use crate::elements::hex::{FromHex, ToHex};
+use crate::elements::opcodes::all::{
+    OP_CHECKSIGVERIFY, OP_CLTV, OP_CSV, OP_DUP, OP_EQUALVERIFY, OP_HASH160, OP_RETURN,
+};
+use crate::elements::script::Builder;
+use crate::elements::secp256k1_zkp::ZERO_TWEAK;
 use crate::elements::{Address, AssetId, OutPoint, Script, Transaction, TxOutSecrets, Txid};
 use crate::pset_create::validate_address;
 use crate::secp256k1::PublicKey;
 use crate::store::Timestamp;
 use crate::{ElementsNetwork, Error};
 use lwk_common::burn_script;
+use lwk_common::precision::parse_amount;
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 use std::fmt::Debug;
@@ -21,6 +30,84 @@ pub struct WalletTxOut {
     pub ext_int: Chain,
 }
 
+impl WalletTxOut {
+    /// Reconstruct the address this output has been sent to
+    ///
+    /// If the blinding public key is not given, the explicit (unconfidential) address is
+    /// returned. Returns `None` if the script is not a standard address script.
+    pub fn address(
+        &self,
+        network: ElementsNetwork,
+        blinding_pubkey: Option<PublicKey>,
+    ) -> Option<Address> {
+        Address::from_script(
+            &self.script_pubkey,
+            blinding_pubkey,
+            network.address_params(),
+        )
+    }
+}
+
+/// The kind of a [`WalletTx`] from the perspective of the wallet, see [`WalletTx::classify()`]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum TxType {
+    /// The wallet only receives funds
+    Incoming,
+
+    /// The wallet only sends funds
+    Outgoing,
+
+    /// The wallet sends funds to itself, paying only the fee
+    Redeposit,
+
+    /// The transaction issues a new asset
+    Issuance,
+
+    /// The transaction reissues an asset
+    Reissuance,
+
+    /// The transaction burns some asset
+    Burn,
+
+    /// The wallet sends some assets and receives others
+    Swap,
+
+    /// The transaction doesn't involve any funds of the wallet
+    Unknown,
+}
+
+impl std::fmt::Display for TxType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            TxType::Incoming => "incoming",
+            TxType::Outgoing => "outgoing",
+            TxType::Redeposit => "redeposit",
+            TxType::Issuance => "issuance",
+            TxType::Reissuance => "reissuance",
+            TxType::Burn => "burn",
+            TxType::Swap => "swap",
+            TxType::Unknown => "unknown",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// The block explorer used to build the links returned by [`WalletTx::unblinded_url()`]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ExplorerKind {
+    /// Blockstream explorer (and self-hosted esplora), unblinding data in the `#blinded=` fragment
+    #[default]
+    Blockstream,
+
+    /// Mempool explorer, it accepts the same `#blinded=` fragment of Blockstream
+    Mempool,
+
+    /// Explorer not supporting unblinding links, only the transaction page is linked
+    Plain,
+}
+
 /// Value returned by [`crate::Wollet::transactions()`] containing details about a transaction
 /// from the perspective of the wallet, for example the net-balance of the wallet.
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
@@ -30,12 +117,50 @@ pub struct WalletTx {
     pub height: Option<u32>,
     pub balance: BTreeMap<AssetId, i64>,
     pub fee: u64,
+
+    /// The string rendering of [`WalletTx::classify()`]
     pub type_: String,
     pub timestamp: Option<Timestamp>,
     pub inputs: Vec<Option<WalletTxOut>>,
     pub outputs: Vec<Option<WalletTxOut>>,
 }
 
+/// A row of the CSV export of the wallet transactions, see [`WalletTx::to_csv_rows()`]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct CsvRow {
+    pub txid: Txid,
+    pub timestamp: Option<Timestamp>,
+    pub height: Option<u32>,
+    pub asset: AssetId,
+
+    /// The net amount of the asset for the wallet, in satoshi
+    pub amount: i64,
+
+    /// The transaction fee in satoshi, only set in the first row of each transaction
+    pub fee: u64,
+}
+
+impl CsvRow {
+    /// The CSV header matching the [`Display`](std::fmt::Display) implementation
+    pub const HEADER: &'static str = "txid,timestamp,height,asset,amount,fee";
+}
+
+impl std::fmt::Display for CsvRow {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let opt = |v: Option<u32>| v.map(|v| v.to_string()).unwrap_or_default();
+        write!(
+            f,
+            "{},{},{},{},{},{}",
+            self.txid,
+            opt(self.timestamp),
+            opt(self.height),
+            self.asset,
+            self.amount,
+            self.fee
+        )
+    }
+}
+
 /// A recipient of a transaction.
 ///
 /// Note that, since it doesn't use the [`Address`] but the [`Script`] and the [`PublicKey`] it's
@@ -65,73 +190,286 @@ impl Recipient {
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct UnvalidatedRecipient {
     /// The amount to send in satoshi
-    pub satoshi: u64,
+    ///
+    /// If `None`, all the remaining balance of the asset is sent (minus fees for the policy asset).
+    /// Only one recipient per asset can have `None`.
+    pub satoshi: Option<u64>,
 
     /// The address to send to
     ///
-    /// If "burn", the output will be burned
+    /// If "burn", the output will be burned.
+    /// If "op_return:<hex>", the output is an OP_RETURN carrying the given data, at most
+    /// 80 bytes, with zero value (`satoshi` must be `Some(0)`).
     pub address: String,
 
     /// The asset to send
     ///
     /// If empty, the policy asset
     pub asset: String,
+
+    /// Lock the output until a block height or for a number of blocks
+    ///
+    /// The address must be a P2WPKH address with key `K`, the output is sent to the P2WSH of
+    /// `and_v(v:pkh(K),after(height))` or `and_v(v:pkh(K),older(blocks))`, blinded with the
+    /// address blinding key. Spending it requires the witness script, which is not tracked by
+    /// the wallets.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timelock: Option<Timelock>,
+}
+
+/// When an output can be spent, see [`UnvalidatedRecipient::timelock`]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Timelock {
+    /// Spendable from the given block height, enforced with `OP_CHECKLOCKTIMEVERIFY`
+    Height(u32),
+
+    /// Spendable once the output has the given number of confirmations, enforced with
+    /// `OP_CHECKSEQUENCEVERIFY`
+    Blocks(u16),
 }
 
 impl UnvalidatedRecipient {
     pub fn lbtc(address: String, satoshi: u64) -> Self {
         UnvalidatedRecipient {
             address,
-            satoshi,
+            satoshi: Some(satoshi),
             asset: "".to_string(),
+            timelock: None,
         }
     }
     pub fn burn(asset: String, satoshi: u64) -> Self {
         UnvalidatedRecipient {
             address: "burn".to_string(),
-            satoshi,
+            satoshi: Some(satoshi),
             asset: asset.to_string(),
+            timelock: None,
         }
     }
+    pub fn op_return(data: &[u8]) -> Self {
+        UnvalidatedRecipient {
+            address: format!("{OP_RETURN_PREFIX}{}", data.to_hex()),
+            satoshi: Some(0),
+            asset: "".to_string(),
+            timelock: None,
+        }
+    }
+
+    /// Whether this recipient receives all the remaining balance of the asset
+    pub fn is_max(&self) -> bool {
+        self.satoshi.is_none()
+    }
+
+    /// Create a recipient from a BIP-21 style URI, e.g.
+    /// `liquidnetwork:<address>?amount=0.0001&assetid=<asset_id>`
+    ///
+    /// The amount is expressed in decimal units (8 decimals) and converted to satoshi.
+    /// If the asset id is missing the policy asset is used.
+    pub fn from_uri(uri: &str) -> Result<Self, Error> {
+        let invalid = |reason: &str| Error::InvalidUri(format!("{reason} in \"{uri}\""));
+        let (scheme, rest) = uri
+            .split_once(':')
+            .ok_or_else(|| invalid("missing scheme"))?;
+        if !URI_SCHEMES.contains(&scheme.to_lowercase().as_str()) {
+            return Err(invalid(&format!("unsupported scheme \"{scheme}\"")));
+        }
+        let (address, query) = rest.split_once('?').unwrap_or((rest, ""));
+        if address.is_empty() {
+            return Err(invalid("missing address"));
+        }
+
+        let mut satoshi = None;
+        let mut asset = "".to_string();
+        for param in query.split('&').filter(|p| !p.is_empty()) {
+            let (key, value) = param
+                .split_once('=')
+                .ok_or_else(|| invalid(&format!("invalid parameter \"{param}\"")))?;
+            match key {
+                "amount" => {
+                    let invalid_amount = || invalid(&format!("invalid amount \"{value}\""));
+                    let amount = parse_amount(value, 8).map_err(|_| invalid_amount())?;
+                    satoshi = Some(u64::try_from(amount).map_err(|_| invalid_amount())?);
+                }
+                "assetid" => asset = value.to_string(),
+                // BIP-21: required parameters that are not understood must make the URI invalid
+                k if k.starts_with("req-") => {
+                    return Err(invalid(&format!("unsupported required parameter \"{k}\"")))
+                }
+                _ => {}
+            }
+        }
+        let satoshi = satoshi.ok_or_else(|| invalid("missing amount"))?;
+
+        Ok(UnvalidatedRecipient {
+            satoshi: Some(satoshi),
+            address: address.to_string(),
+            asset,
+            timelock: None,
+        })
+    }
+}
+
+/// Prefix of [`UnvalidatedRecipient::address`] for OP_RETURN outputs
+const OP_RETURN_PREFIX: &str = "op_return:";
+
+/// Maximum number of bytes of data in an OP_RETURN output
+const MAX_OP_RETURN_DATA: usize = 80;
+
+/// Lock times below this value are block heights, above are timestamps
+const LOCKTIME_THRESHOLD: u32 = 500_000_000;
+
+/// URI schemes accepted by [`UnvalidatedRecipient::from_uri()`]
+const URI_SCHEMES: [&str; 2] = ["liquidnetwork", "liquidtestnet"];
+
+/// Split "address:amount:assetid" in its parts, the address may contain ':', e.g. "op_return:<hex>"
+fn split_recipient(value: &str) -> Result<(&str, &str, &str), Error> {
+    let mut pieces = value.rsplitn(3, ':');
+    match (pieces.next(), pieces.next(), pieces.next()) {
+        (Some(asset), Some(amount), Some(address)) => Ok((address, amount, asset)),
+        _ => Err(Error::InvalidRecipientFormat(value.to_string())),
+    }
 }
 
 impl TryFrom<String> for UnvalidatedRecipient {
     type Error = crate::Error;
 
     fn try_from(value: String) -> Result<Self, Self::Error> {
-        let pieces: Vec<_> = value.split(':').collect();
-        if pieces.len() != 3 {
-            // TODO make specific error
-            return Err(Error::Generic(format!(
-                r#"Invalid number of elements in string "{}", should be "address:satoshi:assetid"#,
-                value,
-            )));
-        }
+        let (address, amount, asset) = split_recipient(&value)?;
+        let satoshi = match amount {
+            "max" => None,
+            s => Some(
+                s.parse()
+                    .map_err(|_| Error::InvalidRecipientFormat(value.clone()))?,
+            ),
+        };
         Ok(UnvalidatedRecipient {
-            satoshi: pieces[1].parse()?,
-            address: pieces[0].to_string(),
-            asset: pieces[2].to_string(),
+            satoshi,
+            address: address.to_string(),
+            asset: asset.to_string(),
+            timelock: None,
         })
     }
 }
 
 impl UnvalidatedRecipient {
+    /// Parse "address:amount:assetid" as [`UnvalidatedRecipient::try_from()`], but with a decimal
+    /// amount, e.g. "0.001", converted to satoshi according to the `precision` of the asset
+    ///
+    /// Amounts with more fractional digits than `precision` are rejected instead of rounded.
+    pub fn from_decimal(value: &str, precision: u8) -> Result<Self, Error> {
+        let (address, amount, asset) = split_recipient(value)?;
+        let satoshi = match amount {
+            "max" => None,
+            s => {
+                let invalid = |reason: String| Error::InvalidDecimalAmount(s.to_string(), reason);
+                let satoshi = parse_amount(s, precision).map_err(|e| invalid(e.to_string()))?;
+                let satoshi = u64::try_from(satoshi).map_err(|_| invalid("negative".into()))?;
+                Some(satoshi)
+            }
+        };
+        Ok(UnvalidatedRecipient {
+            satoshi,
+            address: address.to_string(),
+            asset: asset.to_string(),
+            timelock: None,
+        })
+    }
+
     fn validate_asset(&self, network: ElementsNetwork) -> Result<AssetId, Error> {
         if self.asset.is_empty() {
             Ok(network.policy_asset())
         } else {
-            Ok(AssetId::from_str(&self.asset)?)
+            AssetId::from_str(&self.asset).map_err(|_| Error::UnknownAsset(self.asset.clone()))
         }
     }
 
     fn validate_satoshi(&self) -> Result<u64, Error> {
-        if self.satoshi == 0 {
+        match self.satoshi {
+            Some(0) => Err(Error::InvalidAmount),
+            Some(satoshi) => Ok(satoshi),
+            // the amount is computed when creating the transaction
+            None => Ok(0),
+        }
+    }
+
+    fn validate_op_return(&self, hex: &str, network: ElementsNetwork) -> Result<Recipient, Error> {
+        if self.satoshi != Some(0) {
             return Err(Error::InvalidAmount);
         }
-        Ok(self.satoshi)
+        if hex.len() % 2 != 0 {
+            return Err(Error::InvalidOpReturn("odd length hex".to_string()));
+        }
+        let data = Vec::<u8>::from_hex(hex).map_err(|e| Error::InvalidOpReturn(e.to_string()))?;
+        if data.len() > MAX_OP_RETURN_DATA {
+            return Err(Error::InvalidOpReturn(format!(
+                "{} bytes, at most {MAX_OP_RETURN_DATA} are allowed",
+                data.len()
+            )));
+        }
+        let script_pubkey = Builder::new()
+            .push_opcode(OP_RETURN)
+            .push_slice(&data)
+            .into_script();
+        Ok(Recipient {
+            satoshi: 0,
+            script_pubkey,
+            blinding_pubkey: None,
+            asset: self.validate_asset(network)?,
+        })
+    }
+
+    /// The witness script locking the funds sent to the P2WPKH `address` with `timelock`
+    fn timelock_witness_script(address: &Address, timelock: Timelock) -> Result<Script, Error> {
+        let script_pubkey = address.script_pubkey();
+        if !script_pubkey.is_v0_p2wpkh() {
+            return Err(Error::InvalidTimelock(
+                "the address must be P2WPKH".to_string(),
+            ));
+        }
+        let (value, opcode) = match timelock {
+            Timelock::Height(height) if height == 0 || height >= LOCKTIME_THRESHOLD => {
+                return Err(Error::InvalidTimelock(format!(
+                    "height {height} must be between 1 and {}",
+                    LOCKTIME_THRESHOLD - 1
+                )))
+            }
+            Timelock::Height(height) => (height as i64, OP_CLTV),
+            Timelock::Blocks(0) => {
+                return Err(Error::InvalidTimelock(
+                    "blocks must be at least 1".to_string(),
+                ))
+            }
+            Timelock::Blocks(blocks) => (blocks as i64, OP_CSV),
+        };
+        // and_v(v:pkh(K),after(n)) or and_v(v:pkh(K),older(n))
+        Ok(Builder::new()
+            .push_opcode(OP_DUP)
+            .push_opcode(OP_HASH160)
+            .push_slice(&script_pubkey.as_bytes()[2..])
+            .push_opcode(OP_EQUALVERIFY)
+            .push_opcode(OP_CHECKSIGVERIFY)
+            .push_int(value)
+            .push_opcode(opcode)
+            .into_script())
     }
 
+    /// Validate the recipient
+    ///
+    /// If [`UnvalidatedRecipient::is_max()`] the returned [`Recipient`] has 0 satoshi.
     pub fn validate(&self, network: ElementsNetwork) -> Result<Recipient, Error> {
+        let is_special = self.address == "burn" || self.address.starts_with(OP_RETURN_PREFIX);
+        if self.timelock.is_some() && is_special {
+            return Err(Error::InvalidTimelock(
+                "burn and OP_RETURN outputs cannot be locked".to_string(),
+            ));
+        }
+        if let Some(hex) = self.address.strip_prefix(OP_RETURN_PREFIX) {
+            return self.validate_op_return(hex, network);
+        }
+        // Burning the policy asset because the asset was left empty is almost never intended
+        if self.address == "burn" && self.asset.is_empty() {
+            return Err(Error::MissingBurnAsset);
+        }
         let satoshi = self.validate_satoshi()?;
         let asset = self.validate_asset(network)?;
         if self.address == "burn" {
@@ -144,22 +482,54 @@ impl UnvalidatedRecipient {
             })
         } else {
             let address = validate_address(&self.address, network)?;
-            Ok(Recipient::from_address(self.satoshi, &address, asset))
+            let mut recipient = Recipient::from_address(satoshi, &address, asset);
+            if let Some(timelock) = self.timelock {
+                recipient.script_pubkey =
+                    Self::timelock_witness_script(&address, timelock)?.to_v0_p2wsh();
+            }
+            Ok(recipient)
         }
     }
 }
 
+/// A recipient which failed validation, see [`crate::TxBuilder::set_unvalidated_recipients()`]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct InvalidRecipient {
+    /// Position of the recipient in the given list
+    pub index: usize,
+
+    /// Why the recipient is invalid
+    pub reason: String,
+}
+
+impl std::fmt::Display for InvalidRecipient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "#{}: {}", self.index, self.reason)
+    }
+}
+
 /// Value returned from [`crate::Wollet::address()`], containing the confidential [`Address`] and the
 /// derivation index (the last element in the derivation path)
+///
+/// Use [`AddressResult::to_unconfidential()`] to get the unconfidential form of the address.
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct AddressResult {
     address: Address,
     index: u32,
+    #[serde(default)]
+    chain: Chain,
+    #[serde(default = "DerivationPath::master")]
+    full_path: DerivationPath,
 }
 
 impl AddressResult {
-    pub fn new(address: Address, index: u32) -> Self {
-        Self { address, index }
+    pub fn new(address: Address, index: u32, chain: Chain, full_path: DerivationPath) -> Self {
+        Self {
+            address,
+            index,
+            chain,
+            full_path,
+        }
     }
 
     pub fn address(&self) -> &Address {
@@ -169,6 +539,30 @@ impl AddressResult {
     pub fn index(&self) -> u32 {
         self.index
     }
+
+    /// Whether the address is external or internal (change)
+    pub fn chain(&self) -> Chain {
+        self.chain
+    }
+
+    /// The derivation path from the master key, for descriptors with multiple keys it's the
+    /// path of the first key
+    pub fn full_path(&self) -> DerivationPath {
+        self.full_path.clone()
+    }
+
+    /// Whether the address has a blinding public key
+    pub fn is_confidential(&self) -> bool {
+        self.address.is_blinded()
+    }
+
+    /// The same address without the blinding public key, with the same script pubkey and index
+    pub fn to_unconfidential(&self) -> Self {
+        Self {
+            address: self.address.to_unconfidential(),
+            ..self.clone()
+        }
+    }
 }
 
 /// Value returned from [`crate::Wollet::issuance()`] containing details about an issuance
@@ -182,8 +576,16 @@ pub struct IssuanceDetails {
     pub asset_amount: Option<u64>,
     pub token_amount: Option<u64>,
     pub is_reissuance: bool,
-    // asset_blinder
-    // token_blinder
+
+    /// The blinding factor of the issued asset amount
+    ///
+    /// Zero if the amount is explicit, `None` if the amount is confidential.
+    pub asset_blinder: Option<ValueBlindingFactor>,
+
+    /// The blinding factor of the issued token amount
+    ///
+    /// Zero if the amount is explicit, `None` if the amount is confidential.
+    pub token_blinder: Option<ValueBlindingFactor>,
 }
 
 pub(crate) struct DisplayTxOutSecrets<'a>(&'a TxOutSecrets);
@@ -226,13 +628,124 @@ impl<'a> std::fmt::Display for DisplayWalletTxInputOutputs<'a> {
 }
 
 impl WalletTx {
-    pub fn unblinded_url(&self, explorer_url: &str) -> String {
-        format!(
-            "{}tx/{}#blinded={}",
-            explorer_url,
-            &self.txid,
-            DisplayWalletTxInputOutputs(self)
-        )
+    /// Net balance of the given asset for the wallet, 0 if the transaction doesn't involve it
+    pub fn net_balance_for(&self, asset: &AssetId) -> i64 {
+        self.balance.get(asset).copied().unwrap_or(0)
+    }
+
+    /// Number of confirmations of the transaction given the current blockchain tip height
+    ///
+    /// Returns 0 if unconfirmed or if the transaction height is above the tip (e.g. after a reorg)
+    pub fn confirmations(&self, tip_height: u32) -> u32 {
+        match self.height {
+            Some(height) if height <= tip_height => tip_height - height + 1,
+            _ => 0,
+        }
+    }
+
+    /// Whether the wallet net balance of the policy asset is increased by this transaction
+    pub fn is_incoming(&self, policy_asset: &AssetId) -> bool {
+        self.net_balance_for(policy_asset) > 0
+    }
+
+    /// Classify the transaction according to its issuances, burns and the net balance of the
+    /// wallet
+    ///
+    /// If the wallet has inputs in the transaction the fee is considered paid by the wallet and
+    /// it's not taken into account, so that for instance a transaction sending an asset and
+    /// receiving another one is a [`TxType::Swap`] even if the wallet pays the fee.
+    pub fn classify(&self) -> TxType {
+        let tx = &self.tx;
+        if tx.input.iter().any(|i| {
+            !i.asset_issuance.is_null() && i.asset_issuance.asset_blinding_nonce == ZERO_TWEAK
+        }) {
+            return TxType::Issuance;
+        }
+        if tx.input.iter().any(|i| {
+            !i.asset_issuance.is_null() && i.asset_issuance.asset_blinding_nonce != ZERO_TWEAK
+        }) {
+            return TxType::Reissuance;
+        }
+        let burn_script = burn_script();
+        if tx.output.iter().any(|o| o.script_pubkey == burn_script) {
+            return TxType::Burn;
+        }
+        if self.balance.is_empty() {
+            return TxType::Unknown;
+        }
+
+        let mut balance = self.balance.clone();
+        let pays_fee = self.inputs.iter().any(Option::is_some);
+        let policy_asset = tx
+            .output
+            .iter()
+            .find(|o| o.is_fee())
+            .and_then(|o| o.asset.explicit());
+        if let (true, Some(policy_asset)) = (pays_fee, policy_asset) {
+            *balance.entry(policy_asset).or_default() += self.fee as i64;
+        }
+        balance.retain(|_, v| *v != 0);
+
+        if balance.is_empty() {
+            TxType::Redeposit
+        } else if balance.values().all(|v| *v > 0) {
+            TxType::Incoming
+        } else if balance.values().all(|v| *v < 0) {
+            TxType::Outgoing
+        } else {
+            TxType::Swap
+        }
+    }
+
+    /// The amounts of each asset burned by the transaction, in the outputs with [`burn_script()`]
+    ///
+    /// Burned amounts leave the circulating supply. Confidential burn outputs are not included,
+    /// since their amounts are not known.
+    pub fn burned(&self) -> BTreeMap<AssetId, u64> {
+        let burn_script = burn_script();
+        let mut burned = BTreeMap::new();
+        for output in self.tx.output.iter() {
+            if output.script_pubkey != burn_script {
+                continue;
+            }
+            if let (Some(asset), Some(value)) = (output.asset.explicit(), output.value.explicit()) {
+                *burned.entry(asset).or_default() += value;
+            }
+        }
+        burned
+    }
+
+    /// Flatten the transaction in one [`CsvRow`] for each asset in the wallet net balance
+    ///
+    /// The fee is set only in the first row, so that summing the fee column of the rows of many
+    /// transactions gives the total fee.
+    pub fn to_csv_rows(&self) -> Vec<CsvRow> {
+        self.balance
+            .iter()
+            .enumerate()
+            .map(|(i, (asset, amount))| CsvRow {
+                txid: self.txid,
+                timestamp: self.timestamp,
+                height: self.height,
+                asset: *asset,
+                amount: *amount,
+                fee: if i == 0 { self.fee } else { 0 },
+            })
+            .collect()
+    }
+
+    /// The url of the transaction in the given explorer, with the unblinding data of the wallet
+    /// inputs and outputs if the explorer supports it
+    pub fn unblinded_url(&self, explorer_url: &str, explorer: ExplorerKind) -> String {
+        match explorer {
+            ExplorerKind::Blockstream | ExplorerKind::Mempool => format!(
+                "{}tx/{}#blinded={}",
+                explorer_url,
+                &self.txid,
+                DisplayWalletTxInputOutputs(self)
+            ),
+            ExplorerKind::Plain => format!("{}tx/{}", explorer_url, &self.txid),
+        }
     }
 }
 
@@ -253,8 +766,427 @@ mod tests {
         let json_str = include_str!("../tests/data/wallet_tx.json");
         let wallet_tx: WalletTx = serde_json::from_str(json_str).unwrap();
         assert_eq!(
-            wallet_tx.unblinded_url("https://blockstream.info/liquidtestnet/"),
+            wallet_tx.unblinded_url("https://blockstream.info/liquidtestnet/", ExplorerKind::default()),
             "https://blockstream.info/liquidtestnet/tx/c6e3187f028942973ad27224ca79baa8382e90ad686e927fc29896e8a2edf3f3#blinded=5000,38fca2d939696061a8f76d4e6b5eecd54e3b4221c846f24a6b279e79952850a5,ab9a42053c7a6ae0d55b774f3d462b1adfaa630e5d0f9b3c0f16640d55b8f6ab,6c5c2b44a0777e463d25eecb70adee84b316c2597b8a28108ffeea38c7acf45d"
         );
     }
+
+    #[test]
+    fn test_wallet_tx_out_address() {
+        let json_str = include_str!("../tests/data/wallet_tx.json");
+        let wallet_tx: WalletTx = serde_json::from_str(json_str).unwrap();
+        let outputs: Vec<_> = wallet_tx.outputs.iter().flatten().collect();
+        assert_eq!(outputs.len(), 1);
+        let network = ElementsNetwork::LiquidTestnet;
+        for output in outputs {
+            let explicit = output.address(network, None).unwrap();
+            assert!(!explicit.is_blinded());
+            assert_eq!(explicit.script_pubkey(), output.script_pubkey);
+            assert_eq!(
+                explicit.to_string(),
+                "tex1qqtlnn8ktsgtczg406xqq83ayq8d6zm4hnrw4qj"
+            );
+
+            let blinding_pubkey = PublicKey::from_str(
+                "0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798",
+            )
+            .unwrap();
+            let confidential = output.address(network, Some(blinding_pubkey)).unwrap();
+            assert!(confidential.is_blinded());
+            assert_eq!(confidential.blinding_pubkey, Some(blinding_pubkey));
+            assert_eq!(confidential.to_unconfidential(), explicit);
+            assert_eq!(confidential.script_pubkey(), output.script_pubkey);
+        }
+
+        // Non address scripts cannot be converted
+        let mut output = wallet_tx.outputs[0].clone().unwrap();
+        output.script_pubkey = burn_script();
+        assert!(output.address(network, None).is_none());
+    }
+
+    #[test]
+    fn test_confirmations() {
+        let json_str = include_str!("../tests/data/wallet_tx.json");
+        let mut wallet_tx: WalletTx = serde_json::from_str(json_str).unwrap();
+        assert_eq!(wallet_tx.height, Some(1306469));
+        assert_eq!(wallet_tx.confirmations(1306469), 1);
+        assert_eq!(wallet_tx.confirmations(1306474), 6);
+        assert_eq!(wallet_tx.confirmations(1306468), 0);
+
+        wallet_tx.height = None;
+        assert_eq!(wallet_tx.confirmations(1306474), 0);
+    }
+
+    #[test]
+    fn test_unblinded_url_explorers() {
+        let json_str = include_str!("../tests/data/wallet_tx.json");
+        let wallet_tx: WalletTx = serde_json::from_str(json_str).unwrap();
+        let txid = "c6e3187f028942973ad27224ca79baa8382e90ad686e927fc29896e8a2edf3f3";
+        let blinded = "5000,38fca2d939696061a8f76d4e6b5eecd54e3b4221c846f24a6b279e79952850a5,ab9a42053c7a6ae0d55b774f3d462b1adfaa630e5d0f9b3c0f16640d55b8f6ab,6c5c2b44a0777e463d25eecb70adee84b316c2597b8a28108ffeea38c7acf45d";
+
+        let url = "https://blockstream.info/liquidtestnet/";
+        assert_eq!(
+            wallet_tx.unblinded_url(url, ExplorerKind::Blockstream),
+            format!("{url}tx/{txid}#blinded={blinded}")
+        );
+
+        let url = "https://liquid.network/testnet/";
+        assert_eq!(
+            wallet_tx.unblinded_url(url, ExplorerKind::Mempool),
+            format!("{url}tx/{txid}#blinded={blinded}")
+        );
+
+        let url = "http://localhost:8080/";
+        assert_eq!(
+            wallet_tx.unblinded_url(url, ExplorerKind::Plain),
+            format!("{url}tx/{txid}")
+        );
+    }
+
+    #[test]
+    fn test_wollet_tx_net_balance() {
+        let json_str = include_str!("../tests/data/wallet_tx.json");
+        let wallet_tx: WalletTx = serde_json::from_str(json_str).unwrap();
+        let asset =
+            AssetId::from_str("38fca2d939696061a8f76d4e6b5eecd54e3b4221c846f24a6b279e79952850a5")
+                .unwrap();
+        let policy_asset = ElementsNetwork::LiquidTestnet.policy_asset();
+        assert_eq!(wallet_tx.net_balance_for(&asset), 5000);
+        assert_eq!(wallet_tx.net_balance_for(&policy_asset), 0);
+        assert!(wallet_tx.is_incoming(&asset));
+        assert!(!wallet_tx.is_incoming(&policy_asset));
+    }
+
+    #[test]
+    fn test_wollet_tx_classify() {
+        let json_str = include_str!("../tests/data/wallet_tx.json");
+        let incoming: WalletTx = serde_json::from_str(json_str).unwrap();
+        let asset =
+            AssetId::from_str("38fca2d939696061a8f76d4e6b5eecd54e3b4221c846f24a6b279e79952850a5")
+                .unwrap();
+        let policy_asset = ElementsNetwork::LiquidTestnet.policy_asset();
+        let fee = incoming.fee as i64;
+        assert_eq!(incoming.classify(), TxType::Incoming);
+        assert_eq!(incoming.classify().to_string(), "incoming");
+
+        let mut unknown = incoming.clone();
+        unknown.balance.clear();
+        assert_eq!(unknown.classify(), TxType::Unknown);
+
+        // The wallet spends an input, so it pays the fee
+        let mut spending = incoming.clone();
+        spending.inputs[0] = spending.outputs[0].clone();
+
+        let mut outgoing = spending.clone();
+        outgoing.balance = [(policy_asset, -1000 - fee)].into();
+        assert_eq!(outgoing.classify(), TxType::Outgoing);
+
+        let mut redeposit = spending.clone();
+        redeposit.balance = [(policy_asset, -fee)].into();
+        assert_eq!(redeposit.classify(), TxType::Redeposit);
+
+        let mut swap = spending.clone();
+        swap.balance = [(policy_asset, -1000 - fee), (asset, 5000)].into();
+        assert_eq!(swap.classify(), TxType::Swap);
+
+        // Paying only the fee to receive an asset is not a swap
+        let mut incoming_paying_fee = spending.clone();
+        incoming_paying_fee.balance = [(policy_asset, -fee), (asset, 5000)].into();
+        assert_eq!(incoming_paying_fee.classify(), TxType::Incoming);
+
+        let mut burn = outgoing.clone();
+        let mut burn_output = burn.tx.output[0].clone();
+        burn_output.script_pubkey = burn_script();
+        burn.tx.output.push(burn_output.clone());
+        assert_eq!(burn.classify(), TxType::Burn);
+
+        // The amounts of confidential burn outputs are not known
+        assert!(outgoing.burned().is_empty());
+        assert!(burn.burned().is_empty());
+        burn_output.asset = crate::elements::confidential::Asset::Explicit(asset);
+        burn_output.value = crate::elements::confidential::Value::Explicit(300);
+        burn.tx.output.push(burn_output.clone());
+        burn.tx.output.push(burn_output.clone());
+        burn_output.asset = crate::elements::confidential::Asset::Explicit(policy_asset);
+        burn.tx.output.push(burn_output);
+        assert_eq!(burn.burned(), [(asset, 600), (policy_asset, 300)].into());
+
+        let mut issuance = outgoing.clone();
+        issuance.tx.input[0].asset_issuance.amount =
+            crate::elements::confidential::Value::Explicit(1000);
+        assert_eq!(issuance.classify(), TxType::Issuance);
+
+        let mut reissuance = issuance.clone();
+        reissuance.tx.input[0].asset_issuance.asset_blinding_nonce =
+            crate::elements::secp256k1_zkp::Tweak::from_slice(&[1; 32]).unwrap();
+        assert_eq!(reissuance.classify(), TxType::Reissuance);
+    }
+
+    #[test]
+    fn test_wollet_tx_csv_rows() {
+        let json_str = include_str!("../tests/data/wallet_tx.json");
+        let mut wallet_tx: WalletTx = serde_json::from_str(json_str).unwrap();
+        let asset =
+            AssetId::from_str("38fca2d939696061a8f76d4e6b5eecd54e3b4221c846f24a6b279e79952850a5")
+                .unwrap();
+        let policy_asset = ElementsNetwork::LiquidTestnet.policy_asset();
+        wallet_tx.balance.insert(policy_asset, -1000);
+
+        let rows = wallet_tx.to_csv_rows();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows.iter().map(|r| r.fee).sum::<u64>(), wallet_tx.fee);
+        for row in rows.iter() {
+            assert_eq!(row.txid, wallet_tx.txid);
+            assert_eq!(row.height, wallet_tx.height);
+            assert_eq!(row.timestamp, wallet_tx.timestamp);
+            assert_eq!(row.amount, wallet_tx.net_balance_for(&row.asset));
+        }
+        let row = rows.iter().find(|r| r.asset == asset).unwrap();
+        assert_eq!(
+            row.to_string().split(',').collect::<Vec<_>>()[3..5],
+            [asset.to_string(), "5000".to_string()]
+        );
+        assert_eq!(
+            row.to_string().split(',').count(),
+            CsvRow::HEADER.split(',').count()
+        );
+    }
+
+    #[test]
+    fn test_recipient_from_uri() {
+        let address = "tlq1qqf5wd9ju8pfwn9s4z2ng2asfcqfvkypl95sdnzhfv7d3ng8srz80q8ekmqvrpxanr6hmcngar3ncg9f2uxmptn6r8ua6qkzql";
+        let asset = "38fca2d939696061a8f76d4e6b5eecd54e3b4221c846f24a6b279e79952850a5";
+
+        let uri = format!("liquidtestnet:{address}?amount=0.00001&assetid={asset}");
+        let recipient = UnvalidatedRecipient::from_uri(&uri).unwrap();
+        assert_eq!(recipient.address, address);
+        assert_eq!(recipient.satoshi, Some(1_000));
+        assert_eq!(recipient.asset, asset);
+
+        let uri = format!("liquidnetwork:{address}?label=x&amount=1.5");
+        let recipient = UnvalidatedRecipient::from_uri(&uri).unwrap();
+        assert_eq!(recipient.satoshi, Some(150_000_000));
+        assert_eq!(recipient.asset, "");
+
+        for uri in [
+            format!("bitcoin:{address}?amount=1"),
+            "liquidnetwork:?amount=1".to_string(),
+            format!("liquidnetwork:{address}"),
+            format!("liquidnetwork:{address}?amount=0.000000001"),
+            format!("liquidnetwork:{address}?amount=1e3"),
+            format!("liquidnetwork:{address}?amount=-1"),
+            format!("liquidnetwork:{address}?amount=1&req-foo=bar"),
+            address.to_string(),
+        ] {
+            let err = UnvalidatedRecipient::from_uri(&uri).unwrap_err();
+            assert!(matches!(err, Error::InvalidUri(_)), "{uri}");
+        }
+    }
+
+    #[test]
+    fn test_op_return_recipient() {
+        let network = ElementsNetwork::LiquidTestnet;
+        let recipient = UnvalidatedRecipient::op_return(&[0xde, 0xad, 0xbe, 0xef]);
+        assert_eq!(recipient.address, "op_return:deadbeef");
+        let validated = recipient.validate(network).unwrap();
+        assert_eq!(validated.satoshi, 0);
+        assert_eq!(validated.blinding_pubkey, None);
+        assert_eq!(validated.asset, network.policy_asset());
+        assert!(validated.script_pubkey.is_op_return());
+        assert_eq!(validated.script_pubkey.to_hex(), "6a04deadbeef");
+
+        let recipient =
+            UnvalidatedRecipient::try_from("op_return:deadbeef:0:".to_string()).unwrap();
+        assert_eq!(recipient.address, "op_return:deadbeef");
+        let script = recipient.validate(network).unwrap().script_pubkey;
+        assert_eq!(script, validated.script_pubkey);
+
+        let max = UnvalidatedRecipient::op_return(&[0u8; MAX_OP_RETURN_DATA]);
+        let script = max.validate(network).unwrap().script_pubkey;
+        assert_eq!(script.len(), 3 + MAX_OP_RETURN_DATA); // OP_RETURN OP_PUSHDATA1 <len> <data>
+
+        let too_big = UnvalidatedRecipient::op_return(&[0u8; MAX_OP_RETURN_DATA + 1]);
+        let err = too_big.validate(network).unwrap_err();
+        assert!(matches!(err, Error::InvalidOpReturn(_)));
+
+        for address in ["op_return:abc", "op_return:zz"] {
+            let recipient = UnvalidatedRecipient {
+                satoshi: Some(0),
+                address: address.to_string(),
+                asset: "".to_string(),
+                timelock: None,
+            };
+            let err = recipient.validate(network).unwrap_err();
+            assert!(matches!(err, Error::InvalidOpReturn(_)), "{address}");
+        }
+
+        for satoshi in [None, Some(1)] {
+            let recipient = UnvalidatedRecipient {
+                satoshi,
+                ..UnvalidatedRecipient::op_return(&[1])
+            };
+            let err = recipient.validate(network).unwrap_err();
+            assert!(matches!(err, Error::InvalidAmount));
+        }
+    }
+
+    #[test]
+    fn test_recipient_errors() {
+        let network = ElementsNetwork::LiquidTestnet;
+        for s in ["address:1000", "address:ten:", "address:-1:"] {
+            let err = UnvalidatedRecipient::try_from(s.to_string()).unwrap_err();
+            assert!(matches!(err, Error::InvalidRecipientFormat(_)), "{s}");
+        }
+
+        let address = "tlq1qq2xvpcvfup5j8zscjq05u2wxxjcyewk7979f3mmz5l7uw5pqmx6xf5xy50hsn6vhkm5euwt72x878eq6zxx2z58hd7zrsg9qn";
+        let recipient = UnvalidatedRecipient::try_from(format!("{address}:1000:xyz")).unwrap();
+        let err = recipient.validate(network).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Unknown asset 'xyz', it must be an asset id"
+        );
+
+        let recipient = UnvalidatedRecipient::try_from(format!("{address}:1000:")).unwrap();
+        let err = recipient.validate(ElementsNetwork::Liquid).unwrap_err();
+        assert!(matches!(err, Error::AddressNetworkMismatch(_, _)));
+
+        // Burns need an explicit asset
+        let err = UnvalidatedRecipient::burn("".to_string(), 1000)
+            .validate(network)
+            .unwrap_err();
+        assert!(matches!(err, Error::MissingBurnAsset));
+        let recipient = UnvalidatedRecipient::try_from("burn:1000:".to_string()).unwrap();
+        let err = recipient.validate(network).unwrap_err();
+        assert!(matches!(err, Error::MissingBurnAsset));
+        let policy_asset = network.policy_asset().to_string();
+        let burn = UnvalidatedRecipient::burn(policy_asset, 1000)
+            .validate(network)
+            .unwrap();
+        assert_eq!(burn.asset, network.policy_asset());
+        assert_eq!(burn.script_pubkey, burn_script());
+    }
+
+    #[test]
+    fn test_decimal_recipient() {
+        let address = "tlq1qq2xvpcvfup5j8zscjq05u2wxxjcyewk7979f3mmz5l7uw5pqmx6xf5xy50hsn6vhkm5euwt72x878eq6zxx2z58hd7zrsg9qn";
+        let asset = "38fca2d939696061a8f76d4e6b5eecd54e3b4221c846f24a6b279e79952850a5";
+
+        // (amount, precision, satoshi)
+        let cases: [(&str, u8, u64); 9] = [
+            ("0.001", 8, 100_000),
+            ("0.00000001", 8, 1),
+            ("1", 8, 100_000_000),
+            (".5", 8, 50_000_000),
+            ("1.5", 2, 150),
+            ("1.50", 2, 150),
+            ("1,000.25", 2, 100_025),
+            ("42", 0, 42),
+            ("0.10", 3, 100),
+        ];
+        for (amount, precision, satoshi) in cases {
+            let r = UnvalidatedRecipient::from_decimal(
+                &format!("{address}:{amount}:{asset}"),
+                precision,
+            )
+            .unwrap();
+            assert_eq!(r.satoshi, Some(satoshi), "{amount} {precision}");
+            assert_eq!(r.address, address);
+            assert_eq!(r.asset, asset);
+        }
+
+        let r = UnvalidatedRecipient::from_decimal(&format!("{address}:max:{asset}"), 2).unwrap();
+        assert!(r.is_max());
+        let r = UnvalidatedRecipient::from_decimal("op_return:deadbeef:0:", 8).unwrap();
+        assert_eq!(r.address, "op_return:deadbeef");
+
+        // Too precise amounts are not rounded
+        for (amount, precision) in [("0.000000001", 8), ("1.555", 2), ("0.1", 0)] {
+            let s = format!("{address}:{amount}:{asset}");
+            let err = UnvalidatedRecipient::from_decimal(&s, precision).unwrap_err();
+            assert!(matches!(err, Error::InvalidDecimalAmount(_, _)), "{amount}");
+        }
+        for amount in ["-1", "abc", "1.2.3", "1,0"] {
+            let s = format!("{address}:{amount}:{asset}");
+            let err = UnvalidatedRecipient::from_decimal(&s, 8).unwrap_err();
+            assert!(matches!(err, Error::InvalidDecimalAmount(_, _)), "{amount}");
+        }
+        let err = UnvalidatedRecipient::from_decimal(&format!("{address}:1"), 8).unwrap_err();
+        assert!(matches!(err, Error::InvalidRecipientFormat(_)));
+        let err =
+            UnvalidatedRecipient::from_decimal(&format!("{address}:1:{asset}"), 9).unwrap_err();
+        assert!(matches!(err, Error::InvalidDecimalAmount(_, _)));
+
+        // The integer satoshi form is unchanged
+        let r = UnvalidatedRecipient::try_from(format!("{address}:1000:{asset}")).unwrap();
+        assert_eq!(r.satoshi, Some(1000));
+    }
+
+    #[test]
+    fn test_timelock_recipient() {
+        let network = ElementsNetwork::LiquidTestnet;
+        let address = "tlq1qq2xvpcvfup5j8zscjq05u2wxxjcyewk7979f3mmz5l7uw5pqmx6xf5xy50hsn6vhkm5euwt72x878eq6zxx2z58hd7zrsg9qn";
+        let recipient = UnvalidatedRecipient {
+            satoshi: Some(1_000),
+            address: address.to_string(),
+            asset: "".to_string(),
+            timelock: None,
+        };
+        let plain = recipient.validate(network).unwrap();
+        let pubkey_hash = plain.script_pubkey.as_bytes()[2..].to_hex();
+
+        let locked = UnvalidatedRecipient {
+            timelock: Some(Timelock::Height(1_000)),
+            ..recipient.clone()
+        };
+        let validated = locked.validate(network).unwrap();
+        let witness_script = Script::from_hex(&format!("76a914{pubkey_hash}88ad02e803b1")).unwrap();
+        assert_eq!(validated.script_pubkey, witness_script.to_v0_p2wsh());
+        assert_eq!(validated.blinding_pubkey, plain.blinding_pubkey);
+        assert_eq!(validated.satoshi, 1_000);
+
+        let locked = UnvalidatedRecipient {
+            timelock: Some(Timelock::Blocks(144)),
+            ..recipient.clone()
+        };
+        let validated = locked.validate(network).unwrap();
+        let witness_script = Script::from_hex(&format!("76a914{pubkey_hash}88ad029000b2")).unwrap();
+        assert_eq!(validated.script_pubkey, witness_script.to_v0_p2wsh());
+
+        for timelock in [
+            Timelock::Height(0),
+            Timelock::Height(LOCKTIME_THRESHOLD),
+            Timelock::Blocks(0),
+        ] {
+            let locked = UnvalidatedRecipient {
+                timelock: Some(timelock),
+                ..recipient.clone()
+            };
+            let err = locked.validate(network).unwrap_err();
+            assert!(matches!(err, Error::InvalidTimelock(_)), "{timelock:?}");
+        }
+
+        // Only P2WPKH addresses can be locked
+        let p2wsh = Address::from_script(
+            &validated.script_pubkey,
+            plain.blinding_pubkey,
+            network.address_params(),
+        )
+        .unwrap();
+        for address in [
+            p2wsh.to_string(),
+            "burn".to_string(),
+            UnvalidatedRecipient::op_return(&[1]).address,
+        ] {
+            let locked = UnvalidatedRecipient {
+                address: address.clone(),
+                timelock: Some(Timelock::Blocks(1)),
+                ..recipient.clone()
+            };
+            let err = locked.validate(network).unwrap_err();
+            assert!(matches!(err, Error::InvalidTimelock(_)), "{address}");
+        }
+
+        let json = serde_json::to_string(&Timelock::Height(1_000)).unwrap();
+        assert_eq!(json, r#"{"height":1000}"#);
+    }
 }
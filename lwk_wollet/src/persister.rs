@@ -31,6 +31,13 @@ pub trait Persister {
     ///
     /// Implementors are encouraged to coalesce consequent updates with `update.only_tip() == true`
     fn push(&self, update: Update) -> Result<(), PersistError>;
+
+    /// Remove all the persisted updates
+    ///
+    /// Persisters not supporting it return an error
+    fn clear(&self) -> Result<(), PersistError> {
+        Err(PersistError::Other("clear is not supported".to_string()))
+    }
 }
 
 sha256t_hash_newtype! {
@@ -59,6 +66,44 @@ impl Persister for NoPersist {
     fn push(&self, _update: Update) -> Result<(), PersistError> {
         Ok(())
     }
+
+    fn clear(&self) -> Result<(), PersistError> {
+        Ok(())
+    }
+}
+
+/// Implementation of a [`Persister`] keeping the updates in memory
+///
+/// Nothing is written on the file system, but a [`crate::Wollet`] created again with the same
+/// persister restores its state, useful for tests and short-lived processes.
+#[derive(Default)]
+pub struct MemoryPersister(Mutex<Vec<Update>>);
+
+impl MemoryPersister {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self::default())
+    }
+}
+
+impl Persister for MemoryPersister {
+    fn get(&self, index: usize) -> Result<Option<Update>, PersistError> {
+        Ok(self.0.lock().map_err(to_other)?.get(index).cloned())
+    }
+
+    fn push(&self, update: Update) -> Result<(), PersistError> {
+        let mut updates = self.0.lock().map_err(to_other)?;
+        if update.only_tip() && updates.last().map_or(false, |u| u.only_tip()) {
+            // Like the file system persister, replace the last update if both are only tips
+            updates.pop();
+        }
+        updates.push(update);
+        Ok(())
+    }
+
+    fn clear(&self) -> Result<(), PersistError> {
+        self.0.lock().map_err(to_other)?.clear();
+        Ok(())
+    }
 }
 
 struct FsPersisterInner {
@@ -180,6 +225,15 @@ impl Persister for FsPersister {
         inner.next = inner.next.clone() + 1;
         Ok(())
     }
+
+    fn clear(&self) -> Result<(), PersistError> {
+        let mut inner = self.inner.lock().map_err(to_other)?;
+        for i in 0..inner.next.0 {
+            fs::remove_file(inner.path(&Counter::from(i)))?;
+        }
+        inner.next = Counter::default();
+        Ok(())
+    }
 }
 
 const PERSISTED_FILE_NAME_LENGTH: usize = 12;
@@ -229,31 +283,11 @@ impl Add<usize> for Counter {
 
 #[cfg(test)]
 mod test {
-    use std::{
-        str::FromStr,
-        sync::{Arc, Mutex},
-    };
-
-    use crate::{ElementsNetwork, FsPersister, PersistError, Update, WolletDescriptor};
-
-    use super::{Counter, NoPersist, Persister};
+    use std::{str::FromStr, sync::Arc};
 
-    struct MemoryPersister(Mutex<Vec<Update>>);
-    impl MemoryPersister {
-        pub fn new() -> Arc<Self> {
-            Arc::new(Self(Mutex::new(vec![])))
-        }
-    }
-    impl Persister for MemoryPersister {
-        fn get(&self, index: usize) -> Result<Option<Update>, PersistError> {
-            Ok(self.0.lock().unwrap().get(index).cloned())
-        }
+    use crate::{ElementsNetwork, FsPersister, Update, WolletDescriptor};
 
-        fn push(&self, update: crate::Update) -> Result<(), PersistError> {
-            self.0.lock().unwrap().push(update);
-            Ok(())
-        }
-    }
+    use super::{Counter, MemoryPersister, NoPersist, Persister};
 
     fn inner_test_persister(persister: Arc<dyn Persister>, first_time: bool) {
         if first_time {
@@ -311,6 +345,26 @@ mod test {
         inner_test_persister(persister, false);
     }
 
+    #[test]
+    fn test_fs_persister_clear() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let desc = wollet_descriptor_test_vector();
+        let n = ElementsNetwork::LiquidTestnet;
+        let persister = FsPersister::new(&tempdir, n, &desc).unwrap();
+        inner_test_persister(persister.clone(), true);
+        persister.clear().unwrap();
+        assert!(persister.get(0).unwrap().is_none());
+
+        let persister = FsPersister::new(&tempdir, n, &desc).unwrap();
+        assert!(persister.get(0).unwrap().is_none());
+        inner_test_persister(persister, true);
+
+        let persister = MemoryPersister::new();
+        inner_test_persister(persister.clone(), true);
+        persister.clear().unwrap();
+        assert!(persister.get(0).unwrap().is_none());
+    }
+
     #[test]
     fn test_counter() {
         let c = Counter::default();
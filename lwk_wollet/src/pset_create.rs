@@ -172,9 +172,20 @@ fn convert_pubkey(pk: crate::elements::secp256k1_zkp::PublicKey) -> BitcoinPubli
     BitcoinPublicKey::new(pk)
 }
 
-pub(crate) fn validate_address(address: &str, network: ElementsNetwork) -> Result<Address, Error> {
+/// Parse the given address for the given network, erroring if it's not confidential
+pub fn validate_address(address: &str, network: ElementsNetwork) -> Result<Address, Error> {
     let params = network.address_params();
-    let address = Address::parse_with_params(address, params)?;
+    let address = match Address::parse_with_params(address, params) {
+        Ok(parsed) => parsed,
+        // A valid address of another network
+        Err(_) if address.parse::<Address>().is_ok() => {
+            return Err(Error::AddressNetworkMismatch(
+                address.to_string(),
+                network.as_str().to_string(),
+            ))
+        }
+        Err(e) => return Err(e.into()),
+    };
     if address.blinding_pubkey.is_none() {
         return Err(Error::NotConfidentialAddress);
     };
@@ -183,7 +194,7 @@ pub(crate) fn validate_address(address: &str, network: ElementsNetwork) -> Resul
 
 #[cfg(test)]
 mod test {
-    use crate::{pset_create::validate_address, ElementsNetwork};
+    use crate::{pset_create::validate_address, ElementsNetwork, Error};
 
     #[test]
     fn test_validate() {
@@ -193,6 +204,14 @@ mod test {
         assert_eq!(addr.to_string(), testnet_address);
 
         let network = ElementsNetwork::Liquid;
-        assert!(validate_address(testnet_address, network).is_err())
+        let err = validate_address(testnet_address, network).unwrap_err();
+        assert!(matches!(err, Error::AddressNetworkMismatch(_, _)));
+        assert_eq!(
+            err.to_string(),
+            format!("Address {testnet_address} is not for network liquid")
+        );
+
+        let err = validate_address("not an address", network).unwrap_err();
+        assert!(matches!(err, Error::AddressError(_)));
     }
 }
@@ -70,6 +70,9 @@ impl Contract {
             return Err(Error::InvalidTicker);
         }
 
+        if self.issuer_pubkey.len() != 33 {
+            return Err(Error::InvalidIssuerPubkey);
+        }
         verify_pubkey(&self.issuer_pubkey).map_err(|_| Error::InvalidIssuerPubkey)?;
 
         let Entity::Domain(domain) = &self.entity;
@@ -161,9 +164,23 @@ mod tests {
         assert!(contract.validate().is_err());
         contract.entity = Entity::Domain("tether.to".to_string());
 
+        let pk = crate::secp256k1::PublicKey::from_slice(&contract.issuer_pubkey).unwrap();
         contract.issuer_pubkey = vec![2];
         assert!(contract.validate().is_err());
-        contract.issuer_pubkey = vec![2; 32];
+        // Valid pubkey but not compressed
+        contract.issuer_pubkey = pk.serialize_uncompressed().to_vec();
+        assert!(matches!(
+            contract.validate(),
+            Err(Error::InvalidIssuerPubkey)
+        ));
+        // Right length but the x coordinate exceeds the field size
+        contract.issuer_pubkey = vec![0xff; 33];
+        contract.issuer_pubkey[0] = 2;
+        assert!(matches!(
+            contract.validate(),
+            Err(Error::InvalidIssuerPubkey)
+        ));
+        contract.issuer_pubkey = pk.serialize().to_vec();
 
         contract.ticker = "US".to_string();
         assert!(contract.validate().is_err());
@@ -0,0 +1,430 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use elements::{
+    confidential::{Asset, Value},
+    encode::{deserialize, serialize},
+    opcodes::all::OP_PUSHNUM_1,
+    pset::{raw::ProprietaryKey, Input, Output, PartiallySignedTransaction},
+    script::Builder,
+    secp256k1_zkp::{Generator, RangeProof, SurjectionProof},
+    AssetId, BlindAssetProofs, BlindValueProofs, BlockHash, EcdsaSighashType, OutPoint, Script,
+    TxOut, TxOutSecrets, Txid,
+};
+use elements_miniscript::psbt::PsbtExt;
+use rand::thread_rng;
+
+use crate::{hashes::Hash, Error, Wollet, EC};
+
+/// Prefix of the message committed by the challenge input
+const CHALLENGE_PREFIX: &str = "Proof-of-Reserves: ";
+
+/// PSET input key with the explicit value of the spent output
+const PSBT_ELEMENTS_IN_EXPLICIT_VALUE: u8 = 0x11;
+
+/// PSET input key with the proof the value commitment matches the explicit value
+const PSBT_ELEMENTS_IN_VALUE_PROOF: u8 = 0x12;
+
+/// PSET input key with the explicit asset of the spent output
+const PSBT_ELEMENTS_IN_EXPLICIT_ASSET: u8 = 0x13;
+
+/// PSET input key with the proof the asset commitment matches the explicit asset
+const PSBT_ELEMENTS_IN_ASSET_PROOF: u8 = 0x14;
+
+/// The outpoint spent by the first input of a proof of reserves, committing to the challenge
+///
+/// The outpoint does not exist, thus the proof can't be broadcast.
+pub fn challenge_outpoint(challenge: &str) -> OutPoint {
+    let message = format!("{CHALLENGE_PREFIX}{challenge}");
+    OutPoint::new(Txid::hash(message.as_bytes()), 0)
+}
+
+/// Anyone can spend script, used by the outputs of the proof
+fn op_true() -> Script {
+    Builder::new().push_opcode(OP_PUSHNUM_1).into_script()
+}
+
+fn invalid(reason: impl Into<String>) -> Error {
+    Error::InvalidProofOfReserves(reason.into())
+}
+
+fn set_prop(input: &mut Input, subtype: u8, value: Vec<u8>) {
+    let key = ProprietaryKey::from_pset_pair(subtype, vec![]);
+    input.proprietary.insert(key, value);
+}
+
+fn get_prop(input: &Input, subtype: u8) -> Option<&Vec<u8>> {
+    let key = ProprietaryKey::from_pset_pair(subtype, vec![]);
+    input.proprietary.get(&key)
+}
+
+/// The first input of a proof of reserves, see [`challenge_outpoint()`]
+fn challenge_input(challenge: &str, policy_asset: AssetId) -> Input {
+    let mut input = Input::from_prevout(challenge_outpoint(challenge));
+    // Signers need all the spent outputs to compute the sighash
+    input.witness_utxo = Some(TxOut {
+        asset: Asset::Explicit(policy_asset),
+        value: Value::Explicit(0),
+        script_pubkey: op_true(),
+        ..Default::default()
+    });
+    input
+}
+
+/// Set the explicit value and asset of the input, with the proofs they match the spent output
+fn set_explicit_proofs(input: &mut Input, secrets: &TxOutSecrets) -> Result<(), Error> {
+    let txout = input.witness_utxo.clone().ok_or(Error::MissingVout)?;
+    let mut rng = thread_rng();
+    set_prop(
+        input,
+        PSBT_ELEMENTS_IN_EXPLICIT_VALUE,
+        serialize(&secrets.value),
+    );
+    set_prop(
+        input,
+        PSBT_ELEMENTS_IN_EXPLICIT_ASSET,
+        serialize(&secrets.asset),
+    );
+    if let (Some(value_commit), Some(asset_gen)) =
+        (txout.value.commitment(), txout.asset.commitment())
+    {
+        let proof = RangeProof::blind_value_proof(
+            &mut rng,
+            &EC,
+            secrets.value,
+            value_commit,
+            asset_gen,
+            secrets.value_bf,
+        )?;
+        set_prop(input, PSBT_ELEMENTS_IN_VALUE_PROOF, proof.serialize());
+    }
+    if txout.asset.is_confidential() {
+        let proof =
+            SurjectionProof::blind_asset_proof(&mut rng, &EC, secrets.asset, secrets.asset_bf)?;
+        set_prop(input, PSBT_ELEMENTS_IN_ASSET_PROOF, proof.serialize());
+    }
+    Ok(())
+}
+
+impl Wollet {
+    /// Create a proof of reserves of all the wallet utxos, committing to `challenge`
+    ///
+    /// The PSET follows BIP-127: the first input spends a non-existent outpoint derived from the
+    /// challenge (see [`challenge_outpoint()`]), making the transaction invalid, the other inputs
+    /// spend all the wallet utxos. Each wallet input has the unblinded value and asset, with the
+    /// proofs they match the commitments of the spent output. There is an explicit output for
+    /// each asset, with the total amount of the asset.
+    ///
+    /// The PSET must be signed by the wallet signers, then it can be checked with
+    /// [`verify_proof_of_reserves()`].
+    pub fn proof_of_reserves(&self, challenge: &str) -> Result<PartiallySignedTransaction, Error> {
+        let utxos = self.utxos()?;
+        if utxos.is_empty() {
+            return Err(Error::InsufficientFunds);
+        }
+
+        let mut pset = PartiallySignedTransaction::new_v2();
+        pset.add_input(challenge_input(challenge, self.policy_asset()));
+
+        let mut inp_txout_sec = HashMap::new();
+        let mut inp_weight = 0;
+        let mut totals = BTreeMap::new();
+        for utxo in utxos.iter() {
+            let idx = self.add_input(&mut pset, &mut inp_txout_sec, &mut inp_weight, utxo)?;
+            let secrets = utxo.unblinded;
+            set_explicit_proofs(&mut pset.inputs_mut()[idx], &secrets)?;
+            *totals.entry(secrets.asset).or_insert(0) += secrets.value;
+        }
+
+        for (asset, satoshi) in totals {
+            pset.add_output(Output {
+                script_pubkey: op_true(),
+                amount: Some(satoshi),
+                asset: Some(asset),
+                ..Default::default()
+            });
+        }
+
+        self.add_details(&mut pset)?;
+
+        Ok(pset)
+    }
+}
+
+/// Verify a proof of reserves created with [`Wollet::proof_of_reserves()`] for `challenge`
+///
+/// Returns the total amount proven for each asset. Every input, except the challenge one, must
+/// have the explicit value and asset with their proofs and must be signed with `SIGHASH_ALL`, so
+/// that the signatures commit to the challenge. The signatures must not be finalized.
+///
+/// Note that the proof does not show the spent outputs are unspent, the caller must check it.
+pub fn verify_proof_of_reserves(
+    pset: &PartiallySignedTransaction,
+    challenge: &str,
+) -> Result<BTreeMap<AssetId, u64>, Error> {
+    let inputs = pset.inputs();
+    let first = inputs.first().ok_or_else(|| invalid("no inputs"))?;
+    let outpoint = OutPoint::new(first.previous_txid, first.previous_output_index);
+    if outpoint != challenge_outpoint(challenge) {
+        return Err(invalid("the first input does not commit to the challenge"));
+    }
+    if inputs.len() < 2 {
+        return Err(invalid("no reserves"));
+    }
+
+    let mut outpoints = HashSet::new();
+    let mut totals = BTreeMap::new();
+    for (idx, input) in inputs.iter().enumerate().skip(1) {
+        let outpoint = OutPoint::new(input.previous_txid, input.previous_output_index);
+        if !outpoints.insert(outpoint) {
+            return Err(invalid(format!("input #{idx} spends {outpoint} twice")));
+        }
+        let (asset, satoshi) =
+            verify_input(input).map_err(|e| invalid(format!("input #{idx}: {e}")))?;
+        if input.final_script_witness.is_some() {
+            return Err(invalid(format!("input #{idx} is finalized")));
+        }
+        let all = EcdsaSighashType::All.as_u32();
+        if input.sighash_type.map_or(false, |t| t.to_u32() != all)
+            || input
+                .partial_sigs
+                .values()
+                .any(|sig| sig.last().map(|t| *t as u32) != Some(all))
+        {
+            return Err(invalid(format!(
+                "input #{idx} is not signed with SIGHASH_ALL"
+            )));
+        }
+        // Finalizing checks the signatures are valid and enough
+        // genesis_hash is only used for BIP341 (taproot) sighash computation
+        pset.clone()
+            .finalize_inp_mut(&EC, idx, BlockHash::all_zeros())
+            .map_err(|e| invalid(format!("input #{idx} is not signed: {e}")))?;
+        *totals.entry(asset).or_insert(0) += satoshi;
+    }
+
+    let mut outputs = BTreeMap::new();
+    for output in pset.outputs() {
+        match (output.asset, output.amount) {
+            (Some(asset), Some(satoshi)) if output.script_pubkey == op_true() => {
+                *outputs.entry(asset).or_insert(0) += satoshi;
+            }
+            _ => return Err(invalid("outputs must be explicit and anyone can spend")),
+        }
+    }
+    if outputs != totals {
+        return Err(invalid("outputs do not match the inputs"));
+    }
+
+    Ok(totals)
+}
+
+/// Verify the explicit value and asset of the input match the spent output
+fn verify_input(input: &Input) -> Result<(AssetId, u64), String> {
+    let txout = input.witness_utxo.as_ref().ok_or("missing spent output")?;
+    let satoshi: u64 = get_prop(input, PSBT_ELEMENTS_IN_EXPLICIT_VALUE)
+        .and_then(|v| deserialize(v).ok())
+        .ok_or("missing explicit value")?;
+    let asset: AssetId = get_prop(input, PSBT_ELEMENTS_IN_EXPLICIT_ASSET)
+        .and_then(|v| deserialize(v).ok())
+        .ok_or("missing explicit asset")?;
+
+    let asset_gen = match txout.asset {
+        Asset::Explicit(explicit) if explicit == asset => {
+            Generator::new_unblinded(&EC, asset.into_tag())
+        }
+        Asset::Confidential(asset_gen) => {
+            let proof = get_prop(input, PSBT_ELEMENTS_IN_ASSET_PROOF)
+                .and_then(|v| SurjectionProof::from_slice(v).ok())
+                .ok_or("missing asset proof")?;
+            if !proof.blind_asset_proof_verify(&EC, asset, asset_gen) {
+                return Err("invalid asset proof".into());
+            }
+            asset_gen
+        }
+        _ => return Err("explicit asset does not match".into()),
+    };
+    match txout.value {
+        Value::Explicit(explicit) if explicit == satoshi => (),
+        Value::Confidential(value_commit) => {
+            let proof = get_prop(input, PSBT_ELEMENTS_IN_VALUE_PROOF)
+                .and_then(|v| RangeProof::from_slice(v).ok())
+                .ok_or("missing value proof")?;
+            if !proof.blind_value_proof_verify(&EC, satoshi, asset_gen, value_commit) {
+                return Err("invalid value proof".into());
+            }
+        }
+        _ => return Err("explicit value does not match".into()),
+    }
+    Ok((asset, satoshi))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bitcoin::bip32::DerivationPath;
+    use crate::bitcoin::PublicKey;
+    use crate::ElementsNetwork;
+    use elements::confidential::{AssetBlindingFactor, ValueBlindingFactor};
+    use elements::{Address, AddressParams};
+    use lwk_common::Signer;
+    use lwk_signer::SwSigner;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_proof_of_reserves_signed() {
+        let challenge = "challenge";
+        let network = ElementsNetwork::LiquidTestnet;
+        let signer = SwSigner::new(lwk_test_util::TEST_MNEMONIC, false).unwrap();
+        let path = DerivationPath::from_str("m/84h/1h/0h/0/0").unwrap();
+        let public_key = PublicKey::new(signer.derive_xpub(&path).unwrap().public_key);
+        let blinding_key = PublicKey::new(signer.xpub().public_key);
+        let address = Address::p2wpkh(
+            &public_key,
+            Some(blinding_key.inner),
+            &AddressParams::LIQUID_TESTNET,
+        );
+
+        // A confidential output owned by the signer
+        let asset = network.policy_asset();
+        let value = 10_000;
+        let spent = [TxOutSecrets::new(
+            asset,
+            AssetBlindingFactor::zero(),
+            value,
+            ValueBlindingFactor::zero(),
+        )];
+        let (txout, asset_bf, value_bf, _) =
+            TxOut::new_not_last_confidential(&mut thread_rng(), &EC, value, address, asset, &spent)
+                .unwrap();
+        let secrets = TxOutSecrets::new(asset, asset_bf, value, value_bf);
+
+        let mut pset = PartiallySignedTransaction::new_v2();
+        pset.add_input(challenge_input(challenge, asset));
+        let mut input = Input::from_prevout(OutPoint::new(Txid::all_zeros(), 1));
+        input.witness_utxo = Some(txout);
+        input
+            .bip32_derivation
+            .insert(public_key, (signer.fingerprint(), path));
+        set_explicit_proofs(&mut input, &secrets).unwrap();
+        verify_input(&input).unwrap();
+        pset.add_input(input);
+        pset.add_output(Output {
+            script_pubkey: op_true(),
+            amount: Some(value),
+            asset: Some(asset),
+            ..Default::default()
+        });
+
+        let err = verify_proof_of_reserves(&pset, challenge).unwrap_err();
+        assert!(err.to_string().contains("input #1 is not signed"));
+
+        let unsigned = pset.clone();
+        assert_eq!(signer.sign(&mut pset).unwrap(), 1);
+        // The proofs survive the serialization
+        let pset = PartiallySignedTransaction::from_str(&pset.to_string()).unwrap();
+        let reserves = verify_proof_of_reserves(&pset, challenge).unwrap();
+        assert_eq!(reserves, BTreeMap::from([(asset, value)]));
+
+        let err = verify_proof_of_reserves(&pset, "other").unwrap_err();
+        assert!(err.to_string().contains("does not commit to the challenge"));
+
+        // Signatures must commit to the challenge input
+        let mut anyonecanpay = pset.clone();
+        let sig = anyonecanpay.inputs_mut()[1]
+            .partial_sigs
+            .values_mut()
+            .next()
+            .unwrap();
+        *sig.last_mut().unwrap() = EcdsaSighashType::AllPlusAnyoneCanPay as u8;
+        let err = verify_proof_of_reserves(&anyonecanpay, challenge).unwrap_err();
+        assert!(err.to_string().contains("SIGHASH_ALL"));
+
+        // Changing the outputs invalidates the signatures
+        let mut inflated = pset.clone();
+        inflated.outputs_mut()[0].amount = Some(value + 1);
+        let err = verify_proof_of_reserves(&inflated, challenge).unwrap_err();
+        assert!(err.to_string().contains("input #1 is not signed"));
+
+        // The outputs must match the proven amounts
+        let mut inflated = unsigned;
+        inflated.outputs_mut()[0].amount = Some(value + 1);
+        signer.sign(&mut inflated).unwrap();
+        let err = verify_proof_of_reserves(&inflated, challenge).unwrap_err();
+        assert!(err.to_string().contains("outputs do not match"));
+
+        // The explicit value is bound to the spent output
+        let mut tampered = pset.clone();
+        let input = &mut tampered.inputs_mut()[1];
+        set_prop(
+            input,
+            PSBT_ELEMENTS_IN_EXPLICIT_VALUE,
+            serialize(&(value + 1)),
+        );
+        assert_eq!(verify_input(input).unwrap_err(), "invalid value proof");
+    }
+
+    #[test]
+    fn test_challenge_outpoint() {
+        let outpoint = challenge_outpoint("challenge");
+        assert_eq!(outpoint.vout, 0);
+        assert_eq!(outpoint, challenge_outpoint("challenge"));
+        assert_ne!(outpoint, challenge_outpoint("other"));
+    }
+
+    #[test]
+    fn test_verify_proof_of_reserves_invalid() {
+        let challenge = "challenge";
+        let mut pset = PartiallySignedTransaction::new_v2();
+        let err = verify_proof_of_reserves(&pset, challenge).unwrap_err();
+        assert_eq!(err.to_string(), "Invalid proof of reserves: no inputs");
+
+        pset.add_input(Input::from_prevout(challenge_outpoint("other")));
+        let err = verify_proof_of_reserves(&pset, challenge).unwrap_err();
+        assert!(err.to_string().contains("does not commit to the challenge"));
+
+        let mut pset = PartiallySignedTransaction::new_v2();
+        pset.add_input(Input::from_prevout(challenge_outpoint(challenge)));
+        let err = verify_proof_of_reserves(&pset, challenge).unwrap_err();
+        assert!(err.to_string().contains("no reserves"));
+
+        // An input without the explicit value and asset
+        let asset = AssetId::from_slice(&[1; 32]).unwrap();
+        let mut input = Input::from_prevout(OutPoint::new(Txid::all_zeros(), 1));
+        input.witness_utxo = Some(TxOut {
+            asset: Asset::Explicit(asset),
+            value: Value::Explicit(1_000),
+            script_pubkey: op_true(),
+            ..Default::default()
+        });
+        pset.add_input(input.clone());
+        let err = verify_proof_of_reserves(&pset, challenge).unwrap_err();
+        assert!(err.to_string().contains("missing explicit value"));
+
+        // Explicit values must match the spent output
+        set_prop(
+            &mut input,
+            PSBT_ELEMENTS_IN_EXPLICIT_VALUE,
+            serialize(&2_000u64),
+        );
+        set_prop(
+            &mut input,
+            PSBT_ELEMENTS_IN_EXPLICIT_ASSET,
+            serialize(&asset),
+        );
+        assert_eq!(
+            verify_input(&input).unwrap_err(),
+            "explicit value does not match"
+        );
+        set_prop(
+            &mut input,
+            PSBT_ELEMENTS_IN_EXPLICIT_VALUE,
+            serialize(&1_000u64),
+        );
+        assert_eq!(verify_input(&input).unwrap(), (asset, 1_000));
+
+        // Inputs must be signed
+        pset.inputs_mut()[1] = input;
+        let err = verify_proof_of_reserves(&pset, challenge).unwrap_err();
+        assert!(err.to_string().contains("input #1 is not signed"));
+    }
+}
@@ -8,7 +8,8 @@ use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 use std::sync::atomic::{AtomicU32, Ordering};
 
-pub const BATCH_SIZE: u32 = 20;
+/// The default number of consecutive unused scripts after which the scans stop
+pub const GAP_LIMIT: u32 = 20;
 pub type Height = u32;
 pub type Timestamp = u32;
 
@@ -19,7 +20,7 @@ pub struct RawCache {
     /// contains all my tx and all prevouts
     pub all_txs: HashMap<Txid, Transaction>,
 
-    /// contains all my script up to an empty batch of BATCHSIZE
+    /// contains all my script up to an empty batch of the gap limit size
     pub paths: HashMap<Script, (Chain, ChildNumber)>,
 
     /// inverse of `paths`
@@ -60,6 +61,22 @@ impl Default for RawCache {
     }
 }
 
+impl Clone for RawCache {
+    fn clone(&self) -> Self {
+        Self {
+            all_txs: self.all_txs.clone(),
+            paths: self.paths.clone(),
+            scripts: self.scripts.clone(),
+            heights: self.heights.clone(),
+            unblinded: self.unblinded.clone(),
+            tip: self.tip,
+            timestamps: self.timestamps.clone(),
+            last_unused_external: self.last_unused_external.load(Ordering::Relaxed).into(),
+            last_unused_internal: self.last_unused_internal.load(Ordering::Relaxed).into(),
+        }
+    }
+}
+
 impl std::hash::Hash for RawCache {
     fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
         let mut vec: Vec<_> = self.all_txs.keys().collect();
@@ -98,7 +115,7 @@ impl std::hash::Hash for RawCache {
     }
 }
 
-#[derive(Default, Hash)]
+#[derive(Clone, Default, Hash)]
 pub struct Store {
     pub cache: RawCache,
 }
@@ -114,14 +131,15 @@ impl Store {
         &self,
         batch: u32,
         descriptor: &Descriptor<DescriptorPublicKey>, // non confidential (we need only script_pubkey), non multipath (we need to be able to derive with index)
+        batch_size: u32,
     ) -> Result<ScriptBatch, Error> {
         let mut result = ScriptBatch {
             cached: true,
             ..Default::default()
         };
 
-        let start = batch * BATCH_SIZE;
-        let end = start + BATCH_SIZE;
+        let start = batch * batch_size;
+        let end = start + batch_size;
         let ext_int: Chain = descriptor.try_into().unwrap_or(Chain::External);
         for j in start..end {
             let child = ChildNumber::from_normal_idx(j)?;
@@ -165,7 +183,10 @@ impl Store {
 
 #[cfg(test)]
 mod tests {
-    use crate::{store::Store, WolletDescriptor};
+    use crate::{
+        store::{Store, GAP_LIMIT},
+        WolletDescriptor,
+    };
     use elements::Txid;
     use elements_miniscript::ConfidentialDescriptor;
     use std::{
@@ -195,7 +216,7 @@ mod tests {
         let store = Store::default();
 
         let x = store
-            .get_script_batch(0, &desc.as_ref().descriptor)
+            .get_script_batch(0, &desc.as_ref().descriptor, GAP_LIMIT)
             .unwrap();
         assert_eq!(format!("{:?}", x.value[0]), "(Script(OP_0 OP_PUSHBYTES_20 d11ef9e68385138627b09d52d6fe12662d049224), (External, Normal { index: 0 }))");
         assert_ne!(x.value[0], x.value[1]);
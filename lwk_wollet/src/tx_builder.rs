@@ -1,19 +1,20 @@
 use std::collections::{HashMap, HashSet};
 
 use elements::{
-    confidential::Value,
+    confidential::{AssetBlindingFactor, Value, ValueBlindingFactor},
     issuance::ContractHash,
-    pset::{Output, PartiallySignedTransaction},
+    pset::{Output, PartiallySignedTransaction, PsbtSighashType},
     secp256k1_zkp::ZERO_TWEAK,
-    Address, AssetId, Script, Transaction,
+    Address, AssetId, EcdsaSighashType, OutPoint, Script, Sequence, Transaction, Txid,
 };
 use rand::thread_rng;
 
 use crate::{
+    coin_selection::{select_utxos, CoinSelection},
     hashes::Hash,
-    model::{IssuanceDetails, Recipient},
+    model::{InvalidRecipient, IssuanceDetails, Recipient, WalletTxOut},
     pset_create::{validate_address, IssuanceRequest},
-    Contract, ElementsNetwork, Error, UnvalidatedRecipient, Wollet, EC,
+    Chain, Contract, ElementsNetwork, Error, UnvalidatedRecipient, Wollet, EC,
 };
 
 pub fn extract_issuances(tx: &Transaction) -> Vec<IssuanceDetails> {
@@ -26,14 +27,10 @@ pub fn extract_issuances(tx: &Transaction) -> Vec<IssuanceDetails> {
             let (asset, token) = txin.issuance_ids();
             let is_reissuance = txin.asset_issuance.asset_blinding_nonce != ZERO_TWEAK;
             // FIXME: attempt to unblind if blinded
-            let asset_amount = match txin.asset_issuance.amount {
-                Value::Explicit(a) => Some(a),
-                _ => None,
-            };
-            let token_amount = match txin.asset_issuance.inflation_keys {
-                Value::Explicit(a) => Some(a),
-                _ => None,
-            };
+            let (asset_amount, asset_blinder) =
+                explicit_issuance_value(&txin.asset_issuance.amount);
+            let (token_amount, token_blinder) =
+                explicit_issuance_value(&txin.asset_issuance.inflation_keys);
             // FIXME: comment if the issuance is blinded
             r.push(IssuanceDetails {
                 txid: tx.txid(),
@@ -44,12 +41,208 @@ pub fn extract_issuances(tx: &Transaction) -> Vec<IssuanceDetails> {
                 is_reissuance,
                 asset_amount,
                 token_amount,
+                asset_blinder,
+                token_blinder,
             });
         }
     }
     r
 }
 
+/// Returns the amount and the blinder of an explicit issuance value, `None` for both otherwise
+fn explicit_issuance_value(value: &Value) -> (Option<u64>, Option<ValueBlindingFactor>) {
+    match value {
+        Value::Explicit(a) => (Some(*a), Some(ValueBlindingFactor::zero())),
+        // A null value means no issuance of that kind, which is explicitly zero
+        Value::Null => (None, Some(ValueBlindingFactor::zero())),
+        Value::Confidential(_) => (None, None),
+    }
+}
+
+/// Weight of the transaction fields not depending on inputs and outputs
+const TX_OVERHEAD_WEIGHT: usize = 11 * 4;
+
+/// Size of the range proof of a blinded output, including its length
+const RANGEPROOF_SIZE: usize = 4_177;
+
+/// Length of the longest standard script pubkey, P2WSH and P2TR
+const MAX_SCRIPT_LEN: usize = 34;
+
+fn varint_len(n: usize) -> usize {
+    match n {
+        0..=0xfc => 1,
+        0xfd..=0xffff => 3,
+        _ => 5,
+    }
+}
+
+/// Weight of an input spending a script with the given satisfaction weight
+fn input_weight(satisfaction_weight: usize) -> usize {
+    // Outpoint, empty script sig and sequence; empty issuance proofs, script and pegin witness
+    (36 + 1 + 4) * 4 + 4 + satisfaction_weight
+}
+
+/// Weight of a blinded output, dominated by its range proof, in a transaction with `n_inputs`
+fn blinded_output_weight(script_len: usize, n_inputs: usize) -> usize {
+    let surjection_proof = 2 + (n_inputs + 7) / 8 + 32 * (1 + n_inputs.min(3));
+    (33 + 33 + 33 + varint_len(script_len) + script_len) * 4
+        + RANGEPROOF_SIZE
+        + varint_len(surjection_proof)
+        + surjection_proof
+}
+
+/// Weight of an explicit output, such as the fee output
+fn explicit_output_weight(script_len: usize) -> usize {
+    (33 + 9 + 1 + varint_len(script_len) + script_len) * 4 + 2
+}
+
+/// The fee in satoshi for the given weight, with the fee rate in sat/kvb
+fn weight_to_fee(weight: usize, fee_rate: f32) -> u64 {
+    let vsize = (weight + 4 - 1) / 4;
+    (vsize as f32 * fee_rate / 1000.0).ceil() as u64
+}
+
+/// The projected size and fee of a transaction, see [`TxBuilder::estimate_fee()`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FeeEstimate {
+    /// The virtual size of the transaction once signed
+    pub vsize: usize,
+
+    /// The fee in satoshi
+    pub fee: u64,
+
+    /// The number of wallet utxos spent
+    pub inputs: usize,
+}
+
+/// Selection of the wallet utxos spent by a transaction
+struct InputSelection<'a> {
+    wollet: &'a Wollet,
+
+    /// The utxos set by the caller, which must be all spent
+    selected: Option<Vec<WalletTxOut>>,
+
+    strategy: Option<CoinSelection>,
+
+    /// In sat/kvb
+    fee_rate: f32,
+}
+
+impl<'a> InputSelection<'a> {
+    fn new(builder: &TxBuilder, wollet: &'a Wollet) -> Result<Self, Error> {
+        let selected = match &builder.selected_utxos {
+            Some(outpoints) => {
+                let utxos = wollet.utxos()?;
+                let mut selected = vec![];
+                for outpoint in outpoints {
+                    let utxo = utxos
+                        .iter()
+                        .find(|u| &u.outpoint == outpoint)
+                        .ok_or(Error::MissingWalletUtxo(*outpoint))?;
+                    if !selected.contains(utxo) {
+                        selected.push(utxo.clone());
+                    }
+                }
+                Some(selected)
+            }
+            None => None,
+        };
+        Ok(Self {
+            wollet,
+            selected,
+            strategy: builder.coin_selection,
+            fee_rate: builder.fee_rate,
+        })
+    }
+
+    /// The assets of the utxos set by the caller, other than L-BTC
+    fn selected_assets(&self) -> Vec<AssetId> {
+        let policy_asset = self.wollet.policy_asset();
+        self.selected
+            .iter()
+            .flatten()
+            .map(|u| u.unblinded.asset)
+            .filter(|a| a != &policy_asset)
+            .collect()
+    }
+
+    fn utxos(&self, asset: &AssetId) -> Result<Vec<WalletTxOut>, Error> {
+        Ok(match &self.selected {
+            Some(selected) => selected
+                .iter()
+                .filter(|u| &u.unblinded.asset == asset)
+                .cloned()
+                .collect(),
+            None => self.wollet.asset_utxos(asset)?,
+        })
+    }
+
+    /// The utxos of an asset other than L-BTC to spend to send `satoshi_out`, all of them if
+    /// `spend_all`
+    fn asset(
+        &self,
+        asset: &AssetId,
+        satoshi_out: u64,
+        spend_all: bool,
+    ) -> Result<Vec<WalletTxOut>, Error> {
+        let mut utxos = self.utxos(asset)?;
+        if self.selected.is_some() || spend_all {
+            return Ok(utxos);
+        }
+        if let Some(strategy) = self.strategy {
+            return Ok(select_utxos(strategy, utxos, satoshi_out, 0));
+        }
+        let mut satoshi_in = 0;
+        let covered = utxos.iter().position(|u| {
+            satoshi_in += u.unblinded.value;
+            satoshi_in >= satoshi_out
+        });
+        if let Some(last) = covered {
+            utxos.truncate(last + 1);
+        }
+        Ok(utxos)
+    }
+
+    /// The L-BTC utxos to spend to send `satoshi_out` and pay the fee, all of them if `spend_all`
+    ///
+    /// The fee is estimated with `n_outputs` blinded outputs besides the L-BTC change and the fee
+    /// output.
+    ///
+    /// FIXME: For implementation simplicity without a coin selection strategy we always spend
+    /// all L-BTC utxos
+    fn lbtc(
+        &self,
+        satoshi_out: u64,
+        n_outputs: usize,
+        n_inputs: usize,
+        spend_all: bool,
+    ) -> Result<Vec<WalletTxOut>, Error> {
+        let utxos = self.utxos(&self.wollet.policy_asset())?;
+        let strategy = match self.strategy {
+            Some(strategy) if self.selected.is_none() && !spend_all => strategy,
+            _ => return Ok(utxos),
+        };
+        let first = match utxos.first() {
+            Some(first) => first,
+            None => return Ok(utxos),
+        };
+        let outputs_weight = (n_outputs + 1) * blinded_output_weight(MAX_SCRIPT_LEN, n_inputs + 1)
+            + explicit_output_weight(0);
+        let fee_target = weight_to_fee(TX_OVERHEAD_WEIGHT + outputs_weight, self.fee_rate);
+        let satisfaction_weight = self
+            .wollet
+            .definite_descriptor(&first.script_pubkey)?
+            .max_weight_to_satisfy()?;
+        let input_cost = weight_to_fee(input_weight(satisfaction_weight), self.fee_rate);
+        Ok(select_utxos(
+            strategy,
+            utxos,
+            satoshi_out + fee_target,
+            input_cost,
+        ))
+    }
+}
+
 /// A transaction builder
 ///
 /// See [`WolletTxBuilder`] for usage from rust.
@@ -64,10 +257,15 @@ pub fn extract_issuances(tx: &Transaction) -> Vec<IssuanceDetails> {
 pub struct TxBuilder {
     network: ElementsNetwork,
     recipients: Vec<Recipient>,
+    max_recipients: Vec<Recipient>,
     fee_rate: f32,
     issuance_request: IssuanceRequest,
     drain_lbtc: bool,
     drain_to: Option<Address>,
+    change_address: Option<Address>,
+    selected_utxos: Option<Vec<OutPoint>>,
+    coin_selection: Option<CoinSelection>,
+    enable_rbf: bool,
 }
 
 impl TxBuilder {
@@ -76,10 +274,15 @@ impl TxBuilder {
         TxBuilder {
             network,
             recipients: vec![],
+            max_recipients: vec![],
             fee_rate: 100.0,
             issuance_request: IssuanceRequest::None,
             drain_lbtc: false,
             drain_to: None,
+            change_address: None,
+            selected_utxos: None,
+            coin_selection: None,
+            enable_rbf: false,
         }
     }
 
@@ -95,9 +298,10 @@ impl TxBuilder {
         asset_id: AssetId,
     ) -> Result<Self, Error> {
         let rec = UnvalidatedRecipient {
-            satoshi,
+            satoshi: Some(satoshi),
             address: address.to_string(),
             asset: asset_id.to_string(),
+            timelock: None,
         };
         self.add_unvalidated_recipient(&rec)
     }
@@ -107,11 +311,26 @@ impl TxBuilder {
         mut self,
         recipient: &UnvalidatedRecipient,
     ) -> Result<Self, Error> {
-        let addr: Recipient = recipient.validate(self.network())?;
-        self.recipients.push(addr);
+        self.push_unvalidated_recipient(recipient)?;
         Ok(self)
     }
 
+    fn push_unvalidated_recipient(
+        &mut self,
+        recipient: &UnvalidatedRecipient,
+    ) -> Result<(), Error> {
+        let addr: Recipient = recipient.validate(self.network())?;
+        if recipient.is_max() {
+            if self.max_recipients.iter().any(|r| r.asset == addr.asset) {
+                return Err(Error::MultipleMaxRecipients(addr.asset));
+            }
+            self.max_recipients.push(addr);
+        } else {
+            self.recipients.push(addr);
+        }
+        Ok(())
+    }
+
     /// Add validated recipient to the internal list
     pub fn add_validated_recipient(mut self, recipient: Recipient) -> Self {
         self.recipients.push(recipient);
@@ -119,13 +338,26 @@ impl TxBuilder {
     }
 
     /// Replace current recipients with the given list
+    ///
+    /// All the recipients are validated, if any is invalid [`Error::InvalidRecipients`] is
+    /// returned containing the position and the reason of every failure.
     pub fn set_unvalidated_recipients(
         mut self,
         recipients: &[UnvalidatedRecipient],
     ) -> Result<Self, Error> {
         self.recipients.clear();
-        for recipient in recipients {
-            self = self.add_unvalidated_recipient(recipient)?;
+        self.max_recipients.clear();
+        let mut invalid = vec![];
+        for (index, recipient) in recipients.iter().enumerate() {
+            if let Err(e) = self.push_unvalidated_recipient(recipient) {
+                invalid.push(InvalidRecipient {
+                    index,
+                    reason: e.to_string(),
+                });
+            }
+        }
+        if !invalid.is_empty() {
+            return Err(Error::InvalidRecipients(invalid));
         }
         Ok(self)
     }
@@ -142,7 +374,25 @@ impl TxBuilder {
         self.add_unvalidated_recipient(&rec)
     }
 
-    /// Set custom fee rate
+    /// Spend only the given wallet utxos
+    ///
+    /// All the given utxos are spent, even if they are not needed to cover the amounts sent
+    /// and the fee.
+    pub fn set_wallet_utxos(mut self, utxos: Vec<OutPoint>) -> Self {
+        self.selected_utxos = Some(utxos);
+        self
+    }
+
+    /// Select the wallet utxos to spend with the given strategy
+    ///
+    /// It has no effect on the utxos set with [`TxBuilder::set_wallet_utxos()`] and on the
+    /// assets sent entirely, since all their utxos are spent.
+    pub fn coin_selection(mut self, strategy: CoinSelection) -> Self {
+        self.coin_selection = Some(strategy);
+        self
+    }
+
+    /// Set custom fee rate in sat/kvb, default is 100 sat/kvb (0.1 sat/vb)
     pub fn fee_rate(mut self, fee_rate: Option<f32>) -> Self {
         if let Some(fee_rate) = fee_rate {
             self.fee_rate = fee_rate
@@ -150,6 +400,13 @@ impl TxBuilder {
         self
     }
 
+    /// Signal replaceability on all the inputs, so that the fee can be bumped later with
+    /// [`Wollet::bump_fee()`]
+    pub fn enable_rbf(mut self) -> Self {
+        self.enable_rbf = true;
+        self
+    }
+
     /// Issue an asset
     ///
     /// There will be `asset_sats` units of this asset that will be received by
@@ -240,8 +497,127 @@ impl TxBuilder {
         self
     }
 
+    /// Send the change to the given address instead of an internal address of the wallet
+    ///
+    /// The address is used for the change of every asset, including the reissuance token when
+    /// reissuing, but not for the L-BTC excess if [`TxBuilder::drain_lbtc_to()`] is set.
+    ///
+    /// If the address is not owned by the wallet, the change leaves the wallet: it's not part of
+    /// its balance and the wallet can't unblind it.
+    pub fn set_change_address(mut self, address: Address) -> Result<Self, Error> {
+        validate_address(&address.to_string(), self.network())?;
+        self.change_address = Some(address);
+        Ok(self)
+    }
+
+    /// Estimate the size and the fee of the transaction without creating it
+    ///
+    /// The wallet utxos are selected as in [`TxBuilder::finish()`], while the weight of the
+    /// blinded outputs, dominated by their range proofs, and of the input signatures is
+    /// projected, thus the fee can slightly differ from the one of the transaction created.
+    ///
+    /// (Re)issuances are not supported.
+    pub fn estimate_fee(self, wollet: &Wollet) -> Result<FeeEstimate, Error> {
+        if !self.fee_rate.is_finite() || self.fee_rate <= 0.0 {
+            return Err(Error::InvalidFeeRate(self.fee_rate));
+        }
+        if !matches!(self.issuance_request, IssuanceRequest::None) {
+            return Err(Error::FeeEstimationIssuanceUnsupported);
+        }
+        let selection = InputSelection::new(&self, wollet)?;
+        let policy_asset = self.network().policy_asset();
+        let change = match &self.change_address {
+            Some(address) => Recipient::from_address(0, address, policy_asset),
+            None => Recipient::from_address(0, wollet.change(None)?.address(), policy_asset),
+        };
+        let max_lbtc = self.max_recipients.iter().find(|a| a.asset == policy_asset);
+        if max_lbtc.is_some() && self.drain_to.is_some() {
+            return Err(Error::MultipleMaxRecipients(policy_asset));
+        }
+
+        let mut inputs: Vec<WalletTxOut> = vec![];
+        let mut outputs: Vec<&Recipient> = vec![];
+
+        // Assets inputs and outputs
+        let mut assets: HashSet<_> = self
+            .recipients
+            .iter()
+            .chain(self.max_recipients.iter())
+            .map(|a| a.asset)
+            .filter(|a| a != &policy_asset)
+            .collect();
+        assets.extend(selection.selected_assets());
+        for asset in assets {
+            let addressees: Vec<_> = self
+                .recipients
+                .iter()
+                .filter(|a| a.asset == asset)
+                .collect();
+            let satoshi_out: u64 = addressees.iter().map(|a| a.satoshi).sum();
+            let max_addressee = self.max_recipients.iter().find(|a| a.asset == asset);
+            let utxos = selection.asset(&asset, satoshi_out, max_addressee.is_some())?;
+            let satoshi_in: u64 = utxos.iter().map(|u| u.unblinded.value).sum();
+            if satoshi_in < satoshi_out || (max_addressee.is_some() && satoshi_in == satoshi_out) {
+                return Err(Error::InsufficientFunds);
+            }
+            outputs.extend(addressees);
+            if let Some(max_addressee) = max_addressee {
+                outputs.push(max_addressee);
+            } else if satoshi_in > satoshi_out {
+                outputs.push(&change);
+            }
+            inputs.extend(utxos);
+        }
+
+        // L-BTC inputs and outputs
+        let addressees: Vec<_> = self
+            .recipients
+            .iter()
+            .filter(|a| a.asset == policy_asset)
+            .collect();
+        let satoshi_out: u64 = addressees.iter().map(|a| a.satoshi).sum();
+        outputs.extend(addressees);
+        let spend_all = max_lbtc.is_some() || self.drain_to.is_some();
+        let utxos = selection.lbtc(satoshi_out, outputs.len(), inputs.len(), spend_all)?;
+        let satoshi_in: u64 = utxos.iter().map(|u| u.unblinded.value).sum();
+        inputs.extend(utxos);
+        let drain = self
+            .drain_to
+            .as_ref()
+            .map(|address| Recipient::from_address(0, address, policy_asset));
+        outputs.push(drain.as_ref().or(max_lbtc).unwrap_or(&change));
+
+        let mut weight = TX_OVERHEAD_WEIGHT + explicit_output_weight(0);
+        for input in inputs.iter() {
+            let satisfaction_weight = wollet
+                .definite_descriptor(&input.script_pubkey)?
+                .max_weight_to_satisfy()?;
+            weight += input_weight(satisfaction_weight);
+        }
+        for output in outputs {
+            let script_len = output.script_pubkey.len();
+            weight += match output.blinding_pubkey {
+                Some(_) => blinded_output_weight(script_len, inputs.len()),
+                None => explicit_output_weight(script_len),
+            };
+        }
+        let fee = weight_to_fee(weight, self.fee_rate);
+        if satoshi_in <= satoshi_out + fee {
+            return Err(Error::InsufficientFunds);
+        }
+        Ok(FeeEstimate {
+            vsize: (weight + 4 - 1) / 4,
+            fee,
+            inputs: inputs.len(),
+        })
+    }
+
     /// Finish building the transaction
     pub fn finish(self, wollet: &Wollet) -> Result<PartiallySignedTransaction, Error> {
+        if !self.fee_rate.is_finite() || self.fee_rate <= 0.0 {
+            return Err(Error::InvalidFeeRate(self.fee_rate));
+        }
+
         // Init PSET
         let mut pset = PartiallySignedTransaction::new_v2();
         let mut inp_txout_sec = HashMap::new();
@@ -250,14 +626,32 @@ impl TxBuilder {
 
         let mut inp_weight = 0;
 
+        // Utxos available for coin selection, if set by the caller they must be all spent
+        let selection = InputSelection::new(&self, wollet)?;
+
         let policy_asset = self.network().policy_asset();
+        let change_address = self.change_address;
+        let addressee_change = |satoshi, asset, last_unused: &mut u32| match &change_address {
+            Some(address) => Ok(Recipient::from_address(satoshi, address, asset)),
+            None => wollet.addressee_change(satoshi, asset, last_unused),
+        };
         let (addressees_lbtc, addressees_asset): (Vec<_>, Vec<_>) = self
             .recipients
             .into_iter()
             .partition(|a| a.asset == policy_asset);
+        let (max_lbtc, max_assets): (Vec<_>, Vec<_>) = self
+            .max_recipients
+            .into_iter()
+            .partition(|a| a.asset == policy_asset);
+        let max_lbtc = max_lbtc.into_iter().next();
+        if max_lbtc.is_some() && self.drain_to.is_some() {
+            return Err(Error::MultipleMaxRecipients(policy_asset));
+        }
 
         // Assets inputs and outputs
-        let assets: HashSet<_> = addressees_asset.iter().map(|a| a.asset).collect();
+        let mut assets: HashSet<_> = addressees_asset.iter().map(|a| a.asset).collect();
+        assets.extend(max_assets.iter().map(|a| a.asset));
+        assets.extend(selection.selected_assets());
         for asset in assets {
             let mut satoshi_out = 0;
             let mut satoshi_in = 0;
@@ -265,25 +659,29 @@ impl TxBuilder {
                 wollet.add_output(&mut pset, addressee)?;
                 satoshi_out += addressee.satoshi;
             }
-            for utxo in wollet.asset_utxos(&asset)? {
+            // If an addressee receives all the remaining balance, all the utxos are needed
+            let max_addressee = max_assets.iter().find(|a| a.asset == asset);
+            for utxo in selection.asset(&asset, satoshi_out, max_addressee.is_some())? {
                 wollet.add_input(&mut pset, &mut inp_txout_sec, &mut inp_weight, &utxo)?;
                 satoshi_in += utxo.unblinded.value;
-                if satoshi_in >= satoshi_out {
-                    if satoshi_in > satoshi_out {
-                        let satoshi_change = satoshi_in - satoshi_out;
-                        let addressee = wollet.addressee_change(
-                            satoshi_change,
-                            asset,
-                            &mut last_unused_internal,
-                        )?;
-                        wollet.add_output(&mut pset, &addressee)?;
-                    }
-                    break;
-                }
             }
             if satoshi_in < satoshi_out {
                 return Err(Error::InsufficientFunds);
             }
+            if let Some(max_addressee) = max_addressee {
+                if satoshi_in == satoshi_out {
+                    return Err(Error::InsufficientFunds);
+                }
+                let addressee = Recipient {
+                    satoshi: satoshi_in - satoshi_out,
+                    ..max_addressee.clone()
+                };
+                wollet.add_output(&mut pset, &addressee)?;
+            } else if satoshi_in > satoshi_out {
+                let satoshi_change = satoshi_in - satoshi_out;
+                let addressee = addressee_change(satoshi_change, asset, &mut last_unused_internal)?;
+                wollet.add_output(&mut pset, &addressee)?;
+            }
         }
 
         // L-BTC inputs and outputs
@@ -295,8 +693,10 @@ impl TxBuilder {
             satoshi_out += addressee.satoshi;
         }
 
-        // FIXME: For implementation simplicity now we always add all L-BTC inputs
-        for utxo in wollet.asset_utxos(&wollet.policy_asset())? {
+        // Outputs not added yet: at most 2 for the (re)issuance
+        let spend_all = max_lbtc.is_some() || self.drain_to.is_some();
+        let n_outputs = pset.n_outputs() + 2;
+        for utxo in selection.lbtc(satoshi_out, n_outputs, pset.n_inputs(), spend_all)? {
             wollet.add_input(&mut pset, &mut inp_txout_sec, &mut inp_weight, &utxo)?;
             satoshi_in += utxo.unblinded.value;
         }
@@ -355,7 +755,7 @@ impl TxBuilder {
                         Some((idx, u)) => (*idx, u.asset_bf),
                         None => {
                             // Add an input sending the token,
-                            let utxos_token = wollet.asset_utxos(&token)?;
+                            let utxos_token = selection.utxos(&token)?;
                             let utxo_token = utxos_token
                                 .first()
                                 .ok_or_else(|| Error::InsufficientFunds)?;
@@ -368,11 +768,8 @@ impl TxBuilder {
 
                             // and an outpout receiving the token
                             let satoshi_token = utxo_token.unblinded.value;
-                            let addressee = wollet.addressee_change(
-                                satoshi_token,
-                                token,
-                                &mut last_unused_internal,
-                            )?;
+                            let addressee =
+                                addressee_change(satoshi_token, token, &mut last_unused_internal)?;
                             wollet.add_output(&mut pset, &addressee)?;
 
                             (idx, utxo_token.unblinded.asset_bf)
@@ -409,8 +806,13 @@ impl TxBuilder {
         let satoshi_change = satoshi_in - satoshi_out - temp_fee;
         let addressee = if let Some(address) = self.drain_to {
             Recipient::from_address(satoshi_change, &address, wollet.policy_asset())
+        } else if let Some(max_lbtc) = max_lbtc {
+            Recipient {
+                satoshi: satoshi_change,
+                ..max_lbtc
+            }
         } else {
-            wollet.addressee_change(
+            addressee_change(
                 satoshi_change,
                 wollet.policy_asset(),
                 &mut last_unused_internal,
@@ -444,6 +846,12 @@ impl TxBuilder {
 
         // TODO inputs/outputs(except fee) randomization, not trivial because of blinder_index on inputs
 
+        if self.enable_rbf {
+            for input in pset.inputs_mut() {
+                input.sequence = Some(Sequence::ENABLE_RBF_NO_LOCKTIME);
+            }
+        }
+
         // Blind the transaction
         let mut rng = thread_rng();
         pset.blind_last(&mut rng, &EC, &inp_txout_sec)?;
@@ -455,6 +863,204 @@ impl TxBuilder {
     }
 }
 
+impl Wollet {
+    /// Create a PSET replacing the given wallet transaction, paying a fee computed with the given
+    /// fee rate in sat/kvb
+    ///
+    /// The replacement spends the same inputs and keeps the outputs not belonging to the wallet
+    /// unchanged, the fee increase is deducted from a wallet L-BTC output: the change output with
+    /// the highest value, or if there is no L-BTC change, the external one with the highest value.
+    ///
+    /// The transaction must be unconfirmed, signal replaceability and spend only wallet inputs.
+    pub fn bump_fee(
+        &self,
+        txid: &Txid,
+        fee_rate: f32,
+    ) -> Result<PartiallySignedTransaction, Error> {
+        if !fee_rate.is_finite() || fee_rate <= 0.0 {
+            return Err(Error::InvalidFeeRate(fee_rate));
+        }
+        let wallet_tx = self
+            .transaction(txid)?
+            .ok_or_else(|| Error::TxNotInWallet(*txid))?;
+        if wallet_tx.height.is_some() {
+            return Err(Error::TxAlreadyConfirmed(*txid));
+        }
+        let tx = &wallet_tx.tx;
+        if !tx.input.iter().any(|i| i.sequence.is_rbf()) {
+            return Err(Error::TxNotReplaceable(*txid));
+        }
+
+        // Same inputs in the same order, since the surjection proofs of the kept outputs commit
+        // to them
+        let mut pset = PartiallySignedTransaction::new_v2();
+        let mut inp_txout_sec = HashMap::new();
+        let mut inp_weight = 0;
+        for (vin, (txin, utxo)) in tx.input.iter().zip(wallet_tx.inputs.iter()).enumerate() {
+            let utxo = utxo
+                .as_ref()
+                .ok_or_else(|| Error::BumpFeeUnsupported(format!("input {vin} is not owned")))?;
+            if txin.has_issuance() {
+                let reason = format!("input {vin} has an issuance");
+                return Err(Error::BumpFeeUnsupported(reason));
+            }
+            self.add_input(&mut pset, &mut inp_txout_sec, &mut inp_weight, utxo)?;
+        }
+        for input in pset.inputs_mut() {
+            input.sequence = Some(Sequence::ENABLE_RBF_NO_LOCKTIME);
+        }
+
+        // Wallet outputs are blinded again, the others are kept as they are
+        let policy_asset = self.policy_asset();
+        let mut wallet_out_secrets = vec![];
+        let mut keep_blinded = false;
+        let mut fee_vout = None;
+        let mut change_vout: Option<(usize, bool, u64)> = None;
+        for (vout, (txout, wallet_out)) in
+            tx.output.iter().zip(wallet_tx.outputs.iter()).enumerate()
+        {
+            if txout.is_fee() {
+                fee_vout = Some(vout);
+                pset.add_output(Output::from_txout(txout.clone()));
+                continue;
+            }
+            match wallet_out {
+                Some(out) => {
+                    let address = match out.ext_int {
+                        Chain::External => self.address(Some(out.wildcard_index))?,
+                        Chain::Internal => self.change(Some(out.wildcard_index))?,
+                    };
+                    let sec = out.unblinded;
+                    let recipient =
+                        Recipient::from_address(sec.value, address.address(), sec.asset);
+                    self.add_output(&mut pset, &recipient)?;
+                    wallet_out_secrets.push((sec.value, sec.asset_bf, sec.value_bf));
+                    if sec.asset == policy_asset {
+                        let candidate = (vout, out.ext_int == Chain::Internal, sec.value);
+                        if change_vout.map_or(true, |c| (c.1, c.2) < (candidate.1, candidate.2)) {
+                            change_vout = Some(candidate);
+                        }
+                    }
+                }
+                None => {
+                    let mut output = Output::from_txout(txout.clone());
+                    if txout.is_partially_blinded() {
+                        // Mark as blinded by someone else, so it's left untouched by the blinding
+                        output.blinding_key = output.ecdh_pubkey;
+                        keep_blinded = true;
+                    }
+                    pset.add_output(output);
+                }
+            }
+        }
+        let fee_vout =
+            fee_vout.ok_or_else(|| Error::BumpFeeUnsupported("missing fee output".into()))?;
+        let (change_vout, _, change_value) = change_vout.ok_or(Error::InsufficientFunds)?;
+
+        if keep_blinded {
+            // The kept outputs were balanced by the wallet inputs and outputs of the original
+            // transaction, account for them when blinding the last output
+            let inp_secrets: Vec<_> = inp_txout_sec
+                .values()
+                .map(|s| (s.value, s.asset_bf, s.value_bf))
+                .collect();
+            let kept = ValueBlindingFactor::last(
+                &EC,
+                0,
+                AssetBlindingFactor::zero(),
+                &inp_secrets,
+                &wallet_out_secrets,
+            );
+            pset.global.scalars.push((-kept).into_inner());
+        }
+
+        let weight = {
+            let mut rng = thread_rng();
+            let mut temp_pset = pset.clone();
+            temp_pset.blind_last(&mut rng, &EC, &inp_txout_sec)?;
+            inp_weight + temp_pset.extract_tx()?.weight()
+        };
+        let vsize = (weight + 4 - 1) / 4;
+        let fee = (vsize as f32 * fee_rate / 1000.0).ceil() as u64;
+        if fee <= wallet_tx.fee {
+            return Err(Error::FeeNotIncreased {
+                fee,
+                original: wallet_tx.fee,
+            });
+        }
+        let increase = fee - wallet_tx.fee;
+        if change_value <= increase {
+            return Err(Error::InsufficientFunds);
+        }
+        let outputs = pset.outputs_mut();
+        outputs[change_vout].amount = Some(change_value - increase);
+        outputs[fee_vout].amount = Some(fee);
+
+        let mut rng = thread_rng();
+        pset.blind_last(&mut rng, &EC, &inp_txout_sec)?;
+
+        self.add_details(&mut pset)?;
+
+        Ok(pset)
+    }
+
+    /// Create a PSET offering `send_satoshi` of `send_asset` in exchange for `recv_satoshi` of
+    /// `recv_asset`
+    ///
+    /// The PSET spends a single wallet utxo of exactly `send_satoshi` and has a single explicit
+    /// output paying `recv_satoshi` to a wallet address. The input must be signed with
+    /// `SIGHASH_SINGLE | SIGHASH_ANYONECANPAY`, so the counterparty can add its inputs and
+    /// outputs, pay the fee and blind the transaction, but cannot change the swap terms.
+    /// To blind, the counterparty needs the secrets of the offered utxo, which is returned along
+    /// with the PSET.
+    ///
+    /// If there is no such utxo, split one first, for instance sending `send_satoshi` to a wallet
+    /// address.
+    pub fn create_swap(
+        &self,
+        send_asset: AssetId,
+        send_satoshi: u64,
+        recv_asset: AssetId,
+        recv_satoshi: u64,
+    ) -> Result<(PartiallySignedTransaction, WalletTxOut), Error> {
+        if send_satoshi == 0 || recv_satoshi == 0 {
+            return Err(Error::InvalidAmount);
+        }
+        if send_asset == recv_asset {
+            return Err(Error::SwapSameAsset);
+        }
+        let utxo = self
+            .asset_utxos(&send_asset)?
+            .into_iter()
+            .find(|u| u.unblinded.value == send_satoshi)
+            .ok_or(Error::SwapUtxoNotFound {
+                satoshi: send_satoshi,
+                asset: send_asset,
+            })?;
+
+        let mut pset = PartiallySignedTransaction::new_v2();
+        let mut inp_txout_sec = HashMap::new();
+        let mut inp_weight = 0;
+        let idx = self.add_input(&mut pset, &mut inp_txout_sec, &mut inp_weight, &utxo)?;
+        pset.inputs_mut()[idx].sighash_type = Some(PsbtSighashType::from(
+            EcdsaSighashType::SinglePlusAnyoneCanPay,
+        ));
+
+        // The output is explicit since its commitments must be known when the input is signed
+        let address = self.address(None)?;
+        pset.add_output(Output {
+            script_pubkey: address.address().script_pubkey(),
+            amount: Some(recv_satoshi),
+            asset: Some(recv_asset),
+            ..Default::default()
+        });
+
+        self.add_details(&mut pset)?;
+
+        Ok((pset, utxo))
+    }
+}
+
 /// A transaction builder.
 #[derive(Debug)]
 pub struct WolletTxBuilder<'a> {
@@ -535,6 +1141,27 @@ impl<'a> WolletTxBuilder<'a> {
         })
     }
 
+    /// Wrapper of [`TxBuilder::set_wallet_utxos()`]
+    pub fn set_wallet_utxos(self, utxos: Vec<OutPoint>) -> Self {
+        Self {
+            wollet: self.wollet,
+            inner: self.inner.set_wallet_utxos(utxos),
+        }
+    }
+
+    /// Wrapper of [`TxBuilder::estimate_fee()`]
+    pub fn estimate_fee(self) -> Result<FeeEstimate, Error> {
+        self.inner.estimate_fee(self.wollet)
+    }
+
+    /// Wrapper of [`TxBuilder::coin_selection()`]
+    pub fn coin_selection(self, strategy: CoinSelection) -> Self {
+        Self {
+            wollet: self.wollet,
+            inner: self.inner.coin_selection(strategy),
+        }
+    }
+
     /// Wrapper of [`TxBuilder::fee_rate()`]
     pub fn fee_rate(self, fee_rate: Option<f32>) -> Self {
         Self {
@@ -543,6 +1170,14 @@ impl<'a> WolletTxBuilder<'a> {
         }
     }
 
+    /// Wrapper of [`TxBuilder::enable_rbf()`]
+    pub fn enable_rbf(self) -> Self {
+        Self {
+            wollet: self.wollet,
+            inner: self.inner.enable_rbf(),
+        }
+    }
+
     /// Wrapper of [`TxBuilder::issue_asset()`]
     pub fn issue_asset(
         self,
@@ -598,4 +1233,41 @@ impl<'a> WolletTxBuilder<'a> {
             inner: self.inner.drain_lbtc_to(address),
         }
     }
+
+    /// Wrapper of [`TxBuilder::set_change_address()`]
+    pub fn set_change_address(self, address: Address) -> Result<Self, Error> {
+        Ok(Self {
+            wollet: self.wollet,
+            inner: self.inner.set_change_address(address)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_weights() {
+        // An unsigned PSET with 2 inputs, 3 blinded P2WPKH outputs and the fee output, see
+        // lwk_common/test_data/pset_details/pset.base64
+        let weight = TX_OVERHEAD_WEIGHT
+            + 2 * input_weight(0)
+            + 3 * blinded_output_weight(22, 2)
+            + explicit_output_weight(0);
+        assert_eq!(weight, 14_853);
+        assert_eq!(weight_to_fee(weight, 100.0), 372);
+
+        // The surjection proof uses at most 3 inputs
+        assert_eq!(
+            blinded_output_weight(22, 4) - blinded_output_weight(22, 3),
+            0
+        );
+        assert_eq!(
+            blinded_output_weight(22, 9) - blinded_output_weight(22, 8),
+            1
+        );
+        assert_eq!(varint_len(0xfc), 1);
+        assert_eq!(varint_len(0xfd), 3);
+    }
 }
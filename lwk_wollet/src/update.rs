@@ -1,6 +1,9 @@
+use crate::clients::try_unblind;
 use crate::descriptor::Chain;
 use crate::elements::{OutPoint, Script, Transaction, TxOutSecrets, Txid};
 use crate::error::Error;
+use crate::headers::HeaderChain;
+use crate::merkle::MerkleProof;
 use crate::store::{Height, Timestamp};
 use crate::{Wollet, WolletDescriptor};
 use aes_gcm_siv::aead::generic_array::GenericArray;
@@ -104,11 +107,137 @@ impl Update {
     }
 }
 
+/// An [`Update`] with the proofs that its confirmed transactions are included in the blocks,
+/// returned by [`crate::BlockchainBackend::full_scan_verifiable()`]
+///
+/// It allows to scan on a networked machine and to apply the update on an offline one, which
+/// doesn't have to trust the former, see [`Wollet::apply_verifiable_update()`].
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct VerifiableUpdate {
+    pub update: Update,
+
+    /// A proof for every transaction confirmed in [`Update::txid_height_new`]
+    pub proofs: Vec<MerkleProof>,
+}
+
+impl VerifiableUpdate {
+    pub fn serialize(&self) -> Result<Vec<u8>, elements::encode::Error> {
+        let mut vec = vec![];
+        self.consensus_encode(&mut vec)?;
+        Ok(vec)
+    }
+
+    pub fn deserialize(bytes: &[u8]) -> Result<VerifiableUpdate, elements::encode::Error> {
+        VerifiableUpdate::consensus_decode(bytes)
+    }
+
+    /// Verify that the transactions match their ids and that the confirmed ones are included in
+    /// the blocks at their height, with the block timestamps matching the headers
+    ///
+    /// Unconfirmed transactions cannot be proven. The headers are not validated against the
+    /// chain, the blocks must be trusted in another way, for instance by checking the hash of
+    /// [`Update::tip`] and the proofs headers with a trusted source.
+    pub fn verify(&self) -> Result<(), Error> {
+        let invalid = |s: String| Err(Error::InvalidVerifiableUpdate(s));
+        let update = &self.update;
+        for (txid, tx) in update.new_txs.txs.iter() {
+            if tx.txid() != *txid {
+                return invalid(format!("transaction {txid} does not match its id"));
+            }
+        }
+
+        let mut headers = HashMap::new();
+        for proof in self.proofs.iter() {
+            if !proof.verify() {
+                return invalid(format!("invalid merkle proof of {}", proof.txid));
+            }
+            headers.insert(proof.txid, &proof.header);
+        }
+        let mut timestamps = HashMap::new();
+        timestamps.insert(update.tip.height, update.tip.time);
+        for (txid, height) in update.txid_height_new.iter() {
+            if let Some(height) = height {
+                match headers.get(txid) {
+                    Some(header) if header.height == *height => {
+                        timestamps.insert(header.height, header.time);
+                    }
+                    Some(_) => return invalid(format!("{txid} is not confirmed at {height}")),
+                    None => return invalid(format!("missing merkle proof of {txid}")),
+                }
+            }
+        }
+        for (height, timestamp) in update.timestamps.iter() {
+            if timestamps.get(height) != Some(timestamp) {
+                return invalid(format!("timestamp of block {height} not proven"));
+            }
+        }
+        Ok(())
+    }
+
+    /// Like [`VerifiableUpdate::verify()`], also checking that the tip and the proofs headers
+    /// are in the validated `headers`, so that the blocks don't need to be trusted
+    pub fn verify_with_headers(&self, headers: &HeaderChain) -> Result<(), Error> {
+        self.verify()?;
+        let tip = &self.update.tip;
+        let blocks = std::iter::once(tip).chain(self.proofs.iter().map(|p| &p.header));
+        for header in blocks {
+            if !headers.contains(header) {
+                return Err(Error::SpvVerification(format!(
+                    "block {} is not in the validated chain",
+                    header.height
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
 impl Wollet {
     pub fn apply_update(&mut self, update: Update) -> Result<(), Error> {
         self.apply_update_inner(update, true)
     }
 
+    /// Verify the update with [`VerifiableUpdate::verify()`] and check that its scripts and
+    /// unblinded outputs belong to this wallet, then apply it
+    ///
+    /// The wallet doesn't need network access, thus it can be on an offline machine.
+    pub fn apply_verifiable_update(&mut self, update: VerifiableUpdate) -> Result<(), Error> {
+        update.verify()?;
+        let invalid = |s: String| Err(Error::InvalidVerifiableUpdate(s));
+        let update = update.update;
+        let descriptor = self.wollet_descriptor();
+        for (script, (chain, child)) in update.scripts.iter() {
+            let derived = descriptor
+                .definite_descriptor(*chain, u32::from(*child))?
+                .script_pubkey();
+            if derived != *script {
+                return invalid(format!(
+                    "script {script} is not derived at {chain:?}/{child}"
+                ));
+            }
+        }
+        for (outpoint, secrets) in update.new_txs.unblinds.iter() {
+            let output = update
+                .new_txs
+                .txs
+                .iter()
+                .find(|(txid, _)| *txid == outpoint.txid)
+                .and_then(|(_, tx)| tx.output.get(outpoint.vout as usize))
+                .or_else(|| {
+                    self.store
+                        .cache
+                        .all_txs
+                        .get(&outpoint.txid)
+                        .and_then(|tx| tx.output.get(outpoint.vout as usize))
+                });
+            let unblinded = output.and_then(|o| try_unblind(o.clone(), &descriptor).ok());
+            if unblinded.as_ref() != Some(secrets) {
+                return invalid(format!("output {outpoint} cannot be unblinded as given"));
+            }
+        }
+        self.apply_update(update)
+    }
+
     pub fn apply_update_no_persist(&mut self, update: Update) -> Result<(), Error> {
         self.apply_update_inner(update, false)
     }
@@ -429,6 +558,55 @@ impl Decodable for Update {
     }
 }
 
+const VERIFIABLE_UPDATE_MAGIC_BYTES: [u8; 4] = [0x89, 0x61, 0xb8, 0xc9];
+impl Encodable for VerifiableUpdate {
+    fn consensus_encode<W: std::io::Write>(
+        &self,
+        mut w: W,
+    ) -> Result<usize, elements::encode::Error> {
+        let mut bytes_written = 0;
+
+        bytes_written += VERIFIABLE_UPDATE_MAGIC_BYTES.consensus_encode(&mut w)?; // Magic bytes
+        bytes_written += 0u8.consensus_encode(&mut w)?; // Version
+
+        bytes_written += self.update.consensus_encode(&mut w)?;
+
+        bytes_written += elements::VarInt(self.proofs.len() as u64).consensus_encode(&mut w)?;
+        for proof in self.proofs.iter() {
+            bytes_written += proof.consensus_encode(&mut w)?;
+        }
+
+        Ok(bytes_written)
+    }
+}
+
+impl Decodable for VerifiableUpdate {
+    fn consensus_decode<D: std::io::Read>(mut d: D) -> Result<Self, elements::encode::Error> {
+        let magic_bytes: [u8; 4] = Decodable::consensus_decode(&mut d)?;
+        if magic_bytes != VERIFIABLE_UPDATE_MAGIC_BYTES {
+            return Err(elements::encode::Error::ParseFailed("Invalid magic bytes"));
+        }
+
+        let version = u8::consensus_decode(&mut d)?;
+        if version != 0 {
+            return Err(elements::encode::Error::ParseFailed("Unsupported version"));
+        }
+
+        let update = Update::consensus_decode(&mut d)?;
+
+        let proofs = {
+            let len = elements::VarInt::consensus_decode(&mut d)?.0;
+            let mut vec = vec![];
+            for _ in 0..len {
+                vec.push(MerkleProof::consensus_decode(&mut d)?);
+            }
+            vec
+        };
+
+        Ok(Self { update, proofs })
+    }
+}
+
 #[cfg(test)]
 mod test {
 
@@ -439,7 +617,10 @@ mod test {
         Script,
     };
 
-    use crate::{update::DownloadTxResult, Chain, Update, WolletDescriptor};
+    use crate::{
+        update::DownloadTxResult, Chain, HeaderChain, MerkleProof, Update, VerifiableUpdate,
+        WolletDescriptor,
+    };
 
     use super::EncodableTxOutSecrets;
 
@@ -555,4 +736,57 @@ mod test {
         let back = Update::deserialize_decrypted_base64(&update_ser, &desc).unwrap();
         assert_eq!(update, back)
     }
+
+    #[test]
+    fn test_verifiable_update() {
+        let block = lwk_test_util::liquid_block_1();
+        let tx = block.txdata[0].clone();
+        let txid = tx.txid();
+        let header = block.header;
+        let proof = MerkleProof::from_txids(header.clone(), &[txid], 0).unwrap();
+        let update = Update {
+            new_txs: DownloadTxResult {
+                txs: vec![(txid, tx)],
+                unblinds: vec![],
+            },
+            txid_height_new: vec![(txid, Some(header.height))],
+            txid_height_delete: vec![],
+            timestamps: vec![(header.height, header.time)],
+            scripts: HashMap::new(),
+            tip: header.clone(),
+        };
+        let verifiable = VerifiableUpdate {
+            update,
+            proofs: vec![proof],
+        };
+        verifiable.verify().unwrap();
+        let headers = HeaderChain::new(&header).unwrap();
+        verifiable.verify_with_headers(&headers).unwrap();
+        let mut other = header.clone();
+        other.time += 1;
+        let other_headers = HeaderChain::new(&other).unwrap();
+        let err = verifiable.verify_with_headers(&other_headers).unwrap_err();
+        assert!(err.to_string().contains("not in the validated chain"));
+
+        let bytes = verifiable.serialize().unwrap();
+        let back = VerifiableUpdate::deserialize(&bytes).unwrap();
+        assert_eq!(verifiable, back);
+        assert!(Update::deserialize(&bytes).is_err());
+
+        let mut wrong = verifiable.clone();
+        wrong.proofs.clear();
+        assert!(wrong.verify().unwrap_err().to_string().contains("missing"));
+
+        let mut wrong = verifiable.clone();
+        wrong.update.txid_height_new[0].1 = Some(header.height + 1);
+        assert!(wrong.verify().is_err());
+
+        let mut wrong = verifiable.clone();
+        wrong.update.timestamps[0].1 += 1;
+        assert!(wrong.verify().is_err());
+
+        let mut wrong = verifiable;
+        wrong.proofs[0].header.merkle_root = elements::hashes::Hash::all_zeros();
+        assert!(wrong.verify().is_err());
+    }
 }
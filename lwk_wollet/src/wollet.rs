@@ -2,13 +2,12 @@ use crate::bitcoin::bip32::Fingerprint;
 use crate::config::{Config, ElementsNetwork};
 use crate::descriptor::Chain;
 use crate::elements::pset::PartiallySignedTransaction;
-use crate::elements::secp256k1_zkp::ZERO_TWEAK;
 use crate::elements::{AssetId, BlockHash, OutPoint, Script, Transaction, Txid};
 use crate::error::Error;
 use crate::hashes::Hash;
 use crate::model::{AddressResult, IssuanceDetails, WalletTx, WalletTxOut};
 use crate::persister::PersistError;
-use crate::store::{Height, Store, Timestamp};
+use crate::store::{Height, Store, Timestamp, GAP_LIMIT};
 use crate::tx_builder::{extract_issuances, WolletTxBuilder};
 use crate::util::EC;
 use crate::{FsPersister, NoPersist, Persister, Update, WolletDescriptor};
@@ -19,7 +18,7 @@ use elements_miniscript::{
     ConfidentialDescriptor, DefiniteDescriptorKey, Descriptor, DescriptorPublicKey,
 };
 use fxhash::FxHasher;
-use lwk_common::{burn_script, pset_balance, pset_issuances, pset_signatures, PsetDetails};
+use lwk_common::{pset_balance, pset_issuances, pset_signatures, PsetDetails};
 use std::cmp::Ordering;
 use std::collections::{BTreeMap, HashMap, HashSet};
 use std::hash::Hasher;
@@ -32,6 +31,8 @@ pub struct Wollet {
     pub(crate) store: Store,
     pub(crate) persister: Arc<dyn Persister + Send + Sync>,
     descriptor: WolletDescriptor,
+    start_height: Option<Height>,
+    gap_limit: u32,
 }
 
 impl std::fmt::Debug for Wollet {
@@ -63,6 +64,8 @@ impl Wollet {
             config,
             descriptor,
             persister,
+            start_height: None,
+            gap_limit: GAP_LIMIT,
         };
 
         for i in 0.. {
@@ -96,6 +99,21 @@ impl Wollet {
         Self::new(network, Arc::new(NoPersist {}), descriptor)
     }
 
+    /// A copy of this wollet that does not persist, to scan it without holding this wollet
+    ///
+    /// The updates obtained scanning the copy must be applied to this wollet, while its
+    /// [`Wollet::status()`] is unchanged.
+    pub fn snapshot(&self) -> Self {
+        Self {
+            config: self.config.clone(),
+            store: self.store.clone(),
+            persister: NoPersist::new(),
+            descriptor: self.descriptor.clone(),
+            start_height: self.start_height,
+            gap_limit: self.gap_limit,
+        }
+    }
+
     /// Get the network policy asset
     pub fn policy_asset(&self) -> AssetId {
         self.config.policy_asset()
@@ -132,24 +150,31 @@ impl Wollet {
         }
     }
 
+    /// The index of the first address of the given chain not used by any wallet transaction
+    ///
+    /// It's the index of the address returned by [`Wollet::address()`] or [`Wollet::change()`]
+    /// when no index is given.
+    pub fn next_unused_index(&self, chain: Chain) -> u32 {
+        let index = match chain {
+            Chain::External => &self.store.cache.last_unused_external,
+            Chain::Internal => &self.store.cache.last_unused_internal,
+        };
+        index.load(atomic::Ordering::Relaxed)
+    }
+
     /// Get a wallet address
     ///
     /// If Some return the address at the given index,
     /// otherwise the last unused address.
     pub fn address(&self, index: Option<u32>) -> Result<AddressResult, Error> {
-        let index = match index {
-            Some(i) => i,
-            None => self
-                .store
-                .cache
-                .last_unused_external
-                .load(atomic::Ordering::Relaxed),
-        };
+        let index = index.unwrap_or_else(|| self.next_unused_index(Chain::External));
 
         let address = self
             .descriptor
             .address(index, self.config.address_params())?;
-        Ok(AddressResult::new(address, index))
+        let chain = self.descriptor.address_chain(Chain::External);
+        let full_path = self.descriptor.full_path(chain, index)?;
+        Ok(AddressResult::new(address, index, chain, full_path))
     }
 
     /// Get a wallet change address
@@ -160,19 +185,14 @@ impl Wollet {
     /// If Some return the address at the given index,
     /// otherwise the last unused address.
     pub fn change(&self, index: Option<u32>) -> Result<AddressResult, Error> {
-        let index = match index {
-            Some(i) => i,
-            None => self
-                .store
-                .cache
-                .last_unused_internal
-                .load(atomic::Ordering::Relaxed),
-        };
+        let index = index.unwrap_or_else(|| self.next_unused_index(Chain::Internal));
 
         let address = self
             .descriptor
             .change(index, self.config.address_params())?;
-        Ok(AddressResult::new(address, index))
+        let chain = self.descriptor.address_chain(Chain::Internal);
+        let full_path = self.descriptor.full_path(chain, index)?;
+        Ok(AddressResult::new(address, index, chain, full_path))
     }
 
     pub fn txos_inner(&self, unspent: bool) -> Result<Vec<WalletTxOut>, Error> {
@@ -286,22 +306,19 @@ impl Wollet {
                 continue;
             }
             let fee = tx_fee(tx);
-            let policy_asset = self.policy_asset();
-            let type_ = tx_type(tx, &policy_asset, &balance, fee);
             let timestamp = height.and_then(|h| self.store.cache.timestamps.get(&h).cloned());
             let inputs = tx_inputs(tx, &txos);
             let outputs = tx_outputs(**txid, tx, &txos);
-            txs.push(WalletTx {
-                tx: tx.clone(),
-                txid: **txid,
-                height: **height,
+            txs.push(wallet_tx(
+                tx.clone(),
+                **txid,
+                **height,
                 balance,
                 fee,
-                type_,
                 timestamp,
                 inputs,
                 outputs,
-            });
+            ));
         }
 
         Ok(txs)
@@ -316,23 +333,20 @@ impl Wollet {
 
             let balance = tx_balance(*txid, tx, &txos);
             let fee = tx_fee(tx);
-            let policy_asset = self.policy_asset();
-            let type_ = tx_type(tx, &policy_asset, &balance, fee);
             let timestamp = height.and_then(|h| self.store.cache.timestamps.get(&h).cloned());
             let inputs = tx_inputs(tx, &txos);
             let outputs = tx_outputs(*txid, tx, &txos);
 
-            Ok(Some(WalletTx {
-                tx: tx.clone(),
-                txid: *txid,
-                height: *height,
+            Ok(Some(wallet_tx(
+                tx.clone(),
+                *txid,
+                *height,
                 balance,
                 fee,
-                type_,
                 timestamp,
                 inputs,
                 outputs,
-            }))
+            )))
         } else {
             Ok(None)
         }
@@ -367,6 +381,21 @@ impl Wollet {
         })
     }
 
+    /// Whether the script pubkey is derived from the wallet descriptor
+    ///
+    /// Only the scripts already derived by the wallet, for instance while syncing, are considered.
+    pub fn is_mine(&self, script_pubkey: &Script) -> bool {
+        self.index(script_pubkey).is_ok()
+    }
+
+    /// The chain and the index of the address with the given script pubkey, if derived from the
+    /// wallet descriptor
+    ///
+    /// As in [`Wollet::is_mine()`], only the scripts already derived by the wallet are considered.
+    pub fn script_derivation(&self, script_pubkey: &Script) -> Option<(Chain, u32)> {
+        self.index(script_pubkey).ok()
+    }
+
     pub(crate) fn index(&self, script_pubkey: &Script) -> Result<(Chain, u32), Error> {
         let (ext_int, index) = self
             .store
@@ -450,7 +479,11 @@ impl Wollet {
         psets: &[PartiallySignedTransaction],
     ) -> Result<PartiallySignedTransaction, Error> {
         let mut res = psets.first().ok_or_else(|| Error::MissingPset)?.clone();
-        for pset in psets.iter().skip(1) {
+        let unique_id = res.unique_id().map_err(|_| Error::IncompatiblePset(0))?;
+        for (idx, pset) in psets.iter().enumerate().skip(1) {
+            if pset.unique_id().ok() != Some(unique_id) {
+                return Err(Error::IncompatiblePset(idx));
+            }
             res.merge(pset.clone())?;
         }
         Ok(res)
@@ -485,6 +518,46 @@ impl Wollet {
         hasher.finish()
     }
 
+    /// Discard the scan state and the persisted updates, so that the next scan restores the wallet
+    ///
+    /// If `start_height` is given, the next scans ignore the transactions confirmed before it,
+    /// speeding up the restore of wallets known to have no older transactions, since they are
+    /// not downloaded. The history of the wallet scripts is still fetched in full.
+    pub fn reset_scan(&mut self, start_height: Option<Height>) -> Result<(), Error> {
+        self.persister.clear()?;
+        self.store = Store::default();
+        self.start_height = start_height;
+        Ok(())
+    }
+
+    /// The height from which the transactions are considered by the scans, see [`Wollet::reset_scan()`]
+    pub fn start_height(&self) -> Option<Height> {
+        self.start_height
+    }
+
+    /// Set the height from which the transactions are considered by the scans, keeping the scan
+    /// state, used to restore the value given to [`Wollet::reset_scan()`] when the wallet is reloaded
+    pub fn set_start_height(&mut self, start_height: Option<Height>) {
+        self.start_height = start_height;
+    }
+
+    /// Set the number of consecutive unused addresses after which the scans stop, default [`GAP_LIMIT`]
+    ///
+    /// Raise it to find the funds of wallets with large gaps of unused addresses, for instance
+    /// when restoring a wallet used with other software.
+    pub fn set_gap_limit(&mut self, gap_limit: u32) -> Result<(), Error> {
+        if gap_limit == 0 {
+            return Err(Error::ZeroGapLimit);
+        }
+        self.gap_limit = gap_limit;
+        Ok(())
+    }
+
+    /// The gap limit used by the scans, see [`Wollet::set_gap_limit()`]
+    pub fn gap_limit(&self) -> u32 {
+        self.gap_limit
+    }
+
     /// Returns true if this wollet has never received an updated applyed to it
     pub fn never_scanned(&self) -> bool {
         self.store.cache.tip == (0, BlockHash::all_zeros())
@@ -517,9 +590,15 @@ pub fn full_scan_with_electrum_client(
     wollet: &mut Wollet,
     electrum_client: &mut crate::ElectrumClient,
 ) -> Result<(), Error> {
-    use crate::BlockchainBackend;
+    full_scan_with_backend(wollet, electrum_client)
+}
 
-    let update = electrum_client.full_scan(wollet)?;
+/// Scan the blockchain with any backend and apply the resulting update to the wallet
+pub fn full_scan_with_backend(
+    wollet: &mut Wollet,
+    backend: &mut dyn crate::BlockchainBackend,
+) -> Result<(), Error> {
+    let update = backend.full_scan(wollet)?;
     if let Some(update) = update {
         wollet.apply_update(update)?
     }
@@ -535,43 +614,30 @@ fn tx_fee(tx: &Transaction) -> u64 {
         .sum()
 }
 
-/// Get a string that hopefully defines the transaction type.
-///
-/// Defining clear rules for types is highly arbitrary so here we provide a string that should
-/// define the type, but it might be inaccurate in some cases.
-fn tx_type(
-    tx: &Transaction,
-    policy_asset: &AssetId,
-    balance: &BTreeMap<AssetId, i64>,
+#[allow(clippy::too_many_arguments)]
+fn wallet_tx(
+    tx: Transaction,
+    txid: Txid,
+    height: Option<u32>,
+    balance: BTreeMap<AssetId, i64>,
     fee: u64,
-) -> String {
-    let burn_script = burn_script();
-    if tx
-        .input
-        .iter()
-        .any(|i| !i.asset_issuance.is_null() && i.asset_issuance.asset_blinding_nonce == ZERO_TWEAK)
-    {
-        "issuance".to_string()
-    } else if tx
-        .input
-        .iter()
-        .any(|i| !i.asset_issuance.is_null() && i.asset_issuance.asset_blinding_nonce != ZERO_TWEAK)
-    {
-        "reissuance".to_string()
-    } else if tx.output.iter().any(|o| o.script_pubkey == burn_script) {
-        "burn".to_string()
-    } else if balance.len() == 1 && balance.get(policy_asset) == Some(&(fee as i64)) {
-        "redeposit".to_string()
-    } else if balance.is_empty() {
-        "unknown".to_string()
-    } else if balance.values().all(|v| *v > 0) {
-        "incoming".to_string()
-    } else if balance.values().all(|v| *v < 0) {
-        // redeposit case handled above
-        "outgoing".to_string()
-    } else {
-        "unknown".to_string()
-    }
+    timestamp: Option<u32>,
+    inputs: Vec<Option<WalletTxOut>>,
+    outputs: Vec<Option<WalletTxOut>>,
+) -> WalletTx {
+    let mut wallet_tx = WalletTx {
+        tx,
+        txid,
+        height,
+        balance,
+        fee,
+        type_: String::new(),
+        timestamp,
+        inputs,
+        outputs,
+    };
+    wallet_tx.type_ = wallet_tx.classify().to_string();
+    wallet_tx
 }
 
 fn tx_inputs(tx: &Transaction, txos: &HashMap<OutPoint, WalletTxOut>) -> Vec<Option<WalletTxOut>> {
@@ -622,7 +688,7 @@ mod tests {
     use crate::elements::bitcoin::bip32::{Xpriv, Xpub};
     use crate::elements::bitcoin::network::Network;
     use crate::elements::AddressParams;
-    use crate::NoPersist;
+    use crate::{MemoryPersister, NoPersist, UnvalidatedRecipient};
     use elements_miniscript::confidential::bare::tweak_private_key;
     use elements_miniscript::confidential::Key;
     use elements_miniscript::descriptor::checksum::desc_checksum;
@@ -687,6 +753,56 @@ mod tests {
         Wollet::new(ElementsNetwork::LiquidTestnet, NoPersist::new(), desc).unwrap()
     }
 
+    #[test]
+    fn test_address_chain_full_path() {
+        let view_key = "1111111111111111111111111111111111111111111111111111111111111111";
+        let xpub = "tpubDD7tXK8KeQ3YY83yWq755fHY2JW8Ha8Q765tknUM5rSvjPcGWfUppDFMpQ1ScziKfW3ZNtZvAD7M3u7bSs7HofjTD3KP3YxPK7X6hwV8Rk2";
+
+        let wollet = new_wollet(&format!(
+            "ct({view_key},elwpkh([73c5da0a/84'/1'/0']{xpub}/<0;1>/*))"
+        ));
+        let address = wollet.address(Some(3)).unwrap();
+        assert_eq!(address.chain(), Chain::External);
+        assert_eq!(address.full_path().to_string(), "m/84'/1'/0'/0/3");
+        let change = wollet.change(Some(5)).unwrap();
+        assert_eq!(change.chain(), Chain::Internal);
+        assert_eq!(change.full_path().to_string(), "m/84'/1'/0'/1/5");
+
+        // Without multipath all the addresses are external
+        let wollet = new_wollet(&format!("ct({view_key},elwpkh({xpub}/*))"));
+        let change = wollet.change(Some(2)).unwrap();
+        assert_eq!(change.chain(), Chain::External);
+        assert_eq!(change.full_path().to_string(), "m/2");
+
+        // Old serializations without chain and full path can still be deserialized
+        let json = serde_json::to_value(&change).unwrap();
+        let mut old = json.as_object().unwrap().clone();
+        old.remove("chain");
+        old.remove("full_path");
+        let old: AddressResult = serde_json::from_value(old.into()).unwrap();
+        assert_eq!(old.chain(), Chain::External);
+        assert_eq!(old.address(), change.address());
+    }
+
+    #[test]
+    fn test_unconfidential_address() {
+        let view_key = "1111111111111111111111111111111111111111111111111111111111111111";
+        let xpub = "tpubDD7tXK8KeQ3YY83yWq755fHY2JW8Ha8Q765tknUM5rSvjPcGWfUppDFMpQ1ScziKfW3ZNtZvAD7M3u7bSs7HofjTD3KP3YxPK7X6hwV8Rk2";
+
+        let wollet = new_wollet(&format!("ct({view_key},elwpkh({xpub}/*))"));
+        let confidential = wollet.address(Some(3)).unwrap();
+        let unconfidential = confidential.to_unconfidential();
+        assert!(confidential.is_confidential());
+        assert!(!unconfidential.is_confidential());
+        assert_ne!(confidential.address(), unconfidential.address());
+        assert_eq!(
+            confidential.address().script_pubkey(),
+            unconfidential.address().script_pubkey()
+        );
+        assert_eq!(confidential.index(), unconfidential.index());
+        assert_eq!(confidential.full_path(), unconfidential.full_path());
+    }
+
     #[test]
     fn test_signers() {
         let view_key = "1111111111111111111111111111111111111111111111111111111111111111";
@@ -716,6 +832,110 @@ mod tests {
         assert_eq!(new_wollet(&desc_m_12).signers(), vec![fp1, fp2]);
     }
 
+    #[test]
+    fn test_combine_incompatible() {
+        let view_key = "1111111111111111111111111111111111111111111111111111111111111111";
+        let xpub = "tpubDD7tXK8KeQ3YY83yWq755fHY2JW8Ha8Q765tknUM5rSvjPcGWfUppDFMpQ1ScziKfW3ZNtZvAD7M3u7bSs7HofjTD3KP3YxPK7X6hwV8Rk2";
+        let wollet = new_wollet(&format!("ct({view_key},elwpkh({xpub}/*))"));
+
+        let pset = |vout| {
+            let mut pset = PartiallySignedTransaction::new_v2();
+            let txid = elements::Txid::from_str(&"00".repeat(32)).unwrap();
+            pset.add_input(elements::pset::Input::from_prevout(
+                elements::OutPoint::new(txid, vout),
+            ));
+            pset
+        };
+        assert!(matches!(wollet.combine(&[]), Err(Error::MissingPset)));
+        wollet.combine(&[pset(0), pset(0)]).unwrap();
+        let err = wollet.combine(&[pset(0), pset(0), pset(1)]).unwrap_err();
+        assert!(matches!(err, Error::IncompatiblePset(2)));
+    }
+
+    #[test]
+    fn test_invalid_fee_rate() {
+        let view_key = "1111111111111111111111111111111111111111111111111111111111111111";
+        let xpub = "tpubDD7tXK8KeQ3YY83yWq755fHY2JW8Ha8Q765tknUM5rSvjPcGWfUppDFMpQ1ScziKfW3ZNtZvAD7M3u7bSs7HofjTD3KP3YxPK7X6hwV8Rk2";
+        let wollet = new_wollet(&format!("ct({view_key},elwpkh({xpub}/*))"));
+
+        for fee_rate in [0.0, -1.0, f32::NAN, f32::INFINITY] {
+            let err = wollet
+                .tx_builder()
+                .fee_rate(Some(fee_rate))
+                .finish()
+                .unwrap_err();
+            assert!(matches!(err, Error::InvalidFeeRate(_)));
+        }
+    }
+
+    #[test]
+    fn test_invalid_recipients() {
+        let view_key = "1111111111111111111111111111111111111111111111111111111111111111";
+        let xpub = "tpubDD7tXK8KeQ3YY83yWq755fHY2JW8Ha8Q765tknUM5rSvjPcGWfUppDFMpQ1ScziKfW3ZNtZvAD7M3u7bSs7HofjTD3KP3YxPK7X6hwV8Rk2";
+        let wollet = new_wollet(&format!("ct({view_key},elwpkh({xpub}/*))"));
+        let address = wollet.address(None).unwrap().address().to_string();
+
+        let recipients = [
+            UnvalidatedRecipient::lbtc(address.clone(), 0),
+            UnvalidatedRecipient::lbtc(address.clone(), 1_000),
+            UnvalidatedRecipient::lbtc("".to_string(), 1_000),
+        ];
+        let err = wollet
+            .tx_builder()
+            .set_unvalidated_recipients(&recipients)
+            .unwrap_err();
+        match err {
+            Error::InvalidRecipients(invalid) => {
+                assert_eq!(invalid.len(), 2);
+                assert_eq!(invalid[0].index, 0);
+                assert_eq!(invalid[0].reason, Error::InvalidAmount.to_string());
+                assert_eq!(invalid[1].index, 2);
+            }
+            e => panic!("unexpected error {e}"),
+        }
+
+        let recipients = [UnvalidatedRecipient::lbtc(address, 1_000)];
+        assert!(wollet
+            .tx_builder()
+            .set_unvalidated_recipients(&recipients)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_max_recipients() {
+        let view_key = "1111111111111111111111111111111111111111111111111111111111111111";
+        let xpub = "tpubDD7tXK8KeQ3YY83yWq755fHY2JW8Ha8Q765tknUM5rSvjPcGWfUppDFMpQ1ScziKfW3ZNtZvAD7M3u7bSs7HofjTD3KP3YxPK7X6hwV8Rk2";
+        let wollet = new_wollet(&format!("ct({view_key},elwpkh({xpub}/*))"));
+        let address = wollet.address(None).unwrap().address().to_string();
+
+        let max = UnvalidatedRecipient::try_from(format!("{address}:max:")).unwrap();
+        assert!(max.is_max());
+        let recipients = [
+            max.clone(),
+            UnvalidatedRecipient::lbtc(address.clone(), 1_000),
+        ];
+        assert!(wollet
+            .tx_builder()
+            .set_unvalidated_recipients(&recipients)
+            .is_ok());
+
+        let recipients = [max.clone(), max];
+        let err = wollet
+            .tx_builder()
+            .set_unvalidated_recipients(&recipients)
+            .unwrap_err();
+        match err {
+            Error::InvalidRecipients(invalid) => {
+                assert_eq!(invalid.len(), 1);
+                assert_eq!(invalid[0].index, 1);
+                let policy_asset = wollet.policy_asset();
+                let expected = Error::MultipleMaxRecipients(policy_asset).to_string();
+                assert_eq!(invalid[0].reason, expected);
+            }
+            e => panic!("unexpected error {e}"),
+        }
+    }
+
     #[test]
     fn test_apply_old_update() {
         let bytes = lwk_test_util::update_test_vector_bytes();
@@ -808,4 +1028,84 @@ mod tests {
 
         assert_eq!(4667218140179748739, wollet.status());
     }
+
+    #[test]
+    fn test_reset_scan() {
+        let bytes = lwk_test_util::update_test_vector_bytes();
+
+        let update = crate::Update::deserialize(&bytes[..]).unwrap();
+        let exp = "ct(slip77(9c8e4f05c7711a98c838be228bcb84924d4570ca53f35fa1c793e58841d47023),elwpkh([73c5da0a/84'/1'/0']tpubDC8msFGeGuwnKG9Upg7DM2b4DaRqg3CUZa5g8v2SRQ6K4NSkxUgd7HsL2XVWbVm39yBA4LAxysQAm397zwQSQoQgewGiYZqrA9DsP4zbQ1M/<0;1>/*))";
+        let mut wollet = new_wollet(exp);
+        let status = wollet.status();
+        wollet.apply_update(update).unwrap();
+        assert!(!wollet.never_scanned());
+        assert_eq!(wollet.start_height(), None);
+
+        wollet.reset_scan(Some(100)).unwrap();
+        assert!(wollet.never_scanned());
+        assert_eq!(wollet.start_height(), Some(100));
+        assert_eq!(wollet.status(), status);
+
+        wollet.set_start_height(None);
+        assert_eq!(wollet.start_height(), None);
+    }
+
+    #[test]
+    fn test_snapshot() {
+        let desc: WolletDescriptor = lwk_test_util::wollet_descriptor_string2().parse().unwrap();
+        let bytes = lwk_test_util::update_test_vector_encrypted_bytes2();
+        let update = crate::Update::deserialize_decrypted(&bytes, &desc).unwrap();
+        let network = ElementsNetwork::LiquidTestnet;
+        let persister = MemoryPersister::new();
+
+        let mut wollet = Wollet::new(network, persister.clone(), desc.clone()).unwrap();
+        wollet.set_gap_limit(30).unwrap();
+        let mut snapshot = wollet.snapshot();
+        assert_eq!(snapshot.status(), wollet.status());
+        assert_eq!(snapshot.gap_limit(), 30);
+
+        // Updates applied to the snapshot are not persisted
+        snapshot.apply_update(update.clone()).unwrap();
+        assert_ne!(snapshot.status(), wollet.status());
+        let wollet2 = Wollet::new(network, persister, desc).unwrap();
+        assert!(wollet2.never_scanned());
+
+        wollet.apply_update(update).unwrap();
+        assert_eq!(wollet.balance().unwrap(), snapshot.balance().unwrap());
+    }
+
+    #[test]
+    fn test_memory_persister() {
+        let desc: WolletDescriptor = lwk_test_util::wollet_descriptor_string2().parse().unwrap();
+        let bytes = lwk_test_util::update_test_vector_encrypted_bytes2();
+        let update = crate::Update::deserialize_decrypted(&bytes, &desc).unwrap();
+        let network = ElementsNetwork::LiquidTestnet;
+        let persister = MemoryPersister::new();
+
+        let mut wollet = Wollet::new(network, persister.clone(), desc.clone()).unwrap();
+        assert!(wollet.never_scanned());
+        wollet.apply_update(update).unwrap();
+        let balance = wollet.balance().unwrap();
+        let lbtc = "144c654344aa716d6f3abcc1ca90e5641e4e2a7f633bc09fe3baf64585819a49";
+        assert_eq!(
+            balance.get(&AssetId::from_str(lbtc).unwrap()),
+            Some(&100_000)
+        );
+
+        // A wollet with the same persister restores the state
+        let wollet2 = Wollet::new(network, persister, desc).unwrap();
+        assert_eq!(wollet2.balance().unwrap(), balance);
+        assert_eq!(wollet2.status(), wollet.status());
+    }
+
+    #[test]
+    fn test_gap_limit() {
+        let exp = "ct(slip77(9c8e4f05c7711a98c838be228bcb84924d4570ca53f35fa1c793e58841d47023),elwpkh([73c5da0a/84'/1'/0']tpubDC8msFGeGuwnKG9Upg7DM2b4DaRqg3CUZa5g8v2SRQ6K4NSkxUgd7HsL2XVWbVm39yBA4LAxysQAm397zwQSQoQgewGiYZqrA9DsP4zbQ1M/<0;1>/*))";
+        let mut wollet = new_wollet(exp);
+        assert_eq!(wollet.gap_limit(), GAP_LIMIT);
+        wollet.set_gap_limit(100).unwrap();
+        assert_eq!(wollet.gap_limit(), 100);
+        assert!(matches!(wollet.set_gap_limit(0), Err(Error::ZeroGapLimit)));
+        assert_eq!(wollet.gap_limit(), 100);
+    }
 }
@@ -390,6 +390,348 @@ fn fee_rate() {
     wallet.burnasset(&signers, 5_000, &asset, fee_rate);
 }
 
+#[test]
+fn bump_fee() {
+    let server = setup(false);
+    let signer = generate_signer();
+    let view_key = generate_view_key();
+    let desc = format!("ct({},elwpkh({}/*))", view_key, signer.xpub());
+    let signers = [&AnySigner::Software(signer)];
+
+    let mut wallet = TestWollet::new(&server.electrs.electrum_url, &desc);
+    wallet.fund_btc(&server);
+    let policy_asset = wallet.policy_asset();
+    let node_address = server.node_getnewaddress();
+
+    // Not replaceable
+    let mut pset = wallet
+        .tx_builder()
+        .add_lbtc_recipient(&node_address, 10_000)
+        .unwrap()
+        .finish()
+        .unwrap();
+    for signer in signers {
+        wallet.sign(signer, &mut pset);
+    }
+    let txid = wallet.send(&mut pset);
+    let err = wallet.wollet.bump_fee(&txid, 1_000.0).unwrap_err();
+    assert!(matches!(err, Error::TxNotReplaceable(_)));
+
+    // Replaceable
+    let mut pset = wallet
+        .tx_builder()
+        .add_lbtc_recipient(&node_address, 10_000)
+        .unwrap()
+        .enable_rbf()
+        .finish()
+        .unwrap();
+    for signer in signers {
+        wallet.sign(signer, &mut pset);
+    }
+    let txid = wallet.send(&mut pset);
+    let original = wallet.wollet.transaction(&txid).unwrap().unwrap();
+    let balance = wallet.balance(&policy_asset);
+
+    let err = wallet.wollet.bump_fee(&txid, 1.0).unwrap_err();
+    assert!(matches!(err, Error::FeeNotIncreased { .. }));
+
+    let mut pset = wallet.wollet.bump_fee(&txid, 1_000.0).unwrap();
+    assert_eq!(pset.inputs().len(), original.tx.input.len());
+    for signer in signers {
+        wallet.sign(signer, &mut pset);
+    }
+    let new_txid = wallet.send(&mut pset);
+    assert_ne!(txid, new_txid);
+    let replacement = wallet.wollet.transaction(&new_txid).unwrap().unwrap();
+    assert!(replacement.fee > original.fee);
+    assert_eq!(
+        wallet.balance(&policy_asset),
+        balance - (replacement.fee - original.fee)
+    );
+
+    // Confirmed
+    server.generate(1);
+    wait_tx_update(&mut wallet);
+    let err = wallet.wollet.bump_fee(&new_txid, 2_000.0).unwrap_err();
+    assert!(matches!(err, Error::TxAlreadyConfirmed(_)));
+
+    let not_in_wallet = elements::Txid::from_str(&"11".repeat(32)).unwrap();
+    let err = wallet.wollet.bump_fee(&not_in_wallet, 2_000.0).unwrap_err();
+    assert!(matches!(err, Error::TxNotInWallet(_)));
+}
+
+#[test]
+fn change_address() {
+    let server = setup(false);
+    let signer = generate_signer();
+    let view_key = generate_view_key();
+    let desc = format!("ct({},elwpkh({}/*))", view_key, signer.xpub());
+    let signers = [&AnySigner::Software(signer)];
+
+    let mut wallet = TestWollet::new(&server.electrs.electrum_url, &desc);
+    wallet.fund_btc(&server);
+    let policy_asset = wallet.policy_asset();
+    let node_address = server.node_getnewaddress();
+
+    let signer_change = generate_signer();
+    let view_key_change = generate_view_key();
+    let desc_change = format!("ct({},elwpkh({}/*))", view_key_change, signer_change.xpub());
+    let mut wallet_change = TestWollet::new(&server.electrs.electrum_url, &desc_change);
+    let change_address = wallet_change.address();
+
+    let err = wallet
+        .tx_builder()
+        .set_change_address(change_address.to_unconfidential())
+        .unwrap_err();
+    assert!(matches!(err, Error::NotConfidentialAddress));
+
+    // The change goes to the other wallet
+    let balance = wallet.balance(&policy_asset);
+    let mut pset = wallet
+        .tx_builder()
+        .add_lbtc_recipient(&node_address, 10_000)
+        .unwrap()
+        .set_change_address(change_address.clone())
+        .unwrap()
+        .finish()
+        .unwrap();
+    assert!(pset
+        .outputs()
+        .iter()
+        .any(|o| o.script_pubkey == change_address.script_pubkey()));
+    for signer in signers {
+        wallet.sign(signer, &mut pset);
+    }
+    let txid = wallet.send(&mut pset);
+    let fee = wallet.wollet.transaction(&txid).unwrap().unwrap().fee;
+    assert_eq!(wallet.balance(&policy_asset), 0);
+
+    wait_tx_update(&mut wallet_change);
+    assert_eq!(wallet_change.balance(&policy_asset), balance - 10_000 - fee);
+}
+
+#[test]
+fn manual_coin_selection() {
+    let server = setup(false);
+    let signer = generate_signer();
+    let view_key = generate_view_key();
+    let desc = format!("ct({},elwpkh({}/*))", view_key, signer.xpub());
+    let signers = [&AnySigner::Software(signer)];
+
+    let mut wallet = TestWollet::new(&server.electrs.electrum_url, &desc);
+    wallet.fund_btc(&server);
+    wallet.fund_btc(&server);
+    let utxos = wallet.wollet.utxos().unwrap();
+    assert_eq!(utxos.len(), 2);
+    let utxo = utxos[0].outpoint;
+    let node_address = server.node_getnewaddress();
+
+    // The selected utxo doesn't cover the amount
+    let err = wallet
+        .tx_builder()
+        .add_lbtc_recipient(&node_address, 1_500_000)
+        .unwrap()
+        .set_wallet_utxos(vec![utxo])
+        .finish()
+        .unwrap_err();
+    assert!(matches!(err, Error::InsufficientFunds));
+
+    let mut pset = wallet
+        .tx_builder()
+        .add_lbtc_recipient(&node_address, 10_000)
+        .unwrap()
+        .set_wallet_utxos(vec![utxo])
+        .finish()
+        .unwrap();
+    assert_eq!(pset.inputs().len(), 1);
+    assert_eq!(pset.inputs()[0].previous_txid, utxo.txid);
+    assert_eq!(pset.inputs()[0].previous_output_index, utxo.vout);
+
+    for signer in signers {
+        wallet.sign(signer, &mut pset);
+    }
+    wallet.send(&mut pset);
+
+    // The utxo has been spent
+    let err = wallet
+        .tx_builder()
+        .add_lbtc_recipient(&node_address, 10_000)
+        .unwrap()
+        .set_wallet_utxos(vec![utxo])
+        .finish()
+        .unwrap_err();
+    assert!(matches!(err, Error::MissingWalletUtxo(_)));
+}
+
+#[test]
+fn coin_selection() {
+    let server = setup(false);
+    let signer = generate_signer();
+    let view_key = generate_view_key();
+    let desc = format!("ct({},elwpkh({}/*))", view_key, signer.xpub());
+
+    let mut wallet = TestWollet::new(&server.electrs.electrum_url, &desc);
+    for satoshi in [100_000, 300_000, 500_000] {
+        wallet.fund(&server, satoshi, None, None);
+    }
+    let asset = server.node_issueasset(30_000);
+    for satoshi in [5_000, 10_000, 15_000] {
+        wallet.fund(&server, satoshi, None, Some(asset));
+    }
+    let node_address = server.node_getnewaddress();
+
+    let send = |satoshi: u64, asset: elements::AssetId, strategy: Option<CoinSelection>| {
+        let mut builder = wallet
+            .tx_builder()
+            .add_recipient(&node_address, satoshi, asset)
+            .unwrap();
+        if let Some(strategy) = strategy {
+            builder = builder.coin_selection(strategy);
+        }
+        builder.finish().unwrap()
+    };
+    let asset_outputs = |pset: &elements::pset::PartiallySignedTransaction| {
+        pset.outputs()
+            .iter()
+            .filter(|o| o.asset == Some(asset))
+            .count()
+    };
+    let policy_asset = wallet.policy_asset();
+
+    // By default all the L-BTC utxos are spent
+    let pset = send(50_000, policy_asset, None);
+    assert_eq!(pset.inputs().len(), 3);
+
+    let pset = send(50_000, policy_asset, Some(CoinSelection::LargestFirst));
+    assert_eq!(pset.inputs().len(), 1);
+    let pset = send(150_000, policy_asset, Some(CoinSelection::SmallestFirst));
+    assert_eq!(pset.inputs().len(), 2);
+    let pset = send(380_000, policy_asset, Some(CoinSelection::BranchAndBound));
+    assert_eq!(pset.inputs().len(), 2);
+
+    // Largest first needs a change output for the asset, branch and bound finds an exact match
+    let pset = send(20_000, asset, Some(CoinSelection::LargestFirst));
+    assert_eq!(asset_outputs(&pset), 2);
+    let pset = send(20_000, asset, Some(CoinSelection::BranchAndBound));
+    assert_eq!(asset_outputs(&pset), 1);
+    let pset = send(20_000, asset, Some(CoinSelection::SmallestFirst));
+    assert_eq!(asset_outputs(&pset), 2);
+}
+
+#[test]
+fn estimate_fee() {
+    let server = setup(false);
+    let signer = generate_signer();
+    let view_key = generate_view_key();
+    let desc = format!("ct({},elwpkh({}/*))", view_key, signer.xpub());
+
+    let mut wallet = TestWollet::new(&server.electrs.electrum_url, &desc);
+    wallet.fund_btc(&server);
+    wallet.fund_btc(&server);
+    let asset = wallet.fund_asset(&server);
+    let node_address = server.node_getnewaddress();
+
+    for (satoshi, asset) in [(10_000, wallet.policy_asset()), (1_000, asset)] {
+        let estimate = wallet
+            .tx_builder()
+            .add_recipient(&node_address, satoshi, asset)
+            .unwrap()
+            .fee_rate(Some(1000.0))
+            .estimate_fee()
+            .unwrap();
+        let pset = wallet
+            .tx_builder()
+            .add_recipient(&node_address, satoshi, asset)
+            .unwrap()
+            .fee_rate(Some(1000.0))
+            .finish()
+            .unwrap();
+        let fee = wallet.wollet.get_details(&pset).unwrap().balance.fee;
+        assert_eq!(estimate.inputs, pset.inputs().len());
+        // The signatures and the proofs sizes slightly vary
+        assert!(estimate.fee.abs_diff(fee) <= 5, "{} {}", estimate.fee, fee);
+        let vsize = lwk_common::pset_vsize(&pset, wallet.wollet.descriptor()).unwrap();
+        assert!(estimate.vsize.abs_diff(vsize) <= 5);
+    }
+
+    let err = wallet
+        .tx_builder()
+        .add_lbtc_recipient(&node_address, 10_000_000)
+        .unwrap()
+        .estimate_fee()
+        .unwrap_err();
+    assert!(matches!(err, Error::InsufficientFunds));
+
+    let err = wallet
+        .tx_builder()
+        .issue_asset(10, None, 1, None, None)
+        .unwrap()
+        .estimate_fee()
+        .unwrap_err();
+    assert!(matches!(err, Error::FeeEstimationIssuanceUnsupported));
+}
+
+#[test]
+fn next_unused_index() {
+    let server = setup(false);
+    let signer = generate_signer();
+    let view_key = generate_view_key();
+    let desc = format!("ct({},elwpkh({}/*))", view_key, signer.xpub());
+    let signers = [&AnySigner::Software(signer)];
+
+    let mut wallet = TestWollet::new(&server.electrs.electrum_url, &desc);
+    assert_eq!(wallet.wollet.next_unused_index(Chain::External), 0);
+    assert_eq!(wallet.wollet.next_unused_index(Chain::Internal), 0);
+
+    wallet.fund_btc(&server);
+    assert_eq!(wallet.wollet.next_unused_index(Chain::External), 1);
+    assert_eq!(wallet.wollet.next_unused_index(Chain::Internal), 0);
+    assert_eq!(wallet.wollet.change(None).unwrap().index(), 0);
+
+    // Sending to an external address creates a change output
+    let node_address = server.node_getnewaddress();
+    wallet.send_btc(&signers, None, Some((node_address, 10_000)));
+    assert_eq!(wallet.wollet.next_unused_index(Chain::External), 1);
+    assert_eq!(wallet.wollet.next_unused_index(Chain::Internal), 1);
+    assert_eq!(wallet.wollet.change(None).unwrap().index(), 1);
+}
+
+#[test]
+fn send_max() {
+    let server = setup(false);
+    let signer = generate_signer();
+    let view_key = generate_view_key();
+    let desc = format!("ct({},elwpkh({}/*))", view_key, signer.xpub());
+    let signers = [&AnySigner::Software(signer)];
+
+    let mut wallet = TestWollet::new(&server.electrs.electrum_url, &desc);
+    wallet.fund_btc(&server);
+    wallet.fund_btc(&server);
+    let node_address = server.node_getnewaddress();
+
+    let recipient = UnvalidatedRecipient {
+        satoshi: None,
+        address: node_address.to_string(),
+        asset: "".to_string(),
+        timelock: None,
+    };
+    let mut pset = wallet
+        .tx_builder()
+        .add_unvalidated_recipient(&recipient)
+        .unwrap()
+        .finish()
+        .unwrap();
+    // All the utxos are spent and there is no change, only the recipient and the fee
+    assert_eq!(pset.inputs().len(), 2);
+    assert_eq!(pset.outputs().len(), 2);
+
+    for signer in signers {
+        wallet.sign(signer, &mut pset);
+    }
+    wallet.send(&mut pset);
+    assert_eq!(wallet.balance(&wallet.policy_asset()), 0);
+}
+
 #[test]
 fn contract() {
     // Issue an asset with a contract
@@ -588,67 +930,83 @@ fn create_pset_error() {
 
     // Invalid address
     let addressees = vec![UnvalidatedRecipient {
-        satoshi: 1_000,
+        satoshi: Some(1_000),
         address: "".to_string(),
         asset: "".to_string(),
+        timelock: None,
     }];
     let err = wallet
         .tx_builder()
         .set_unvalidated_recipients(&addressees)
         .unwrap_err();
     let expected = "base58 error: base58ck data not even long enough for a checksum";
-    assert_eq!(err.to_string(), expected);
+    assert_eq!(
+        err.to_string(),
+        format!("Invalid recipients: #0: {expected}")
+    );
 
     // Not confidential address
     let mut address = wallet.address();
     address.blinding_pubkey = None;
     let not_conf_address = address.to_string();
     let addressees = vec![UnvalidatedRecipient {
-        satoshi: 1_000,
+        satoshi: Some(1_000),
         address: not_conf_address,
         asset: "".to_string(),
+        timelock: None,
     }];
     let err = wallet
         .tx_builder()
         .set_unvalidated_recipients(&addressees)
         .unwrap_err();
-    assert_eq!(err.to_string(), Error::NotConfidentialAddress.to_string());
+    assert_eq!(
+        err.to_string(),
+        format!("Invalid recipients: #0: {}", Error::NotConfidentialAddress)
+    );
 
     let address = wallet.address().to_string();
     // Invalid amount
     let addressees = vec![UnvalidatedRecipient {
-        satoshi: 0,
+        satoshi: Some(0),
         address: address.clone(),
         asset: "".to_string(),
+        timelock: None,
     }];
     let err = wallet
         .tx_builder()
         .set_unvalidated_recipients(&addressees)
         .unwrap_err();
-    assert_eq!(err.to_string(), Error::InvalidAmount.to_string());
+    assert_eq!(
+        err.to_string(),
+        format!("Invalid recipients: #0: {}", Error::InvalidAmount)
+    );
 
     // Invalid asset
     let addressees = vec![UnvalidatedRecipient {
-        satoshi: 1_000,
+        satoshi: Some(1_000),
         address: address.clone(),
         asset: "aaaa".to_string(),
+        timelock: None,
     }];
-    let _err = wallet
+    let err = wallet
         .tx_builder()
         .set_unvalidated_recipients(&addressees)
         .unwrap_err();
-    // TODO uncomment once https://github.com/ElementsProject/rust-elements/issues/189 is resolved
-    // assert_eq!(
-    //     err.to_string(),
-    //     "bad hex string length 4 (expected 64)".to_string()
-    // );
+    assert_eq!(
+        err.to_string(),
+        format!(
+            "Invalid recipients: #0: {}",
+            Error::UnknownAsset("aaaa".into())
+        )
+    );
 
     // Insufficient funds
     // Not enough lbtc
     let addressees = vec![UnvalidatedRecipient {
-        satoshi: 2_200_000_000_000_000,
+        satoshi: Some(2_200_000_000_000_000),
         address: address.clone(),
         asset: "".to_string(),
+        timelock: None,
     }];
     let err = wallet
         .tx_builder()
@@ -660,9 +1018,10 @@ fn create_pset_error() {
 
     // Not enough asset
     let addressees = vec![UnvalidatedRecipient {
-        satoshi: satoshi_a + 1,
+        satoshi: Some(satoshi_a + 1),
         address,
         asset: asset_str.to_string(),
+        timelock: None,
     }];
     let err = wallet
         .tx_builder()
@@ -1043,6 +1402,339 @@ fn drain() {
     }
 }
 
+#[test]
+#[cfg(feature = "bip158")]
+fn block_filter_scan() {
+    let server = setup(false);
+    let signer = generate_signer();
+    let view_key = generate_view_key();
+    let desc = format!("ct({},elwpkh({}/*))", view_key, signer.xpub());
+    let signers = [&AnySigner::Software(signer)];
+
+    let mut wallet = TestWollet::new(&server.electrs.electrum_url, &desc);
+    wallet.fund_btc(&server);
+    let node_address = server.node_getnewaddress();
+    wallet.send_btc(&signers, None, Some((node_address, 10_000)));
+
+    // Block filters see only confirmed transactions
+    server.generate(1);
+    wait_tx_update(&mut wallet);
+
+    let (url, auth) = server.node_rpc();
+    let mut client = BlockFilterClient::new(&url, Some(auth), 0);
+    let descriptor = wallet.wollet.wollet_descriptor();
+    let mut wollet = Wollet::new(network_regtest(), NoPersist::new(), descriptor).unwrap();
+    full_scan_with_backend(&mut wollet, &mut client).unwrap();
+
+    assert_eq!(wollet.balance().unwrap(), wallet.wollet.balance().unwrap());
+    let txids = |w: &Wollet| -> HashSet<_> {
+        w.transactions()
+            .unwrap()
+            .into_iter()
+            .map(|tx| tx.txid)
+            .collect()
+    };
+    assert_eq!(txids(&wollet).len(), 2);
+    assert_eq!(txids(&wollet), txids(&wallet.wollet));
+}
+
+#[test]
+fn incremental_scan() {
+    let server = setup(false);
+    let signer = generate_signer();
+    let view_key = generate_view_key();
+    let desc = format!("ct({},elwpkh({}/*))", view_key, signer.xpub());
+    let descriptor: WolletDescriptor = desc.parse().unwrap();
+    let mut wollet = Wollet::new(network_regtest(), NoPersist::new(), descriptor).unwrap();
+    let electrum_url = ElectrumUrl::new(&server.electrs.electrum_url, false, false);
+    let mut client = ElectrumClient::new(&electrum_url).unwrap();
+
+    // The same client is used for every scan, so only the changed scripts are queried.
+    // Index 15 is a known empty script, index 30 is beyond the gap of the previous scans
+    for (i, index) in [0, 15, 30].iter().enumerate() {
+        let address = wollet.address(Some(*index)).unwrap().address().clone();
+        server.node_sendtoaddress(&address, 10_000, None);
+        for _ in 0..50 {
+            if let Some(update) = client.full_scan(&wollet).unwrap() {
+                wollet.apply_update(update).unwrap();
+            }
+            if wollet.transactions().unwrap().len() == i + 1 {
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(200));
+        }
+        assert_eq!(wollet.transactions().unwrap().len(), i + 1);
+    }
+    assert_eq!(wollet.balance().unwrap()[&wollet.policy_asset()], 30_000);
+
+    // Nothing changed, nothing to update
+    assert!(client.full_scan(&wollet).unwrap().is_none());
+
+    // Once forgotten, the scripts are subscribed and queried again
+    client.forget_wollet(&wollet).unwrap();
+    let descriptor = wollet.wollet_descriptor();
+    let mut restored = Wollet::new(network_regtest(), NoPersist::new(), descriptor).unwrap();
+    let update = client.full_scan(&restored).unwrap().unwrap();
+    restored.apply_update(update).unwrap();
+    assert_eq!(restored.balance().unwrap(), wollet.balance().unwrap());
+}
+
+#[test]
+fn gap_limit() {
+    let server = setup(false);
+    let signer = generate_signer();
+    let view_key = generate_view_key();
+    let desc = format!("ct({},elwpkh({}/*))", view_key, signer.xpub());
+    let descriptor: WolletDescriptor = desc.parse().unwrap();
+    let mut wollet = Wollet::new(network_regtest(), NoPersist::new(), descriptor).unwrap();
+    let electrum_url = ElectrumUrl::new(&server.electrs.electrum_url, false, false);
+    let mut client = ElectrumClient::new(&electrum_url).unwrap();
+
+    // Fund an address past the default gap
+    let index = GAP_LIMIT + 5;
+    let address = wollet.address(Some(index)).unwrap().address().clone();
+    let txid = server.node_sendtoaddress(&address, 10_000, None);
+    server.generate(1);
+
+    // Recover the wallet with a gap limit large enough
+    let mut recovery = Wollet::new(
+        network_regtest(),
+        NoPersist::new(),
+        wollet.wollet_descriptor(),
+    )
+    .unwrap();
+    recovery.set_gap_limit(index + 1).unwrap();
+    // Wait for the server to see the transaction
+    for _ in 0..50 {
+        if let Some(update) = client.full_scan(&recovery).unwrap() {
+            recovery.apply_update(update).unwrap();
+        }
+        if !recovery.transactions().unwrap().is_empty() {
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(200));
+    }
+    assert_eq!(recovery.transactions().unwrap()[0].txid, txid);
+    assert_eq!(
+        recovery.balance().unwrap()[&recovery.policy_asset()],
+        10_000
+    );
+    assert_eq!(recovery.address(None).unwrap().index(), index + 1);
+
+    // With the default gap limit the funds are not found
+    if let Some(update) = client.full_scan(&wollet).unwrap() {
+        wollet.apply_update(update).unwrap();
+    }
+    assert!(wollet.transactions().unwrap().is_empty());
+    assert_eq!(wollet.address(None).unwrap().index(), 0);
+}
+
+#[test]
+fn verifiable_update() {
+    let server = setup(false);
+    let signer = generate_signer();
+    let view_key = generate_view_key();
+    let desc = format!("ct({},elwpkh({}/*))", view_key, signer.xpub());
+    let signers = [&AnySigner::Software(signer)];
+
+    let mut wallet = TestWollet::new(&server.electrs.electrum_url, &desc);
+    wallet.fund_btc(&server);
+    let node_address = server.node_getnewaddress();
+    wallet.send_btc(&signers, None, Some((node_address, 10_000)));
+    server.generate(1);
+    wait_tx_update(&mut wallet);
+
+    // Scan on the online machine
+    let descriptor = wallet.wollet.wollet_descriptor();
+    let online = Wollet::new(network_regtest(), NoPersist::new(), descriptor.clone()).unwrap();
+    let electrum_url = ElectrumUrl::new(&server.electrs.electrum_url, false, false);
+    let mut client = ElectrumClient::new(&electrum_url).unwrap();
+    let update = client.full_scan_verifiable(&online).unwrap().unwrap();
+    assert_eq!(update.proofs.len(), 2);
+    let bytes = update.serialize().unwrap();
+
+    // Apply on the offline machine
+    let update = VerifiableUpdate::deserialize(&bytes).unwrap();
+    let mut offline = Wollet::new(network_regtest(), NoPersist::new(), descriptor.clone()).unwrap();
+    offline.apply_verifiable_update(update.clone()).unwrap();
+    assert_eq!(offline.balance().unwrap(), wallet.wollet.balance().unwrap());
+    assert_eq!(offline.transactions().unwrap().len(), 2);
+
+    // Inflated amounts are rejected
+    let mut wrong = update;
+    wrong.update.new_txs.unblinds[0].1.value += 1;
+    let mut offline = Wollet::new(network_regtest(), NoPersist::new(), descriptor).unwrap();
+    let err = offline.apply_verifiable_update(wrong).unwrap_err();
+    assert!(matches!(err, Error::InvalidVerifiableUpdate(_)));
+    assert!(offline.transactions().unwrap().is_empty());
+}
+
+#[test]
+fn spv_scan() {
+    let server = setup(false);
+    let electrum_url = ElectrumUrl::new(&server.electrs.electrum_url, false, false);
+    let mut client = ElectrumClient::new(&electrum_url).unwrap();
+    let checkpoint = client.tip().unwrap();
+
+    let signer = generate_signer();
+    let view_key = generate_view_key();
+    let desc = format!("ct({},elwpkh({}/*))", view_key, signer.xpub());
+    let mut wallet = TestWollet::new(&server.electrs.electrum_url, &desc);
+    wallet.fund_btc(&server);
+    server.generate(1);
+    wait_tx_update(&mut wallet);
+
+    let descriptor = wallet.wollet.wollet_descriptor();
+    let mut wollet = Wollet::new(network_regtest(), NoPersist::new(), descriptor.clone()).unwrap();
+    let mut headers = HeaderChain::new(&checkpoint).unwrap();
+    let update = client
+        .full_scan_spv(&wollet, &mut headers)
+        .unwrap()
+        .unwrap();
+    assert_eq!(headers.tip_height(), update.update.tip.height);
+    assert!(headers.tip_height() > checkpoint.height);
+    wollet.apply_verifiable_update(update).unwrap();
+    assert_eq!(wollet.balance().unwrap(), wallet.wollet.balance().unwrap());
+    let tx = &wollet.transactions().unwrap()[0];
+    assert_eq!(tx.height, wallet.wollet.transactions().unwrap()[0].height);
+
+    // The headers are not linked to an untrusted checkpoint
+    let mut forged = checkpoint.clone();
+    forged.time += 1;
+    let mut headers = HeaderChain::new(&forged).unwrap();
+    let wollet = Wollet::new(network_regtest(), NoPersist::new(), descriptor).unwrap();
+    let err = client.full_scan_spv(&wollet, &mut headers).unwrap_err();
+    assert!(matches!(err, Error::SpvVerification(_)));
+}
+
+#[test]
+fn proof_of_reserves() {
+    let server = setup(false);
+    let signer = generate_signer();
+    let view_key = generate_view_key();
+    let desc = format!("ct({},elwpkh({}/*))", view_key, signer.xpub());
+    let mut wallet = TestWollet::new(&server.electrs.electrum_url, &desc);
+    wallet.fund_btc(&server);
+    wallet.fund_asset(&server);
+    let balance = wallet.wollet.balance().unwrap();
+
+    let challenge = "exchange audit 2024";
+    let mut pset = wallet.wollet.proof_of_reserves(challenge).unwrap();
+    assert_eq!(
+        pset.inputs().len(),
+        wallet.wollet.utxos().unwrap().len() + 1
+    );
+    assert_eq!(
+        pset.inputs()[0].previous_txid,
+        challenge_outpoint(challenge).txid
+    );
+    let err = verify_proof_of_reserves(&pset, challenge).unwrap_err();
+    assert!(matches!(err, Error::InvalidProofOfReserves(_)));
+
+    wallet.sign(&signer, &mut pset);
+    let reserves = verify_proof_of_reserves(&pset, challenge).unwrap();
+    assert_eq!(reserves, balance);
+
+    let err = verify_proof_of_reserves(&pset, "another challenge").unwrap_err();
+    assert!(matches!(err, Error::InvalidProofOfReserves(_)));
+}
+
+#[test]
+fn swap() {
+    let server = setup(false);
+    let signer_maker = generate_signer();
+    let view_key = generate_view_key();
+    let desc = format!("ct({},elwpkh({}/*))", view_key, signer_maker.xpub());
+    let mut maker = TestWollet::new(&server.electrs.electrum_url, &desc);
+    let asset = maker.fund_asset(&server);
+    let policy_asset = maker.policy_asset();
+
+    let signer_taker = generate_signer();
+    let view_key = generate_view_key();
+    let desc = format!("ct({},elwpkh({}/*))", view_key, signer_taker.xpub());
+    let mut taker = TestWollet::new(&server.electrs.electrum_url, &desc);
+    taker.fund_btc(&server);
+
+    let err = maker
+        .wollet
+        .create_swap(asset, 10_000, asset, 1)
+        .unwrap_err();
+    assert!(matches!(err, Error::SwapSameAsset));
+    let err = maker
+        .wollet
+        .create_swap(asset, 0, policy_asset, 50_000)
+        .unwrap_err();
+    assert!(matches!(err, Error::InvalidAmount));
+    let err = maker
+        .wollet
+        .create_swap(asset, 5_000, policy_asset, 50_000)
+        .unwrap_err();
+    assert!(matches!(err, Error::SwapUtxoNotFound { .. }));
+
+    // The maker offers its asset utxo for some L-BTC
+    let (mut pset, maker_utxo) = maker
+        .wollet
+        .create_swap(asset, 10_000, policy_asset, 50_000)
+        .unwrap();
+    assert_eq!(maker_utxo.unblinded.value, 10_000);
+    assert_eq!(pset.inputs().len(), 1);
+    assert_eq!(pset.outputs().len(), 1);
+    assert_eq!(pset.outputs()[0].amount, Some(50_000));
+    assert_eq!(pset.outputs()[0].asset, Some(policy_asset));
+    maker.sign(&signer_maker, &mut pset);
+    let sig = pset.inputs()[0].partial_sigs.values().next().unwrap();
+    assert_eq!(
+        *sig.last().unwrap(),
+        elements::EcdsaSighashType::SinglePlusAnyoneCanPay as u8
+    );
+
+    // The taker adds its input and outputs, pays the fee and blinds
+    let taker_utxo = taker.wollet.utxos().unwrap()[0].clone();
+    let taker_tx = taker
+        .wollet
+        .transaction(&taker_utxo.outpoint.txid)
+        .unwrap()
+        .unwrap()
+        .tx;
+    let mut input = elements::pset::Input::from_prevout(taker_utxo.outpoint);
+    input.witness_utxo = Some(taker_tx.output[taker_utxo.outpoint.vout as usize].clone());
+    pset.add_input(input);
+
+    let fee = 5_000;
+    let satoshi_change = taker_utxo.unblinded.value - 50_000 - fee;
+    for (satoshi, asset) in [(10_000, asset), (satoshi_change, policy_asset)] {
+        let address = taker.address();
+        let mut output = elements::pset::Output::new_explicit(
+            address.script_pubkey(),
+            satoshi,
+            asset,
+            address
+                .blinding_pubkey
+                .map(elements::bitcoin::PublicKey::new),
+        );
+        output.blinder_index = Some(1);
+        pset.add_output(output);
+    }
+    pset.add_output(elements::pset::Output::from_txout(
+        elements::TxOut::new_fee(fee, policy_asset),
+    ));
+
+    let mut inp_txout_sec = std::collections::HashMap::new();
+    inp_txout_sec.insert(0, maker_utxo.unblinded);
+    inp_txout_sec.insert(1, taker_utxo.unblinded);
+    pset.blind_last(&mut rand::thread_rng(), &EC, &inp_txout_sec)
+        .unwrap();
+    taker.wollet.add_details(&mut pset).unwrap();
+    taker.sign(&signer_taker, &mut pset);
+    taker.send(&mut pset);
+
+    wait_tx_update(&mut maker);
+    assert_eq!(maker.balance(&asset), 0);
+    assert_eq!(maker.balance(&policy_asset), 50_000);
+    assert_eq!(taker.balance(&asset), 10_000);
+    assert_eq!(taker.balance(&policy_asset), satoshi_change);
+}
+
 fn wait_tx_update(wallet: &mut TestWollet) {
     let mut client = ElectrumClient::new(&wallet.electrum_url).unwrap();
     for _ in 0..50 {